@@ -1,6 +1,21 @@
 -- Import utility functions
 local utils = require("utils")
 
+-- Send an encoded event to the client: through the named pipe, or as one UDP datagram
+-- when a port is configured (requires --enable-lua-udp). Datagrams carry a sequence
+-- number so the client can detect loss.
+local function emit_event(json_str)
+  local port = settings.global["wandb-weave-udp-port"].value
+  if port > 0 then
+    storage.udp_seq = (storage.udp_seq or 0) + 1
+    helpers.send_udp(port, '{"seq":' .. storage.udp_seq .. ',"event":' .. json_str .. '}')
+  elseif settings.global["wandb-weave-write-files"].value then
+    helpers.write_file("wandb-events/events.jsonl", json_str .. "\n", true)
+  else
+    helpers.write_file("events.pipe", json_str .. "\n", true)
+  end
+end
+
 -- Generate a new session ID using level name and current tick
 local function generate_session_id()
   local level_name = script.level.level_name or "unknown"
@@ -8,12 +23,67 @@ local function generate_session_id()
   return level_name .. "_" .. current_tick
 end
 
+-- Fingerprint of the save file, fixed when the game is created and kept in every save made
+-- from it, so later continuations of the same playthrough can be linked together
+local function generate_save_id()
+  local seed = game.surfaces["nauvis"] and game.surfaces["nauvis"].map_gen_settings.seed or 0
+  return (script.level.level_name or "unknown") .. "_" .. seed .. "_" .. game.tick
+end
+
+-- Expected amount of a recipe product, including random amounts and probabilities
+local function product_amount(product)
+  local amount = product.amount or ((product.amount_min or 0) + (product.amount_max or 0)) / 2
+  return amount * (product.probability or 1)
+end
+
+-- Send the item and recipe prototypes once per session, so the client can derive metrics
+-- like raw-resource costs. The payload is too large for a UDP datagram, so it is only
+-- sent through the pipe or event files.
+local function send_prototypes()
+  if settings.global["wandb-weave-udp-port"].value > 0 then
+    return
+  end
+
+  local items = {}
+  for name, item in pairs(prototypes.item) do
+    items[name] = {
+      stack_size = item.stack_size,
+      subgroup = item.subgroup and item.subgroup.name
+    }
+  end
+
+  local recipes = {}
+  for name, recipe in pairs(prototypes.recipe) do
+    -- Empty lists would encode as JSON objects; such recipes can't be costed anyway
+    if not recipe.hidden and #recipe.ingredients > 0 and #recipe.products > 0 then
+      local ingredients = {}
+      for _, ingredient in ipairs(recipe.ingredients) do
+        table.insert(ingredients, {name = ingredient.name, amount = ingredient.amount})
+      end
+      local products = {}
+      for _, product in ipairs(recipe.products) do
+        table.insert(products, {name = product.name, amount = product_amount(product)})
+      end
+      recipes[name] = {ingredients = ingredients, products = products}
+    end
+  end
+
+  emit_event(helpers.table_to_json({
+    type = "prototypes",
+    session_id = storage.session_id,
+    tick = game.tick,
+    items = items,
+    recipes = recipes
+  }))
+end
+
 -- Local flag to track if we've regenerated session after load
 local session_regenerated = false
 
 -- Initialize session ID on new game
 script.on_init(function()
   storage.session_id = generate_session_id()
+  storage.save_id = generate_save_id()
   storage.last_tick = game.tick 
 end)
 
@@ -28,6 +98,10 @@ local function check_and_regenerate_session()
     local old_session = storage.session_id
     storage.session_id = generate_session_id()
     storage.last_tick = game.tick
+    -- Saves from before fingerprinting get one on their first load
+    storage.save_id = storage.save_id or generate_save_id()
+    -- Stats of the previous session don't belong to the new one
+    storage.backlog = {}
     session_regenerated = true
 
     -- Send session init event to named pipe
@@ -35,10 +109,12 @@ local function check_and_regenerate_session()
       type = "session_init",
       session_id = storage.session_id,
       tick = game.tick,
-      level_name = script.level.level_name or "unknown"
+      level_name = script.level.level_name or "unknown",
+      save_id = storage.save_id
     }
     local json_str = helpers.table_to_json(init_event)
-    helpers.write_file("events.pipe", json_str .. "\n", true)
+    emit_event(json_str)
+    send_prototypes()
 
     -- Debug output
     game.print("Session ID regenerated: " .. (old_session or "none") .. " -> " .. storage.session_id)
@@ -63,7 +139,7 @@ script.on_event(defines.events.on_built_entity, function(event)
       surface = entity.surface.name
     }
     local json_str = helpers.table_to_json(event_data)
-    helpers.write_file("events.pipe", json_str .. "\n", true)
+    emit_event(json_str)
   end
 end)
 
@@ -84,7 +160,7 @@ script.on_event(defines.events.on_player_mined_entity, function(event)
       surface = entity.surface.name
     }
     local json_str = helpers.table_to_json(event_data)
-    helpers.write_file("events.pipe", json_str .. "\n", true)
+    emit_event(json_str)
   end
 end)
 
@@ -99,10 +175,12 @@ script.on_event(defines.events.on_research_started, function(event)
     session_id = storage.session_id,
     tick = event.tick,
     tech_name = research.name,
-    tech_level = research.level
+    tech_level = research.level,
+    -- Infinite techs level up with a cost formula instead of a fixed unit count
+    infinite = research.research_unit_count_formula ~= nil
   }
   local json_str = helpers.table_to_json(event_data)
-  helpers.write_file("events.pipe", json_str .. "\n", true)
+  emit_event(json_str)
 end)
 
 -- Event handler for research completed
@@ -116,10 +194,12 @@ script.on_event(defines.events.on_research_finished, function(event)
     session_id = storage.session_id,
     tick = event.tick,
     tech_name = research.name,
-    tech_level = research.level
+    tech_level = research.level,
+    -- Infinite techs level up with a cost formula instead of a fixed unit count
+    infinite = research.research_unit_count_formula ~= nil
   }
   local json_str = helpers.table_to_json(event_data)
-  helpers.write_file("events.pipe", json_str .. "\n", true)
+  emit_event(json_str)
 end)
 
 -- Event handler for player crafted item
@@ -136,9 +216,323 @@ script.on_event(defines.events.on_player_crafted_item, function(event)
     count = event.item_stack.count
   }
   local json_str = helpers.table_to_json(event_data)
-  helpers.write_file("events.pipe", json_str .. "\n", true)
+  emit_event(json_str)
+end)
+
+-- Rocket silo pipeline: each silo reports its rocket parts as they change, the launch
+-- order with the rocket's cargo, the launch and the arrival of the rocket's cargo pod.
+-- The client turns every launch into a trace; silos are identified by unit number.
+local function emit_silo_stage(silo, stage, tick, fields)
+  local silo_data = {
+    type = "rocket_silo",
+    session_id = storage.session_id,
+    tick = tick,
+    silo = silo.unit_number,
+    stage = stage,
+    surface = silo.surface.name,
+    parts_required = silo.prototype.rocket_parts_required
+  }
+  for key, value in pairs(fields or {}) do
+    silo_data[key] = value
+  end
+  emit_event(helpers.table_to_json(silo_data))
+end
+
+-- Items in the rocket of a silo, by name
+local function rocket_cargo(silo)
+  local cargo = {}
+  local inventory = silo.get_inventory(defines.inventory.rocket_silo_rocket)
+  if inventory then
+    for _, stack in pairs(inventory.get_contents()) do
+      cargo[stack.name] = (cargo[stack.name] or 0) + stack.count
+    end
+  end
+  return cargo
+end
+
+-- Samples the rocket parts of every silo (every 60 ticks), reporting the ones that changed
+script.on_nth_tick(60, function(event)
+  check_and_regenerate_session()
+  storage.silo_parts = storage.silo_parts or {}
+
+  local seen = {}
+  for _, surface in pairs(game.surfaces) do
+    for _, silo in pairs(surface.find_entities_filtered{type = "rocket-silo"}) do
+      local parts = silo.rocket_parts
+      seen[silo.unit_number] = true
+      if parts ~= (storage.silo_parts[silo.unit_number] or 0) then
+        storage.silo_parts[silo.unit_number] = parts
+        if parts > 0 then
+          emit_silo_stage(silo, "parts", event.tick, {parts = parts})
+        end
+      end
+    end
+  end
+  -- Forget silos that were removed
+  for unit_number, _ in pairs(storage.silo_parts) do
+    if not seen[unit_number] then
+      storage.silo_parts[unit_number] = nil
+    end
+  end
+end)
+
+script.on_event(defines.events.on_rocket_launch_ordered, function(event)
+  check_and_regenerate_session()
+  local silo = event.rocket_silo
+  if silo and silo.valid then
+    emit_silo_stage(silo, "launch_ordered", event.tick, {cargo = rocket_cargo(silo)})
+  end
 end)
 
+-- Event handler for rocket launches
+script.on_event(defines.events.on_rocket_launched, function(event)
+  check_and_regenerate_session()
+  local silo = event.rocket_silo
+
+  local event_data = {
+    type = "event",
+    event_name = "on_rocket_launched",
+    session_id = storage.session_id,
+    tick = event.tick,
+    surface = silo and silo.valid and silo.surface.name or nil,
+    count = silo and silo.valid and silo.force.rockets_launched or nil
+  }
+  local json_str = helpers.table_to_json(event_data)
+  emit_event(json_str)
+
+  if silo and silo.valid then
+    emit_silo_stage(silo, "launched", event.tick)
+  end
+end)
+
+-- A cargo pod launched by a rocket reached its destination
+script.on_event(defines.events.on_cargo_pod_finished_ascending, function(event)
+  check_and_regenerate_session()
+  local pod = event.cargo_pod
+  if not (event.launched_by_rocket and pod and pod.valid) then
+    return
+  end
+  local origin = pod.cargo_pod_origin
+  if origin and origin.valid and origin.type == "rocket-silo" then
+    emit_silo_stage(origin, "cargo_delivered", event.tick)
+  end
+end)
+
+-- Event handler for player deaths
+script.on_event(defines.events.on_player_died, function(event)
+  check_and_regenerate_session()
+  local player = game.get_player(event.player_index)
+  local cause = event.cause
+
+  local event_data = {
+    type = "event",
+    event_name = "on_player_died",
+    session_id = storage.session_id,
+    tick = event.tick,
+    player_index = event.player_index,
+    entity = cause and cause.valid and cause.name or nil,
+    surface = player and player.valid and player.surface.name or nil
+  }
+  local json_str = helpers.table_to_json(event_data)
+  emit_event(json_str)
+end)
+
+-- Writes a chunk exploration event; positions are chunk coordinates
+local function write_chunk_event(event_name, tick, chunk_position, surface_name)
+  local event_data = {
+    type = "event",
+    event_name = event_name,
+    session_id = storage.session_id,
+    tick = tick,
+    position = {x = chunk_position.x, y = chunk_position.y},
+    surface = surface_name
+  }
+  local json_str = helpers.table_to_json(event_data)
+  emit_event(json_str)
+end
+
+-- Event handler for newly generated chunks
+script.on_event(defines.events.on_chunk_generated, function(event)
+  check_and_regenerate_session()
+  write_chunk_event("on_chunk_generated", event.tick, event.position, event.surface.name)
+end)
+
+-- Event handler for chunks charted by the player force
+script.on_event(defines.events.on_chunk_charted, function(event)
+  check_and_regenerate_session()
+  if event.force.name == "player" then
+    local surface = game.surfaces[event.surface_index]
+    write_chunk_event("on_chunk_charted", event.tick, event.position, surface.name)
+  end
+end)
+
+-- Event handler for radar sector scans
+script.on_event(defines.events.on_sector_scanned, function(event)
+  check_and_regenerate_session()
+  local radar = event.radar
+  if radar and radar.valid and radar.force.name == "player" then
+    write_chunk_event("on_sector_scanned", event.tick, event.chunk_position, radar.surface.name)
+  end
+end)
+
+-- Periodic per-chunk pollution sample of nauvis (every 600 ticks = 10 seconds)
+script.on_nth_tick(600, function(event)
+  check_and_regenerate_session()
+
+  local nauvis = game.surfaces["nauvis"]
+  if not nauvis then
+    return
+  end
+
+  local chunks = {}
+  for chunk in nauvis.get_chunks() do
+    local pollution = nauvis.get_pollution({chunk.x * 32 + 16, chunk.y * 32 + 16})
+    if pollution > 0 then
+      table.insert(chunks, {x = chunk.x, y = chunk.y, pollution = utils.format_number(pollution)})
+    end
+  end
+
+  local pollution_data = {
+    type = "pollution",
+    session_id = storage.session_id,
+    tick = event.tick,
+    surface = nauvis.name,
+    chunks = chunks
+  }
+  local json_str = helpers.table_to_json(pollution_data)
+  emit_event(json_str)
+end)
+
+-- Probes: belts and inserters marked with a map tag whose text is "probe:<name>", identified
+-- by the tag number. The registry is sent whenever it changes and readings reference ids.
+-- Inserters count the items they pick up each tick; belts are estimated from item
+-- density times belt speed. State lives in storage so it survives saves and stays in sync.
+local function probe_state()
+  storage.probes = storage.probes or {
+    inserters = {}, was_holding = {}, counts = {}, last_tick = 0, registry_signature = ""
+  }
+  return storage.probes
+end
+
+local function find_probes(force, surface)
+  local probes = {}
+  for _, tag in pairs(force.find_chart_tags(surface)) do
+    local name = tag.text and string.match(tag.text, "^probe:(.+)$")
+    if name then
+      local entity = surface.find_entities_filtered{
+        position = tag.position,
+        radius = 1,
+        type = {"transport-belt", "inserter"},
+        force = force,
+        limit = 1
+      }[1]
+      if entity then
+        probes[tostring(tag.tag_number)] = {name = name, entity = entity}
+      end
+    end
+  end
+  return probes
+end
+
+-- Sends the probe registry if it differs from the last one sent
+local function sync_probe_registry(state, probes, tick)
+  local registry = {}
+  local parts = {}
+  for id, probe in pairs(probes) do
+    local kind = probe.entity.type == "transport-belt" and "belt" or "inserter"
+    local position = {x = probe.entity.position.x, y = probe.entity.position.y}
+    table.insert(registry, {id = id, name = probe.name, kind = kind, position = position})
+    table.insert(parts, id .. "=" .. probe.name .. "@" .. kind .. ":" .. position.x .. "," .. position.y)
+  end
+  table.sort(parts)
+  local signature = table.concat(parts, ";")
+
+  if signature ~= state.registry_signature then
+    state.registry_signature = signature
+    local registry_event = {
+      type = "probe_registry",
+      session_id = storage.session_id,
+      tick = tick,
+      probes = registry
+    }
+    local json_str = helpers.table_to_json(registry_event)
+    emit_event(json_str)
+  end
+end
+
+script.on_event(defines.events.on_tick, function(event)
+  local state = storage.probes
+  if not state then
+    return
+  end
+  for id, inserter in pairs(state.inserters) do
+    if inserter.valid then
+      local held = inserter.held_stack
+      local holding = held.valid_for_read
+      if holding and not state.was_holding[id] then
+        state.counts[id] = (state.counts[id] or 0) + held.count
+      end
+      state.was_holding[id] = holding
+    end
+  end
+end)
+
+-- Returns items per minute for every probe since the previous call, keyed by probe id
+local function sample_probes(force, surface, tick)
+  local state = probe_state()
+  local readings = {}
+  local elapsed = tick - state.last_tick
+  local probes = find_probes(force, surface)
+  sync_probe_registry(state, probes, tick)
+
+  for id, probe in pairs(probes) do
+    local entity = probe.entity
+    if entity.type == "transport-belt" then
+      local items = 0
+      for lane = 1, 2 do
+        items = items + entity.get_transport_line(lane).get_item_count()
+      end
+      -- Items per tile times tiles per tick, converted to per minute
+      readings[id] = utils.format_number(items * entity.prototype.belt_speed * 3600)
+    elseif state.inserters[id] and elapsed > 0 then
+      readings[id] = utils.format_number((state.counts[id] or 0) * 3600 / elapsed)
+    end
+  end
+
+  -- Track the current inserter probes for the next window
+  state.inserters = {}
+  state.counts = {}
+  for id, probe in pairs(probes) do
+    if probe.entity.type == "inserter" then
+      state.inserters[id] = probe.entity
+    end
+  end
+  state.last_tick = tick
+
+  return readings
+end
+
+-- Keeps the last stats events for a catch-up of a late-joining client
+local function remember_stats(json_str)
+  local limit = settings.global["wandb-weave-backlog-cycles"].value
+  storage.backlog = storage.backlog or {}
+  table.insert(storage.backlog, json_str)
+  while #storage.backlog > limit do
+    table.remove(storage.backlog, 1)
+  end
+end
+
+-- Whether this stats cycle takes a screenshot: every cycle, unless the client
+-- schedules them over RCON (see schedule_screenshots), then only when it asked for one
+local function screenshot_wanted(tick)
+  if (storage.screenshots_scheduled_until or 0) < tick then
+    return true
+  end
+  local requested = storage.screenshot_requested
+  storage.screenshot_requested = false
+  return requested
+end
+
 -- Periodic production/consumption rate dump (every 120 ticks = 2 seconds)
 script.on_nth_tick(120, function(event)
   -- Check if we need to regenerate session ID after load
@@ -163,16 +557,18 @@ script.on_nth_tick(120, function(event)
       }
       
       -- Take screenshot centered on player
-      screenshot_path = "screenshots/" .. storage.session_id .. "/tick_" .. event.tick .. ".png"
-      game.take_screenshot{
-        player = player,
-        position = player.position,
-        resolution = {x = 1920, y = 1080},
-        zoom = 0.5,
-        path = screenshot_path,
-        show_gui = false,
-        show_entity_info = true
-      }
+      if screenshot_wanted(event.tick) then
+        screenshot_path = "screenshots/" .. storage.session_id .. "/tick_" .. event.tick .. ".png"
+        game.take_screenshot{
+          player = player,
+          position = player.position,
+          resolution = {x = 1920, y = 1080},
+          zoom = 0.5,
+          path = screenshot_path,
+          show_gui = false,
+          show_entity_info = true
+        }
+      end
     end
 
     -- Build stats data structure
@@ -235,8 +631,135 @@ script.on_nth_tick(120, function(event)
       end
     end
 
+    stats_data.probes = sample_probes(player_force, nauvis, event.tick)
+    local enemy_force = game.forces["enemy"]
+    if enemy_force then
+      stats_data.evolution = utils.format_number(enemy_force.get_evolution_factor(nauvis))
+    end
+
+    -- Count labs and how many of them are researching
+    local labs = nauvis.find_entities_filtered{type = "lab", force = player_force}
+    if #labs > 0 then
+      local working = 0
+      for _, lab in pairs(labs) do
+        if lab.status == defines.entity_status.working then
+          working = working + 1
+        end
+      end
+      stats_data.labs = {count = #labs, working = working}
+    end
+
+    -- Sample crafting machine utilization per recipe (every 600 ticks, it walks all machines)
+    if event.tick % 600 == 0 then
+      local recipes = {}
+      local machines = nauvis.find_entities_filtered{
+        type = {"assembling-machine", "furnace"},
+        force = player_force
+      }
+      for _, machine in pairs(machines) do
+        local recipe = machine.get_recipe() or (machine.type == "furnace" and machine.previous_recipe)
+        if recipe then
+          local name = recipe.name
+          local entry = recipes[name]
+          if not entry then
+            local group = recipe.prototype and recipe.prototype.group or recipe.group
+            entry = {recipe = name, category = group and group.name, count = 0, working = 0}
+            recipes[name] = entry
+          end
+          entry.count = entry.count + 1
+          if machine.status == defines.entity_status.working then
+            entry.working = entry.working + 1
+          end
+        end
+      end
+
+      local assemblers = {}
+      for _, entry in pairs(recipes) do
+        table.insert(assemblers, {
+          recipe = entry.recipe,
+          category = entry.category,
+          count = entry.count,
+          utilization = utils.format_number(entry.working / entry.count)
+        })
+      end
+      if #assemblers > 0 then
+        stats_data.assemblers = assemblers
+      end
+    end
+
     -- Convert to JSON and write to named pipe
     local json_str = helpers.table_to_json(stats_data)
-    helpers.write_file("events.pipe", json_str .. "\n", true)
+    emit_event(json_str)
+    remember_stats(json_str)
+  end
+end)
+
+-- Re-sends the session, its recent stats and the current research, marked as backlog, so a
+-- client that started mid-session can fill its gaps. Cycles it already has are skipped.
+local function catch_up()
+  check_and_regenerate_session()
+
+  local init_event = {
+    type = "session_init",
+    session_id = storage.session_id,
+    tick = game.tick,
+    level_name = script.level.level_name or "unknown",
+    save_id = storage.save_id,
+    backlog = true
+  }
+  emit_event(helpers.table_to_json(init_event))
+
+  local backlog = storage.backlog or {}
+  for _, json_str in ipairs(backlog) do
+    emit_event('{"backlog":true,' .. json_str:sub(2))
+  end
+
+  local player_force = game.forces["player"]
+  local research = player_force and player_force.current_research
+  if research then
+    local event_data = {
+      type = "event",
+      event_name = "on_research_started",
+      session_id = storage.session_id,
+      tick = game.tick,
+      tech_name = research.name,
+      tech_level = research.level,
+      infinite = research.research_unit_count_formula ~= nil,
+      backlog = true
+    }
+    emit_event(helpers.table_to_json(event_data))
+  end
+
+  return #backlog
+end
+
+-- Called by the client over RCON when it schedules the screenshots itself: for the
+-- next lease_secs, stats cycles only take one after a call with take set. Once the
+-- lease runs out (the client stopped) every cycle takes one again.
+local function schedule_screenshots(lease_secs, take)
+  storage.screenshots_scheduled_until = game.tick + lease_secs * 60
+  if take then
+    storage.screenshot_requested = true
+  end
+  return "ok"
+end
+
+-- Called by the client over RCON:
+-- /silent-command remote.call("wandb_weave", "catch_up") when it connects
+-- /silent-command remote.call("wandb_weave", "schedule_screenshots", 90, true)
+remote.add_interface("wandb_weave", {
+  catch_up = catch_up,
+  schedule_screenshots = schedule_screenshots
+})
+
+-- Manual catch-up for games without RCON
+commands.add_command("wandb-catchup", "Re-send recent stats to the WandB/Weave client", function(command)
+  local count = catch_up()
+  local player = command.player_index and game.get_player(command.player_index)
+  local message = "Re-sent " .. count .. " stats cycles to the client"
+  if player then
+    player.print(message)
+  else
+    game.print(message)
   end
 end)
@@ -0,0 +1,27 @@
+data:extend({
+  {
+    type = "int-setting",
+    name = "wandb-weave-udp-port",
+    setting_type = "runtime-global",
+    default_value = 0,
+    minimum_value = 0,
+    maximum_value = 65535,
+    order = "a"
+  },
+  {
+    type = "bool-setting",
+    name = "wandb-weave-write-files",
+    setting_type = "runtime-global",
+    default_value = false,
+    order = "b"
+  },
+  {
+    type = "int-setting",
+    name = "wandb-weave-backlog-cycles",
+    setting_type = "runtime-global",
+    default_value = 150,
+    minimum_value = 0,
+    maximum_value = 1800,
+    order = "c"
+  }
+})
@@ -0,0 +1,12 @@
+fn main() {
+    // Use the bundled protoc so building doesn't need one installed
+    if std::env::var_os("PROTOC").is_none() {
+        if let Ok(protoc) = protoc_bin_vendored::protoc_bin_path() {
+            std::env::set_var("PROTOC", protoc);
+        }
+    }
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/control.proto"], &["proto"])
+        .unwrap_or_else(|e| panic!("Failed to compile proto/control.proto: {}", e));
+}
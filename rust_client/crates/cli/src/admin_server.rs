@@ -0,0 +1,226 @@
+use crate::instances::Instance;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use factorio_events_core::config::AdminSettings;
+use factorio_events_sinks_live::dashboard::Dashboard;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::net::TcpListener;
+
+/// What the admin endpoints inspect and control
+pub struct AdminState {
+    instances: Vec<Instance>,
+    started: Instant,
+    /// Bearer token every request must carry, when set
+    token: Option<String>,
+    dashboard: Option<Dashboard>,
+}
+
+impl AdminState {
+    pub fn new(instances: Vec<Instance>) -> Self {
+        Self {
+            instances,
+            started: Instant::now(),
+            token: std::env::var("FACTORIO_ADMIN_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            dashboard: None,
+        }
+    }
+
+    /// Serves the web dashboard from the state kept by this sink
+    pub fn with_dashboard(mut self, dashboard: Option<Dashboard>) -> Self {
+        self.dashboard = dashboard;
+        self
+    }
+}
+
+/// Starts the admin/status server when a bind address is configured. It only listens on
+/// loopback addresses unless `allow_remote` is set, and a remote listener requires
+/// FACTORIO_ADMIN_TOKEN so the control surface is never exposed unauthenticated.
+pub async fn start(settings: &AdminSettings, state: AdminState) -> Result<(), String> {
+    let Some(ref bind) = settings.bind else {
+        return Ok(());
+    };
+
+    let listener = TcpListener::bind(bind)
+        .await
+        .map_err(|e| format!("Failed to bind admin server to {}: {}", bind, e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read admin server address: {}", e))?;
+
+    check_exposure(
+        "Admin server",
+        addr,
+        settings.allow_remote,
+        state.token.is_some(),
+        "admin.allow_remote",
+        "FACTORIO_ADMIN_TOKEN",
+    )?;
+
+    let authenticated = state.token.is_some();
+    let dashboard = state.dashboard.is_some();
+    let state = Arc::new(state);
+    let mut api = Router::new()
+        .route("/status", get(status))
+        .route("/rotate", post(rotate));
+    if dashboard {
+        api = api
+            .route("/dashboard/state", get(dashboard_state))
+            .route("/dashboard/thumbnails/:id", get(thumbnail));
+    }
+    let mut app = api.layer(middleware::from_fn_with_state(state.clone(), require_token));
+    // The page itself holds no data, so it loads without a token and sends the token
+    // given in its URL fragment with every request
+    if dashboard {
+        app = app.route("/", get(dashboard_page));
+    }
+    let app = app.with_state(state);
+
+    println!(
+        "🛠️  Admin server listening on http://{} ({})",
+        addr,
+        if authenticated { "token required" } else { "no token, loopback only" }
+    );
+    if dashboard {
+        println!("🛠️  Dashboard at http://{}/", addr);
+    }
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("⚠️  Admin server stopped: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Refuses a non-loopback listener unless remote access was allowed and a token is set
+pub(crate) fn check_exposure(
+    server: &str,
+    addr: SocketAddr,
+    allow_remote: bool,
+    has_token: bool,
+    allow_key: &str,
+    token_var: &str,
+) -> Result<(), String> {
+    if addr.ip().is_loopback() {
+        return Ok(());
+    }
+    if !allow_remote {
+        return Err(format!(
+            "{} refuses non-loopback address {} (set {})",
+            server, addr, allow_key
+        ));
+    }
+    if !has_token {
+        return Err(format!(
+            "{} on non-loopback address {} requires {}",
+            server, addr, token_var
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects requests without `Authorization: Bearer <token>` when a token is configured
+async fn require_token(State(state): State<Arc<AdminState>>, request: Request, next: Next) -> Response {
+    if let Some(ref token) = state.token {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let authorized = presented.is_some_and(|presented| constant_time_eq(presented, token));
+        if !authorized {
+            return (StatusCode::UNAUTHORIZED, "missing or invalid admin token").into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Compares tokens without an early exit, so timing doesn't reveal matching prefixes
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a
+            .bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+async fn status(State(state): State<Arc<AdminState>>) -> Json<serde_json::Value> {
+    let mut instances = Vec::new();
+    for instance in &state.instances {
+        let run = instance
+            .mediator
+            .current_run()
+            .map(|(id, url)| serde_json::json!({ "id": id, "url": url }));
+        instances.push(serde_json::json!({
+            "id": instance.id,
+            "run": run,
+            "sessions": instance.mediator.active_sessions().await,
+            "sinks": instance.mediator.sink_status(),
+            "cache": {
+                "queued": instance.cache.len(),
+                "dropped": instance.cache.dropped_count(),
+                "blocked": instance.cache.blocked_count(),
+                "udp_lost": instance.cache.udp_lost_count(),
+            },
+        }));
+    }
+
+    Json(serde_json::json!({
+        "uptime_secs": state.started.elapsed().as_secs(),
+        "instances": instances,
+    }))
+}
+
+async fn rotate(State(state): State<Arc<AdminState>>) -> StatusCode {
+    for instance in &state.instances {
+        instance.mediator.request_rotation();
+    }
+    StatusCode::ACCEPTED
+}
+
+async fn dashboard_page() -> Html<&'static str> {
+    Html(include_str!("dashboard.html"))
+}
+
+async fn dashboard_state(State(state): State<Arc<AdminState>>) -> Json<serde_json::Value> {
+    let mut instances = Vec::new();
+    for instance in &state.instances {
+        let weave_calls: Vec<serde_json::Value> = instance
+            .mediator
+            .active_weave_calls()
+            .await
+            .into_iter()
+            .map(|(id, start_tick)| serde_json::json!({ "id": id, "start_tick": start_tick }))
+            .collect();
+        let run = instance
+            .mediator
+            .current_run()
+            .map(|(id, url)| serde_json::json!({ "id": id, "url": url }));
+        instances.push(serde_json::json!({
+            "id": instance.id,
+            "run": run,
+            "weave_calls": weave_calls,
+            "view": state.dashboard.as_ref().and_then(|dashboard| dashboard.view(&instance.id)),
+        }));
+    }
+
+    Json(serde_json::json!({
+        "uptime_secs": state.started.elapsed().as_secs(),
+        "instances": instances,
+    }))
+}
+
+async fn thumbnail(State(state): State<Arc<AdminState>>, Path(id): Path<u64>) -> Response {
+    match state.dashboard.as_ref().and_then(|dashboard| dashboard.thumbnail(id)) {
+        Some(png) => ([(header::CONTENT_TYPE, "image/png")], png.as_ref().clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
@@ -0,0 +1,136 @@
+use clap::{Args, Parser, Subcommand};
+use factorio_events_core::config::AppConfig;
+use std::path::PathBuf;
+
+/// Streams Factorio game events to WandB and Weave
+#[derive(Debug, Parser)]
+#[command(name = "rust_client", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub options: GlobalOptions,
+
+    /// Print every config key with its type, default and env var as JSON, then exit
+    #[arg(long)]
+    pub print_config_schema: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Read the game's event pipe and upload continuously (the default)
+    Run,
+    /// Feed a capture log through the client in tick order, as if it came from the pipe
+    Replay {
+        file: PathBuf,
+        /// Pace events at this multiple of game speed (e.g. 10 for 10x, 0.5 for half
+        /// speed) instead of as fast as possible
+        #[arg(long)]
+        speed: Option<f64>,
+    },
+    /// Check every line of a capture log against the event schema
+    Validate {
+        file: PathBuf,
+    },
+    /// Summarize a capture log (defaults to the configured log path)
+    Stats {
+        file: Option<PathBuf>,
+    },
+    /// Compare two sessions from capture logs
+    DiffRuns {
+        /// Capture log, optionally followed by `@<session_id>`
+        run_a: String,
+        run_b: String,
+        /// Number of item rows per section
+        #[arg(long, default_value_t = 20)]
+        rows: usize,
+    },
+    /// Print the published runs of a session from the run index
+    FindSession {
+        session_id: String,
+    },
+    /// Bundle a session's events, screenshots, config and run/trace ids into a .tar.zst
+    Export {
+        session_id: String,
+        /// Bundle to write (defaults to `<session_id>.tar.zst`)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Backfill a session from a bundle made by `export`, replaying its events
+    Import {
+        bundle: PathBuf,
+        /// Pace events at this multiple of game speed, like `replay --speed`
+        #[arg(long)]
+        speed: Option<f64>,
+    },
+    /// Write events into the client's source like the mod does, from the arguments or
+    /// one JSON line each from stdin
+    Emit {
+        events: Vec<String>,
+        /// `udp:<host>:<port>` or a pipe path (defaults to `udp:<udp_bind>` when UDP
+        /// ingestion is on, else the configured pipe)
+        #[arg(long)]
+        target: Option<String>,
+        /// Name the events are numbered under, apart from the mod's
+        #[arg(long, default_value = "emit")]
+        source: String,
+    },
+}
+
+/// Options shared by all subcommands; they override the config file and env vars
+#[derive(Debug, Clone, Args)]
+pub struct GlobalOptions {
+    /// TOML or YAML config file (or FACTORIO_CONFIG)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Named pipe the mod writes events to
+    #[arg(long, global = true)]
+    pub pipe_path: Option<String>,
+
+    /// WandB project of new runs
+    #[arg(long, global = true)]
+    pub project: Option<String>,
+
+    /// Seconds between drains of the event cache
+    #[arg(long, global = true)]
+    pub drain_interval: Option<u64>,
+
+    /// Parse and classify events without uploading anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Emit client lifecycle events as JSON lines to `stdout` or `unix:<path>`
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "stdout",
+        value_name = "TARGET"
+    )]
+    pub json_events: Option<String>,
+
+    /// Run metadata as key=value (repeatable), merged over FACTORIO_META
+    #[arg(long = "meta", global = true, value_name = "KEY=VALUE")]
+    pub meta: Vec<String>,
+}
+
+impl GlobalOptions {
+    /// Loads the app config, with these options taking precedence over file and env
+    pub fn load_config(&self) -> Result<AppConfig, String> {
+        let mut config = AppConfig::load(self.config.as_deref())?;
+
+        if let Some(ref pipe_path) = self.pipe_path {
+            config.pipe_path = pipe_path.clone();
+        }
+        if let Some(ref project) = self.project {
+            config.wandb.project = project.clone();
+        }
+        if let Some(secs) = self.drain_interval {
+            config.drain_interval_secs = secs;
+        }
+        Ok(config)
+    }
+}
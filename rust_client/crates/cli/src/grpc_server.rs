@@ -0,0 +1,198 @@
+use crate::admin_server::{check_exposure, constant_time_eq};
+use crate::instances::Instance;
+use factorio_events_core::config::GrpcSettings;
+use std::time::Instant;
+use tokio::net::TcpListener;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+mod proto {
+    tonic::include_proto!("factorio.control");
+}
+
+use proto::control_server::{Control, ControlServer};
+use proto::{
+    FinishSessionReply, FinishSessionRequest, FlushReply, FlushRequest, InjectEventsReply,
+    InjectEventsRequest, InstanceStatus, Run, SetDryRunReply, SetDryRunRequest, Sink, StatusReply,
+    StatusRequest,
+};
+
+/// What the control RPCs inspect and control
+pub struct ControlService {
+    instances: Vec<Instance>,
+    started: Instant,
+}
+
+impl ControlService {
+    pub fn new(instances: Vec<Instance>) -> Self {
+        Self {
+            instances,
+            started: Instant::now(),
+        }
+    }
+
+    /// The instance with the given id, or every instance for an empty id
+    fn select(&self, id: &str) -> Result<Vec<&Instance>, Status> {
+        if id.is_empty() {
+            return Ok(self.instances.iter().collect());
+        }
+        let selected: Vec<&Instance> = self
+            .instances
+            .iter()
+            .filter(|instance| instance.id.as_deref() == Some(id))
+            .collect();
+        if selected.is_empty() {
+            return Err(Status::not_found(format!("No instance {:?}", id)));
+        }
+        Ok(selected)
+    }
+}
+
+/// Starts the gRPC control server when a bind address is configured. Like the admin
+/// server, it only listens on loopback addresses unless `allow_remote` is set, and a
+/// remote listener requires FACTORIO_GRPC_TOKEN.
+pub async fn start(settings: &GrpcSettings, service: ControlService) -> Result<(), String> {
+    let Some(ref bind) = settings.bind else {
+        return Ok(());
+    };
+    let token = std::env::var("FACTORIO_GRPC_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+
+    let listener = TcpListener::bind(bind)
+        .await
+        .map_err(|e| format!("Failed to bind gRPC server to {}: {}", bind, e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read gRPC server address: {}", e))?;
+
+    check_exposure(
+        "gRPC server",
+        addr,
+        settings.allow_remote,
+        token.is_some(),
+        "grpc.allow_remote",
+        "FACTORIO_GRPC_TOKEN",
+    )?;
+
+    let incoming = TcpIncoming::from_listener(listener, true, None)
+        .map_err(|e| format!("Failed to serve gRPC on {}: {}", addr, e))?;
+    println!(
+        "🛠️  gRPC control server listening on {} ({})",
+        addr,
+        if token.is_some() { "token required" } else { "no token, loopback only" }
+    );
+    let service = ControlServer::with_interceptor(service, move |request: Request<()>| {
+        require_token(token.as_deref(), request)
+    });
+    tokio::spawn(async move {
+        if let Err(e) = Server::builder()
+            .add_service(service)
+            .serve_with_incoming(incoming)
+            .await
+        {
+            eprintln!("⚠️  gRPC server stopped: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Rejects calls without `authorization: Bearer <token>` metadata when a token is set
+fn require_token(token: Option<&str>, request: Request<()>) -> Result<Request<()>, Status> {
+    if let Some(token) = token {
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if !presented.is_some_and(|presented| constant_time_eq(presented, token)) {
+            return Err(Status::unauthenticated("missing or invalid control token"));
+        }
+    }
+    Ok(request)
+}
+
+#[tonic::async_trait]
+impl Control for ControlService {
+    async fn status(&self, _request: Request<StatusRequest>) -> Result<Response<StatusReply>, Status> {
+        let mut instances = Vec::new();
+        for instance in &self.instances {
+            let sinks = instance
+                .mediator
+                .sink_status()
+                .into_iter()
+                .map(|sink| Sink {
+                    name: sink.name.to_string(),
+                    enabled: sink.enabled,
+                    reason: sink.reason.unwrap_or_default(),
+                })
+                .collect();
+            instances.push(InstanceStatus {
+                id: instance.id.clone().unwrap_or_default(),
+                run: instance.mediator.current_run().map(|(id, url)| Run { id, url }),
+                sessions: instance.mediator.active_sessions().await,
+                sinks,
+                queued: instance.cache.len() as u64,
+                dropped: instance.cache.dropped_count(),
+                udp_lost: instance.cache.udp_lost_count(),
+            });
+        }
+        Ok(Response::new(StatusReply {
+            uptime_secs: self.started.elapsed().as_secs(),
+            dry_run: self.instances.iter().any(|instance| instance.mediator.is_dry_run()),
+            instances,
+        }))
+    }
+
+    async fn flush(&self, request: Request<FlushRequest>) -> Result<Response<FlushReply>, Status> {
+        for instance in self.select(&request.get_ref().instance)? {
+            instance.mediator.flush().await;
+        }
+        Ok(Response::new(FlushReply {}))
+    }
+
+    async fn finish_session(
+        &self,
+        request: Request<FinishSessionRequest>,
+    ) -> Result<Response<FinishSessionReply>, Status> {
+        for instance in self.select(&request.get_ref().instance)? {
+            instance.mediator.request_rotation();
+        }
+        Ok(Response::new(FinishSessionReply {}))
+    }
+
+    async fn set_dry_run(
+        &self,
+        request: Request<SetDryRunRequest>,
+    ) -> Result<Response<SetDryRunReply>, Status> {
+        let enabled = request.get_ref().enabled;
+        let mut was_enabled = false;
+        for instance in &self.instances {
+            was_enabled |= instance.mediator.set_dry_run(enabled);
+        }
+        Ok(Response::new(SetDryRunReply { was_enabled }))
+    }
+
+    async fn inject_events(
+        &self,
+        request: Request<InjectEventsRequest>,
+    ) -> Result<Response<InjectEventsReply>, Status> {
+        let request = request.into_inner();
+        // Events go to one instance: the first one unless named
+        let instance = match self.select(&request.instance)?.first() {
+            Some(instance) => *instance,
+            None => return Err(Status::failed_precondition("No instance to inject into")),
+        };
+        println!("🛠️  Injecting {} event(s) over gRPC", request.events.len());
+        let summary = instance.mediator.process_events(request.events).await;
+        Ok(Response::new(InjectEventsReply {
+            routed: summary.routed as u64,
+            dropped: summary
+                .dropped
+                .into_iter()
+                .map(|(reason, count)| (reason, count as u64))
+                .collect(),
+        }))
+    }
+}
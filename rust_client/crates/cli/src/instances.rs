@@ -0,0 +1,112 @@
+use factorio_events_core::config::{AppConfig, RconSettings};
+use factorio_events_core::event_mediator::EventMediator;
+use factorio_events_sources::pipe_cache::PipeCache;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// One game instance served by the client, with its own cache and mediator (and so
+/// its own WandB runs and Weave traces)
+#[derive(Clone)]
+pub struct Instance {
+    pub id: Option<String>,
+    pub mediator: Arc<EventMediator>,
+    pub cache: Arc<PipeCache>,
+}
+
+/// Instance whose server the RCON address reaches: the only one, or the one named by
+/// `rcon.instance` when the client serves several
+pub fn rcon_instance<'a>(
+    instances: &'a [Instance],
+    settings: &RconSettings,
+) -> Result<&'a Instance, String> {
+    if let [instance] = instances {
+        return Ok(instance);
+    }
+    let Some(ref id) = settings.instance else {
+        return Err(format!(
+            "RCON polling disabled: {} instances are served, set rcon.instance \
+             (FACTORIO_RCON_INSTANCE) to the one whose server the address reaches",
+            instances.len()
+        ));
+    };
+    instances
+        .iter()
+        .find(|instance| instance.id.as_deref() == Some(id.as_str()))
+        .ok_or_else(|| format!("RCON polling disabled: no instance {:?}", id))
+}
+
+/// Splits the app config into one config per game instance. Each pipe of `pipe_paths`
+/// (after expanding glob patterns) becomes an instance named after its server
+/// directory; without `pipe_paths`, or with UDP or file ingestion, there is a single
+/// instance.
+pub fn resolve(config: &AppConfig) -> Result<Vec<AppConfig>, String> {
+    if config.pipe_paths.is_empty() {
+        return Ok(vec![config.clone()]);
+    }
+    if config.udp_bind.is_some() || config.tail_dir.is_some() {
+        eprintln!("⚠️  pipe_paths is ignored with UDP or file ingestion, serving a single instance");
+        return Ok(vec![config.clone()]);
+    }
+
+    let mut pipes = Vec::new();
+    for pattern in &config.pipe_paths {
+        let matches =
+            glob::glob(pattern).map_err(|e| format!("Invalid pipe pattern {:?}: {}", pattern, e))?;
+        let before = pipes.len();
+        for path in matches.flatten() {
+            pipes.push(path.to_string_lossy().into_owned());
+        }
+        // A plain path that doesn't exist yet is still served; the reader waits for it
+        if pipes.len() == before && !pattern.contains(['*', '?', '[']) {
+            pipes.push(pattern.clone());
+        }
+    }
+    if pipes.is_empty() {
+        return Err(format!("No pipes match {:?}", config.pipe_paths));
+    }
+
+    let mut ids = HashSet::new();
+    let mut instances = Vec::new();
+    for pipe in pipes {
+        let id = instance_id(Path::new(&pipe));
+        if !ids.insert(id.clone()) {
+            return Err(format!("Two pipes map to instance id {:?}: rename a server directory", id));
+        }
+
+        let mut instance = config.clone();
+        instance.pipe_paths.clear();
+        instance.log_path = config.log_path.as_deref().map(|log| instance_log_path(log, &id));
+        instance.pipe_path = pipe;
+        instance.instance = Some(id);
+        instances.push(instance);
+    }
+    Ok(instances)
+}
+
+/// Names an instance after the directory holding its `script-output` directory
+/// (`/srv/factorio/alpha/script-output/events.pipe` -> `alpha`), or else after the
+/// pipe's own directory
+fn instance_id(pipe: &Path) -> String {
+    let parent = pipe.parent();
+    let server_dir = match parent {
+        Some(dir) if dir.file_name().is_some_and(|name| name == "script-output") => dir.parent(),
+        _ => parent,
+    };
+    server_dir
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| pipe.to_string_lossy().into_owned())
+}
+
+/// Gives each instance its own capture log: `events.log` -> `events.alpha.log`
+fn instance_log_path(log: &str, id: &str) -> String {
+    let path = Path::new(log);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => path
+            .with_file_name(format!("{}.{}.{}", stem.to_string_lossy(), id, ext.to_string_lossy()))
+            .to_string_lossy()
+            .into_owned(),
+        _ => format!("{}.{}", log, id),
+    }
+}
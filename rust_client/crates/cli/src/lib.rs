@@ -0,0 +1,11 @@
+//! The `rust_client` binary: startup, instances, and the admin, control and WebSocket
+//! servers that wire the sink crates together.
+
+pub mod admin_server;
+pub mod cli;
+pub mod grpc_server;
+pub mod instances;
+pub mod panic_report;
+pub mod session_bundle;
+pub mod watchdog;
+pub mod ws_server;
@@ -0,0 +1,893 @@
+use clap::Parser;
+use factorio_events_cli::admin_server::{self, AdminState};
+use factorio_events_cli::cli::{Cli, Command, GlobalOptions};
+use factorio_events_cli::grpc_server::{self, ControlService};
+use factorio_events_cli::instances::{self, Instance};
+use factorio_events_cli::panic_report;
+use factorio_events_cli::session_bundle;
+use factorio_events_cli::watchdog::{self, RestartPolicy};
+use factorio_events_cli::ws_server;
+use factorio_events_core::capture_log;
+use factorio_events_core::client_events::{self, ClientEvent, EventTarget};
+use factorio_events_core::config::AppConfig;
+use factorio_events_core::correlation::StepCorrelation;
+use factorio_events_core::event_mediator::{EventMediator, FactorioEvent, WorkerManagers};
+use factorio_events_core::event_sink::{EventRecorder, Leaderboard};
+use factorio_events_core::project_routing::ProjectRouter;
+use factorio_events_core::rotation::RotationPolicy;
+use factorio_events_core::run_index::RunIndex;
+use factorio_events_core::run_metadata::RunMetadata;
+use factorio_events_core::run_report;
+use factorio_events_core::upload_budget::UploadBudget;
+use factorio_events_sinks_export::session_export::SessionExporter;
+use factorio_events_sinks_influx::influx_sink::InfluxSink;
+use factorio_events_sinks_kafka::kafka_producer::KafkaProducer;
+use factorio_events_sinks_live::dashboard::Dashboard;
+use factorio_events_sinks_live::metrics_stream::MetricsStream;
+use factorio_events_sinks_mqtt::mqtt_sink::MqttSink;
+use factorio_events_sinks_report::completion_report::CompletionReporter;
+use factorio_events_sinks_wandb::tournament::Tournament;
+use factorio_events_sinks_wandb::wandb_manager::WandbManager;
+use factorio_events_sinks_weave::weave_manager::WeaveManager;
+use factorio_events_sinks_webhook::notifications::Notifier;
+use factorio_events_sinks_webhook::webhook_sink::WebhookSink;
+use factorio_events_sources::emitter::{EmitTarget, Emitter};
+use factorio_events_sources::ingest_wal::IngestWal;
+use factorio_events_sources::pipe_cache::{OverflowPolicy, PipeCache};
+use factorio_events_sources::rcon_client;
+use factorio_events_storage::event_store::EventStore;
+use factorio_events_storage::run_index::{self, SqliteRunIndex};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::{sleep, Duration};
+
+/// Factorio runs at 60 ticks per second of game time
+const TICKS_PER_SECOND: f64 = 60.0;
+
+/// Settings of the drain loops, reloaded from the config file on SIGHUP
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DrainSettings {
+    interval: Duration,
+    /// Gathering window after the first event wakes the loop, None to only follow the
+    /// interval
+    batch: Option<Duration>,
+    deadline: Duration,
+    /// Max events carried over between cycles
+    capacity: usize,
+}
+
+impl DrainSettings {
+    fn from_config(config: &AppConfig) -> Self {
+        Self {
+            interval: config.drain_interval(),
+            batch: config.drain_batch(),
+            deadline: config.cycle_deadline(),
+            capacity: config.cache_capacity,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let options = &cli.options;
+
+    if cli.print_config_schema {
+        exit_on_error(AppConfig::schema().map(|schema| {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema).unwrap_or_default()
+            );
+        }));
+        return;
+    }
+
+    // Offline commands that don't start the client
+    let offline = match cli.command {
+        Some(Command::Validate { ref file }) => Some(capture_log::validate(file)),
+        Some(Command::Stats { ref file }) => Some(stats(file.clone(), options)),
+        Some(Command::DiffRuns {
+            ref run_a,
+            ref run_b,
+            rows,
+        }) => Some(run_report::diff_runs(run_a, run_b, rows)),
+        Some(Command::FindSession { ref session_id }) => Some(
+            options
+                .load_config()
+                .and_then(|config| run_index::find_session(session_id, &config)),
+        ),
+        Some(Command::Export {
+            ref session_id,
+            ref output,
+        }) => Some(
+            options
+                .load_config()
+                .and_then(|config| session_bundle::export(session_id, output.as_deref(), &config)),
+        ),
+        Some(Command::Emit {
+            ref events,
+            ref target,
+            ref source,
+        }) => Some(emit(events, target.as_deref(), source, options)),
+        Some(Command::Run) | Some(Command::Replay { .. }) | Some(Command::Import { .. }) | None => {
+            None
+        }
+    };
+    if let Some(result) = offline {
+        exit_on_error(result);
+        return;
+    }
+
+    println!("Starting Factorio Rust Client...");
+
+    if let Some(ref target) = options.json_events {
+        if let Err(e) = EventTarget::parse(target).and_then(client_events::init) {
+            eprintln!("⚠️  {}", e);
+        }
+    }
+
+    // Settings come from the config file (if any), env vars and command-line flags
+    let config = match options.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Record panics (with backtrace) in the capture log
+    panic_report::install(config.log_path.clone());
+
+    if let Some(Command::Replay { ref file, speed }) = cli.command {
+        exit_on_error(replay(file, speed, &config, options).await);
+        return;
+    }
+    if let Some(Command::Import { ref bundle, speed }) = cli.command {
+        exit_on_error(import(bundle, speed, &config, options).await);
+        return;
+    }
+
+    let instance_configs = match instances::resolve(&config) {
+        Ok(instance_configs) => instance_configs,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    for instance in &instance_configs {
+        let prefix = instance
+            .instance
+            .as_ref()
+            .map(|id| format!("[{}] ", id))
+            .unwrap_or_default();
+        match (&instance.udp_bind, &instance.tail_dir) {
+            (Some(bind), _) => println!("{}UDP address: {}", prefix, bind),
+            (None, Some(dir)) => println!("{}Tailed directory: {}", prefix, dir),
+            (None, None) => println!("{}Pipe path: {}", prefix, instance.pipe_path),
+        }
+        if let Some(ref log) = instance.log_path {
+            println!("{}Log path: {}", prefix, log);
+        }
+    }
+
+    let drain_settings = Arc::new(Mutex::new(DrainSettings::from_config(&config)));
+
+    if options.dry_run {
+        println!("🧪 Dry run: events are classified but nothing is uploaded");
+        let cache = Arc::new(PipeCache::new(
+            config.cache_capacity,
+            OverflowPolicy::from_settings(&config.cache_overflow),
+        ));
+        for instance in &instance_configs {
+            start_ingest(&cache, instance);
+        }
+        dry_run_loop(cache, config.drain_interval()).await;
+        return;
+    }
+
+    // Create WandB manager, Weave manager, and event mediator of every instance,
+    // and start their background readers
+    let metadata = RunMetadata::from_settings_and_pairs(&config.metadata, &options.meta);
+    let upload_budget = UploadBudget::from_settings(&config.upload_budget);
+    let router = Arc::new(ProjectRouter::from_settings(&config.project_routes));
+    // Metrics of every instance are streamed to the WebSocket `/metrics` subscribers
+    let metrics_stream = MetricsStream::new();
+    let influx = InfluxSink::from_settings(&config.influx);
+    let mqtt = MqttSink::from_settings(&config.mqtt);
+    let exporter = SessionExporter::from_settings(&config.export);
+    let kafka = KafkaProducer::from_settings(&config.kafka);
+    let webhooks = WebhookSink::from_settings(&config.webhooks);
+    let notifier = Notifier::from_settings(&config.notifications);
+    let reporter = CompletionReporter::from_settings(&config.completion_report);
+    let dashboard = Dashboard::from_settings(&config.admin, config.output_path.as_deref());
+    let instances: Vec<Instance> = instance_configs
+        .iter()
+        .map(|instance_config| {
+            let cache = Arc::new(
+                PipeCache::new(
+                    instance_config.cache_capacity,
+                    OverflowPolicy::from_settings(&instance_config.cache_overflow),
+                )
+                .with_immediate(&instance_config.immediate_events),
+            );
+            let stream = metrics_stream.for_instance(instance_config.instance.clone());
+            let mut mediator = build_mediator(
+                instance_config,
+                metadata.clone(),
+                upload_budget.clone(),
+                router.clone(),
+            )
+            .with_sink(Arc::new(stream))
+            .with_recorders(recorders(instance_config, kafka.as_ref()));
+            if let Some(ref influx) = influx {
+                mediator = mediator.with_sink(Arc::new(
+                    influx.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            if let Some(ref mqtt) = mqtt {
+                mediator = mediator.with_sink(Arc::new(
+                    mqtt.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            if let Some(ref exporter) = exporter {
+                mediator = mediator.with_sink(Arc::new(
+                    exporter.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            if let Some(ref webhooks) = webhooks {
+                mediator = mediator.with_sink(Arc::new(
+                    webhooks.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            if let Some(ref notifier) = notifier {
+                mediator = mediator.with_sink(Arc::new(
+                    notifier.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            if let Some(ref reporter) = reporter {
+                mediator = mediator.with_sink(Arc::new(
+                    reporter.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            if let Some(ref dashboard) = dashboard {
+                mediator = mediator.with_sink(Arc::new(
+                    dashboard.for_instance(instance_config.instance.clone()),
+                ));
+            }
+            let mediator = Arc::new(mediator);
+            client_events::emit(ClientEvent::Started {
+                pipe_path: instance_config.pipe_path.clone(),
+            });
+            start_ingest(&cache, instance_config);
+            Instance {
+                id: instance_config.instance.clone(),
+                mediator,
+                cache,
+            }
+        })
+        .collect();
+    let mediators: Vec<Arc<EventMediator>> = instances
+        .iter()
+        .map(|instance| instance.mediator.clone())
+        .collect();
+
+    // Launch the Weave sidecars now, so a misconfiguration shows before the game starts
+    if config.weave.preflight {
+        for mediator in &mediators {
+            if let Err(e) = mediator.preflight().await {
+                eprintln!("❌ Weave preflight failed: {}", e);
+                client_events::emit(ClientEvent::Error {
+                    component: "weave".to_string(),
+                    message: format!("Preflight failed: {}", e),
+                });
+                if config.weave.preflight_required {
+                    std::process::exit(1);
+                }
+                eprintln!("⚠️  Weave will be started again at the first event");
+            }
+        }
+    }
+
+    // Resume runs cut short by a crash before any new event is processed
+    if let Some(max_age) = config.warm_start() {
+        for mediator in &mediators {
+            mediator.warm_start(max_age).await;
+        }
+    }
+
+    // Optionally poll the server over RCON alongside the events the mod pushes; the
+    // metrics go to the instance of that server
+    if config.rcon.address.is_some() {
+        match instances::rcon_instance(&instances, &config.rcon) {
+            Ok(instance) => {
+                let mediator_rcon = instance.mediator.clone();
+                rcon_client::start_polling(config.rcon.clone(), upload_budget, move |metrics| {
+                    mediator_rcon.log_client_metrics(metrics)
+                });
+            }
+            Err(e) => eprintln!("⚠️  {}", e),
+        }
+    }
+
+    // Events pushed over WebSocket go to the instance each connection names
+    if let Err(e) = ws_server::start(&config.websocket, instances.clone(), metrics_stream).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+
+    let drains: Vec<_> = instances
+        .iter()
+        .map(|instance| {
+            let wal = if config.wal.enabled {
+                open_wal(&config, instance.id.as_deref())
+            } else {
+                None
+            };
+            (instance.cache.clone(), instance.mediator.clone(), wal)
+        })
+        .collect();
+    let control = ControlService::new(instances.clone());
+    if let Err(e) = grpc_server::start(&config.grpc, control).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+    let admin = AdminState::new(instances).with_dashboard(dashboard);
+    if let Err(e) = admin_server::start(&config.admin, admin).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+
+    println!("Pipe reader started. Monitoring events...\n");
+
+    // Set up graceful shutdown on Ctrl-C, or SIGTERM from systemd and docker
+    let mediators_shutdown = mediators.clone();
+    tokio::spawn(async move {
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(signals) => Some(signals),
+            Err(e) => {
+                eprintln!("⚠️  Failed to install SIGTERM handler: {}", e);
+                None
+            }
+        };
+        let received = tokio::select! {
+            _ = tokio::signal::ctrl_c() => "Ctrl-C",
+            _ = async {
+                match terminate.as_mut() {
+                    Some(signals) => signals.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => "SIGTERM",
+        };
+        println!("\n🛑 Received {}, cleaning up...", received);
+        for mediator in &mediators_shutdown {
+            mediator.shutdown().await;
+        }
+        client_events::emit(ClientEvent::Shutdown);
+        std::process::exit(0);
+    });
+
+    // SIGUSR2 finishes the current runs; the next event of each instance starts a fresh one
+    let mediators_rotate = mediators;
+    tokio::spawn(async move {
+        let mut signals = match signal(SignalKind::user_defined2()) {
+            Ok(signals) => signals,
+            Err(e) => {
+                eprintln!("⚠️  Failed to install SIGUSR2 handler: {}", e);
+                return;
+            }
+        };
+        while signals.recv().await.is_some() {
+            for mediator in &mediators_rotate {
+                mediator.request_rotation();
+            }
+        }
+    });
+
+    // SIGHUP reloads the config file; the drain settings apply from the next cycle, and
+    // the current runs and Weave sessions go on
+    let reload_options = options.clone();
+    let reload_settings = drain_settings.clone();
+    tokio::spawn(async move {
+        let mut signals = match signal(SignalKind::hangup()) {
+            Ok(signals) => signals,
+            Err(e) => {
+                eprintln!("⚠️  Failed to install SIGHUP handler: {}", e);
+                return;
+            }
+        };
+        while signals.recv().await.is_some() {
+            reload_config(&reload_options, &reload_settings);
+        }
+    });
+
+    // Process events of every instance by draining its queue, restarting the loop if it dies
+    let supervisors: Vec<_> = drains
+        .into_iter()
+        .map(|(cache, mediator, wal)| {
+            let mediator_panic = mediator.clone();
+            let drain_settings = drain_settings.clone();
+            tokio::spawn(watchdog::supervise(
+                "drain loop",
+                RestartPolicy::from_settings(&config.watchdog),
+                move || {
+                    drain_loop(
+                        cache.clone(),
+                        mediator.clone(),
+                        wal.clone(),
+                        drain_settings.clone(),
+                    )
+                },
+                move |panic_message| {
+                    let mediator = mediator_panic.clone();
+                    async move { mediator.report_panic(&panic_message).await }
+                },
+            ))
+        })
+        .collect();
+    for supervisor in supervisors {
+        supervisor.await.ok();
+    }
+}
+
+/// Reloads the config file after a SIGHUP and applies its drain settings. A config
+/// that fails to load keeps the current settings.
+fn reload_config(options: &GlobalOptions, settings: &Mutex<DrainSettings>) {
+    let config = match options.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!(
+                "⚠️  Config reload failed, keeping the current settings: {}",
+                e
+            );
+            return;
+        }
+    };
+    let reloaded = DrainSettings::from_config(&config);
+    let mut settings = settings.lock().unwrap();
+    if *settings == reloaded {
+        println!("🔄 Config reloaded, drain settings unchanged");
+    } else {
+        println!(
+            "🔄 Config reloaded: drain interval {:?}, cycle deadline {:?}, backlog capacity {}",
+            reloaded.interval, reloaded.deadline, reloaded.capacity
+        );
+        *settings = reloaded;
+    }
+    client_events::emit(ClientEvent::ConfigReloaded);
+}
+
+/// Prints the error of a finished command and exits nonzero
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Starts the background reader of the pipe, or of UDP datagrams or tailed files
+/// when configured
+fn start_ingest(cache: &PipeCache, config: &AppConfig) {
+    let log_path = config.log_path.clone();
+    match (&config.udp_bind, &config.tail_dir) {
+        (Some(bind), _) => cache.start_udp_reader(bind.clone(), log_path),
+        (None, Some(dir)) => cache.start_file_tail(dir.clone(), log_path),
+        (None, None) => cache.start_reader(config.pipe_path.clone(), log_path),
+    }
+}
+
+/// Opens the write-ahead log of an instance, running without one when it can't be opened
+fn open_wal(config: &AppConfig, instance: Option<&str>) -> Option<Arc<Mutex<IngestWal>>> {
+    let dir = IngestWal::dir_for(config.wal.dir.as_ref(), instance);
+    match IngestWal::open(dir) {
+        Ok(wal) => Some(Arc::new(Mutex::new(wal))),
+        Err(e) => {
+            eprintln!("⚠️  Write-ahead log disabled: {}", e);
+            None
+        }
+    }
+}
+
+/// Opens the recorders of an instance: its event store, when enabled, and its handle on
+/// the shared Kafka producer
+fn recorders(config: &AppConfig, kafka: Option<&KafkaProducer>) -> Vec<Box<dyn EventRecorder>> {
+    let mut recorders: Vec<Box<dyn EventRecorder>> = Vec::new();
+    if config.store_events {
+        match EventStore::open(&EventStore::path(config), config.instance.clone()) {
+            Ok(store) => recorders.push(Box::new(store)),
+            Err(e) => eprintln!("⚠️  Event store disabled: {}", e),
+        }
+    }
+    if let Some(kafka) = kafka {
+        recorders.push(Box::new(kafka.for_instance(config.instance.clone())));
+    }
+    recorders
+}
+
+/// Creates the WandB and Weave managers of one event worker, which share their step
+/// alignment
+fn managers(
+    config: &AppConfig,
+    metadata: &RunMetadata,
+    upload_budget: &Option<Arc<UploadBudget>>,
+    router: &Arc<ProjectRouter>,
+) -> (WandbManager, WeaveManager) {
+    let correlation = Arc::new(StepCorrelation::new());
+    let wandb_manager = WandbManager::new(config)
+        .with_project_router(router.clone())
+        .with_upload_budget(upload_budget.clone())
+        .with_metadata(metadata.clone())
+        .with_correlation(correlation.clone());
+    let weave_manager = WeaveManager::new(config)
+        .with_project_router(router.clone())
+        .with_upload_budget(upload_budget.clone())
+        .with_metadata(metadata.clone())
+        .with_correlation(correlation);
+    (wandb_manager, weave_manager)
+}
+
+/// Settings of the managers of event worker `worker` (from 1): a state directory and
+/// weave-sender socket of their own, named after the instance and the worker
+fn worker_config(config: &AppConfig, worker: usize) -> AppConfig {
+    let mut config = config.clone();
+    config.instance = Some(match config.instance {
+        Some(ref instance) => format!("{}-worker{}", instance, worker),
+        None => format!("worker{}", worker),
+    });
+    config
+}
+
+/// Creates the managers of the first event worker and the mediator routing events to
+/// them, which builds those of the other workers as sessions are assigned to them.
+/// Instances of one process share the metadata given at launch, the upload budget and
+/// the project routes.
+fn build_mediator(
+    config: &AppConfig,
+    mut metadata: RunMetadata,
+    upload_budget: Option<Arc<UploadBudget>>,
+    router: Arc<ProjectRouter>,
+) -> EventMediator {
+    if let Some(ref instance) = config.instance {
+        metadata.insert("instance", instance);
+    }
+    let (wandb_manager, weave_manager) = managers(config, &metadata, &upload_budget, &router);
+    let worker_settings = config.clone();
+    let worker_managers = move |worker: usize| {
+        let config = worker_config(&worker_settings, worker);
+        let (wandb_manager, weave_manager) = managers(&config, &metadata, &upload_budget, &router);
+        WorkerManagers::new(wandb_manager, weave_manager)
+    };
+    let index_path = SqliteRunIndex::path(config);
+    let run_index = match SqliteRunIndex::open(&index_path, config.log_path.clone()) {
+        Ok(index) => Some(Box::new(index) as Box<dyn RunIndex>),
+        Err(e) => {
+            eprintln!("⚠️  Run index disabled: {}", e);
+            None
+        }
+    };
+    EventMediator::new(wandb_manager, weave_manager)
+        .with_run_index(run_index)
+        .with_instance(config.instance.clone())
+        .with_status_interval(config.status_interval())
+        .with_workers(config.event_workers, worker_managers)
+        .with_rotation(RotationPolicy::from_settings(&config.rotation))
+        .with_maps(&config.maps)
+        .with_leaderboard(
+            Tournament::from_settings(&config.tournament)
+                .map(|t| Box::new(t) as Box<dyn Leaderboard>),
+        )
+}
+
+/// Runs `stats [file]` on the given capture log or the configured one
+fn stats(file: Option<PathBuf>, options: &GlobalOptions) -> Result<(), String> {
+    let file = match file {
+        Some(file) => file,
+        None => options
+            .load_config()?
+            .log_path
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                "No capture log given, and neither log_path in the config file nor \
+                 FACTORIO_LOG_PATH is set"
+                    .to_string()
+            })?,
+    };
+    capture_log::stats(&file)
+}
+
+/// Runs `emit`: writes the given events, or each line of stdin, to the target
+fn emit(
+    events: &[String],
+    target: Option<&str>,
+    source: &str,
+    options: &GlobalOptions,
+) -> Result<(), String> {
+    let target = match target {
+        Some(target) => EmitTarget::parse(target),
+        None => {
+            let config = options.load_config()?;
+            match config.udp_bind {
+                Some(addr) => EmitTarget::Udp(addr),
+                None => EmitTarget::Pipe(PathBuf::from(config.pipe_path)),
+            }
+        }
+    };
+    let mut emitter = Emitter::connect(&target, source)?;
+
+    let mut sent = 0;
+    if events.is_empty() {
+        for line in std::io::stdin().lock().lines() {
+            let line = line.map_err(|e| format!("Failed to read stdin: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            emitter.emit_line(&line)?;
+            sent += 1;
+        }
+    } else {
+        for event in events {
+            emitter.emit_line(event)?;
+            sent += 1;
+        }
+    }
+    eprintln!("✅ Emitted {} events", sent);
+    Ok(())
+}
+
+/// Runs `replay <file>`: feeds a capture log through the mediator in tick order and in
+/// cache-sized batches, as if the events had just come from the pipe. With `speed`,
+/// batches span one drain interval of replayed time and are paced at that multiple of
+/// game speed, so historical sessions keep their timing.
+async fn replay(
+    file: &Path,
+    speed: Option<f64>,
+    config: &AppConfig,
+    options: &GlobalOptions,
+) -> Result<(), String> {
+    let lines = capture_log::read_lines(file)?;
+    println!(
+        "🔁 Replaying {} events from {}",
+        lines.len(),
+        file.display()
+    );
+
+    if options.dry_run {
+        capture_log::print_summary(&capture_log::classify_all(&lines));
+        return Ok(());
+    }
+
+    let speed = match speed {
+        Some(speed) if speed.is_nan() || speed <= 0.0 => {
+            return Err(format!("Invalid replay speed {}", speed))
+        }
+        speed => speed,
+    };
+    // Game ticks covered by one batch when pacing
+    let window_ticks = speed.map(|speed| {
+        (config.drain_interval().as_secs_f64() * TICKS_PER_SECOND * speed).max(1.0) as u64
+    });
+
+    let metadata = RunMetadata::from_settings_and_pairs(&config.metadata, &options.meta);
+    // Replayed events are already recorded, so the mediator gets no recorders
+    let router = Arc::new(ProjectRouter::from_settings(&config.project_routes));
+    let mediator = build_mediator(
+        config,
+        metadata,
+        UploadBudget::from_settings(&config.upload_budget),
+        router,
+    );
+    let capacity = config.cache_capacity.max(1);
+
+    let mut batch = Vec::new();
+    let mut batch_start = None;
+    for (tick, line) in capture_log::sort_by_tick(lines) {
+        let start = *batch_start.get_or_insert(tick);
+        let window_done = window_ticks.is_some_and(|window| tick >= start + window);
+        if !batch.is_empty() && (window_done || batch.len() >= capacity) {
+            mediator.process_events(std::mem::take(&mut batch)).await;
+            if let Some(speed) = speed {
+                let game_secs = (tick - start) as f64 / TICKS_PER_SECOND;
+                sleep(Duration::from_secs_f64(game_secs / speed)).await;
+            }
+            batch_start = Some(tick);
+        }
+        batch.push(line);
+    }
+    mediator.process_events(batch).await;
+    mediator.shutdown().await;
+    Ok(())
+}
+
+/// Runs `import <bundle>`: unpacks a session bundle and replays its events, reading the
+/// screenshots from the bundle, so a session recorded on another machine is backfilled
+/// into this one's WandB and Weave projects
+async fn import(
+    bundle: &Path,
+    speed: Option<f64>,
+    config: &AppConfig,
+    options: &GlobalOptions,
+) -> Result<(), String> {
+    let imported = session_bundle::unpack(bundle)?;
+    if !options.dry_run {
+        imported.adopt_trace(config)?;
+    }
+    // Screenshot paths of the events are relative to the Factorio output directory
+    let mut config = config.clone();
+    config.output_path = Some(imported.screenshot_dir().to_string_lossy().into_owned());
+    replay(&imported.events_path(), speed, &config, options).await
+}
+
+/// Drains the cache like the drain loop but only reports how events would be handled
+async fn dry_run_loop(cache: Arc<PipeCache>, interval: Duration) {
+    loop {
+        sleep(interval).await;
+
+        let events = cache.drain_all();
+        if events.is_empty() {
+            continue;
+        }
+        capture_log::print_summary(&capture_log::classify_events(&events));
+    }
+}
+
+/// Drains the cache shortly after events arrive (or once per drain interval without
+/// wakeups) and processes the events through the mediator. A cycle
+/// stops at the deadline and carries the remaining events to the next one, logging the
+/// backlog as `ingest/backlog` so sustained overload is visible. With a write-ahead log,
+/// drained events are logged before they are processed, and the loop starts with the
+/// events a crash (or a restart of the loop) left unprocessed.
+async fn drain_loop(
+    cache: Arc<PipeCache>,
+    mediator: Arc<EventMediator>,
+    mut wal: Option<Arc<Mutex<IngestWal>>>,
+    settings: Arc<Mutex<DrainSettings>>,
+) {
+    let mut reported_drops = cache.dropped_count();
+    let mut reported_lost = cache.udp_lost_count();
+    let mut reported_blocked = cache.blocked_count();
+    let mut reported_parse_errors = cache.parse_error_count();
+    let mut backlog: Vec<FactorioEvent> = Vec::new();
+    if let Some(ref log) = wal {
+        match log.lock().unwrap().pending() {
+            Ok(pending) => backlog = pending,
+            Err(e) => eprintln!("⚠️  Failed to read the write-ahead log: {}", e),
+        }
+        if !backlog.is_empty() {
+            println!(
+                "📶 Replaying {} events left unprocessed in the write-ahead log",
+                backlog.len()
+            );
+        }
+    }
+    loop {
+        let DrainSettings {
+            interval,
+            batch,
+            deadline,
+            capacity,
+        } = *settings.lock().unwrap();
+        // Wake at the first event to keep traces current, with the interval as the cap.
+        // A milestone event cuts the wait and the gathering window short.
+        let urgent = match batch {
+            Some(batch) => tokio::select! {
+                _ = cache.wait_for_urgent() => true,
+                _ = async {
+                    cache.wait_for_events().await;
+                    sleep(batch).await
+                } => false,
+                _ = sleep(interval) => false,
+            },
+            None => tokio::select! {
+                _ = cache.wait_for_urgent() => true,
+                _ = sleep(interval) => false,
+            },
+        };
+
+        // Drain all events from the cache, after the ones carried over
+        let carried = backlog.len();
+        let mut events = std::mem::take(&mut backlog);
+        let drained = cache.drain_all();
+        if let Some(log) = wal.clone() {
+            if let Err(e) = log.lock().unwrap().append(&drained) {
+                eprintln!("❌ Write-ahead log disabled: {}", e);
+                wal = None;
+            }
+        }
+        events.extend(drained);
+        let total = events.len();
+
+        let dropped = cache.dropped_count();
+        if dropped > reported_drops {
+            client_events::emit(ClientEvent::EventsDropped {
+                count: dropped - reported_drops,
+                reason: "cache_overflow".to_string(),
+            });
+            reported_drops = dropped;
+        }
+
+        // Lines the readers couldn't parse never reach the queue
+        let parse_errors = cache.parse_error_count() - reported_parse_errors;
+        if parse_errors > 0 {
+            client_events::emit(ClientEvent::EventsDropped {
+                count: parse_errors,
+                reason: "parse_error".to_string(),
+            });
+            reported_parse_errors += parse_errors;
+        }
+
+        // Readers held back by a full cache under the `block` policy
+        let blocked = cache.blocked_count();
+        if blocked != reported_blocked {
+            mediator.log_client_metrics(HashMap::from([(
+                "ingest/blocked".to_string(),
+                blocked as f64,
+            )]));
+            reported_blocked = blocked;
+        }
+
+        let lost = cache.udp_lost_count();
+        if lost != reported_lost {
+            if lost > reported_lost {
+                client_events::emit(ClientEvent::EventsDropped {
+                    count: lost - reported_lost,
+                    reason: "udp_loss".to_string(),
+                });
+            }
+            mediator.log_client_metrics(HashMap::from([(
+                "ingest/udp_lost".to_string(),
+                lost as f64,
+            )]));
+            reported_lost = lost;
+        }
+
+        // Process events through the mediator (async) until the cycle deadline
+        let (_, mut remaining) = mediator
+            .process_events_within(
+                events,
+                parse_errors as usize,
+                Some(Instant::now() + deadline),
+            )
+            .await;
+        if total == 0 {
+            mediator.report_idle().await;
+        }
+        // Push the milestone out of the sinks' own queues instead of waiting for their next
+        // send; with a write-ahead log the commit below flushes them anyway
+        if urgent && total > 0 && wal.is_none() {
+            mediator.flush().await;
+        }
+
+        // Keep the backlog bounded like the cache, dropping the oldest events
+        if remaining.len() > capacity {
+            let overflow = remaining.len() - capacity;
+            remaining.drain(..overflow);
+            client_events::emit(ClientEvent::EventsDropped {
+                count: overflow as u64,
+                reason: "backlog_overflow".to_string(),
+            });
+        }
+
+        // Processed and dropped events leave the log once the sinks hold them
+        let done = total - remaining.len();
+        if done > 0 {
+            if let Some(log) = wal.clone() {
+                mediator.flush().await;
+                if let Err(e) = log.lock().unwrap().commit(done) {
+                    eprintln!("❌ Write-ahead log disabled: {}", e);
+                    wal = None;
+                }
+            }
+        }
+
+        if !remaining.is_empty() || carried > 0 {
+            mediator.log_client_metrics(HashMap::from([(
+                "ingest/backlog".to_string(),
+                remaining.len() as f64,
+            )]));
+        }
+        backlog = remaining;
+    }
+}
@@ -0,0 +1,89 @@
+use std::backtrace::Backtrace;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Mutex;
+
+/// A panic captured by the hook, waiting to be reported to the run
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    pub thread: String,
+    pub message: String,
+    pub location: String,
+    pub backtrace: String,
+}
+
+impl PanicReport {
+    /// One-line description used as the exception of force-ended calls
+    pub fn summary(&self) -> String {
+        format!("{} at {} (thread '{}')", self.message, self.location, self.thread)
+    }
+}
+
+static LAST_PANIC: Mutex<Option<PanicReport>> = Mutex::new(None);
+
+/// Installs a panic hook that records panics with a backtrace in the capture log
+/// (as a `client_panic` line next to the raw events) and keeps the latest one for
+/// the watchdog to report. The default hook still prints to stderr.
+pub fn install(capture_log: Option<String>) {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let report = capture(info);
+        eprintln!("❌ Panic captured: {}\n{}", report.summary(), report.backtrace);
+
+        if let Some(ref path) = capture_log {
+            let line = serde_json::json!({
+                "type": "client_panic",
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "thread": report.thread,
+                "message": report.message,
+                "location": report.location,
+                "backtrace": report.backtrace,
+            });
+            // Appends of a single line keep the log readable alongside the reader thread
+            match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", line).ok();
+                }
+                Err(e) => eprintln!("⚠️  Failed to write panic to capture log: {}", e),
+            }
+        }
+
+        *LAST_PANIC
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(report);
+    }));
+}
+
+/// Takes the most recent panic captured by the hook, if any
+pub fn take_last() -> Option<PanicReport> {
+    LAST_PANIC
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .take()
+}
+
+fn capture(info: &PanicHookInfo) -> PanicReport {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string());
+
+    PanicReport {
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("<unnamed>")
+            .to_string(),
+        message,
+        location: info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "<unknown>".to_string()),
+        backtrace: Backtrace::force_capture().to_string(),
+    }
+}
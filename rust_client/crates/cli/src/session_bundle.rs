@@ -0,0 +1,314 @@
+use factorio_events_core::capture_log;
+use factorio_events_core::config::{state_dir, AppConfig};
+use factorio_events_core::event_mediator::{parse_event, FactorioEvent};
+use factorio_events_core::run_index::{RunIndex, RunRecord};
+use factorio_events_sinks_weave::trace_store::{SessionTrace, TraceStore};
+use factorio_events_sinks_weave::weave_client::trace_store_path;
+use factorio_events_storage::event_store::EventStore;
+use factorio_events_storage::run_index::SqliteRunIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Version of the bundle layout, bumped when an importer couldn't read older bundles
+const BUNDLE_FORMAT: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const EVENTS: &str = "events.jsonl";
+const CONFIG: &str = "config.json";
+const RUNS: &str = "runs.json";
+const WEAVE_TRACE: &str = "weave_trace.json";
+const SCREENSHOTS: &str = "screenshots";
+
+/// What a bundle holds; the first entry of the archive so an import can check it before
+/// unpacking anything else
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub session_id: String,
+    pub exported_at: String,
+    pub client_version: String,
+    /// Where the events came from: `event_store` or the capture logs read
+    pub source: String,
+    pub events: usize,
+    /// Screenshot paths, relative to `output_path` like in the stats events
+    pub screenshots: Vec<String>,
+    /// Screenshots referenced by the events but not found locally
+    pub missing_screenshots: Vec<String>,
+}
+
+/// Runs `export <session_id>`: gathers the session's events (from the event store, else
+/// the capture logs), the screenshots they reference, the config, and the runs and
+/// Weave trace published for it into one `.tar.zst` bundle
+pub fn export(session_id: &str, output: Option<&Path>, config: &AppConfig) -> Result<(), String> {
+    let runs = find_runs(session_id, config)?;
+    let (source, lines) = session_lines(session_id, config, &runs)?;
+    if lines.is_empty() {
+        return Err(format!("No local events found for session {}", session_id));
+    }
+
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.zst", session_id)));
+    let file =
+        File::create(&output).map_err(|e| format!("Failed to create {:?}: {}", output, e))?;
+    let encoder = zstd::Encoder::new(file, 0)
+        .map_err(|e| format!("Failed to start compressing {:?}: {}", output, e))?;
+    let mut archive = tar::Builder::new(encoder);
+
+    let output_dir = config.output_path.as_ref().map(PathBuf::from);
+    let mut screenshots = Vec::new();
+    let mut missing_screenshots = Vec::new();
+    for path in screenshot_paths(&lines) {
+        match output_dir.as_ref().map(|dir| dir.join(&path)) {
+            Some(full_path) if full_path.is_file() => screenshots.push((path, full_path)),
+            _ => missing_screenshots.push(path),
+        }
+    }
+    if output_dir.is_none() && !missing_screenshots.is_empty() {
+        eprintln!("⚠️  output_path not set, leaving the session's screenshots out");
+    }
+
+    let manifest = Manifest {
+        format: BUNDLE_FORMAT,
+        session_id: session_id.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        client_version: env!("CARGO_PKG_VERSION").to_string(),
+        source,
+        events: lines.len(),
+        screenshots: screenshots.iter().map(|(path, _)| path.clone()).collect(),
+        missing_screenshots,
+    };
+    append_json(&mut archive, MANIFEST, &manifest)?;
+
+    let mut events = lines.join("\n");
+    events.push('\n');
+    append_bytes(&mut archive, EVENTS, events.as_bytes())?;
+    append_json(&mut archive, CONFIG, config)?;
+    append_json(&mut archive, RUNS, &runs)?;
+    if let Some(trace) = TraceStore::load(trace_store_path(config)).get(session_id) {
+        append_json(&mut archive, WEAVE_TRACE, trace)?;
+    }
+    for (path, full_path) in &screenshots {
+        archive
+            .append_path_with_name(full_path, Path::new(SCREENSHOTS).join(path))
+            .map_err(|e| format!("Failed to add {:?} to the bundle: {}", full_path, e))?;
+    }
+
+    archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .and_then(|mut file| file.flush())
+        .map_err(|e| format!("Failed to write {:?}: {}", output, e))?;
+
+    println!(
+        "📦 Exported session {}: {} events, {} screenshots ({} missing), {} runs to {}",
+        session_id,
+        manifest.events,
+        manifest.screenshots.len(),
+        manifest.missing_screenshots.len(),
+        runs.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// A bundle unpacked for import
+pub struct ImportedBundle {
+    pub manifest: Manifest,
+    pub dir: PathBuf,
+}
+
+impl ImportedBundle {
+    /// Capture log of the session's events
+    pub fn events_path(&self) -> PathBuf {
+        self.dir.join(EVENTS)
+    }
+
+    /// Directory the screenshot paths of the events are relative to
+    pub fn screenshot_dir(&self) -> PathBuf {
+        self.dir.join(SCREENSHOTS)
+    }
+
+    /// Stores the session's Weave trace in the local trace store, so the backfilled
+    /// calls join the trace the session started on the exporting machine
+    pub fn adopt_trace(&self, config: &AppConfig) -> Result<(), String> {
+        let path = self.dir.join(WEAVE_TRACE);
+        if !path.exists() {
+            return Ok(());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let trace: SessionTrace = serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid Weave trace in bundle: {}", e))?;
+
+        let trace_id = trace.trace_id.clone();
+        let mut store = TraceStore::load(trace_store_path(config));
+        if store.import(self.manifest.session_id.clone(), trace)? {
+            println!("🔷 Backfilled Weave calls join trace {}", trace_id);
+        }
+        Ok(())
+    }
+}
+
+/// Unpacks a bundle made by `export` into `imports/<session_id>` of the state directory
+pub fn unpack(bundle: &Path) -> Result<ImportedBundle, String> {
+    let file = File::open(bundle).map_err(|e| format!("Failed to open {:?}: {}", bundle, e))?;
+    let decoder =
+        zstd::Decoder::new(file).map_err(|e| format!("Failed to read {:?}: {}", bundle, e))?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read {:?}: {}", bundle, e))?;
+
+    let invalid = |detail: &str| format!("{:?} is not a session bundle: {}", bundle, detail);
+    let mut first = entries
+        .next()
+        .ok_or_else(|| invalid("empty archive"))?
+        .map_err(|e| invalid(&e.to_string()))?;
+    if first.path().ok().as_deref() != Some(Path::new(MANIFEST)) {
+        return Err(invalid("no manifest"));
+    }
+    let mut contents = String::new();
+    first
+        .read_to_string(&mut contents)
+        .map_err(|e| invalid(&e.to_string()))?;
+    let manifest: Manifest =
+        serde_json::from_str(&contents).map_err(|e| invalid(&e.to_string()))?;
+    if manifest.format > BUNDLE_FORMAT {
+        return Err(format!(
+            "Bundle format {} is newer than this client supports ({})",
+            manifest.format, BUNDLE_FORMAT
+        ));
+    }
+    if !is_relative(&manifest.session_id) {
+        return Err(invalid("unsafe session id"));
+    }
+
+    let dir = state_dir().join("imports").join(&manifest.session_id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    std::fs::write(dir.join(MANIFEST), &contents)
+        .map_err(|e| format!("Failed to write the manifest to {:?}: {}", dir, e))?;
+    for entry in entries {
+        // unpack_in refuses entries that would land outside the directory
+        entry
+            .and_then(|mut entry| entry.unpack_in(&dir))
+            .map_err(|e| format!("Failed to unpack {:?}: {}", bundle, e))?;
+    }
+
+    println!(
+        "📦 Unpacked session {} ({} events, {} screenshots, exported {}) to {}",
+        manifest.session_id,
+        manifest.events,
+        manifest.screenshots.len(),
+        manifest.exported_at,
+        dir.display()
+    );
+    Ok(ImportedBundle { manifest, dir })
+}
+
+/// Runs of the session in the run index, if there is one
+fn find_runs(session_id: &str, config: &AppConfig) -> Result<Vec<RunRecord>, String> {
+    let path = SqliteRunIndex::path(config);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    SqliteRunIndex::open(&path, None)?.find(session_id)
+}
+
+/// Event lines of the session, from the event store when it recorded any, else from
+/// the capture logs of its runs and the configured one
+fn session_lines(
+    session_id: &str,
+    config: &AppConfig,
+    runs: &[RunRecord],
+) -> Result<(String, Vec<String>), String> {
+    let store_path = EventStore::path(config);
+    if store_path.exists() {
+        let lines = EventStore::open(&store_path, None)?.session_payloads(session_id)?;
+        if !lines.is_empty() {
+            return Ok(("event_store".to_string(), lines));
+        }
+    }
+
+    let logs: BTreeSet<&str> = runs
+        .iter()
+        .filter_map(|run| run.archive_path.as_deref())
+        .chain(config.log_path.as_deref())
+        .collect();
+    let mut read = Vec::new();
+    let mut lines = Vec::new();
+    for log in logs {
+        let path = Path::new(log);
+        if !path.exists() {
+            continue;
+        }
+        lines.extend(
+            capture_log::read_lines(path)?.into_iter().filter(|line| {
+                parse_event(line).is_ok_and(|event| event.session_id() == session_id)
+            }),
+        );
+        read.push(log);
+    }
+    Ok((read.join(", "), lines))
+}
+
+/// Screenshot paths referenced by the stats events, once each
+fn screenshot_paths(lines: &[String]) -> BTreeSet<String> {
+    lines
+        .iter()
+        .filter_map(|line| match parse_event(line) {
+            Ok(FactorioEvent::Stats {
+                screenshot_path: Some(path),
+                ..
+            }) => Some(path),
+            _ => None,
+        })
+        .filter(|path| {
+            let safe = is_relative(path);
+            if !safe {
+                eprintln!(
+                    "⚠️  Leaving out screenshot {} outside the output directory",
+                    path
+                );
+            }
+            safe
+        })
+        .collect()
+}
+
+/// Whether a path stays below the directory it is joined to
+fn is_relative(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+fn append_json<W: Write, T: Serialize + ?Sized>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+    append_bytes(archive, name, &bytes)
+}
+
+fn append_bytes<W: Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    bytes: &[u8],
+) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    archive
+        .append_data(&mut header, name, bytes)
+        .map_err(|e| format!("Failed to add {} to the bundle: {}", name, e))
+}
@@ -0,0 +1,85 @@
+use crate::panic_report;
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::WatchdogSettings;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// When to give up restarting a supervised task
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// Restarts allowed before exiting with a nonzero status
+    pub max_restarts: u32,
+    /// A task that ran at least this long resets the restart count
+    pub reset_after: Duration,
+}
+
+impl RestartPolicy {
+    pub fn from_settings(settings: &WatchdogSettings) -> Self {
+        Self {
+            max_restarts: settings.max_restarts,
+            reset_after: Duration::from_secs(settings.reset_secs),
+        }
+    }
+}
+
+/// Runs a core task under supervision. The task is expected to run forever; if it
+/// panics or returns, it is restarted with a growing delay. After too many restarts
+/// the process exits nonzero so a service manager (systemd, docker) can recover it.
+/// Panics are passed to `on_panic` (with the hook's report when available) before
+/// the restart/exit decision, so they can be recorded on the run.
+pub async fn supervise<F, Fut, P, PFut>(
+    name: &str,
+    policy: RestartPolicy,
+    mut make_task: F,
+    mut on_panic: P,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+    P: FnMut(String) -> PFut,
+    PFut: Future<Output = ()>,
+{
+    let mut restarts = 0;
+
+    loop {
+        let started = Instant::now();
+        let reason = match tokio::spawn(make_task()).await {
+            Ok(()) => "returned unexpectedly".to_string(),
+            Err(e) if e.is_panic() => {
+                let panic_message = panic_report::take_last()
+                    .map(|report| report.summary())
+                    .unwrap_or_else(|| format!("{} panicked", name));
+                on_panic(panic_message.clone()).await;
+                format!("panicked: {}", panic_message)
+            }
+            Err(e) => format!("was cancelled: {}", e),
+        };
+
+        if started.elapsed() >= policy.reset_after {
+            restarts = 0;
+        }
+        restarts += 1;
+
+        if restarts > policy.max_restarts {
+            eprintln!(
+                "❌ Watchdog: {} {} after {} restarts, exiting",
+                name, reason, policy.max_restarts
+            );
+            client_events::emit(ClientEvent::Error {
+                component: "watchdog".to_string(),
+                message: format!("{} {}, giving up after {} restarts", name, reason, policy.max_restarts),
+            });
+            std::process::exit(1);
+        }
+
+        let delay = Duration::from_secs(restarts as u64);
+        eprintln!(
+            "⚠️  Watchdog: {} {}, restarting in {:?} (restart {}/{})",
+            name, reason, delay, restarts, policy.max_restarts
+        );
+        client_events::emit(ClientEvent::Error {
+            component: "watchdog".to_string(),
+            message: format!("{} {}, restarting", name, reason),
+        });
+        tokio::time::sleep(delay).await;
+    }
+}
@@ -0,0 +1,222 @@
+use crate::admin_server::{check_exposure, constant_time_eq};
+use crate::instances::Instance;
+use factorio_events_core::config::WebSocketSettings;
+use factorio_events_core::event_mediator::EventMediator;
+use factorio_events_sinks_live::metrics_stream::MetricsStream;
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Path of the endpoint streaming per-cycle metrics; any other path ingests events
+const METRICS_PATH: &str = "/metrics";
+
+/// Starts the WebSocket ingestion endpoint when a bind address is configured. Each text
+/// frame carries one or more JSONL events and is processed as a batch by the mediator.
+/// Connections to `/metrics` instead receive the metrics of every stats cycle as they
+/// are logged, one JSON frame per cycle. An event connection serving several instances
+/// names the one it pushes to in an `instance` query parameter. Connections must present FACTORIO_WS_TOKEN (as `Authorization: Bearer` or a `token`
+/// query parameter, for browsers) when it is set; like the admin server it only listens
+/// on loopback addresses unless `allow_remote` is set and a token is configured.
+pub async fn start(
+    settings: &WebSocketSettings,
+    instances: Vec<Instance>,
+    metrics: MetricsStream,
+) -> Result<(), String> {
+    let Some(ref bind) = settings.bind else {
+        return Ok(());
+    };
+    let token = std::env::var("FACTORIO_WS_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .map(Arc::new);
+    let instances = Arc::new(instances);
+
+    let listener = TcpListener::bind(bind)
+        .await
+        .map_err(|e| format!("Failed to bind WebSocket server to {}: {}", bind, e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read WebSocket server address: {}", e))?;
+    check_exposure(
+        "WebSocket server",
+        addr,
+        settings.allow_remote,
+        token.is_some(),
+        "websocket.allow_remote",
+        "FACTORIO_WS_TOKEN",
+    )?;
+
+    println!(
+        "WebSocket ingestion listening on ws://{} (metrics stream: ws://{}{})",
+        addr, addr, METRICS_PATH
+    );
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    tokio::spawn(handle_connection(
+                        stream,
+                        peer,
+                        token.clone(),
+                        instances.clone(),
+                        metrics.clone(),
+                    ));
+                }
+                Err(e) => eprintln!("⚠️  Failed to accept WebSocket connection: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    peer: SocketAddr,
+    token: Option<Arc<String>>,
+    instances: Arc<Vec<Instance>>,
+    metrics: MetricsStream,
+) {
+    let mut path = String::new();
+    let mut mediator = None;
+    let check_token = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+        path = request.uri().path().to_string();
+        if let Some(ref token) = token {
+            if !is_authorized(request, token) {
+                return Err(rejection(
+                    StatusCode::UNAUTHORIZED,
+                    "missing or invalid token",
+                ));
+            }
+        }
+        if path != METRICS_PATH {
+            mediator = select_mediator(&instances, query_param(request, "instance"));
+            if mediator.is_none() {
+                return Err(rejection(
+                    StatusCode::NOT_FOUND,
+                    "missing or unknown instance",
+                ));
+            }
+        }
+        Ok(response)
+    };
+
+    let mut socket = match tokio_tungstenite::accept_hdr_async(stream, check_token).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("⚠️  Rejected WebSocket connection from {}: {}", peer, e);
+            return;
+        }
+    };
+    if path == METRICS_PATH {
+        println!("Metrics subscriber connected: {}", peer);
+        stream_metrics(socket, peer, metrics.subscribe()).await;
+        println!("Metrics subscriber disconnected: {}", peer);
+        return;
+    }
+    let Some(mediator) = mediator else {
+        return;
+    };
+    println!("WebSocket client connected: {}", peer);
+
+    while let Some(message) = socket.next().await {
+        match message {
+            Ok(Message::Text(text)) => {
+                let events: Vec<String> = text
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                mediator.process_events(events).await;
+            }
+            Ok(Message::Close(_)) => break,
+            // Pings are answered by tungstenite; binary frames aren't events
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("⚠️  WebSocket connection from {} failed: {}", peer, e);
+                break;
+            }
+        }
+    }
+    println!("WebSocket client disconnected: {}", peer);
+}
+
+/// Sends every metrics frame to a subscriber until it disconnects. A subscriber too slow
+/// to keep up skips the frames it missed rather than holding the others back.
+async fn stream_metrics(
+    mut socket: WebSocketStream<TcpStream>,
+    peer: SocketAddr,
+    mut frames: broadcast::Receiver<String>,
+) {
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => {
+                    if let Err(e) = socket.send(Message::Text(frame)).await {
+                        eprintln!("⚠️  Metrics stream to {} failed: {}", peer, e);
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("⚠️  Metrics subscriber {} lagged, skipped {} frames", peer, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = socket.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Subscribers don't send events; pings are answered by tungstenite
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Mediator of the instance an event connection pushes to: the only one, else the
+/// one named
+fn select_mediator(instances: &[Instance], name: Option<&str>) -> Option<Arc<EventMediator>> {
+    match (instances, name) {
+        ([instance], None) => Some(instance.mediator.clone()),
+        (_, Some(name)) => instances
+            .iter()
+            .find(|instance| instance.id.as_deref() == Some(name))
+            .map(|instance| instance.mediator.clone()),
+        _ => None,
+    }
+}
+
+fn rejection(status: StatusCode, reason: &str) -> ErrorResponse {
+    let mut rejection = ErrorResponse::new(Some(reason.to_string()));
+    *rejection.status_mut() = status;
+    rejection
+}
+
+fn query_param<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request.uri().query().and_then(|query| {
+        query.split('&').find_map(|pair| {
+            pair.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+    })
+}
+
+/// Accepts the token as a bearer token or, since browsers can't set headers on
+/// WebSocket requests, as the `token` query parameter
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let from_header = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let from_query = query_param(request, "token");
+
+    from_header
+        .into_iter()
+        .chain(from_query)
+        .any(|presented| constant_time_eq(presented, token))
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Crafting machines set to one recipe, from the optional stats `assemblers` section
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecipeUtilization {
+    pub recipe: String,
+    /// Item group of the recipe (e.g. "intermediate-products"), used for rollups
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Number of machines set to this recipe
+    pub count: u32,
+    /// Fraction of those machines currently crafting, 0..1
+    pub utilization: f64,
+}
+
+/// Running machine-count-weighted mean
+#[derive(Default)]
+struct WeightedMean {
+    sum: f64,
+    weight: f64,
+}
+
+impl WeightedMean {
+    fn add(&mut self, value: f64, weight: f64) {
+        self.sum += value * weight;
+        self.weight += weight;
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.weight > 0.0).then(|| self.sum / self.weight)
+    }
+}
+
+/// Builds `utilization/<recipe>` and `machines/<recipe>` metrics, plus per-category
+/// and overall rollups weighted by machine count, so a category with one idle
+/// machine doesn't weigh as much as one with hundreds
+pub fn utilization_metrics(recipes: &[RecipeUtilization]) -> HashMap<String, f64> {
+    let mut metrics = HashMap::new();
+    let mut categories: HashMap<&str, WeightedMean> = HashMap::new();
+    let mut overall = WeightedMean::default();
+
+    for entry in recipes {
+        let utilization = entry.utilization.clamp(0.0, 1.0);
+        let count = entry.count as f64;
+        metrics.insert(format!("utilization/{}", entry.recipe), utilization);
+        metrics.insert(format!("machines/{}", entry.recipe), count);
+
+        let category = entry.category.as_deref().unwrap_or("uncategorized");
+        categories.entry(category).or_default().add(utilization, count);
+        overall.add(utilization, count);
+    }
+
+    for (category, mean) in categories {
+        if let Some(value) = mean.mean() {
+            metrics.insert(format!("utilization_by_category/{}", category), value);
+        }
+    }
+    if let Some(value) = overall.mean() {
+        metrics.insert("utilization_by_category/all".to_string(), value);
+    }
+
+    metrics
+}
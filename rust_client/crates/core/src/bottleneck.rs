@@ -0,0 +1,176 @@
+use crate::event_sink::apply_stats;
+use crate::prototypes::{Prototypes, RecipeAmount};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Fraction of its consumption an item must lack to count as short
+const MIN_SHORTFALL_RATIO: f64 = 0.05;
+
+/// Shortfall, in items per minute, below which an item never counts as short
+const MIN_SHORTFALL: f64 = 0.1;
+
+/// An item consumed faster than it is produced
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortItem {
+    pub item: String,
+    pub production: f64,
+    pub consumption: f64,
+    /// Consumption minus production, in items per minute
+    pub shortfall: f64,
+}
+
+impl ShortItem {
+    /// Fraction of the consumption that isn't produced
+    pub fn ratio(&self) -> f64 {
+        self.shortfall / self.consumption
+    }
+}
+
+/// The item at the root of the most shortages of a cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct Bottleneck {
+    /// Short items from the most affected one down to the bottleneck, each made from
+    /// the next (the last one is the bottleneck itself)
+    pub chain: Vec<ShortItem>,
+    /// Every short item whose shortage traces back to the bottleneck
+    pub explains: Vec<String>,
+}
+
+impl Bottleneck {
+    pub fn item(&self) -> &ShortItem {
+        self.chain.last().expect("a bottleneck chain is never empty")
+    }
+}
+
+/// Attributes shortfalls up the crafting chain each stats cycle: from every short item,
+/// the chain follows its most short ingredient until it reaches an item whose own
+/// ingredients are all sufficient. The root reached from the most shortages (weighted
+/// by how short they are) is the bottleneck. Needs the mod's recipe prototypes.
+#[derive(Default)]
+pub struct BottleneckTracker {
+    /// Ingredients per unit of each crafted item
+    graph: Option<HashMap<String, Vec<RecipeAmount>>>,
+    production: HashMap<String, f64>,
+    consumption: HashMap<String, f64>,
+    /// Bottleneck of the previous cycle
+    current: Option<String>,
+}
+
+impl BottleneckTracker {
+    pub fn set_prototypes(&mut self, prototypes: &Prototypes) {
+        self.graph = Some(prototypes.ingredient_graph());
+    }
+
+    /// Forgets the item state of the previous session; the recipe graph stays
+    pub fn reset(&mut self) {
+        self.production.clear();
+        self.consumption.clear();
+        self.current = None;
+    }
+
+    /// Updates the item state with a stats cycle and finds its bottleneck. Returns the
+    /// `bottleneck/*` metrics of the cycle, and the bottleneck when it changed.
+    pub fn analyze(
+        &mut self,
+        production: &HashMap<String, f64>,
+        consumption: &HashMap<String, f64>,
+        delta: bool,
+    ) -> (HashMap<String, f64>, Option<Bottleneck>) {
+        apply_stats(&mut self.production, production.clone(), delta);
+        apply_stats(&mut self.consumption, consumption.clone(), delta);
+        let Some(ref graph) = self.graph else {
+            return (HashMap::new(), None);
+        };
+
+        let short = self.short_items();
+        let mut metrics = HashMap::new();
+        metrics.insert("bottleneck/short_items".to_string(), short.len() as f64);
+
+        let bottleneck = find_bottleneck(graph, &short);
+        let item = bottleneck.as_ref().map(|b| b.item().item.clone());
+        if let Some(ref bottleneck) = bottleneck {
+            let root = bottleneck.item();
+            metrics.insert(format!("bottleneck/item/{}", root.item), root.ratio());
+        }
+
+        if item == self.current {
+            return (metrics, None);
+        }
+        self.current = item;
+        (metrics, bottleneck)
+    }
+
+    fn short_items(&self) -> HashMap<&str, ShortItem> {
+        self.consumption
+            .iter()
+            .filter_map(|(item, &consumption)| {
+                let production = self.production.get(item).copied().unwrap_or(0.0);
+                let shortfall = consumption - production;
+                if shortfall <= MIN_SHORTFALL || shortfall <= consumption * MIN_SHORTFALL_RATIO {
+                    return None;
+                }
+                let short = ShortItem {
+                    item: item.clone(),
+                    production,
+                    consumption,
+                    shortfall,
+                };
+                Some((item.as_str(), short))
+            })
+            .collect()
+    }
+}
+
+/// Follows the most short ingredient from `item` until no ingredient is short
+fn chain_from<'a>(
+    graph: &HashMap<String, Vec<RecipeAmount>>,
+    short: &'a HashMap<&str, ShortItem>,
+    item: &'a ShortItem,
+) -> Vec<&'a ShortItem> {
+    let mut chain = vec![item];
+    let mut current = item;
+    loop {
+        let next = graph
+            .get(&current.item)
+            .into_iter()
+            .flatten()
+            .filter_map(|ingredient| short.get(ingredient.name.as_str()))
+            // Recipe loops stop at the first repeated item
+            .filter(|candidate| !chain.iter().any(|seen| seen.item == candidate.item))
+            .max_by(|a, b| a.ratio().total_cmp(&b.ratio()));
+        match next {
+            Some(next) => {
+                chain.push(next);
+                current = next;
+            }
+            None => return chain,
+        }
+    }
+}
+
+fn find_bottleneck(
+    graph: &HashMap<String, Vec<RecipeAmount>>,
+    short: &HashMap<&str, ShortItem>,
+) -> Option<Bottleneck> {
+    // Root item -> (score, chains ending there)
+    let mut roots: HashMap<&str, (f64, Vec<Vec<&ShortItem>>)> = HashMap::new();
+    for item in short.values() {
+        let chain = chain_from(graph, short, item);
+        let root = chain.last().unwrap().item.as_str();
+        let entry = roots.entry(root).or_insert((0.0, Vec::new()));
+        entry.0 += item.ratio();
+        entry.1.push(chain);
+    }
+
+    let (_, (_, chains)) = roots.into_iter().max_by(|(a_root, a), (b_root, b)| {
+        a.0.total_cmp(&b.0).then_with(|| b_root.cmp(a_root))
+    })?;
+    let mut explains: Vec<String> = chains.iter().map(|chain| chain[0].item.clone()).collect();
+    explains.sort();
+    // The longest chain shows how far the shortage spreads
+    let chain = chains.into_iter().max_by_key(|chain| chain.len())?;
+    Some(Bottleneck {
+        chain: chain.into_iter().cloned().collect(),
+        explains,
+    })
+}
@@ -0,0 +1,143 @@
+use crate::event_mediator::{
+    classify_event, classify_parsed, parse_event, FactorioEvent, ProcessSummary,
+};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Reads the non-empty lines of a capture log (FACTORIO_LOG_PATH)
+pub fn read_lines(path: &Path) -> Result<Vec<String>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    BufReader::new(file)
+        .lines()
+        .map(|line| line.map_err(|e| format!("Failed to read {:?}: {}", path, e)))
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .collect()
+}
+
+/// Tick of an event line, if it parses as JSON with a `tick` field
+pub fn line_tick(line: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()?
+        .get("tick")?
+        .as_u64()
+}
+
+/// Orders lines by tick, keeping the file order of equal ticks. Lines without a tick
+/// stay right after the line before them.
+pub fn sort_by_tick(lines: Vec<String>) -> Vec<(u64, String)> {
+    let mut last_tick = 0;
+    let mut keyed: Vec<(u64, String)> = lines
+        .into_iter()
+        .map(|line| {
+            if let Some(tick) = line_tick(&line) {
+                last_tick = tick;
+            }
+            (last_tick, line)
+        })
+        .collect();
+    keyed.sort_by_key(|&(tick, _)| tick);
+    keyed
+}
+
+/// Classifies a batch of lines exactly as the mediator would, without side effects
+pub fn classify_all(lines: &[String]) -> ProcessSummary {
+    let mut summary = ProcessSummary::default();
+    for line in lines {
+        summary.record(classify_event(line));
+    }
+    summary
+}
+
+/// Classifies a batch of parsed events exactly as the mediator would, without side effects
+pub fn classify_events(events: &[FactorioEvent]) -> ProcessSummary {
+    let mut summary = ProcessSummary::default();
+    for event in events {
+        summary.record(classify_parsed(event));
+    }
+    summary
+}
+
+/// Prints a batch summary in the style of the processing cycle output
+pub fn print_summary(summary: &ProcessSummary) {
+    println!("{} routed, {} dropped", summary.routed, summary.total() - summary.routed);
+    let reasons: BTreeMap<_, _> = summary.dropped.iter().collect();
+    for (reason, count) in reasons {
+        println!("  {:<40} {}", reason, count);
+    }
+}
+
+/// Runs `validate <file>`, printing every line the client would drop and why
+pub fn validate(path: &Path) -> Result<(), String> {
+    let lines = read_lines(path)?;
+    let mut invalid = 0;
+
+    for (number, line) in lines.iter().enumerate() {
+        let problem = match parse_event(line) {
+            Ok(event) => event.route_check().err(),
+            Err(e) => Some(format!("parse_error: {}", e)),
+        };
+        if let Some(problem) = problem {
+            invalid += 1;
+            println!("{}:{}: {}", path.display(), number + 1, problem);
+        }
+    }
+
+    if invalid > 0 {
+        return Err(format!("{} of {} lines invalid", invalid, lines.len()));
+    }
+    println!("✅ {} lines valid", lines.len());
+    Ok(())
+}
+
+/// Per-session counters of a capture log
+#[derive(Default)]
+struct SessionStats {
+    events: BTreeMap<String, u64>,
+    first_tick: Option<u64>,
+    last_tick: Option<u64>,
+}
+
+/// Runs `stats [file]`, printing event counts and tick ranges per session
+pub fn stats(path: &Path) -> Result<(), String> {
+    let lines = read_lines(path)?;
+    let mut sessions: BTreeMap<String, SessionStats> = BTreeMap::new();
+
+    // Counted on the raw JSON so events the schema drops still show up
+    for line in &lines {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let session_id = value["session_id"].as_str().unwrap_or("<none>").to_string();
+        let kind = match value["type"].as_str() {
+            Some("event") => value["event_name"].as_str().unwrap_or("event").to_string(),
+            Some(kind) => kind.to_string(),
+            None => "<untyped>".to_string(),
+        };
+
+        let session = sessions.entry(session_id).or_default();
+        *session.events.entry(kind).or_insert(0) += 1;
+        if let Some(tick) = value["tick"].as_u64() {
+            session.first_tick = Some(session.first_tick.map_or(tick, |t| t.min(tick)));
+            session.last_tick = Some(session.last_tick.map_or(tick, |t| t.max(tick)));
+        }
+    }
+
+    println!("{}: {} lines", path.display(), lines.len());
+    print_summary(&classify_all(&lines));
+
+    for (session_id, session) in sessions {
+        println!();
+        println!(
+            "session {} (ticks {}..{})",
+            session_id,
+            session.first_tick.unwrap_or(0),
+            session.last_tick.unwrap_or(0)
+        );
+        for (kind, count) in session.events {
+            println!("  {:<40} {}", kind, count);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::sync::{Mutex, OnceLock};
+
+/// Lifecycle events of the client itself, emitted as JSON lines for wrapper scripts
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ClientEvent {
+    Started {
+        pipe_path: String,
+    },
+    SessionCreated {
+        session_id: String,
+        run_name: String,
+        level_name: String,
+    },
+    RunStarted {
+        run_name: String,
+        run_id: String,
+        url: String,
+    },
+    RunFinished {
+        run_name: String,
+    },
+    /// A sink finished its startup checks and is ready for events
+    SinkReady {
+        component: String,
+    },
+    EventsDropped {
+        count: u64,
+        reason: String,
+    },
+    Error {
+        component: String,
+        message: String,
+    },
+    /// SIGHUP reloaded the config file
+    ConfigReloaded,
+    Shutdown,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a ClientEvent,
+}
+
+/// Where client events are written
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventTarget {
+    Stdout,
+    Socket(String),
+}
+
+impl EventTarget {
+    /// Parses the `--json-events` target: `stdout` or `unix:<path>`
+    pub fn parse(target: &str) -> Result<Self, String> {
+        match target {
+            "stdout" => Ok(EventTarget::Stdout),
+            _ => target
+                .strip_prefix("unix:")
+                .map(|path| EventTarget::Socket(path.to_string()))
+                .ok_or_else(|| format!("Unknown JSON event target: {}", target)),
+        }
+    }
+}
+
+static EMITTER: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+/// Enables the JSON event channel. Events emitted before this are discarded.
+pub fn init(target: EventTarget) -> Result<(), String> {
+    let writer: Box<dyn Write + Send> = match &target {
+        EventTarget::Stdout => Box::new(std::io::stdout()),
+        EventTarget::Socket(path) => Box::new(
+            UnixStream::connect(path)
+                .map_err(|e| format!("Failed to connect to event socket {}: {}", path, e))?,
+        ),
+    };
+
+    EMITTER
+        .set(Mutex::new(writer))
+        .map_err(|_| "JSON event channel already initialized".to_string())
+}
+
+/// Emits a client event if the JSON event channel is enabled
+pub fn emit(event: ClientEvent) {
+    let Some(emitter) = EMITTER.get() else {
+        return;
+    };
+
+    let envelope = Envelope {
+        timestamp: Utc::now(),
+        event: &event,
+    };
+
+    if let Ok(line) = serde_json::to_string(&envelope) {
+        let mut writer = emitter.lock().unwrap();
+        if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() {
+            eprintln!("⚠️  Failed to write client event: {}", line);
+        }
+    }
+}
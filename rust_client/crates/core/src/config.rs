@@ -0,0 +1,1470 @@
+use crate::run_metadata;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// WandB run settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WandbSettings {
+    pub entity: String,
+    pub project: String,
+    /// Distinct metric keys per run before new keys fold into `other` buckets (0: no cap)
+    pub max_metric_keys: usize,
+    /// `online`, `offline` or `disabled`; WANDB_MODE takes precedence
+    pub mode: Option<String>,
+    /// Tags of every run
+    pub tags: Vec<String>,
+    pub downsample: DownsampleSettings,
+}
+
+impl Default for WandbSettings {
+    fn default() -> Self {
+        Self {
+            entity: "wandb".to_string(),
+            project: "factorio-experiments".to_string(),
+            max_metric_keys: 5000,
+            mode: None,
+            tags: Vec::new(),
+            downsample: DownsampleSettings::default(),
+        }
+    }
+}
+
+/// Client-side downsampling of the stats cycles logged to WandB, for very long sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownsampleSettings {
+    /// `off`, `every` (every `every`th cycle) or `adaptive` (cycles with changes, and
+    /// every `every`th cycle while flat)
+    pub mode: String,
+    pub every: u64,
+    /// Relative change of a metric that makes the adaptive mode log a cycle
+    pub change_threshold: f64,
+}
+
+impl Default for DownsampleSettings {
+    fn default() -> Self {
+        Self {
+            mode: "off".to_string(),
+            every: 10,
+            change_threshold: 0.05,
+        }
+    }
+}
+
+/// Weave sidecar settings. The API key is only read from WANDB_API_KEY so it
+/// never ends up in a config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaveSettings {
+    pub entity: Option<String>,
+    pub project: Option<String>,
+    pub base_url: String,
+    /// How calls reach Weave: `http` to the trace server, `sidecar` through the
+    /// weave-sender, or `auto` (the sidecar when `binary_path` is set, else HTTP)
+    pub transport: String,
+    /// Directory containing the `weave-sender` binary
+    pub binary_path: Option<PathBuf>,
+    /// Directory of the sidecar socket (default XDG_RUNTIME_DIR, then /tmp)
+    pub socket_dir: Option<PathBuf>,
+    /// Request gzip framing on the socket protocol
+    pub compression: bool,
+    /// Seconds between health-check pings, at least 1
+    pub ping_interval_secs: u64,
+    /// Time allowed for a request to the sidecar, from queuing it to its reply
+    pub request_timeout_secs: u64,
+    /// Time allowed for the sidecar to deliver what it queued on flush, waits and shutdown
+    pub drain_timeout_secs: u64,
+    /// How long delivered spool segments are kept before compaction deletes them
+    pub spool_retention_hours: u64,
+    pub spool_compact_interval_secs: u64,
+    /// Event calls within this many ticks after a screenshot reference it (0: off)
+    pub screenshot_link_ticks: u64,
+    pub screenshots: ScreenshotSettings,
+    pub delivery: DeliverySettings,
+    /// Launch the sidecar at startup instead of at the first event
+    pub preflight: bool,
+    /// Exit when the startup launch fails, instead of retrying at the first event
+    pub preflight_required: bool,
+    /// Log a `drain_summary` call per drain cycle
+    pub drain_summary: bool,
+    /// Starts and ends of a drain cycle sent per enqueue request (0: one per request)
+    pub enqueue_batch_items: usize,
+    pub retry: WeaveRetrySettings,
+    /// Instant calls merged into one call per tick window, by op
+    pub batching: HashMap<String, CallBatchSettings>,
+    /// Attributes added to every call of an op, by op, e.g. `category = "logistics"`
+    pub op_attributes: HashMap<String, HashMap<String, serde_json::Value>>,
+}
+
+impl Default for WeaveSettings {
+    fn default() -> Self {
+        Self {
+            entity: None,
+            project: None,
+            base_url: "https://trace.wandb.ai".to_string(),
+            transport: "auto".to_string(),
+            binary_path: None,
+            socket_dir: None,
+            compression: false,
+            ping_interval_secs: 30,
+            request_timeout_secs: 10,
+            drain_timeout_secs: 120,
+            spool_retention_hours: 24,
+            spool_compact_interval_secs: 600,
+            screenshot_link_ticks: 120,
+            screenshots: ScreenshotSettings::default(),
+            delivery: DeliverySettings::default(),
+            preflight: true,
+            preflight_required: false,
+            drain_summary: true,
+            enqueue_batch_items: 500,
+            retry: WeaveRetrySettings::default(),
+            batching: HashMap::new(),
+            op_attributes: HashMap::new(),
+        }
+    }
+}
+
+/// Processing of player screenshots before upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreenshotSettings {
+    /// Skip snapshots whose screenshot looks like the previous one on the same surface
+    pub dedupe: bool,
+    /// Most perceptual-hash bits two screenshots differ by to count as the same picture
+    pub dedupe_distance: u32,
+    /// TTF/OTF font the tick, top production and player position are drawn with on
+    /// screenshots; no overlay when unset
+    pub overlay_font: Option<PathBuf>,
+    /// Text height of the overlay, in pixels
+    pub overlay_size: f32,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            dedupe: true,
+            dedupe_distance: 4,
+            overlay_font: None,
+            overlay_size: 18.0,
+        }
+    }
+}
+
+/// Batching of one op's instant calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CallBatchSettings {
+    /// Ticks of a window; the calls of a window (and player) form one batch
+    pub window_ticks: u64,
+    /// Calls after which a batch goes out before its window closes
+    pub max_calls: usize,
+}
+
+impl Default for CallBatchSettings {
+    fn default() -> Self {
+        Self {
+            window_ticks: 600,
+            max_calls: 100,
+        }
+    }
+}
+
+/// Retry queue of Weave calls that failed to send: instant calls under a `retry:<n>`
+/// policy and the starts and ends of spans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaveRetrySettings {
+    /// Calls waiting for a retry before new failures are dropped
+    pub queue_calls: usize,
+    /// Delay before the first retry, doubled per attempt
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Attempts at a span's start or end before it is dropped
+    pub max_attempts: u32,
+}
+
+impl Default for WeaveRetrySettings {
+    fn default() -> Self {
+        Self {
+            queue_calls: 1000,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            max_attempts: 5,
+        }
+    }
+}
+
+/// What happens to Weave calls that fail to send, per event class: `wal` spools them
+/// and replays them until delivered, `retry:<n>` sends up to n times then drops them,
+/// `best_effort` sends once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeliverySettings {
+    /// Surface maps, probe registry changes, drain summaries, status heartbeats and
+    /// bottlenecks
+    pub stats: String,
+    /// Built, mined and crafted events
+    pub entity: String,
+    /// Player snapshots
+    pub screenshot: String,
+    /// Session starts, research, rocket launches and other calls
+    pub session: String,
+}
+
+impl Default for DeliverySettings {
+    fn default() -> Self {
+        Self {
+            stats: "wal".to_string(),
+            entity: "retry:3".to_string(),
+            screenshot: "best_effort".to_string(),
+            session: "wal".to_string(),
+        }
+    }
+}
+
+/// Chaos-testing faults injected into sink submissions (off by default)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultSettings {
+    /// Percentage of submissions that fail without being sent
+    pub fail_percent: f64,
+    /// Percentage of submissions held back by `delay_ms` before being sent
+    pub delay_percent: f64,
+    pub delay_ms: u64,
+    /// Sinks faults apply to: "weave" and/or "wandb"
+    pub sinks: Vec<String>,
+}
+
+impl Default for FaultSettings {
+    fn default() -> Self {
+        Self {
+            fail_percent: 0.0,
+            delay_percent: 0.0,
+            delay_ms: 1000,
+            sinks: vec!["weave".to_string(), "wandb".to_string()],
+        }
+    }
+}
+
+/// Active polling of the game over RCON (off unless an address is set). The password
+/// is only read from FACTORIO_RCON_PASSWORD.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RconSettings {
+    /// RCON address of the server, e.g. `127.0.0.1:27015`
+    pub address: Option<String>,
+    pub poll_interval_secs: u64,
+    /// Surface whose evolution and production are polled
+    pub surface: String,
+    /// Ask the mod to re-send its backlog on every connect
+    pub catch_up: bool,
+    /// Instance whose server the address reaches; required when several are served
+    pub instance: Option<String>,
+    /// Ask the mod for a screenshot this often, while the upload budget allows, instead
+    /// of letting it take one every stats cycle; 0 leaves them to the mod
+    pub screenshot_interval_secs: u64,
+}
+
+impl Default for RconSettings {
+    fn default() -> Self {
+        Self {
+            address: None,
+            poll_interval_secs: 60,
+            surface: "nauvis".to_string(),
+            catch_up: true,
+            instance: None,
+            screenshot_interval_secs: 0,
+        }
+    }
+}
+
+/// Admin/status HTTP server (off unless a bind address is set). The auth token is
+/// only read from FACTORIO_ADMIN_TOKEN.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminSettings {
+    /// Address to listen on, e.g. `127.0.0.1:9110`
+    pub bind: Option<String>,
+    /// Allow binding to a non-loopback address (requires a token)
+    pub allow_remote: bool,
+    /// Serve the live web dashboard at `/`
+    pub dashboard: bool,
+}
+
+/// Item categories rolled up per stats cycle, besides the built-in `plates`, `science`
+/// and `military`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemCategorySettings {
+    /// Output of `factorio --dump-data`; every item also rolls up into its subgroup
+    pub data_dump: Option<PathBuf>,
+    /// Custom categories and their items
+    pub custom: HashMap<String, Vec<String>>,
+}
+
+/// Write-ahead log of drained events, replayed after a crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalSettings {
+    pub enabled: bool,
+    /// Directory of the log (default: `ingest_wal` in the state directory)
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for WalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+        }
+    }
+}
+
+/// Sends the sessions matching its glob patterns (all of those given) to other WandB
+/// and Weave projects than the defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectRouteSettings {
+    /// Pattern of the Factorio session id, e.g. `speedrun-*`
+    pub session_id: Option<String>,
+    /// Pattern of the level name
+    pub level_name: Option<String>,
+    /// Unset values keep the `wandb` and `weave` settings
+    pub wandb_entity: Option<String>,
+    pub wandb_project: Option<String>,
+    pub weave_entity: Option<String>,
+    pub weave_project: Option<String>,
+}
+
+/// gRPC control-plane server (off unless a bind address is set). The auth token is
+/// only read from FACTORIO_GRPC_TOKEN.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcSettings {
+    /// Address to listen on, e.g. `127.0.0.1:9112`
+    pub bind: Option<String>,
+    /// Allow binding to a non-loopback address (requires a token)
+    pub allow_remote: bool,
+}
+
+/// WebSocket ingestion endpoint (off unless a bind address is set). The connection
+/// token is only read from FACTORIO_WS_TOKEN.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSocketSettings {
+    /// Address to listen on, e.g. `127.0.0.1:9111`
+    pub bind: Option<String>,
+    /// Allow binding to a non-loopback address (requires a token)
+    pub allow_remote: bool,
+}
+
+/// Limits of the pool encoding screenshots and rendered maps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageSettings {
+    /// Image jobs running at once (default: half the CPUs)
+    pub workers: usize,
+    /// Image jobs waiting for a worker before new ones are skipped
+    pub queue: usize,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self {
+            workers: (cpus / 2).max(1),
+            queue: 16,
+        }
+    }
+}
+
+/// InfluxDB sink (off unless a URL is set), writing through the v2 write API. The API
+/// token is only read from INFLUX_TOKEN.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InfluxSettings {
+    /// Server URL, e.g. `http://127.0.0.1:8086`
+    pub url: Option<String>,
+    pub org: String,
+    pub bucket: String,
+    /// Points written per request
+    pub batch_size: usize,
+    /// Longest a point waits before its batch is written
+    pub flush_interval_secs: u64,
+    /// Attempts per batch before it is dropped
+    pub max_attempts: u32,
+}
+
+impl Default for InfluxSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            org: "factorio".to_string(),
+            bucket: "factorio".to_string(),
+            batch_size: 5000,
+            flush_interval_secs: 5,
+            max_attempts: 5,
+        }
+    }
+}
+
+/// MQTT sink (off unless a broker is set). Credentials are only read from
+/// MQTT_USERNAME and MQTT_PASSWORD.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    /// Broker address as `host:port`
+    pub broker: Option<String>,
+    pub client_id: String,
+    /// Each selected metric is published, retained, to `<metrics_topic>/<key>`
+    pub metrics_topic: String,
+    /// Milestones are published as JSON to `<events_topic>/<event>`
+    pub events_topic: String,
+    /// Metric keys to publish, as unit registry patterns (`production/iron-plate`,
+    /// `science/*/surplus`, `power/`); stalls are reported for the production ones
+    pub metrics: Vec<String>,
+    /// Also publish every production/consumption value and the player position of the
+    /// session, retained, under `<live_topic>/<session>/`
+    pub live: bool,
+    pub live_topic: String,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            broker: None,
+            client_id: "wandb-factorio".to_string(),
+            metrics_topic: "factorio/metrics".to_string(),
+            events_topic: "factorio/events".to_string(),
+            metrics: vec![
+                "production/iron-plate".to_string(),
+                "production/copper-plate".to_string(),
+                "power/".to_string(),
+            ],
+            live: false,
+            live_topic: "factorio".to_string(),
+        }
+    }
+}
+
+/// Kafka producer of parsed events (off unless brokers are set)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KafkaSettings {
+    /// Comma-separated `host:port` bootstrap servers
+    pub brokers: Option<String>,
+    pub topic: String,
+    pub client_id: String,
+    /// Extra librdkafka properties, e.g. `security.protocol` or `compression.type`
+    pub properties: HashMap<String, String>,
+}
+
+impl Default for KafkaSettings {
+    fn default() -> Self {
+        Self {
+            brokers: None,
+            topic: "factorio-events".to_string(),
+            client_id: "wandb-factorio".to_string(),
+            properties: HashMap::new(),
+        }
+    }
+}
+
+/// An HTTP endpoint that selected events are POSTed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub url: Option<String>,
+    /// Environment variable holding the URL, for endpoints whose URL is a secret
+    pub url_env: Option<String>,
+    /// Event names delivered (`session_init`, `on_research_finished`, ...); empty
+    /// delivers every event
+    pub events: Vec<String>,
+    /// JSON body with `{{field}}` placeholders; the event fields as is when unset
+    pub template: Option<String>,
+    pub timeout_secs: u64,
+    /// Attempts per delivery before it is dropped
+    pub max_attempts: u32,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            url_env: None,
+            events: Vec::new(),
+            template: None,
+            timeout_secs: 10,
+            max_attempts: 3,
+        }
+    }
+}
+
+/// Chat notifications of milestones to a Discord or Slack incoming webhook (off
+/// unless a URL and at least one rule are set)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub webhook_url: Option<String>,
+    /// Environment variable holding the webhook URL, for URLs that are secrets
+    pub webhook_url_env: Option<String>,
+    /// `discord` or `slack`; guessed from the URL when unset
+    pub service: Option<String>,
+    pub rules: Vec<NotificationRule>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            webhook_url_env: None,
+            service: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// A milestone announced by the notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationRule {
+    /// `first_rocket`, `rocket_launched`, `research_finished`, `player_died` or `evolution`
+    pub on: String,
+    /// Research names announced (`research_finished`); empty announces all
+    pub names: Vec<String>,
+    /// Evolution factors announced once each when the enemy reaches them (`evolution`)
+    pub thresholds: Vec<f64>,
+    /// Message with `{{field}}` placeholders; a default per milestone when unset
+    pub message: Option<String>,
+}
+
+impl Default for NotificationRule {
+    fn default() -> Self {
+        Self {
+            on: String::new(),
+            names: Vec::new(),
+            thresholds: Vec::new(),
+            message: None,
+        }
+    }
+}
+
+/// Completion report of every run (off unless a webhook or an SMTP server with
+/// recipients is set). SMTP credentials are only read from SMTP_USERNAME and
+/// SMTP_PASSWORD.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompletionReportSettings {
+    pub webhook_url: Option<String>,
+    /// Environment variable holding the webhook URL, for URLs that are secrets
+    pub webhook_url_env: Option<String>,
+    /// SMTP relay, used with STARTTLS
+    pub smtp_server: Option<String>,
+    pub smtp_port: u16,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Default for CompletionReportSettings {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            webhook_url_env: None,
+            smtp_server: None,
+            smtp_port: 587,
+            from: "wandb-factorio <wandb-factorio@localhost>".to_string(),
+            to: Vec::new(),
+        }
+    }
+}
+
+/// Export of per-session metric time series (off unless a directory is set)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// Directory the session files are written to
+    pub dir: Option<String>,
+    /// `csv` and/or `parquet`
+    pub formats: Vec<String>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            formats: vec!["csv".to_string()],
+        }
+    }
+}
+
+/// Supervision of the drain loop and the other core tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogSettings {
+    /// Restarts allowed before exiting with a nonzero status
+    pub max_restarts: u32,
+    /// A task that ran at least this many seconds resets the restart count
+    pub reset_secs: u64,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            reset_secs: 600,
+        }
+    }
+}
+
+/// Scheduled run rotation: a new run every so many game ticks and/or seconds, whichever
+/// comes first (off unless one is set)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RotationSettings {
+    pub every_ticks: Option<u64>,
+    pub every_secs: Option<u64>,
+}
+
+/// Tournament mode, scoring the sessions on a shared WandB leaderboard (off unless a
+/// name is set)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TournamentSettings {
+    pub name: Option<String>,
+    /// KPIs as `item[:weight]`, e.g. `electronic-circuit:2`
+    pub kpis: Vec<String>,
+}
+
+/// Surface maps logged with the stats cycles; rendered only when the client is built
+/// with the core's `maps` feature
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapSettings {
+    /// Log an exploration heatmap per surface every this many stats cycles
+    pub exploration_heatmap_cycles: Option<u64>,
+    /// Log a pollution map per surface every this many stats cycles
+    pub pollution_map_cycles: Option<u64>,
+}
+
+/// Upload budget for metered connections (unlimited unless set)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadBudgetSettings {
+    /// Megabytes uploaded per hour before deferrable traffic waits
+    pub mb_per_hour: Option<f64>,
+}
+
+/// Runtime settings of the client, loaded from an optional TOML or YAML file
+/// with environment variables taking precedence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub pipe_path: String,
+    /// Pipes (or glob patterns) of several game instances, each served as its own
+    /// instance; overrides `pipe_path` when non-empty
+    pub pipe_paths: Vec<String>,
+    /// Id of the game instance this config serves, set per pipe of `pipe_paths`
+    pub instance: Option<String>,
+    /// Receive events as UDP datagrams on this address instead of reading the pipe
+    pub udp_bind: Option<String>,
+    /// Tail the JSONL files the mod writes into this directory instead of reading the pipe
+    pub tail_dir: Option<String>,
+    /// Capture log of every raw event line
+    pub log_path: Option<String>,
+    /// Factorio's script-output directory, which the screenshot paths of the stats events
+    /// are relative to
+    pub output_path: Option<String>,
+    /// Longest wait between drains; with `drain_wakeup`, the first event wakes the loop
+    pub drain_interval_secs: u64,
+    /// Drain as soon as events arrive instead of waiting for the interval
+    pub drain_wakeup: bool,
+    /// After a wakeup, how long events are gathered into the batch before draining
+    pub drain_batch_ms: u64,
+    /// Event kinds (`session_init`, or game event names) that start a drain at once,
+    /// skipping the interval and the gathering window, and flush the sinks after it
+    pub immediate_events: Vec<String>,
+    /// Soft time limit of processing one drained batch (default: the drain interval);
+    /// events left over are carried to the next cycle
+    pub cycle_deadline_ms: Option<u64>,
+    /// Max events buffered between drains
+    pub cache_capacity: usize,
+    /// What readers do when the cache is full: `block` (hold back the game until the
+    /// next drain), `drop_oldest` or `drop_newest`
+    pub cache_overflow: String,
+    /// Workers processing a drained batch; the events of one session always go to the
+    /// same worker, in order
+    pub event_workers: usize,
+    /// Resume a run left unfinished by a client crash when its session was active
+    /// within this many seconds (off by default)
+    pub warm_start_secs: Option<u64>,
+    /// Log a `status` heartbeat when no events arrived for this many seconds, and again
+    /// every this many seconds while it stays idle (off by default)
+    pub status_interval_secs: Option<u64>,
+    /// Record every parsed event in a local SQLite store before forwarding it
+    pub store_events: bool,
+    /// SQLite event store (default: `events.sqlite` in the state directory)
+    pub event_store_path: Option<PathBuf>,
+    /// SQLite run index (default: `run_index.sqlite` in the state directory)
+    pub run_index_path: Option<PathBuf>,
+    /// `key = value` labels attached to every WandB run and Weave call; `--meta`
+    /// pairs take precedence
+    pub metadata: HashMap<String, String>,
+    pub upload_budget: UploadBudgetSettings,
+    pub watchdog: WatchdogSettings,
+    pub rotation: RotationSettings,
+    pub tournament: TournamentSettings,
+    pub maps: MapSettings,
+    pub wal: WalSettings,
+    pub wandb: WandbSettings,
+    pub weave: WeaveSettings,
+    pub item_categories: ItemCategorySettings,
+    pub faults: FaultSettings,
+    pub rcon: RconSettings,
+    pub admin: AdminSettings,
+    pub grpc: GrpcSettings,
+    pub websocket: WebSocketSettings,
+    pub images: ImageSettings,
+    pub influx: InfluxSettings,
+    pub mqtt: MqttSettings,
+    pub export: ExportSettings,
+    pub kafka: KafkaSettings,
+    pub webhooks: Vec<WebhookSettings>,
+    pub completion_report: CompletionReportSettings,
+    pub notifications: NotificationSettings,
+    pub project_routes: Vec<ProjectRouteSettings>,
+}
+
+/// Directory for client state that outlives the process (FACTORIO_STATE_DIR,
+/// default `~/.local/state/wandb-factorio`)
+pub fn state_dir() -> PathBuf {
+    env::var("FACTORIO_STATE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(home).join(".local/state/wandb-factorio")
+        })
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        Self {
+            pipe_path: format!(
+                "{}/Library/Application Support/factorio/script-output/events.pipe",
+                home
+            ),
+            pipe_paths: Vec::new(),
+            instance: None,
+            udp_bind: None,
+            tail_dir: None,
+            log_path: None,
+            output_path: None,
+            drain_interval_secs: 5,
+            drain_wakeup: true,
+            drain_batch_ms: 100,
+            immediate_events: vec![
+                "session_init".to_string(),
+                "on_rocket_launched".to_string(),
+                "on_player_died".to_string(),
+            ],
+            cycle_deadline_ms: None,
+            cache_capacity: 10000,
+            cache_overflow: "drop_oldest".to_string(),
+            event_workers: 4,
+            warm_start_secs: None,
+            status_interval_secs: None,
+            store_events: false,
+            event_store_path: None,
+            run_index_path: None,
+            metadata: HashMap::new(),
+            upload_budget: UploadBudgetSettings::default(),
+            watchdog: WatchdogSettings::default(),
+            rotation: RotationSettings::default(),
+            tournament: TournamentSettings::default(),
+            maps: MapSettings::default(),
+            wal: WalSettings::default(),
+            wandb: WandbSettings::default(),
+            weave: WeaveSettings::default(),
+            item_categories: ItemCategorySettings::default(),
+            faults: FaultSettings::default(),
+            rcon: RconSettings::default(),
+            admin: AdminSettings::default(),
+            grpc: GrpcSettings::default(),
+            websocket: WebSocketSettings::default(),
+            images: ImageSettings::default(),
+            influx: InfluxSettings::default(),
+            mqtt: MqttSettings::default(),
+            export: ExportSettings::default(),
+            kafka: KafkaSettings::default(),
+            webhooks: Vec::new(),
+            completion_report: CompletionReportSettings::default(),
+            notifications: NotificationSettings::default(),
+            project_routes: Vec::new(),
+        }
+    }
+}
+
+/// Environment variables read by `AppConfig::apply_env`, with the key each one overrides
+/// and its type; keep both in step. `[]` stands for every entry of an array of tables.
+const ENV_VARS: &[(&str, &str, &str)] = &[
+    ("FACTORIO_PIPE_PATH", "pipe_path", "string"),
+    ("FACTORIO_PIPE_PATHS", "pipe_paths", "array"),
+    ("FACTORIO_UDP_BIND", "udp_bind", "string"),
+    ("FACTORIO_TAIL_DIR", "tail_dir", "string"),
+    ("FACTORIO_LOG_PATH", "log_path", "string"),
+    ("FACTORIO_OUTPUT_PATH", "output_path", "string"),
+    ("FACTORIO_DRAIN_INTERVAL_SECS", "drain_interval_secs", "integer"),
+    ("FACTORIO_DRAIN_WAKEUP", "drain_wakeup", "boolean"),
+    ("FACTORIO_DRAIN_BATCH_MS", "drain_batch_ms", "integer"),
+    ("FACTORIO_IMMEDIATE_EVENTS", "immediate_events", "array"),
+    ("FACTORIO_CYCLE_DEADLINE_MS", "cycle_deadline_ms", "integer"),
+    ("FACTORIO_CACHE_CAPACITY", "cache_capacity", "integer"),
+    ("FACTORIO_CACHE_OVERFLOW", "cache_overflow", "string"),
+    ("FACTORIO_EVENT_WORKERS", "event_workers", "integer"),
+    ("FACTORIO_WARM_START_SECS", "warm_start_secs", "integer"),
+    ("FACTORIO_STATUS_INTERVAL_SECS", "status_interval_secs", "integer"),
+    ("FACTORIO_STORE_EVENTS", "store_events", "boolean"),
+    ("FACTORIO_EVENT_STORE", "event_store_path", "string"),
+    ("FACTORIO_RUN_INDEX", "run_index_path", "string"),
+    ("FACTORIO_META", "metadata", "table"),
+    ("UPLOAD_BUDGET_MB_PER_HOUR", "upload_budget.mb_per_hour", "float"),
+    ("WATCHDOG_MAX_RESTARTS", "watchdog.max_restarts", "integer"),
+    ("WATCHDOG_RESET_SECS", "watchdog.reset_secs", "integer"),
+    ("RUN_ROTATE_EVERY_TICKS", "rotation.every_ticks", "integer"),
+    ("RUN_ROTATE_EVERY_SECS", "rotation.every_secs", "integer"),
+    ("TOURNAMENT_NAME", "tournament.name", "string"),
+    ("TOURNAMENT_KPIS", "tournament.kpis", "array"),
+    ("EXPLORATION_HEATMAP_CYCLES", "maps.exploration_heatmap_cycles", "integer"),
+    ("POLLUTION_MAP_CYCLES", "maps.pollution_map_cycles", "integer"),
+    ("FACTORIO_DATA_DUMP", "item_categories.data_dump", "string"),
+    ("FACTORIO_WAL", "wal.enabled", "boolean"),
+    ("FACTORIO_WAL_DIR", "wal.dir", "string"),
+    ("WANDB_ENTITY", "wandb.entity", "string"),
+    ("WANDB_PROJECT", "wandb.project", "string"),
+    ("WANDB_MODE", "wandb.mode", "string"),
+    ("WANDB_MAX_METRIC_KEYS", "wandb.max_metric_keys", "integer"),
+    ("WANDB_DOWNSAMPLE", "wandb.downsample.mode", "string"),
+    ("WANDB_DOWNSAMPLE_EVERY", "wandb.downsample.every", "integer"),
+    ("WANDB_DOWNSAMPLE_THRESHOLD", "wandb.downsample.change_threshold", "float"),
+    ("WEAVE_ENTITY", "weave.entity", "string"),
+    ("WEAVE_PROJECT", "weave.project", "string"),
+    ("WEAVE_BASE_URL", "weave.base_url", "string"),
+    ("WEAVE_TRANSPORT", "weave.transport", "string"),
+    ("WEAVE_BINARY_PATH", "weave.binary_path", "string"),
+    ("WEAVE_SOCKET_DIR", "weave.socket_dir", "string"),
+    ("WEAVE_COMPRESSION", "weave.compression", "boolean"),
+    ("WEAVE_PING_INTERVAL_SECS", "weave.ping_interval_secs", "integer"),
+    ("WEAVE_REQUEST_TIMEOUT_SECS", "weave.request_timeout_secs", "integer"),
+    ("WEAVE_DRAIN_TIMEOUT_SECS", "weave.drain_timeout_secs", "integer"),
+    ("WEAVE_SCREENSHOT_LINK_TICKS", "weave.screenshot_link_ticks", "integer"),
+    ("SCREENSHOT_DEDUPE_DISTANCE", "weave.screenshots.dedupe_distance", "integer"),
+    ("SCREENSHOT_OVERLAY_FONT", "weave.screenshots.overlay_font", "string"),
+    ("SCREENSHOT_OVERLAY_SIZE", "weave.screenshots.overlay_size", "float"),
+    ("WEAVE_PREFLIGHT", "weave.preflight", "boolean"),
+    ("WEAVE_PREFLIGHT_REQUIRED", "weave.preflight_required", "boolean"),
+    ("WEAVE_DRAIN_SUMMARY", "weave.drain_summary", "boolean"),
+    ("WEAVE_ENQUEUE_BATCH_ITEMS", "weave.enqueue_batch_items", "integer"),
+    ("WEAVE_RETRY_QUEUE_CALLS", "weave.retry.queue_calls", "integer"),
+    ("WEAVE_RETRY_MAX_ATTEMPTS", "weave.retry.max_attempts", "integer"),
+    ("WEAVE_DELIVERY_STATS", "weave.delivery.stats", "string"),
+    ("WEAVE_DELIVERY_ENTITY", "weave.delivery.entity", "string"),
+    ("WEAVE_DELIVERY_SCREENSHOT", "weave.delivery.screenshot", "string"),
+    ("WEAVE_DELIVERY_SESSION", "weave.delivery.session", "string"),
+    ("SPOOL_RETENTION_HOURS", "weave.spool_retention_hours", "integer"),
+    ("SPOOL_COMPACT_INTERVAL_SECS", "weave.spool_compact_interval_secs", "integer"),
+    ("FAULT_FAIL_PERCENT", "faults.fail_percent", "float"),
+    ("FAULT_DELAY_PERCENT", "faults.delay_percent", "float"),
+    ("FAULT_DELAY_MS", "faults.delay_ms", "integer"),
+    ("FAULT_SINKS", "faults.sinks", "array"),
+    ("FACTORIO_RCON_ADDRESS", "rcon.address", "string"),
+    ("FACTORIO_RCON_POLL_SECS", "rcon.poll_interval_secs", "integer"),
+    ("FACTORIO_RCON_SURFACE", "rcon.surface", "string"),
+    ("FACTORIO_RCON_CATCH_UP", "rcon.catch_up", "boolean"),
+    ("FACTORIO_RCON_INSTANCE", "rcon.instance", "string"),
+    ("FACTORIO_RCON_SCREENSHOT_SECS", "rcon.screenshot_interval_secs", "integer"),
+    ("FACTORIO_IMAGE_WORKERS", "images.workers", "integer"),
+    ("FACTORIO_IMAGE_QUEUE", "images.queue", "integer"),
+    ("INFLUX_URL", "influx.url", "string"),
+    ("INFLUX_ORG", "influx.org", "string"),
+    ("INFLUX_BUCKET", "influx.bucket", "string"),
+    ("MQTT_BROKER", "mqtt.broker", "string"),
+    ("MQTT_CLIENT_ID", "mqtt.client_id", "string"),
+    ("MQTT_METRICS_TOPIC", "mqtt.metrics_topic", "string"),
+    ("MQTT_EVENTS_TOPIC", "mqtt.events_topic", "string"),
+    ("MQTT_METRICS", "mqtt.metrics", "array"),
+    ("MQTT_LIVE", "mqtt.live", "boolean"),
+    ("MQTT_LIVE_TOPIC", "mqtt.live_topic", "string"),
+    ("KAFKA_BROKERS", "kafka.brokers", "string"),
+    ("KAFKA_TOPIC", "kafka.topic", "string"),
+    ("FACTORIO_WEBHOOK_URL", "webhooks[].url", "string"),
+    ("FACTORIO_WEBHOOK_EVENTS", "webhooks[].events", "array"),
+    ("FACTORIO_NOTIFY_WEBHOOK_URL", "notifications.webhook_url", "string"),
+    ("FACTORIO_NOTIFY_SERVICE", "notifications.service", "string"),
+    ("FACTORIO_REPORT_WEBHOOK_URL", "completion_report.webhook_url", "string"),
+    ("FACTORIO_REPORT_SMTP_SERVER", "completion_report.smtp_server", "string"),
+    ("FACTORIO_REPORT_SMTP_PORT", "completion_report.smtp_port", "integer"),
+    ("FACTORIO_REPORT_FROM", "completion_report.from", "string"),
+    ("FACTORIO_REPORT_TO", "completion_report.to", "array"),
+    ("FACTORIO_EXPORT_DIR", "export.dir", "string"),
+    ("FACTORIO_EXPORT_FORMATS", "export.formats", "array"),
+    ("FACTORIO_ADMIN_BIND", "admin.bind", "string"),
+    ("FACTORIO_ADMIN_ALLOW_REMOTE", "admin.allow_remote", "boolean"),
+    ("FACTORIO_ADMIN_DASHBOARD", "admin.dashboard", "boolean"),
+    ("FACTORIO_GRPC_BIND", "grpc.bind", "string"),
+    ("FACTORIO_GRPC_ALLOW_REMOTE", "grpc.allow_remote", "boolean"),
+    ("FACTORIO_WS_BIND", "websocket.bind", "string"),
+    ("FACTORIO_WS_ALLOW_REMOTE", "websocket.allow_remote", "boolean"),
+];
+
+/// Types of the keys that are unset by default and have no environment variable
+const UNSET_KEYS: &[(&str, &str)] = &[
+    ("instance", "string"),
+    ("webhooks[].url_env", "string"),
+    ("webhooks[].template", "string"),
+    ("notifications.webhook_url_env", "string"),
+    ("notifications.rules[].message", "string"),
+    ("completion_report.webhook_url_env", "string"),
+    ("project_routes[].session_id", "string"),
+    ("project_routes[].level_name", "string"),
+    ("project_routes[].wandb_entity", "string"),
+    ("project_routes[].wandb_project", "string"),
+    ("project_routes[].weave_entity", "string"),
+    ("project_routes[].weave_project", "string"),
+];
+
+/// Environment variables the client reads that no config key sets, with their types:
+/// the config file and state locations, and secrets, which stay out of config files
+const ENV_ONLY_VARS: &[(&str, &str)] = &[
+    ("FACTORIO_CONFIG", "string"),
+    ("FACTORIO_STATE_DIR", "string"),
+    ("FACTORIO_ADMIN_TOKEN", "string"),
+    ("FACTORIO_GRPC_TOKEN", "string"),
+    ("FACTORIO_WS_TOKEN", "string"),
+    ("FACTORIO_RCON_PASSWORD", "string"),
+    ("WANDB_API_KEY", "string"),
+    ("INFLUX_TOKEN", "string"),
+    ("KAFKA_USERNAME", "string"),
+    ("KAFKA_PASSWORD", "string"),
+    ("MQTT_USERNAME", "string"),
+    ("MQTT_PASSWORD", "string"),
+    ("SMTP_USERNAME", "string"),
+    ("SMTP_PASSWORD", "string"),
+    ("HOME", "string"),
+    ("XDG_RUNTIME_DIR", "string"),
+];
+
+impl AppConfig {
+    /// Loads the config file at `path` or FACTORIO_CONFIG (if any), then applies
+    /// environment overrides
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var("FACTORIO_CONFIG").ok().map(PathBuf::from));
+
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    /// Parses a config file; `.yaml`/`.yml` files are YAML, anything else TOML
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {:?}: {}", path, e))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&text)
+                .map_err(|e| format!("Invalid config {:?}: {}", path, e)),
+            _ => toml::from_str(&text).map_err(|e| format!("Invalid config {:?}: {}", path, e)),
+        }
+    }
+
+    /// Overrides file settings with the environment variables the client has always read,
+    /// failing on a value that doesn't parse
+    fn apply_env(&mut self) -> Result<(), String> {
+        fn var(name: &str) -> Option<String> {
+            env::var(name).ok()
+        }
+        fn parsed<T>(name: &str) -> Result<Option<T>, String>
+        where
+            T: std::str::FromStr,
+            T::Err: std::fmt::Display,
+        {
+            var(name)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|e| format!("Invalid {} {:?}: {}", name, value, e))
+                })
+                .transpose()
+        }
+
+        if let Some(value) = var("FACTORIO_PIPE_PATH") {
+            self.pipe_path = value;
+        }
+        if let Some(value) = var("FACTORIO_PIPE_PATHS") {
+            self.pipe_paths = value
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(value) = var("FACTORIO_UDP_BIND") {
+            self.udp_bind = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_TAIL_DIR") {
+            self.tail_dir = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_LOG_PATH") {
+            self.log_path = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_OUTPUT_PATH") {
+            self.output_path = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_DRAIN_INTERVAL_SECS")? {
+            self.drain_interval_secs = value;
+        }
+        if let Some(value) = parsed("FACTORIO_DRAIN_WAKEUP")? {
+            self.drain_wakeup = value;
+        }
+        if let Some(value) = parsed("FACTORIO_DRAIN_BATCH_MS")? {
+            self.drain_batch_ms = value;
+        }
+        if let Some(value) = var("FACTORIO_IMMEDIATE_EVENTS") {
+            self.immediate_events = value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Some(value) = parsed("FACTORIO_CYCLE_DEADLINE_MS")? {
+            self.cycle_deadline_ms = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_CACHE_CAPACITY")? {
+            self.cache_capacity = value;
+        }
+        if let Some(value) = var("FACTORIO_CACHE_OVERFLOW") {
+            self.cache_overflow = value;
+        }
+        if let Some(value) = parsed("FACTORIO_EVENT_WORKERS")? {
+            self.event_workers = value;
+        }
+        if let Some(value) = parsed("FACTORIO_WARM_START_SECS")? {
+            self.warm_start_secs = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_STATUS_INTERVAL_SECS")? {
+            self.status_interval_secs = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_STORE_EVENTS")? {
+            self.store_events = value;
+        }
+        if let Some(value) = var("FACTORIO_EVENT_STORE") {
+            self.event_store_path = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("FACTORIO_RUN_INDEX") {
+            self.run_index_path = Some(PathBuf::from(value));
+        }
+        // Comma-separated `key=value` pairs, added to those of the file
+        if let Some(value) = var("FACTORIO_META") {
+            self.metadata.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|pair| !pair.is_empty())
+                    .filter_map(run_metadata::parse_pair),
+            );
+        }
+        if let Some(value) = parsed("UPLOAD_BUDGET_MB_PER_HOUR")? {
+            self.upload_budget.mb_per_hour = Some(value);
+        }
+        if let Some(value) = parsed("WATCHDOG_MAX_RESTARTS")? {
+            self.watchdog.max_restarts = value;
+        }
+        if let Some(value) = parsed("WATCHDOG_RESET_SECS")? {
+            self.watchdog.reset_secs = value;
+        }
+        if let Some(value) = parsed("RUN_ROTATE_EVERY_TICKS")? {
+            self.rotation.every_ticks = Some(value);
+        }
+        if let Some(value) = parsed("RUN_ROTATE_EVERY_SECS")? {
+            self.rotation.every_secs = Some(value);
+        }
+        if let Some(value) = var("TOURNAMENT_NAME") {
+            self.tournament.name = Some(value);
+        }
+        if let Some(value) = var("TOURNAMENT_KPIS") {
+            self.tournament.kpis = value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Some(value) = parsed("EXPLORATION_HEATMAP_CYCLES")? {
+            self.maps.exploration_heatmap_cycles = Some(value);
+        }
+        if let Some(value) = parsed("POLLUTION_MAP_CYCLES")? {
+            self.maps.pollution_map_cycles = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_DATA_DUMP") {
+            self.item_categories.data_dump = Some(PathBuf::from(value));
+        }
+        if let Some(value) = parsed("FACTORIO_WAL")? {
+            self.wal.enabled = value;
+        }
+        if let Some(value) = var("FACTORIO_WAL_DIR") {
+            self.wal.dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("WANDB_ENTITY") {
+            self.wandb.entity = value;
+        }
+        if let Some(value) = var("WANDB_PROJECT") {
+            self.wandb.project = value;
+        }
+        if let Some(value) = var("WANDB_MODE") {
+            self.wandb.mode = Some(value);
+        }
+        if let Some(value) = parsed("WANDB_MAX_METRIC_KEYS")? {
+            self.wandb.max_metric_keys = value;
+        }
+        if let Some(value) = var("WANDB_DOWNSAMPLE") {
+            self.wandb.downsample.mode = value;
+        }
+        if let Some(value) = parsed("WANDB_DOWNSAMPLE_EVERY")? {
+            self.wandb.downsample.every = value;
+        }
+        if let Some(value) = parsed("WANDB_DOWNSAMPLE_THRESHOLD")? {
+            self.wandb.downsample.change_threshold = value;
+        }
+        if let Some(value) = var("WEAVE_ENTITY") {
+            self.weave.entity = Some(value);
+        }
+        if let Some(value) = var("WEAVE_PROJECT") {
+            self.weave.project = Some(value);
+        }
+        if let Some(value) = var("WEAVE_BASE_URL") {
+            self.weave.base_url = value;
+        }
+        if let Some(value) = var("WEAVE_TRANSPORT") {
+            self.weave.transport = value;
+        }
+        if let Some(value) = var("WEAVE_BINARY_PATH") {
+            self.weave.binary_path = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("WEAVE_SOCKET_DIR") {
+            self.weave.socket_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = parsed("WEAVE_COMPRESSION")? {
+            self.weave.compression = value;
+        }
+        if let Some(value) = parsed("WEAVE_PING_INTERVAL_SECS")? {
+            self.weave.ping_interval_secs = value;
+        }
+        if let Some(value) = parsed("WEAVE_REQUEST_TIMEOUT_SECS")? {
+            self.weave.request_timeout_secs = value;
+        }
+        if let Some(value) = parsed("WEAVE_DRAIN_TIMEOUT_SECS")? {
+            self.weave.drain_timeout_secs = value;
+        }
+        if let Some(value) = parsed("WEAVE_SCREENSHOT_LINK_TICKS")? {
+            self.weave.screenshot_link_ticks = value;
+        }
+        // A distance, or `off` to upload every snapshot
+        if let Some(value) = var("SCREENSHOT_DEDUPE_DISTANCE") {
+            if value.eq_ignore_ascii_case("off") {
+                self.weave.screenshots.dedupe = false;
+            } else {
+                self.weave.screenshots.dedupe_distance = value.parse().map_err(|e| {
+                    format!("Invalid SCREENSHOT_DEDUPE_DISTANCE {:?}: {}", value, e)
+                })?;
+            }
+        }
+        if let Some(value) = var("SCREENSHOT_OVERLAY_FONT") {
+            self.weave.screenshots.overlay_font = Some(PathBuf::from(value));
+        }
+        if let Some(value) = parsed("SCREENSHOT_OVERLAY_SIZE")? {
+            self.weave.screenshots.overlay_size = value;
+        }
+        if let Some(value) = parsed("WEAVE_PREFLIGHT")? {
+            self.weave.preflight = value;
+        }
+        if let Some(value) = parsed("WEAVE_PREFLIGHT_REQUIRED")? {
+            self.weave.preflight_required = value;
+        }
+        if let Some(value) = parsed("WEAVE_DRAIN_SUMMARY")? {
+            self.weave.drain_summary = value;
+        }
+        if let Some(value) = parsed("WEAVE_ENQUEUE_BATCH_ITEMS")? {
+            self.weave.enqueue_batch_items = value;
+        }
+        if let Some(value) = parsed("WEAVE_RETRY_QUEUE_CALLS")? {
+            self.weave.retry.queue_calls = value;
+        }
+        if let Some(value) = parsed("WEAVE_RETRY_MAX_ATTEMPTS")? {
+            self.weave.retry.max_attempts = value;
+        }
+        if let Some(value) = var("WEAVE_DELIVERY_STATS") {
+            self.weave.delivery.stats = value;
+        }
+        if let Some(value) = var("WEAVE_DELIVERY_ENTITY") {
+            self.weave.delivery.entity = value;
+        }
+        if let Some(value) = var("WEAVE_DELIVERY_SCREENSHOT") {
+            self.weave.delivery.screenshot = value;
+        }
+        if let Some(value) = var("WEAVE_DELIVERY_SESSION") {
+            self.weave.delivery.session = value;
+        }
+        if let Some(value) = parsed("SPOOL_RETENTION_HOURS")? {
+            self.weave.spool_retention_hours = value;
+        }
+        if let Some(value) = parsed("SPOOL_COMPACT_INTERVAL_SECS")? {
+            self.weave.spool_compact_interval_secs = value;
+        }
+        if let Some(value) = parsed("FAULT_FAIL_PERCENT")? {
+            self.faults.fail_percent = value;
+        }
+        if let Some(value) = parsed("FAULT_DELAY_PERCENT")? {
+            self.faults.delay_percent = value;
+        }
+        if let Some(value) = parsed("FAULT_DELAY_MS")? {
+            self.faults.delay_ms = value;
+        }
+        if let Some(value) = var("FAULT_SINKS") {
+            self.faults.sinks = value.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Some(value) = var("FACTORIO_RCON_ADDRESS") {
+            self.rcon.address = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_RCON_POLL_SECS")? {
+            self.rcon.poll_interval_secs = value;
+        }
+        if let Some(value) = var("FACTORIO_RCON_SURFACE") {
+            self.rcon.surface = value;
+        }
+        if let Some(value) = parsed("FACTORIO_RCON_CATCH_UP")? {
+            self.rcon.catch_up = value;
+        }
+        if let Some(value) = var("FACTORIO_RCON_INSTANCE") {
+            self.rcon.instance = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_RCON_SCREENSHOT_SECS")? {
+            self.rcon.screenshot_interval_secs = value;
+        }
+        if let Some(value) = parsed("FACTORIO_IMAGE_WORKERS")? {
+            self.images.workers = value;
+        }
+        if let Some(value) = parsed("FACTORIO_IMAGE_QUEUE")? {
+            self.images.queue = value;
+        }
+        if let Some(value) = var("INFLUX_URL") {
+            self.influx.url = Some(value);
+        }
+        if let Some(value) = var("INFLUX_ORG") {
+            self.influx.org = value;
+        }
+        if let Some(value) = var("INFLUX_BUCKET") {
+            self.influx.bucket = value;
+        }
+        if let Some(value) = var("MQTT_BROKER") {
+            self.mqtt.broker = Some(value);
+        }
+        if let Some(value) = var("MQTT_CLIENT_ID") {
+            self.mqtt.client_id = value;
+        }
+        if let Some(value) = var("MQTT_METRICS_TOPIC") {
+            self.mqtt.metrics_topic = value;
+        }
+        if let Some(value) = var("MQTT_EVENTS_TOPIC") {
+            self.mqtt.events_topic = value;
+        }
+        if let Some(value) = var("MQTT_METRICS") {
+            self.mqtt.metrics = value.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Some(value) = parsed("MQTT_LIVE")? {
+            self.mqtt.live = value;
+        }
+        if let Some(value) = var("MQTT_LIVE_TOPIC") {
+            self.mqtt.live_topic = value;
+        }
+        if let Some(value) = var("KAFKA_BROKERS") {
+            self.kafka.brokers = Some(value);
+        }
+        if let Some(value) = var("KAFKA_TOPIC") {
+            self.kafka.topic = value;
+        }
+        // One more webhook, besides those of the config file
+        if let Some(value) = var("FACTORIO_WEBHOOK_URL") {
+            self.webhooks.push(WebhookSettings {
+                url: Some(value),
+                events: var("FACTORIO_WEBHOOK_EVENTS")
+                    .map(|events| events.split(',').map(|s| s.trim().to_string()).collect())
+                    .unwrap_or_default(),
+                ..WebhookSettings::default()
+            });
+        }
+        if let Some(value) = var("FACTORIO_NOTIFY_WEBHOOK_URL") {
+            self.notifications.webhook_url = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_NOTIFY_SERVICE") {
+            self.notifications.service = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_REPORT_WEBHOOK_URL") {
+            self.completion_report.webhook_url = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_REPORT_SMTP_SERVER") {
+            self.completion_report.smtp_server = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_REPORT_SMTP_PORT")? {
+            self.completion_report.smtp_port = value;
+        }
+        if let Some(value) = var("FACTORIO_REPORT_FROM") {
+            self.completion_report.from = value;
+        }
+        if let Some(value) = var("FACTORIO_REPORT_TO") {
+            self.completion_report.to = value.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Some(value) = var("FACTORIO_EXPORT_DIR") {
+            self.export.dir = Some(value);
+        }
+        if let Some(value) = var("FACTORIO_EXPORT_FORMATS") {
+            self.export.formats = value.split(',').map(|s| s.trim().to_string()).collect();
+        }
+        if let Some(value) = var("FACTORIO_ADMIN_BIND") {
+            self.admin.bind = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_ADMIN_ALLOW_REMOTE")? {
+            self.admin.allow_remote = value;
+        }
+        if let Some(value) = parsed("FACTORIO_ADMIN_DASHBOARD")? {
+            self.admin.dashboard = value;
+        }
+        if let Some(value) = var("FACTORIO_GRPC_BIND") {
+            self.grpc.bind = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_GRPC_ALLOW_REMOTE")? {
+            self.grpc.allow_remote = value;
+        }
+        if let Some(value) = var("FACTORIO_WS_BIND") {
+            self.websocket.bind = Some(value);
+        }
+        if let Some(value) = parsed("FACTORIO_WS_ALLOW_REMOTE")? {
+            self.websocket.allow_remote = value;
+        }
+        Ok(())
+    }
+
+    /// Describes every config key (its dotted path, type, default and overriding
+    /// environment variable) for `--print-config-schema`, derived from the default
+    /// settings. Arrays of tables also describe the keys of their entries, and the
+    /// environment variables no key sets follow with a null key. Fails on a null-default
+    /// key with no type in `UNSET_KEYS`.
+    pub fn schema() -> Result<serde_json::Value, String> {
+        fn defaults<T: Serialize>(settings: T) -> serde_json::Value {
+            serde_json::to_value(settings).expect("settings serialize to JSON")
+        }
+        let entries = HashMap::from([
+            ("webhooks", defaults(WebhookSettings::default())),
+            ("project_routes", defaults(ProjectRouteSettings::default())),
+            ("notifications.rules", defaults(NotificationRule::default())),
+        ]);
+
+        let mut keys = Vec::new();
+        describe_keys("", &defaults(Self::default()), &entries, &mut keys)?;
+        keys.extend(ENV_ONLY_VARS.iter().map(|(env, kind)| {
+            serde_json::json!({
+                "key": null,
+                "type": kind,
+                "default": null,
+                "env": env,
+            })
+        }));
+        Ok(serde_json::Value::Array(keys))
+    }
+
+    pub fn drain_interval(&self) -> Duration {
+        Duration::from_secs(self.drain_interval_secs.max(1))
+    }
+
+    /// Gathering window of a woken drain, None when drains only follow the interval
+    pub fn drain_batch(&self) -> Option<Duration> {
+        self.drain_wakeup
+            .then(|| Duration::from_millis(self.drain_batch_ms).min(self.drain_interval()))
+    }
+
+    pub fn cycle_deadline(&self) -> Duration {
+        match self.cycle_deadline_ms {
+            Some(ms) => Duration::from_millis(ms.max(1)),
+            None => self.drain_interval(),
+        }
+    }
+
+    pub fn warm_start(&self) -> Option<Duration> {
+        self.warm_start_secs.map(Duration::from_secs)
+    }
+
+    pub fn status_interval(&self) -> Option<Duration> {
+        self.status_interval_secs
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+    }
+}
+
+/// Appends a description of every key of a table, recursing into sub-tables
+fn describe_keys(
+    prefix: &str,
+    table: &serde_json::Value,
+    entries: &HashMap<&str, serde_json::Value>,
+    keys: &mut Vec<serde_json::Value>,
+) -> Result<(), String> {
+    let Some(table) = table.as_object() else {
+        return Ok(());
+    };
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        let row = ENV_VARS.iter().find(|(_, path, _)| *path == key);
+        let kind = match (value, row) {
+            (serde_json::Value::Object(fields), _) if !fields.is_empty() => {
+                describe_keys(&key, value, entries, keys)?;
+                continue;
+            }
+            (_, Some((_, _, kind))) => *kind,
+            (serde_json::Value::Object(_), None) => "table",
+            (serde_json::Value::Array(_), None) if entries.contains_key(key.as_str()) => {
+                "array of tables"
+            }
+            (serde_json::Value::Array(_), None) => "array",
+            (serde_json::Value::Bool(_), None) => "boolean",
+            (serde_json::Value::Number(number), None) if number.is_f64() => "float",
+            (serde_json::Value::Number(_), None) => "integer",
+            (serde_json::Value::String(_), None) => "string",
+            (serde_json::Value::Null, None) => UNSET_KEYS
+                .iter()
+                .find(|(path, _)| *path == key)
+                .map(|(_, kind)| *kind)
+                .ok_or_else(|| format!("Config key {} has no type in UNSET_KEYS", key))?,
+        };
+        let env = row.map(|(name, _, _)| *name);
+        keys.push(serde_json::json!({
+            "key": key,
+            "type": kind,
+            "default": value,
+            "env": env,
+        }));
+        if let Some(entry) = entries.get(key.as_str()) {
+            describe_keys(&format!("{}[]", key), entry, entries, keys)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Step boundaries kept to resolve the step of late calls (e.g. replayed from the spool)
+const MAX_BOUNDARIES: usize = 10_000;
+
+/// A WandB step that was closed, with the Weave calls made while it was active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosedStep {
+    pub step: u64,
+    pub calls: u64,
+    /// Calls that arrived after their step had already closed
+    pub late_calls: u64,
+}
+
+#[derive(Default)]
+struct State {
+    /// (tick, step) at which each step started, oldest first
+    boundaries: VecDeque<(u64, u64)>,
+    calls: u64,
+    late_calls: u64,
+}
+
+impl State {
+    /// Takes the call counts of the current step
+    fn close(&mut self) -> Option<ClosedStep> {
+        let &(_, step) = self.boundaries.back()?;
+        let closed = ClosedStep {
+            step,
+            calls: self.calls,
+            late_calls: self.late_calls,
+        };
+        self.calls = 0;
+        self.late_calls = 0;
+        Some(closed)
+    }
+}
+
+/// Aligns Weave calls with WandB steps by construction. The step active at a
+/// tick is the last stats cycle logged at or before that tick; every Weave call
+/// carries it as `wandb_step`, and when the step closes, the number of calls made
+/// in its window is logged at that same step as `weave/calls`. Events arrive in
+/// tick order, so both sides agree on the window of every call.
+#[derive(Default)]
+pub struct StepCorrelation {
+    state: Mutex<State>,
+}
+
+impl StepCorrelation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new step at `tick`, returning the step it closes
+    pub fn begin_step(&self, step: u64, tick: u64) -> Option<ClosedStep> {
+        let mut state = self.state.lock().unwrap();
+        let closed = state.close();
+
+        state.boundaries.push_back((tick, step));
+        if state.boundaries.len() > MAX_BOUNDARIES {
+            state.boundaries.pop_front();
+        }
+        closed
+    }
+
+    /// Closes the current step when the run ends and forgets all steps
+    pub fn finish(&self) -> Option<ClosedStep> {
+        let mut state = self.state.lock().unwrap();
+        let closed = state.close();
+        state.boundaries.clear();
+        closed
+    }
+
+    /// Tick at which the current step started
+    pub fn active_tick(&self) -> Option<u64> {
+        self.state.lock().unwrap().boundaries.back().map(|&(tick, _)| tick)
+    }
+
+    /// Records a Weave call at `tick`, returning the WandB step active at that tick.
+    /// Calls before the first step of the run have no step.
+    pub fn record_call(&self, tick: u64) -> Option<u64> {
+        let mut state = self.state.lock().unwrap();
+        let &(current_tick, current_step) = state.boundaries.back()?;
+
+        if tick >= current_tick {
+            state.calls += 1;
+            return Some(current_step);
+        }
+
+        // The call's step already closed; count it as late in the current window
+        state.late_calls += 1;
+        let index = state.boundaries.partition_point(|&(start, _)| start <= tick);
+        index
+            .checked_sub(1)
+            .map(|index| state.boundaries[index].1)
+    }
+}
+
+/// Returns the Weave thread for a call: each player's actions form their own thread,
+/// while session-level events go to a separate control thread
+pub fn thread_id_for(session_id: &str, player_index: Option<u32>) -> String {
+    match player_index {
+        Some(index) => format!("{}:{}", session_id, index),
+        None => format!("{}:control", session_id),
+    }
+}
+
+/// Thread of the session-level calls of a run, recorded on the WandB run for deep-linking
+pub fn control_thread_id(run_name: &str) -> String {
+    thread_id_for(run_name, None)
+}
@@ -0,0 +1,1682 @@
+use crate::assemblers::{self, RecipeUtilization};
+use crate::bottleneck::BottleneckTracker;
+use crate::client_events::{self, ClientEvent};
+use crate::config::MapSettings;
+use crate::event_sink::{
+    CallTracer, EventRecorder, EventSink, GameEvent, Leaderboard, RecordedEvent, RunTracker,
+    SessionStart, StatsCycle, SurfaceMap,
+};
+use crate::event_writer::EventWriter;
+use crate::exploration::ExplorationTracker;
+use crate::pollution::{ChunkPollution, PollutionTracker};
+use crate::probes::{ProbeInfo, ProbeRegistry};
+use crate::project_routing;
+use crate::prototypes::{ItemPrototype, Prototypes, RecipePrototype};
+use crate::rocket_silo::{SiloReport, SiloStage};
+use crate::rotation::{RotationPolicy, RunClock};
+use crate::run_index::{RunIndex, RunRecord};
+use futures_util::future::join_all;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Position in the game world
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Player information from stats event
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PlayerInfo {
+    pub position: Position,
+    pub surface: String,
+    pub health: f64,
+}
+
+/// Lab status from stats event
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LabStatus {
+    /// Number of labs on the surface
+    pub count: u32,
+    /// Labs currently researching
+    pub working: u32,
+}
+
+impl LabStatus {
+    /// Fraction of labs that are researching
+    pub fn utilization(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.working as f64 / self.count as f64
+        }
+    }
+}
+
+/// Event types from Factorio
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum FactorioEvent {
+    #[serde(rename = "session_init")]
+    SessionInit {
+        session_id: String,
+        tick: u64,
+        level_name: String,
+        /// Fingerprint of the save, shared by every session continuing it
+        #[serde(default)]
+        save_id: Option<String>,
+    },
+    #[serde(rename = "stats")]
+    Stats {
+        session_id: String,
+        cycle: u64,
+        tick: u64,
+        #[serde(default)]
+        player: Option<PlayerInfo>,
+        #[serde(default)]
+        screenshot_path: Option<String>,
+        /// Tournament participant name (server or force); defaults to the session_id
+        #[serde(default)]
+        participant: Option<String>,
+        /// When true, only items whose values changed since the last stats are included
+        #[serde(default)]
+        delta: bool,
+        #[serde(default)]
+        labs: Option<LabStatus>,
+        /// Evolution factor of the enemy force
+        #[serde(default)]
+        evolution: Option<f64>,
+        /// Crafting machine utilization per recipe, when the mod samples it
+        #[serde(default)]
+        assemblers: Option<Vec<RecipeUtilization>>,
+        /// Probe readings keyed by probe id (see `probe_registry`)
+        #[serde(default)]
+        probes: HashMap<String, f64>,
+        /// Re-sent by the mod for a catch-up of a client that started mid-session
+        #[serde(default)]
+        backlog: bool,
+        products_production: HashMap<String, f64>,
+        materials_consumption: HashMap<String, f64>,
+    },
+    /// Per-chunk pollution sample of a surface
+    #[serde(rename = "pollution")]
+    Pollution {
+        session_id: String,
+        tick: u64,
+        surface: String,
+        chunks: Vec<ChunkPollution>,
+    },
+    /// Full probe registry, sent by the mod whenever it changes
+    #[serde(rename = "probe_registry")]
+    ProbeRegistry {
+        session_id: String,
+        tick: u64,
+        probes: Vec<ProbeInfo>,
+    },
+    /// Item and recipe prototypes of the game, sent once per session
+    #[serde(rename = "prototypes")]
+    Prototypes {
+        session_id: String,
+        tick: u64,
+        #[serde(default)]
+        items: HashMap<String, ItemPrototype>,
+        #[serde(default)]
+        recipes: HashMap<String, RecipePrototype>,
+    },
+    /// A stage of a rocket silo's launch pipeline (see `rocket_silo`)
+    #[serde(rename = "rocket_silo")]
+    RocketSilo {
+        session_id: String,
+        tick: u64,
+        /// Unit number of the silo
+        silo: u64,
+        stage: String,
+        #[serde(default)]
+        surface: Option<String>,
+        #[serde(default)]
+        parts: Option<u32>,
+        #[serde(default)]
+        parts_required: Option<u32>,
+        #[serde(default)]
+        cargo: HashMap<String, u32>,
+    },
+    #[serde(rename = "event")]
+    GameEvent {
+        event_name: String,
+        session_id: String,
+        tick: u64,
+        #[serde(default)]
+        player_index: Option<u32>,
+        #[serde(default)]
+        entity: Option<String>,
+        #[serde(default)]
+        position: Option<Position>,
+        #[serde(default)]
+        surface: Option<String>,
+        #[serde(default)]
+        tech_name: Option<String>,
+        #[serde(default)]
+        tech_level: Option<u32>,
+        /// Whether the tech is infinite, researched again at every higher level
+        #[serde(default)]
+        infinite: Option<bool>,
+        #[serde(default)]
+        item: Option<String>,
+        #[serde(default)]
+        count: Option<u32>,
+    },
+}
+
+impl FactorioEvent {
+    pub fn session_id(&self) -> &str {
+        match self {
+            FactorioEvent::SessionInit { session_id, .. }
+            | FactorioEvent::Stats { session_id, .. }
+            | FactorioEvent::Pollution { session_id, .. }
+            | FactorioEvent::ProbeRegistry { session_id, .. }
+            | FactorioEvent::Prototypes { session_id, .. }
+            | FactorioEvent::RocketSilo { session_id, .. }
+            | FactorioEvent::GameEvent { session_id, .. } => session_id,
+        }
+    }
+
+    pub fn tick(&self) -> u64 {
+        match self {
+            FactorioEvent::SessionInit { tick, .. }
+            | FactorioEvent::Stats { tick, .. }
+            | FactorioEvent::Pollution { tick, .. }
+            | FactorioEvent::ProbeRegistry { tick, .. }
+            | FactorioEvent::Prototypes { tick, .. }
+            | FactorioEvent::RocketSilo { tick, .. }
+            | FactorioEvent::GameEvent { tick, .. } => *tick,
+        }
+    }
+
+    /// The event's `type`, or the event name of game events (`on_built_entity`, ...)
+    pub fn kind(&self) -> &str {
+        match self {
+            FactorioEvent::SessionInit { .. } => "session_init",
+            FactorioEvent::Stats { .. } => "stats",
+            FactorioEvent::Pollution { .. } => "pollution",
+            FactorioEvent::ProbeRegistry { .. } => "probe_registry",
+            FactorioEvent::Prototypes { .. } => "prototypes",
+            FactorioEvent::RocketSilo { .. } => "rocket_silo",
+            FactorioEvent::GameEvent { event_name, .. } => event_name,
+        }
+    }
+
+    /// Checks that the event can be routed to a handler, returning the drop reason if not
+    pub fn route_check(&self) -> Result<(), String> {
+        if let FactorioEvent::RocketSilo { stage, .. } = self {
+            return match SiloStage::parse(stage) {
+                Some(_) => Ok(()),
+                None => Err(format!("unknown_stage:{}", stage)),
+            };
+        }
+        let FactorioEvent::GameEvent {
+            event_name,
+            player_index,
+            entity,
+            position,
+            surface,
+            tech_name,
+            tech_level,
+            item,
+            count,
+            ..
+        } = self
+        else {
+            return Ok(());
+        };
+
+        let complete = match event_name.as_str() {
+            "on_research_started" | "on_research_finished" => {
+                tech_name.is_some() && tech_level.is_some()
+            }
+            "on_built_entity" | "on_player_mined_entity" => {
+                player_index.is_some() && entity.is_some() && position.is_some() && surface.is_some()
+            }
+            "on_player_crafted_item" => player_index.is_some() && item.is_some() && count.is_some(),
+            "on_rocket_launched" => true,
+            "on_player_died" => player_index.is_some(),
+            "on_chunk_generated" | "on_chunk_charted" | "on_sector_scanned" => {
+                position.is_some() && surface.is_some()
+            }
+            _ => return Err(format!("unknown_event:{}", event_name)),
+        };
+
+        if complete {
+            Ok(())
+        } else {
+            Err(format!("missing_fields:{}", event_name))
+        }
+    }
+}
+
+/// Whether a sink receives events, as reported by the admin status
+#[derive(Debug, Clone, Serialize)]
+pub struct SinkStatus {
+    pub name: &'static str,
+    pub enabled: bool,
+    /// Why a disabled sink is off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// What happened to a single input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// Parsed and handed to a handler
+    Routed,
+    /// Not processed, with the reason
+    Dropped(String),
+}
+
+/// Accounting of a processed batch: every input is either routed or dropped with a reason
+#[derive(Debug, Clone, Default)]
+pub struct ProcessSummary {
+    pub routed: usize,
+    /// Drop counts keyed by reason
+    pub dropped: HashMap<String, usize>,
+    /// Parsed events per type (routed or not)
+    pub kinds: HashMap<String, usize>,
+    /// Latest game tick of the parsed events
+    pub last_tick: Option<u64>,
+}
+
+impl ProcessSummary {
+    pub fn record_kind(&mut self, kind: &str, tick: u64) {
+        *self.kinds.entry(kind.to_string()).or_insert(0) += 1;
+        self.last_tick = Some(self.last_tick.map_or(tick, |last| last.max(tick)));
+    }
+
+    pub fn record(&mut self, outcome: EventOutcome) {
+        match outcome {
+            EventOutcome::Routed => self.routed += 1,
+            EventOutcome::Dropped(reason) => *self.dropped.entry(reason).or_insert(0) += 1,
+        }
+    }
+
+    /// Total number of inputs accounted for
+    pub fn total(&self) -> usize {
+        self.routed + self.dropped.values().sum::<usize>()
+    }
+
+    /// Adds the counts of another part of the batch
+    pub fn merge(&mut self, other: ProcessSummary) {
+        self.routed += other.routed;
+        for (reason, count) in other.dropped {
+            *self.dropped.entry(reason).or_insert(0) += count;
+        }
+        for (kind, count) in other.kinds {
+            *self.kinds.entry(kind).or_insert(0) += count;
+        }
+        self.last_tick = self.last_tick.max(other.last_tick);
+    }
+}
+
+/// Parses a JSONL event line
+pub fn parse_event(line: &str) -> Result<FactorioEvent, String> {
+    parse_event_bytes(line.as_bytes())
+}
+
+/// Parses a JSONL event line straight from the reader's buffer
+pub fn parse_event_bytes(line: &[u8]) -> Result<FactorioEvent, String> {
+    serde_json::from_slice(line).map_err(|e| e.to_string())
+}
+
+/// Decides the outcome of an input line without side effects
+pub fn classify_event(line: &str) -> EventOutcome {
+    match parse_event(line) {
+        Ok(event) => classify_parsed(&event),
+        Err(_) => EventOutcome::Dropped("parse_error".to_string()),
+    }
+}
+
+/// Decides the outcome of a parsed event without side effects
+pub fn classify_parsed(event: &FactorioEvent) -> EventOutcome {
+    match event.route_check() {
+        Ok(()) => EventOutcome::Routed,
+        Err(reason) => EventOutcome::Dropped(reason),
+    }
+}
+
+/// The WandB and Weave managers of one event worker. Each keeps a single current run
+/// and trace, so every worker gets its own and the sessions processed side by side
+/// never share them.
+pub struct WorkerManagers {
+    /// Kept besides the sinks for run linking, rotation and panic reports; None when
+    /// the manager was disabled at startup
+    wandb_manager: Option<Arc<dyn RunTracker>>,
+    weave_manager: Option<Arc<dyn CallTracer>>,
+    /// The enabled managers, WandB first
+    sinks: Vec<Arc<dyn EventSink>>,
+    /// Managers left out at startup, with the reason
+    disabled_sinks: Vec<(&'static str, String)>,
+}
+
+impl WorkerManagers {
+    /// A manager that is disabled (missing credentials or configuration) is left out
+    /// entirely, so events skip it without any per-event work.
+    pub fn new(
+        wandb_manager: impl RunTracker + 'static,
+        weave_manager: impl CallTracer + 'static,
+    ) -> Self {
+        let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+        let mut disabled_sinks = Vec::new();
+
+        let wandb_manager = match wandb_manager.disabled_reason() {
+            Some(reason) => {
+                eprintln!("⚠️  WandB disabled: {}", reason);
+                disabled_sinks.push(("wandb", reason.to_string()));
+                None
+            }
+            None => {
+                let wandb_manager = Arc::new(wandb_manager);
+                sinks.push(wandb_manager.clone());
+                Some(wandb_manager as Arc<dyn RunTracker>)
+            }
+        };
+        let weave_manager = match weave_manager.disabled_reason() {
+            Some(reason) => {
+                disabled_sinks.push(("weave", reason.to_string()));
+                None
+            }
+            None => {
+                let weave_manager = Arc::new(weave_manager);
+                sinks.push(weave_manager.clone());
+                Some(weave_manager as Arc<dyn CallTracer>)
+            }
+        };
+
+        WorkerManagers {
+            wandb_manager,
+            weave_manager,
+            sinks,
+            disabled_sinks,
+        }
+    }
+}
+
+/// Builds the managers of the event worker with the given number (from 1)
+pub type WorkerFactory = Box<dyn Fn(usize) -> WorkerManagers + Send + Sync>;
+
+/// State of one session the stats cycles are derived from
+struct SessionTrackers {
+    /// Charted/generated chunks
+    exploration: ExplorationTracker,
+    /// Latest pollution samples
+    pollution: PollutionTracker,
+    /// Registered probes
+    probes: ProbeRegistry,
+    /// Recipe graph and item state the bottleneck of each cycle is found with
+    bottlenecks: BottleneckTracker,
+}
+
+impl SessionTrackers {
+    fn new(maps: &MapSettings) -> Self {
+        Self {
+            exploration: ExplorationTracker::from_settings(maps),
+            pollution: PollutionTracker::from_settings(maps),
+            probes: ProbeRegistry::default(),
+            bottlenecks: BottleneckTracker::default(),
+        }
+    }
+
+    /// Forgets the state of the session's previous run
+    fn reset(&mut self) {
+        self.exploration.reset();
+        self.pollution.reset();
+        self.probes.reset();
+        self.bottlenecks.reset();
+    }
+}
+
+/// Event mediator that routes Factorio events to its sinks (the WandB and Weave
+/// managers of each worker, and any registered with `with_sink`)
+pub struct EventMediator {
+    /// Managers of each event worker: the first are those given to `new`, the others are
+    /// built by the worker factory once a session is assigned to their worker
+    workers: std::sync::Mutex<Vec<Arc<WorkerManagers>>>,
+    worker_factory: Option<WorkerFactory>,
+    /// Workers processing the sessions of a batch side by side
+    worker_count: usize,
+    /// Worker each session was assigned to; a session keeps its worker
+    assignments: std::sync::Mutex<HashMap<String, usize>>,
+    /// Sinks shared by every worker, registered with `with_sink`
+    sinks: Vec<Arc<dyn EventSink>>,
+    /// Maps Factorio session_id -> enhanced run_name (with random suffix)
+    session_to_runname: std::sync::Arc<tokio::sync::Mutex<HashMap<String, String>>>,
+    /// Leaderboard of the sessions, set with `with_leaderboard`
+    leaderboard: Option<std::sync::Mutex<Box<dyn Leaderboard>>>,
+    /// Trackers of each session
+    trackers: std::sync::Mutex<HashMap<String, Arc<std::sync::Mutex<SessionTrackers>>>>,
+    /// Surface maps the trackers of new sessions render
+    maps: MapSettings,
+    /// Local index of published run/trace ids
+    run_index: Option<Box<dyn RunIndex>>,
+    /// Writer thread recording every parsed event (event store, Kafka)
+    writer: Option<EventWriter>,
+    /// Sessions whose next event starts a fresh run, after a rotation request
+    rotate_requested: std::sync::Mutex<HashSet<String>>,
+    /// Set at runtime to classify events without forwarding them
+    dry_run: AtomicBool,
+    /// Scheduled rotation, and when each session's current run started
+    rotation: RotationPolicy,
+    run_clocks: std::sync::Mutex<HashMap<String, RunClock>>,
+    /// Save fingerprint of each session, for linking continuations of a save
+    save_ids: std::sync::Mutex<HashMap<String, String>>,
+    /// Game instance this mediator serves, when the client serves several
+    instance: Option<String>,
+    /// Latest run started per session, marked finished in the run index once it is
+    /// replaced
+    open_runs: std::sync::Mutex<HashMap<String, String>>,
+    /// Last stats cycle routed per session, so re-sent backlog cycles are skipped
+    last_cycles: std::sync::Mutex<HashMap<String, u64>>,
+    /// Log a `status` heartbeat every this long while no events arrive
+    status_interval: Option<Duration>,
+    /// When the last events were processed, and the latest tick they reached
+    last_events: std::sync::Mutex<(Instant, u64)>,
+    /// When the last status heartbeat of the current idle stretch was logged
+    last_status: std::sync::Mutex<Option<Instant>>,
+}
+
+impl EventMediator {
+    /// Creates a new event mediator, whose first worker uses the given managers. A
+    /// manager that is disabled (missing credentials or configuration) is left out
+    /// entirely, so events skip it without any per-event work.
+    pub fn new(
+        wandb_manager: impl RunTracker + 'static,
+        weave_manager: impl CallTracer + 'static,
+    ) -> Self {
+        EventMediator {
+            workers: std::sync::Mutex::new(vec![Arc::new(WorkerManagers::new(
+                wandb_manager,
+                weave_manager,
+            ))]),
+            worker_factory: None,
+            worker_count: 1,
+            assignments: std::sync::Mutex::new(HashMap::new()),
+            sinks: Vec::new(),
+            session_to_runname: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            leaderboard: None,
+            trackers: std::sync::Mutex::new(HashMap::new()),
+            maps: MapSettings::default(),
+            run_index: None,
+            writer: None,
+            rotate_requested: std::sync::Mutex::new(HashSet::new()),
+            dry_run: AtomicBool::new(false),
+            rotation: RotationPolicy::default(),
+            run_clocks: std::sync::Mutex::new(HashMap::new()),
+            save_ids: std::sync::Mutex::new(HashMap::new()),
+            instance: None,
+            open_runs: std::sync::Mutex::new(HashMap::new()),
+            last_cycles: std::sync::Mutex::new(HashMap::new()),
+            status_interval: None,
+            last_events: std::sync::Mutex::new((Instant::now(), 0)),
+            last_status: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Registers an additional sink, handed each event along with the WandB and Weave
+    /// managers
+    pub fn with_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Prefixes the runs of this mediator with its game instance id
+    pub fn with_instance(mut self, instance: Option<String>) -> Self {
+        self.instance = instance;
+        self
+    }
+
+    /// Logs a `status` heartbeat while no events arrive, see `report_idle`
+    pub fn with_status_interval(mut self, status_interval: Option<Duration>) -> Self {
+        self.status_interval = status_interval;
+        self
+    }
+
+    /// Processes the sessions of a batch on up to `workers` workers, see
+    /// `process_events_within`. The managers of every worker after the first are built
+    /// by `factory` when a session is first assigned to it.
+    pub fn with_workers(
+        mut self,
+        workers: usize,
+        factory: impl Fn(usize) -> WorkerManagers + Send + Sync + 'static,
+    ) -> Self {
+        self.worker_count = workers.max(1);
+        self.worker_factory = Some(Box::new(factory));
+        self
+    }
+
+    /// Requests finishing the current runs; a fresh one starts at the next event of
+    /// each session
+    pub fn request_rotation(&self) {
+        println!("🔄 Run rotation requested, a new run starts at the next event");
+        let sessions: Vec<String> = self.run_clocks.lock().unwrap().keys().cloned().collect();
+        self.rotate_requested.lock().unwrap().extend(sessions);
+    }
+
+    /// Turns dry run on or off, returning whether it was on. In dry run, events are
+    /// classified and counted but no sink is called.
+    pub fn set_dry_run(&self, enabled: bool) -> bool {
+        let was_enabled = self.dry_run.swap(enabled, Ordering::SeqCst);
+        if was_enabled != enabled {
+            println!(
+                "🧪 Dry run {}",
+                if enabled { "on, events are classified but nothing is uploaded" } else { "off" }
+            );
+        }
+        was_enabled
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::SeqCst)
+    }
+
+    /// Delivers what every sink has buffered so far, without finishing the runs
+    pub async fn flush(&self) {
+        for worker in self.workers() {
+            for sink in &worker.sinks {
+                sink.flush().await;
+            }
+        }
+        for sink in &self.sinks {
+            sink.flush().await;
+        }
+        if let Some(ref writer) = self.writer {
+            writer.flush().await;
+        }
+    }
+
+    /// Returns the (run id, url) of the current WandB run of the first worker with one
+    pub fn current_run(&self) -> Option<(String, String)> {
+        self.workers().iter().find_map(|worker| {
+            worker
+                .wandb_manager
+                .as_ref()
+                .and_then(|wandb_manager| wandb_manager.current_run_info())
+        })
+    }
+
+    /// Launches the Weave sidecar of the first worker before the first event, when Weave
+    /// is enabled; the other workers launch theirs at their first event
+    pub async fn preflight(&self) -> Result<(), String> {
+        let first = self.worker(0);
+        match first.weave_manager {
+            Some(ref weave_manager) => weave_manager.preflight().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Returns every sink with whether it is enabled, and why not. Every worker has the
+    /// same managers, so they are listed once.
+    pub fn sink_status(&self) -> Vec<SinkStatus> {
+        let first = self.worker(0);
+        let mut status: Vec<SinkStatus> = first
+            .sinks
+            .iter()
+            .chain(self.sinks.iter())
+            .map(|sink| SinkStatus {
+                name: sink.name(),
+                enabled: true,
+                reason: None,
+            })
+            .collect();
+        if let Some(ref writer) = self.writer {
+            status.extend(writer.names().iter().map(|name| SinkStatus {
+                name: *name,
+                enabled: true,
+                reason: None,
+            }));
+        }
+        let disabled = first.disabled_sinks.iter();
+        status.extend(disabled.map(|(name, reason)| SinkStatus {
+            name: *name,
+            enabled: false,
+            reason: Some(reason.clone()),
+        }));
+        status
+    }
+
+    /// Returns the ids and start ticks of the open Weave calls of every worker, oldest
+    /// first
+    pub async fn active_weave_calls(&self) -> Vec<(String, u64)> {
+        let mut calls = Vec::new();
+        for worker in self.workers() {
+            if let Some(ref weave_manager) = worker.weave_manager {
+                calls.extend(weave_manager.active_calls().await);
+            }
+        }
+        calls.sort_by_key(|(_, tick)| *tick);
+        calls
+    }
+
+    /// Returns the Factorio session ids with an active run
+    pub async fn active_sessions(&self) -> Vec<String> {
+        self.session_to_runname.lock().await.keys().cloned().collect()
+    }
+
+    /// Records every new run in a local index
+    pub fn with_run_index(mut self, run_index: Option<Box<dyn RunIndex>>) -> Self {
+        self.run_index = run_index;
+        self
+    }
+
+    /// Records every parsed event to the recorders before it is forwarded, on a
+    /// writer thread of their own
+    pub fn with_recorders(mut self, recorders: Vec<Box<dyn EventRecorder>>) -> Self {
+        self.writer = EventWriter::spawn(recorders);
+        self
+    }
+
+    /// Starts a new run on the given schedule
+    pub fn with_rotation(mut self, rotation: RotationPolicy) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Renders the surface maps due with the stats cycles
+    pub fn with_maps(mut self, settings: &MapSettings) -> Self {
+        self.maps = settings.clone();
+        self
+    }
+
+    /// Managers of every worker started so far
+    fn workers(&self) -> Vec<Arc<WorkerManagers>> {
+        self.workers.lock().unwrap().clone()
+    }
+
+    /// Managers of worker `index`, built along with those of the workers before it when
+    /// they haven't been yet
+    fn worker(&self, index: usize) -> Arc<WorkerManagers> {
+        let mut workers = self.workers.lock().unwrap();
+        if let Some(ref factory) = self.worker_factory {
+            while workers.len() <= index {
+                let managers = factory(workers.len());
+                workers.push(Arc::new(managers));
+            }
+        }
+        // Without a factory every session is handled by the first worker
+        workers[index.min(workers.len() - 1)].clone()
+    }
+
+    /// Worker of a session, assigning the next one round-robin to a session seen for the
+    /// first time
+    fn assign(&self, session_id: &str) -> usize {
+        let mut assignments = self.assignments.lock().unwrap();
+        let next = assignments.len() % self.worker_count;
+        *assignments.entry(session_id.to_string()).or_insert(next)
+    }
+
+    /// Trackers of a session, created when it is first seen
+    fn trackers(&self, session_id: &str) -> Arc<std::sync::Mutex<SessionTrackers>> {
+        self.trackers
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_insert_with(|| Arc::new(std::sync::Mutex::new(SessionTrackers::new(&self.maps))))
+            .clone()
+    }
+
+    /// Ranks the sessions by their production once per batch
+    pub fn with_leaderboard(mut self, leaderboard: Option<Box<dyn Leaderboard>>) -> Self {
+        self.leaderboard = leaderboard.map(std::sync::Mutex::new);
+        self
+    }
+
+    /// Resumes the latest run this client left unfinished, when its capture log was
+    /// written within `max_age` (a crash mid-session): the WandB run is reopened, the
+    /// Weave trace comes back with its open calls, and the session's next events continue
+    /// the run instead of starting a new one. The session goes to the first worker, whose
+    /// Weave trace store holds its trace. Call before processing any event.
+    pub async fn warm_start(&self, max_age: Duration) {
+        let Some(ref index) = self.run_index else {
+            return;
+        };
+        let first = self.worker(0);
+        let record = match index.latest_unfinished() {
+            Ok(Some(record)) => record,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("⚠️  {}", e);
+                return;
+            }
+        };
+
+        if !idle_time(&record).is_some_and(|idle| idle <= max_age) {
+            println!(
+                "♻️  Not resuming run {}: its session has been idle too long",
+                record.run_name
+            );
+            if let Err(e) = index.mark_finished(&record.run_name) {
+                eprintln!("⚠️  {}", e);
+            }
+            if let Some(ref weave_manager) = first.weave_manager {
+                weave_manager.abort_orphaned_calls(None).await;
+            }
+            return;
+        }
+
+        println!(
+            "♻️  Warm start: resuming run {} of session {}",
+            record.run_name, record.session_id
+        );
+        if let Some(ref wandb_manager) = first.wandb_manager {
+            // The run's URL tells which project it was routed to
+            let project = record
+                .wandb_url
+                .as_deref()
+                .and_then(project_routing::wandb_project_of_url);
+            wandb_manager.resume_run(record.run_name.clone(), record.wandb_run_id.clone(), project);
+        }
+        if let Some(ref weave_manager) = first.weave_manager {
+            let run_info = first
+                .wandb_manager
+                .as_ref()
+                .and_then(|wandb_manager| wandb_manager.current_run_info());
+            weave_manager.set_wandb_run(run_info).await;
+            if !weave_manager
+                .resume_session(record.run_name.clone(), record.session_id.clone())
+                .await
+            {
+                eprintln!(
+                    "⚠️  No stored Weave trace for session {}, its calls start a new trace",
+                    record.session_id
+                );
+            }
+            weave_manager
+                .abort_orphaned_calls(Some(&record.session_id))
+                .await;
+        }
+
+        self.session_to_runname
+            .lock()
+            .await
+            .insert(record.session_id.clone(), record.run_name.clone());
+        self.assignments
+            .lock()
+            .unwrap()
+            .insert(record.session_id.clone(), 0);
+        self.run_clocks.lock().unwrap().insert(
+            record.session_id.clone(),
+            RunClock::start(record.start_tick.unwrap_or(0)),
+        );
+        if let Some(save_id) = record.save_id {
+            self.save_ids
+                .lock()
+                .unwrap()
+                .insert(record.session_id.clone(), save_id);
+        }
+        self.open_runs
+            .lock()
+            .unwrap()
+            .insert(record.session_id, record.run_name);
+    }
+
+    /// Marks the run of a session that was replaced by `next` as finished
+    fn finish_open_run(&self, session_id: &str, next: String) {
+        let previous = self
+            .open_runs
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string(), next);
+        if let (Some(index), Some(previous)) = (self.run_index.as_ref(), previous) {
+            if let Err(e) = index.mark_finished(&previous) {
+                eprintln!("⚠️  {}", e);
+            }
+        }
+    }
+
+    /// Gets or creates a session run_name for a given session_id.
+    /// If the session_id already has a mapping, returns it.
+    /// Otherwise, creates a new run_name with random suffix and initializes both managers.
+    async fn get_or_create_session(
+        &self,
+        worker: &WorkerManagers,
+        session_id: String,
+        tick: u64,
+        level_name: String,
+    ) -> String {
+        let mut mapping = self.session_to_runname.lock().await;
+
+        // A rotation forgets the session's run so a new one is created below
+        let requested = self.rotate_requested.lock().unwrap().remove(&session_id);
+        let scheduled = self.rotation.is_enabled()
+            && self
+                .run_clocks
+                .lock()
+                .unwrap()
+                .get(&session_id)
+                .is_some_and(|clock| clock.is_due(&self.rotation, tick));
+        if requested || scheduled {
+            if let Some(run_name) = mapping.remove(&session_id) {
+                println!("🔄 Rotating run {} of session {}", run_name, session_id);
+                if let Some(ref wandb_manager) = worker.wandb_manager {
+                    wandb_manager.link_next_run();
+                }
+            }
+        }
+
+        if let Some(run_name) = mapping.get(&session_id) {
+            // Session already exists
+            return run_name.clone();
+        }
+
+        // Create new session with random suffix
+        let random_suffix: u32 = rand::thread_rng().gen();
+        let run_name = match self.instance {
+            Some(ref instance) => format!("{}_{}_{}", instance, session_id, random_suffix),
+            None => format!("{}_{}", session_id, random_suffix),
+        };
+
+        println!(
+            "🔶 Creating session: {} -> {} (level: {})",
+            session_id, run_name, level_name
+        );
+
+        // Store the mapping
+        mapping.insert(session_id.clone(), run_name.clone());
+        self.run_clocks
+            .lock()
+            .unwrap()
+            .insert(session_id.clone(), RunClock::start(tick));
+        drop(mapping); // Release lock before async calls
+
+        client_events::emit(ClientEvent::SessionCreated {
+            session_id: session_id.clone(),
+            run_name: run_name.clone(),
+            level_name: level_name.clone(),
+        });
+
+        self.trackers(&session_id).lock().unwrap().reset();
+
+        // A session continuing a save tracked earlier descends from that save's last run
+        let save_id = self.save_ids.lock().unwrap().get(&session_id).cloned();
+        let parent = self.find_parent_run(save_id.as_deref(), &session_id, tick);
+        if let Some(ref parent) = parent {
+            println!(
+                "🧬 Session {} continues the save of run {}",
+                session_id, parent.run_name
+            );
+        }
+
+        // Initialize the worker's managers and the shared sinks; the WandB sink goes first
+        // so the others link to its run
+        let mut session = SessionStart {
+            run_name: run_name.clone(),
+            session_id: session_id.clone(),
+            tick,
+            level_name,
+            parent,
+            wandb_run: None,
+        };
+        for sink in worker.sinks.iter().chain(self.sinks.iter()) {
+            sink.handle_session_init(&mut session).await;
+        }
+
+        if let Some(ref index) = self.run_index {
+            let trace_id = match worker.weave_manager {
+                Some(ref weave_manager) => weave_manager.current_trace_id().await,
+                None => None,
+            };
+            if let Err(e) = index.record(
+                &session_id,
+                &run_name,
+                session.wandb_run,
+                trace_id,
+                save_id.as_deref(),
+                tick,
+            ) {
+                eprintln!("⚠️  {}", e);
+            }
+        }
+        self.finish_open_run(&session_id, run_name.clone());
+
+        run_name
+    }
+
+    /// Returns the latest earlier run of the same save, from another session
+    fn find_parent_run(&self, save_id: Option<&str>, session_id: &str, tick: u64) -> Option<RunRecord> {
+        let (Some(index), Some(save_id)) = (self.run_index.as_ref(), save_id) else {
+            return None;
+        };
+        // Only the first run of a session links back; rotated runs link to their predecessor
+        let lookup = index.find(session_id).and_then(|runs| {
+            if runs.is_empty() {
+                index.find_parent(save_id, session_id, tick)
+            } else {
+                Ok(None)
+            }
+        });
+        lookup.unwrap_or_else(|e| {
+            eprintln!("⚠️  {}", e);
+            None
+        })
+    }
+
+    /// Processes a batch of JSONL event strings (async), dropping the lines that don't
+    /// parse
+    pub async fn process_events(&self, lines: Vec<String>) -> ProcessSummary {
+        let dry_run = self.is_dry_run();
+        let mut parse_errors = 0;
+        let events = lines
+            .iter()
+            .filter_map(|line| match parse_event(line) {
+                Ok(event) => Some(event),
+                Err(e) => {
+                    parse_errors += 1;
+                    if !dry_run {
+                        eprintln!("  Failed to parse event: {} - Error: {}", line, e);
+                        client_events::emit(ClientEvent::EventsDropped {
+                            count: 1,
+                            reason: "parse_error".to_string(),
+                        });
+                    }
+                    None
+                }
+            })
+            .collect();
+
+        self.process_events_within(events, parse_errors, None).await.0
+    }
+
+    /// Processes a batch of parsed events until `deadline` passes, returning the summary
+    /// and the events left unprocessed. At least one event is processed per call.
+    /// `parse_errors` counts the batch's lines that were dropped because they didn't
+    /// parse, for the summary.
+    pub async fn process_events_within(
+        &self,
+        events: Vec<FactorioEvent>,
+        parse_errors: usize,
+        deadline: Option<Instant>,
+    ) -> (ProcessSummary, Vec<FactorioEvent>) {
+        let mut summary = ProcessSummary::default();
+        for _ in 0..parse_errors {
+            summary.record(EventOutcome::Dropped("parse_error".to_string()));
+        }
+        if events.is_empty() && parse_errors == 0 {
+            return (summary, events);
+        }
+
+        if self.is_dry_run() {
+            for event in &events {
+                summary.record(classify_parsed(event));
+            }
+            println!(
+                "🧪 Dry run: {} events would be routed, {} dropped: {:?}",
+                summary.routed,
+                summary.total() - summary.routed,
+                summary.dropped
+            );
+            return (summary, Vec::new());
+        }
+
+        println!("=== Processing Cycle ===");
+        println!("Drained {} events from queue", events.len());
+
+        // The Weave calls of the cycle reach each worker's sidecar in one enqueue request.
+        // Sessions are assigned first, so the workers they start on are included.
+        for event in &events {
+            self.worker(self.assign(event.session_id()));
+        }
+        let workers = self.workers();
+        for worker in &workers {
+            if let Some(ref weave_manager) = worker.weave_manager {
+                weave_manager.begin_batch().await;
+            }
+        }
+
+        let started = Instant::now();
+        let total = events.len();
+        let remaining = self
+            .process_by_session(events, deadline, &mut summary)
+            .await;
+        let processed = total - remaining.len();
+        let latency = started.elapsed();
+        {
+            let mut last_events = self.last_events.lock().unwrap();
+            *last_events = (Instant::now(), summary.last_tick.unwrap_or(last_events.1));
+        }
+        *self.last_status.lock().unwrap() = None;
+        if !remaining.is_empty() {
+            println!(
+                "⚠️  Cycle deadline reached after {} events, carrying {} to the next cycle",
+                processed,
+                remaining.len()
+            );
+        }
+
+        if !summary.dropped.is_empty() {
+            println!(
+                "Routed {} events, dropped {}: {:?}",
+                summary.routed,
+                summary.total() - summary.routed,
+                summary.dropped
+            );
+        }
+
+        // Retry failed calls, upload spooled ones if the budget has refilled and Weave is
+        // reachable, and leave a heartbeat of the cycle in each worker's trace
+        for worker in &workers {
+            let Some(ref weave_manager) = worker.weave_manager else {
+                continue;
+            };
+            weave_manager.retry_failed().await;
+            weave_manager.upload_deferred().await;
+            weave_manager
+                .handle_drain_summary(&summary, remaining.len(), latency)
+                .await;
+            weave_manager.end_batch().await;
+            if let Some(metrics) = weave_manager.retry_metrics().await {
+                if let Some(ref wandb_manager) = worker.wandb_manager {
+                    wandb_manager.log_custom(metrics, None);
+                }
+            }
+        }
+
+        // Update the live leaderboard once per batch
+        if let Some(ref leaderboard) = self.leaderboard {
+            leaderboard.lock().unwrap().log_leaderboard();
+        }
+        println!();
+
+        (summary, remaining)
+    }
+
+    /// Processes the events of each session in order, and different sessions side by
+    /// side: all events of a session go to the worker it is assigned to, with that
+    /// worker's managers. Every worker stops at the deadline after processing at least
+    /// one event. Returns the events left unprocessed, in batch order.
+    async fn process_by_session(
+        &self,
+        events: Vec<FactorioEvent>,
+        deadline: Option<Instant>,
+        summary: &mut ProcessSummary,
+    ) -> Vec<FactorioEvent> {
+        let mut queues: Vec<Vec<(usize, FactorioEvent)>> =
+            (0..self.worker_count).map(|_| Vec::new()).collect();
+        for (position, event) in events.into_iter().enumerate() {
+            let worker = self.assign(event.session_id());
+            queues[worker].push((position, event));
+        }
+
+        let workers = queues
+            .into_iter()
+            .enumerate()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(worker, queue)| async move {
+                let worker = self.worker(worker);
+                let mut summary = ProcessSummary::default();
+                let mut pending = queue.into_iter();
+                for (position, event) in pending.by_ref() {
+                    let outcome = self
+                        .process_single_event(&worker, position + 1, event, &mut summary)
+                        .await;
+                    summary.record(outcome);
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        break;
+                    }
+                }
+                (summary, pending.collect::<Vec<_>>())
+            });
+
+        let mut remaining = Vec::new();
+        for (worker_summary, left) in join_all(workers).await {
+            summary.merge(worker_summary);
+            remaining.extend(left);
+        }
+        remaining.sort_by_key(|(position, _)| *position);
+        remaining.into_iter().map(|(_, event)| event).collect()
+    }
+
+    /// Processes a single event (async), counting its type in `summary`
+    async fn process_single_event(
+        &self,
+        worker: &WorkerManagers,
+        index: usize,
+        event: FactorioEvent,
+        summary: &mut ProcessSummary,
+    ) -> EventOutcome {
+        summary.record_kind(event.kind(), event.tick());
+        // The recorded payload is the event re-encoded, once for every recorder
+        if let Some(ref writer) = self.writer {
+            match serde_json::to_string(&event) {
+                Ok(payload) => {
+                    writer
+                        .record(RecordedEvent {
+                            session_id: event.session_id().to_string(),
+                            tick: event.tick(),
+                            kind: event.kind().to_string(),
+                            payload,
+                        })
+                        .await
+                }
+                Err(e) => eprintln!("⚠️  Failed to encode event for storage: {}", e),
+            }
+        }
+        let reason = match event.route_check() {
+            Ok(()) => {
+                self.route_event(worker, index, event).await;
+                return EventOutcome::Routed;
+            }
+            Err(reason) => {
+                eprintln!("  [{}] Dropping event: {}", index, reason);
+                reason
+            }
+        };
+
+        client_events::emit(ClientEvent::EventsDropped {
+            count: 1,
+            reason: reason.clone(),
+        });
+        EventOutcome::Dropped(reason)
+    }
+
+    /// Routes a parsed event to the appropriate handler (async)
+    async fn route_event(&self, worker: &WorkerManagers, index: usize, event: FactorioEvent) {
+        match event {
+            FactorioEvent::SessionInit {
+                session_id,
+                tick,
+                level_name,
+                save_id,
+            } => {
+                println!(
+                    "  [{}] SessionInit: session={}, tick={}, level={}",
+                    index, session_id, tick, level_name
+                );
+
+                if let Some(save_id) = save_id {
+                    self.save_ids
+                        .lock()
+                        .unwrap()
+                        .insert(session_id.clone(), save_id);
+                }
+
+                // Get or create session (will initialize managers if new)
+                let run_name = self
+                    .get_or_create_session(worker, session_id, tick, level_name)
+                    .await;
+
+                println!("  [{}] Using run_name: {}", index, run_name);
+            }
+            FactorioEvent::Stats {
+                session_id,
+                cycle,
+                tick,
+                player,
+                screenshot_path,
+                participant,
+                delta,
+                labs,
+                evolution,
+                assemblers,
+                probes,
+                backlog,
+                products_production,
+                materials_consumption,
+            } => {
+                {
+                    let mut last_cycles = self.last_cycles.lock().unwrap();
+                    let seen = last_cycles
+                        .get(&session_id)
+                        .is_some_and(|&last| cycle <= last);
+                    if backlog && seen {
+                        println!("  [{}] Skipping backlog cycle {}, already logged", index, cycle);
+                        return;
+                    }
+                    if !seen {
+                        last_cycles.insert(session_id.clone(), cycle);
+                    }
+                }
+
+                println!(
+                    "  [{}] Stats: cycle={}, tick={}, production_items={}, consumption_items={}",
+                    index,
+                    cycle,
+                    tick,
+                    products_production.len(),
+                    materials_consumption.len()
+                );
+
+                if let Some(ref leaderboard) = self.leaderboard {
+                    let participant = participant.as_deref().unwrap_or(&session_id);
+                    leaderboard
+                        .lock()
+                        .unwrap()
+                        .record(participant, &products_production, delta);
+                }
+
+                // Get or create session (will initialize managers if new)
+                let run_name = self
+                    .get_or_create_session(worker, session_id.clone(), tick, "unknown".to_string())
+                    .await;
+
+                let mut scalars = HashMap::new();
+                if let Some(labs) = labs {
+                    scalars.insert("science/labs".to_string(), labs.count as f64);
+                    scalars.insert("science/lab_utilization".to_string(), labs.utilization());
+                }
+                if let Some(evolution) = evolution {
+                    scalars.insert("enemy/evolution".to_string(), evolution);
+                }
+                if let Some(recipes) = assemblers {
+                    scalars.extend(assemblers::utilization_metrics(&recipes));
+                }
+                let trackers = self.trackers(&session_id);
+                let (maps, bottleneck) = {
+                    let mut trackers = trackers.lock().unwrap();
+                    scalars.extend(trackers.probes.name_readings(probes));
+                    let (bottleneck_metrics, bottleneck) = trackers.bottlenecks.analyze(
+                        &products_production,
+                        &materials_consumption,
+                        delta,
+                    );
+                    scalars.extend(bottleneck_metrics);
+                    let (exploration_metrics, mut maps) =
+                        take_exploration(&mut trackers.exploration, cycle);
+                    let (pollution_metrics, pollution_maps) =
+                        take_pollution(&mut trackers.pollution, cycle);
+                    scalars.extend(exploration_metrics);
+                    scalars.extend(pollution_metrics);
+                    maps.extend(pollution_maps);
+                    (maps, bottleneck)
+                };
+
+                let stats = StatsCycle {
+                    run_name,
+                    session_id,
+                    cycle,
+                    tick,
+                    delta,
+                    production: products_production,
+                    consumption: materials_consumption,
+                    scalars,
+                    // A player snapshot is only taken with a screenshot
+                    snapshot: player.zip(screenshot_path),
+                    maps,
+                };
+                join_all(
+                    worker
+                        .sinks
+                        .iter()
+                        .chain(self.sinks.iter())
+                        .map(|sink| sink.handle_stats(&stats)),
+                )
+                .await;
+                if let Some(bottleneck) = bottleneck {
+                    let event = GameEvent::BottleneckChanged { bottleneck };
+                    self.dispatch_game_event(worker, tick, event).await;
+                }
+            }
+            FactorioEvent::Pollution {
+                session_id,
+                tick,
+                surface,
+                chunks,
+            } => {
+                println!(
+                    "  [{}] Pollution: surface={}, tick={}, chunks={}",
+                    index,
+                    surface,
+                    tick,
+                    chunks.len()
+                );
+
+                self.get_or_create_session(worker, session_id.clone(), tick, "unknown".to_string())
+                    .await;
+                self.trackers(&session_id)
+                    .lock()
+                    .unwrap()
+                    .pollution
+                    .record(&surface, chunks);
+            }
+            FactorioEvent::ProbeRegistry {
+                session_id,
+                tick,
+                probes,
+            } => {
+                println!(
+                    "  [{}] ProbeRegistry: tick={}, probes={}",
+                    index,
+                    tick,
+                    probes.len()
+                );
+
+                self.get_or_create_session(worker, session_id.clone(), tick, "unknown".to_string())
+                    .await;
+                let total = probes.len();
+                let trackers = self.trackers(&session_id);
+                let changes = trackers.lock().unwrap().probes.sync(probes);
+                if !changes.is_empty() {
+                    let event = GameEvent::ProbeRegistryChanged { total, changes };
+                    self.dispatch_game_event(worker, tick, event).await;
+                }
+            }
+            FactorioEvent::Prototypes {
+                session_id,
+                tick,
+                items,
+                recipes,
+            } => {
+                println!(
+                    "  [{}] Prototypes: tick={}, items={}, recipes={}",
+                    index,
+                    tick,
+                    items.len(),
+                    recipes.len()
+                );
+
+                self.get_or_create_session(worker, session_id.clone(), tick, "unknown".to_string())
+                    .await;
+                let prototypes = Arc::new(Prototypes { items, recipes });
+                self.trackers(&session_id)
+                    .lock()
+                    .unwrap()
+                    .bottlenecks
+                    .set_prototypes(&prototypes);
+                self.dispatch_game_event(worker, tick, GameEvent::PrototypesLoaded { prototypes })
+                    .await;
+            }
+            FactorioEvent::RocketSilo {
+                session_id,
+                tick,
+                silo,
+                stage,
+                surface,
+                parts,
+                parts_required,
+                cargo,
+            } => {
+                println!("  [{}] RocketSilo: silo={} {} (tick: {})", index, silo, stage, tick);
+
+                self.get_or_create_session(worker, session_id, tick, "unknown".to_string())
+                    .await;
+                let Some(stage) = SiloStage::parse(&stage) else {
+                    eprintln!("  [{}] Unknown rocket silo stage: {}", index, stage);
+                    return;
+                };
+                let report = SiloReport {
+                    silo,
+                    surface,
+                    stage,
+                    parts,
+                    parts_required,
+                    cargo,
+                };
+                self.dispatch_game_event(worker, tick, GameEvent::RocketSilo { report })
+                    .await;
+            }
+            FactorioEvent::GameEvent {
+                event_name,
+                session_id,
+                tick,
+                player_index,
+                entity,
+                position,
+                surface,
+                tech_name,
+                tech_level,
+                infinite,
+                item,
+                count,
+            } => {
+                println!("  [{}] GameEvent: {} (tick: {})", index, event_name, tick);
+
+                // Get or create session (will initialize managers if new)
+                let _run_name = self
+                    .get_or_create_session(worker, session_id.clone(), tick, "unknown".to_string())
+                    .await;
+
+                // Route to the sinks, or to the trackers for exploration events
+                let game_event = match event_name.as_str() {
+                    "on_research_started" => tech_name
+                        .zip(tech_level)
+                        .map(|(tech_name, tech_level)| GameEvent::ResearchStarted {
+                            tech_name,
+                            tech_level,
+                            infinite: infinite.unwrap_or(false),
+                        }),
+                    "on_research_finished" => tech_name
+                        .zip(tech_level)
+                        .map(|(tech_name, tech_level)| GameEvent::ResearchFinished {
+                            tech_name,
+                            tech_level,
+                            infinite: infinite.unwrap_or(false),
+                        }),
+                    "on_built_entity" | "on_player_mined_entity" => {
+                        match (player_index, entity, position, surface) {
+                            (Some(player_index), Some(entity), Some(position), Some(surface)) => {
+                                Some(if event_name == "on_built_entity" {
+                                    GameEvent::EntityBuilt {
+                                        player_index,
+                                        entity,
+                                        position,
+                                        surface,
+                                    }
+                                } else {
+                                    GameEvent::EntityMined {
+                                        player_index,
+                                        entity,
+                                        position,
+                                        surface,
+                                    }
+                                })
+                            }
+                            _ => None,
+                        }
+                    }
+                    "on_player_crafted_item" => match (player_index, item, count) {
+                        (Some(player_index), Some(item), Some(count)) => {
+                            Some(GameEvent::ItemCrafted {
+                                player_index,
+                                item,
+                                count,
+                            })
+                        }
+                        _ => None,
+                    },
+                    "on_rocket_launched" => Some(GameEvent::RocketLaunched { surface, count }),
+                    "on_player_died" => player_index.map(|player_index| GameEvent::PlayerDied {
+                        player_index,
+                        // The mod sends the name of the killing entity as `entity`
+                        cause: entity,
+                        surface,
+                    }),
+                    "on_chunk_generated" => {
+                        if let (Some(pos), Some(surf)) = (position, surface) {
+                            self.trackers(&session_id)
+                                .lock()
+                                .unwrap()
+                                .exploration
+                                .record_generated(&surf, (pos.x as i32, pos.y as i32));
+                        }
+                        None
+                    }
+                    "on_chunk_charted" | "on_sector_scanned" => {
+                        if let (Some(pos), Some(surf)) = (position, surface) {
+                            self.trackers(&session_id)
+                                .lock()
+                                .unwrap()
+                                .exploration
+                                .record_charted(&surf, (pos.x as i32, pos.y as i32));
+                        }
+                        None
+                    }
+                    _ => {
+                        eprintln!("  [{}] Unknown event type: {}", index, event_name);
+                        None
+                    }
+                };
+                if let Some(game_event) = game_event {
+                    self.dispatch_game_event(worker, tick, game_event).await;
+                }
+            }
+        }
+    }
+
+    /// Hands an in-game event to the worker's managers and the shared sinks at once
+    async fn dispatch_game_event(&self, worker: &WorkerManagers, tick: u64, event: GameEvent) {
+        join_all(
+            worker
+                .sinks
+                .iter()
+                .chain(self.sinks.iter())
+                .map(|sink| sink.handle_game_event(tick, &event)),
+        )
+        .await;
+    }
+
+    /// Called by the drain loop after a cycle without events. Once no events arrived for
+    /// the status interval, and every interval after that, logs a `status` call and the
+    /// `client/alive` and `client/idle_secs` metrics, so a quiet game (heartbeats keep
+    /// coming) tells apart from a broken pipeline (heartbeats stop too).
+    pub async fn report_idle(&self) {
+        let Some(interval) = self.status_interval else {
+            return;
+        };
+        let (last_event, tick) = *self.last_events.lock().unwrap();
+        let idle = last_event.elapsed();
+        if idle < interval {
+            return;
+        }
+        {
+            let mut last_status = self.last_status.lock().unwrap();
+            if last_status.is_some_and(|at| at.elapsed() < interval) {
+                return;
+            }
+            *last_status = Some(Instant::now());
+        }
+
+        println!("💤 No events for {}s, logging a status heartbeat", idle.as_secs());
+        self.log_client_metrics(HashMap::from([
+            ("client/alive".to_string(), 1.0),
+            ("client/idle_secs".to_string(), idle.as_secs_f64()),
+        ]));
+        for worker in self.workers() {
+            if let Some(ref weave_manager) = worker.weave_manager {
+                weave_manager.handle_status(tick, idle).await;
+            }
+        }
+    }
+
+    /// Logs client-side metrics (ingestion counters, ...) to the current run of every
+    /// worker
+    pub fn log_client_metrics(&self, metrics: HashMap<String, f64>) {
+        for worker in self.workers() {
+            if let Some(ref wandb_manager) = worker.wandb_manager {
+                wandb_manager.log_custom(metrics.clone(), None);
+            }
+        }
+    }
+
+    /// Reports a panic to the current runs: the WandB run of every worker is marked
+    /// crashed and open Weave calls are ended with the panic as their exception
+    pub async fn report_panic(&self, panic_message: &str) {
+        eprintln!("❌ Reporting panic to the current run: {}", panic_message);
+        for worker in self.workers() {
+            if let Some(ref wandb_manager) = worker.wandb_manager {
+                wandb_manager.mark_crashed();
+            }
+            if let Some(ref weave_manager) = worker.weave_manager {
+                weave_manager.abort_active_calls(panic_message).await;
+            }
+            for sink in &worker.sinks {
+                sink.handle_crash(panic_message).await;
+            }
+        }
+        for sink in &self.sinks {
+            sink.handle_crash(panic_message).await;
+        }
+        client_events::emit(ClientEvent::Error {
+            component: "panic".to_string(),
+            message: panic_message.to_string(),
+        });
+    }
+
+    /// Shuts every sink down gracefully
+    pub async fn shutdown(&self) {
+        println!("Shutting down event mediator...");
+        for worker in self.workers() {
+            for sink in &worker.sinks {
+                sink.shutdown().await;
+            }
+        }
+        for sink in &self.sinks {
+            sink.shutdown().await;
+        }
+        if let Some(ref writer) = self.writer {
+            writer.flush().await;
+        }
+        let open_runs = std::mem::take(&mut *self.open_runs.lock().unwrap());
+        if let Some(ref index) = self.run_index {
+            for run_name in open_runs.into_values() {
+                if let Err(e) = index.mark_finished(&run_name) {
+                    eprintln!("⚠️  {}", e);
+                }
+            }
+        }
+        if let Some(ref leaderboard) = self.leaderboard {
+            leaderboard.lock().unwrap().finish();
+        }
+        println!("Event mediator shutdown complete");
+    }
+}
+
+/// Time since the run's session was last active: the last write to its capture log,
+/// or the run's start without one
+fn idle_time(record: &RunRecord) -> Option<Duration> {
+    let last_active: SystemTime = match record.archive_path {
+        Some(ref path) => std::fs::metadata(path).and_then(|m| m.modified()).ok()?,
+        None => chrono::DateTime::parse_from_rfc3339(&record.created_at).ok()?.into(),
+    };
+    last_active.elapsed().ok()
+}
+
+/// Takes the exploration metrics of the stats cycle, and the heatmaps when due
+fn take_exploration(
+    exploration: &mut ExplorationTracker,
+    cycle: u64,
+) -> (HashMap<String, f64>, Vec<SurfaceMap>) {
+    let heatmaps = if exploration.heatmap_due(cycle) {
+        exploration.render_heatmaps()
+    } else {
+        Vec::new()
+    };
+    (
+        exploration.take_cycle_metrics(),
+        surface_maps("exploration_heatmap", heatmaps),
+    )
+}
+
+/// Takes the pollution spread metrics of new samples, and the maps when due
+fn take_pollution(
+    pollution: &mut PollutionTracker,
+    cycle: u64,
+) -> (HashMap<String, f64>, Vec<SurfaceMap>) {
+    let maps = if pollution.map_due(cycle) {
+        pollution.render_maps()
+    } else {
+        Vec::new()
+    };
+    (pollution.take_metrics(), surface_maps("pollution_map", maps))
+}
+
+fn surface_maps(kind: &'static str, rendered: Vec<(String, usize, Vec<u8>)>) -> Vec<SurfaceMap> {
+    rendered
+        .into_iter()
+        .map(|(surface, chunks, png)| SurfaceMap {
+            kind,
+            surface,
+            chunks,
+            png,
+        })
+        .collect()
+}
@@ -0,0 +1,372 @@
+use crate::bottleneck::Bottleneck;
+use crate::event_mediator::{PlayerInfo, Position, ProcessSummary};
+use crate::probes::RegistryChanges;
+use crate::prototypes::Prototypes;
+use crate::rocket_silo::SiloReport;
+use crate::run_index::RunRecord;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A new run started by the mediator for a Factorio session
+pub struct SessionStart {
+    pub run_name: String,
+    pub session_id: String,
+    pub tick: u64,
+    pub level_name: String,
+    /// Earlier run of the same save this run continues
+    pub parent: Option<RunRecord>,
+    /// (run id, url) of the WandB run, set by the WandB sink for the sinks after it
+    pub wandb_run: Option<(String, String)>,
+}
+
+/// Everything reported for one stats cycle
+pub struct StatsCycle {
+    pub run_name: String,
+    /// Factorio session id
+    pub session_id: String,
+    pub cycle: u64,
+    pub tick: u64,
+    /// When true, the production and consumption maps only hold changed items
+    pub delta: bool,
+    pub production: HashMap<String, f64>,
+    pub consumption: HashMap<String, f64>,
+    /// Derived metrics of the cycle (labs, assemblers, probes, exploration, pollution)
+    pub scalars: HashMap<String, f64>,
+    /// Player and screenshot path, when the mod took a screenshot
+    pub snapshot: Option<(PlayerInfo, String)>,
+    /// Surface maps due this cycle
+    pub maps: Vec<SurfaceMap>,
+}
+
+/// Merges a stats update into the per-item state.
+/// A full update zeroes every item it doesn't mention; a delta update only
+/// overwrites the items it carries and keeps the last value of the rest.
+pub fn apply_stats(state: &mut HashMap<String, f64>, update: HashMap<String, f64>, delta: bool) {
+    if !delta {
+        for value in state.values_mut() {
+            *value = 0.0;
+        }
+    }
+    state.extend(update);
+}
+
+/// A rendered per-surface map image
+pub struct SurfaceMap {
+    /// Operation name: `exploration_heatmap` or `pollution_map`
+    pub kind: &'static str,
+    pub surface: String,
+    pub chunks: usize,
+    pub png: Vec<u8>,
+}
+
+/// A routed in-game event
+pub enum GameEvent {
+    ResearchStarted {
+        tech_name: String,
+        tech_level: u32,
+        /// An infinite tech, whose levels are traced under one progress span
+        infinite: bool,
+    },
+    ResearchFinished {
+        tech_name: String,
+        tech_level: u32,
+        infinite: bool,
+    },
+    EntityBuilt {
+        player_index: u32,
+        entity: String,
+        position: Position,
+        surface: String,
+    },
+    EntityMined {
+        player_index: u32,
+        entity: String,
+        position: Position,
+        surface: String,
+    },
+    ItemCrafted {
+        player_index: u32,
+        item: String,
+        count: u32,
+    },
+    /// A rocket was launched; the mod omits the fields when the silo is gone
+    RocketLaunched {
+        surface: Option<String>,
+        /// Rockets launched by the force so far
+        count: Option<u32>,
+    },
+    /// A player died; the mod omits the cause when no entity killed them
+    PlayerDied {
+        player_index: u32,
+        /// Name of the entity that killed the player
+        cause: Option<String>,
+        surface: Option<String>,
+    },
+    ProbeRegistryChanged {
+        total: usize,
+        changes: RegistryChanges,
+    },
+    /// The mod sent the game's item and recipe prototypes
+    PrototypesLoaded {
+        prototypes: Arc<Prototypes>,
+    },
+    /// The bottleneck of the production changed from one stats cycle to the next
+    BottleneckChanged {
+        bottleneck: Bottleneck,
+    },
+    /// A rocket silo reached a stage of its launch pipeline
+    RocketSilo {
+        report: SiloReport,
+    },
+}
+
+impl GameEvent {
+    /// Name of the event as the mod sends it
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::ResearchStarted { .. } => "on_research_started",
+            GameEvent::ResearchFinished { .. } => "on_research_finished",
+            GameEvent::EntityBuilt { .. } => "on_built_entity",
+            GameEvent::EntityMined { .. } => "on_player_mined_entity",
+            GameEvent::ItemCrafted { .. } => "on_player_crafted_item",
+            GameEvent::RocketLaunched { .. } => "on_rocket_launched",
+            GameEvent::PlayerDied { .. } => "on_player_died",
+            GameEvent::ProbeRegistryChanged { .. } => "probe_registry",
+            GameEvent::PrototypesLoaded { .. } => "prototypes",
+            GameEvent::BottleneckChanged { .. } => "bottleneck",
+            GameEvent::RocketSilo { .. } => "rocket_silo",
+        }
+    }
+
+    /// Fields of the event as a JSON object, for sinks forwarding it generically
+    pub fn fields(&self) -> serde_json::Map<String, serde_json::Value> {
+        let fields = match self {
+            GameEvent::ResearchStarted {
+                tech_name,
+                tech_level,
+                infinite,
+            }
+            | GameEvent::ResearchFinished {
+                tech_name,
+                tech_level,
+                infinite,
+            } => serde_json::json!({
+                "tech_name": tech_name,
+                "tech_level": tech_level,
+                "infinite": infinite,
+            }),
+            GameEvent::EntityBuilt {
+                player_index,
+                entity,
+                position,
+                surface,
+            }
+            | GameEvent::EntityMined {
+                player_index,
+                entity,
+                position,
+                surface,
+            } => serde_json::json!({
+                "player_index": player_index,
+                "entity": entity,
+                "position_x": position.x,
+                "position_y": position.y,
+                "surface": surface,
+            }),
+            GameEvent::ItemCrafted {
+                player_index,
+                item,
+                count,
+            } => serde_json::json!({ "player_index": player_index, "item": item, "count": count }),
+            GameEvent::RocketLaunched { surface, count } => {
+                serde_json::json!({ "surface": surface, "count": count })
+            }
+            GameEvent::PlayerDied {
+                player_index,
+                cause,
+                surface,
+            } => serde_json::json!({
+                "player_index": player_index,
+                "cause": cause,
+                "surface": surface,
+            }),
+            GameEvent::ProbeRegistryChanged { total, changes } => serde_json::json!({
+                "total": total,
+                "added": changes.added,
+                "removed": changes.removed,
+                "changed": changes.changed,
+            }),
+            // The prototypes themselves are too large to forward
+            GameEvent::PrototypesLoaded { prototypes } => serde_json::json!({
+                "items": prototypes.items.len(),
+                "recipes": prototypes.recipes.len(),
+            }),
+            GameEvent::BottleneckChanged { bottleneck } => serde_json::json!({
+                "item": bottleneck.item().item,
+                "shortfall": bottleneck.item().shortfall,
+                "chain": bottleneck.chain.iter().map(|short| &short.item).collect::<Vec<_>>(),
+                "explains": bottleneck.explains,
+            }),
+            GameEvent::RocketSilo { report } => serde_json::json!({
+                "silo": report.silo,
+                "stage": report.stage.name(),
+                "surface": report.surface,
+                "parts": report.parts,
+                "parts_required": report.parts_required,
+                "cargo": report.cargo,
+            }),
+        };
+        match fields {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        }
+    }
+}
+
+/// A backend the mediator forwards events to. Sinks are called in registration order
+/// (WandB first, then Weave, then any added with `EventMediator::with_sink`); a manager
+/// disabled at startup is not called at all.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Starts the sink's run for a new session
+    async fn handle_session_init(&self, session: &mut SessionStart);
+
+    /// Reports a stats cycle of the current run
+    async fn handle_stats(&self, stats: &StatsCycle);
+
+    /// Reports an in-game event at `tick`
+    async fn handle_game_event(&self, tick: u64, event: &GameEvent);
+
+    /// Notes a client panic during the current run; the run goes on
+    async fn handle_crash(&self, _message: &str) {}
+
+    /// Name of the sink in the admin status
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+
+    /// Delivers what the sink has buffered so far, without finishing its run
+    async fn flush(&self) {}
+
+    /// Finishes the current run and flushes pending uploads
+    async fn shutdown(&self);
+}
+
+/// The sink keeping the session's metrics run (WandB), which the mediator also drives
+/// for run linking, rotation, warm starts and panic reports
+pub trait RunTracker: EventSink {
+    /// Why the sink is disabled, when it is; a disabled sink is left out at startup
+    fn disabled_reason(&self) -> Option<&str>;
+
+    /// (run id, url) of the current run
+    fn current_run_info(&self) -> Option<(String, String)>;
+
+    /// Reopens the run of `run_id` as the current run, in its routed (entity, project)
+    fn resume_run(
+        &self,
+        run_name: String,
+        run_id: Option<String>,
+        project: Option<(String, String)>,
+    );
+
+    /// Links the current run to the one the next session init starts
+    fn link_next_run(&self);
+
+    /// Logs client metrics to the current run, or keeps them until one starts
+    fn log_custom(&self, metrics: HashMap<String, f64>, step: Option<u64>);
+
+    /// Marks the current run as crashed
+    fn mark_crashed(&self);
+}
+
+/// The sink tracing the session's calls (Weave), which the mediator also drives for
+/// retries, heartbeats, warm starts and panic reports
+#[async_trait]
+pub trait CallTracer: EventSink {
+    /// Why the sink is disabled, when it is; a disabled sink is left out at startup
+    fn disabled_reason(&self) -> Option<&str>;
+
+    /// Gets the tracer ready before the first event
+    async fn preflight(&self) -> Result<(), String>;
+
+    /// Ids and start ticks of the open calls, oldest first
+    async fn active_calls(&self) -> Vec<(String, u64)>;
+
+    /// Ends the calls a previous process left open, except those of session `keep`
+    async fn abort_orphaned_calls(&self, keep: Option<&str>);
+
+    /// (run id, url) of the metrics run the calls link to
+    async fn set_wandb_run(&self, run_info: Option<(String, String)>);
+
+    /// Continues the stored trace of a session, returning whether there was one
+    async fn resume_session(&self, session_id: String, source_session_id: String) -> bool;
+
+    /// Trace id of the current session
+    async fn current_trace_id(&self) -> Option<String>;
+
+    /// Resends the calls that failed to upload
+    async fn retry_failed(&self);
+
+    /// Uploads the calls deferred while over the upload budget
+    async fn upload_deferred(&self);
+
+    /// Holds the calls started and ended from now on for one delivery
+    async fn begin_batch(&self);
+
+    /// Delivers the calls held since `begin_batch`
+    async fn end_batch(&self);
+
+    /// Records a drain cycle of the mediator
+    async fn handle_drain_summary(
+        &self,
+        summary: &ProcessSummary,
+        carried: usize,
+        latency: Duration,
+    );
+
+    /// Counters of the retry queue, when they changed since the last call
+    async fn retry_metrics(&self) -> Option<HashMap<String, f64>>;
+
+    /// Records a status heartbeat while no events arrive
+    async fn handle_status(&self, tick: u64, idle: Duration);
+
+    /// Ends every open call with the panic as its exception
+    async fn abort_active_calls(&self, panic_message: &str);
+}
+
+/// A parsed event as handed to the recorders
+pub struct RecordedEvent {
+    pub session_id: String,
+    pub tick: u64,
+    /// The event's `type`, or the event name of game events
+    pub kind: String,
+    /// The event re-encoded as a JSON line
+    pub payload: String,
+}
+
+/// A backend every parsed event is recorded to before it is routed (event store, Kafka).
+/// Recorders may block, so the mediator runs them on an `EventWriter` thread.
+pub trait EventRecorder: Send {
+    /// Name of the recorder in the admin status
+    fn name(&self) -> &'static str;
+
+    /// Records one event
+    fn record(&mut self, event: &RecordedEvent) -> Result<(), String>;
+
+    /// Waits for the recorded events to be delivered
+    fn flush(&mut self) {}
+}
+
+/// Live ranking of the sessions by their production, updated from the stats cycles
+pub trait Leaderboard: Send {
+    /// Records a stats cycle of a participant
+    fn record(&mut self, participant: &str, production: &HashMap<String, f64>, delta: bool);
+
+    /// Publishes the current standings
+    fn log_leaderboard(&mut self);
+
+    /// Publishes the final standings
+    fn finish(&mut self);
+}
@@ -0,0 +1,75 @@
+use crate::event_sink::{EventRecorder, RecordedEvent};
+use tokio::sync::{mpsc, oneshot};
+
+/// Events queued for the writer before the mediator waits for room
+const QUEUE_CAPACITY: usize = 1024;
+
+enum Command {
+    Record(RecordedEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Runs the recorders on a dedicated thread fed by a bounded queue, so their blocking
+/// writes (SQLite inserts, Kafka sends) never hold up the mediator's worker futures.
+/// The thread drains the queue and exits once the writer is dropped.
+pub struct EventWriter {
+    sender: mpsc::Sender<Command>,
+    names: Vec<&'static str>,
+}
+
+impl EventWriter {
+    /// Starts the writer thread, or returns None when there is nothing to record to
+    pub fn spawn(mut recorders: Vec<Box<dyn EventRecorder>>) -> Option<Self> {
+        if recorders.is_empty() {
+            return None;
+        }
+        let names = recorders.iter().map(|recorder| recorder.name()).collect();
+        let (sender, mut receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let spawned = std::thread::Builder::new()
+            .name("event-writer".to_string())
+            .spawn(move || {
+                while let Some(command) = receiver.blocking_recv() {
+                    match command {
+                        Command::Record(event) => {
+                            for recorder in &mut recorders {
+                                if let Err(e) = recorder.record(&event) {
+                                    eprintln!("⚠️  {}", e);
+                                }
+                            }
+                        }
+                        Command::Flush(done) => {
+                            for recorder in &mut recorders {
+                                recorder.flush();
+                            }
+                            let _ = done.send(());
+                        }
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            eprintln!("⚠️  Event recording disabled: no writer thread: {}", e);
+            return None;
+        }
+        Some(Self { sender, names })
+    }
+
+    /// Names of the recorders, in the order they record
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    /// Queues an event, waiting while the queue is full
+    pub async fn record(&self, event: RecordedEvent) {
+        if self.sender.send(Command::Record(event)).await.is_err() {
+            eprintln!("⚠️  Event writer stopped, event not recorded");
+        }
+    }
+
+    /// Waits until every event queued so far is recorded and delivered
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.sender.send(Command::Flush(done)).await.is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
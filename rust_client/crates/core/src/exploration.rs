@@ -0,0 +1,170 @@
+use crate::config::MapSettings;
+#[cfg(feature = "maps")]
+use image::{ImageFormat, Rgb, RgbImage};
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "maps")]
+use std::io::Cursor;
+
+/// Area of a 32x32-tile chunk in km²; a tile is one meter across
+const CHUNK_KM2: f64 = 32.0 * 32.0 / 1_000_000.0;
+
+/// Pixels per chunk in the rendered heatmap
+#[cfg(feature = "maps")]
+const HEATMAP_CHUNK_PX: u32 = 4;
+
+/// Per-surface exploration state
+#[derive(Default)]
+struct SurfaceExploration {
+    generated: HashSet<(i32, i32)>,
+    /// Number of times each chunk was charted or scanned by a radar
+    charted: HashMap<(i32, i32), u32>,
+    charted_this_cycle: u64,
+    generated_this_cycle: u64,
+}
+
+/// Tracks map exploration from chunk generation, charting and radar sector scans,
+/// as a proxy for expansion pace
+#[derive(Default)]
+pub struct ExplorationTracker {
+    surfaces: HashMap<String, SurfaceExploration>,
+    /// Render a heatmap every this many stats cycles; None disables heatmaps
+    heatmap_every: Option<u64>,
+}
+
+impl ExplorationTracker {
+    /// Heatmaps are enabled by `maps.exploration_heatmap_cycles`
+    pub fn from_settings(settings: &MapSettings) -> Self {
+        Self {
+            surfaces: HashMap::new(),
+            heatmap_every: settings
+                .exploration_heatmap_cycles
+                .filter(|&cycles| cycles > 0),
+        }
+    }
+
+    /// Forgets all chunks, for a new session
+    pub fn reset(&mut self) {
+        self.surfaces.clear();
+    }
+
+    /// Records a newly generated chunk
+    pub fn record_generated(&mut self, surface: &str, chunk: (i32, i32)) {
+        let state = self.surfaces.entry(surface.to_string()).or_default();
+        if state.generated.insert(chunk) {
+            state.generated_this_cycle += 1;
+        }
+    }
+
+    /// Records a chunk charted by a player or scanned by a radar
+    pub fn record_charted(&mut self, surface: &str, chunk: (i32, i32)) {
+        let state = self.surfaces.entry(surface.to_string()).or_default();
+        let scans = state.charted.entry(chunk).or_insert(0);
+        if *scans == 0 {
+            state.charted_this_cycle += 1;
+        }
+        *scans += 1;
+    }
+
+    /// Returns exploration metrics for the cycle and starts a new one
+    pub fn take_cycle_metrics(&mut self) -> HashMap<String, f64> {
+        let mut metrics = HashMap::new();
+        for (surface, state) in self.surfaces.iter_mut() {
+            metrics.insert(
+                format!("exploration/{}/chunks_charted_per_cycle", surface),
+                state.charted_this_cycle as f64,
+            );
+            metrics.insert(
+                format!("exploration/{}/chunks_generated_per_cycle", surface),
+                state.generated_this_cycle as f64,
+            );
+            metrics.insert(
+                format!("exploration/{}/chunks_charted", surface),
+                state.charted.len() as f64,
+            );
+            metrics.insert(
+                format!("exploration/{}/explored_km2", surface),
+                state.charted.len() as f64 * CHUNK_KM2,
+            );
+            state.charted_this_cycle = 0;
+            state.generated_this_cycle = 0;
+        }
+        metrics
+    }
+
+    /// Whether a heatmap should be rendered for this cycle; never without the `maps`
+    /// feature
+    pub fn heatmap_due(&self, cycle: u64) -> bool {
+        cfg!(feature = "maps")
+            && self
+                .heatmap_every
+                .is_some_and(|every| cycle % every == 0 && !self.surfaces.is_empty())
+    }
+
+    /// Renders a PNG heatmap per surface: generated chunks are dark, charted chunks
+    /// get brighter the more often they were scanned. Returns (surface, charted chunks, PNG).
+    pub fn render_heatmaps(&self) -> Vec<(String, usize, Vec<u8>)> {
+        self.surfaces
+            .iter()
+            .filter_map(|(surface, state)| match render_heatmap(state) {
+                Ok(png) => Some((surface.clone(), state.charted.len(), png)),
+                Err(e) => {
+                    eprintln!("⚠️  Failed to render exploration heatmap for {}: {}", surface, e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(feature = "maps"))]
+fn render_heatmap(_state: &SurfaceExploration) -> Result<Vec<u8>, String> {
+    Err("built without the `maps` feature".to_string())
+}
+
+#[cfg(feature = "maps")]
+fn render_heatmap(state: &SurfaceExploration) -> Result<Vec<u8>, String> {
+    let chunks = state.generated.iter().chain(state.charted.keys());
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+    for &(x, y) in chunks {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    if min_x > max_x {
+        return Err("no chunks recorded".to_string());
+    }
+
+    let width = (max_x - min_x + 1) as u32;
+    let height = (max_y - min_y + 1) as u32;
+    let max_scans = state.charted.values().copied().max().unwrap_or(1) as f64;
+    let mut image = RgbImage::new(width * HEATMAP_CHUNK_PX, height * HEATMAP_CHUNK_PX);
+
+    let mut paint = |(x, y): (i32, i32), color: Rgb<u8>| {
+        let px = (x - min_x) as u32 * HEATMAP_CHUNK_PX;
+        let py = (y - min_y) as u32 * HEATMAP_CHUNK_PX;
+        for dy in 0..HEATMAP_CHUNK_PX {
+            for dx in 0..HEATMAP_CHUNK_PX {
+                image.put_pixel(px + dx, py + dy, color);
+            }
+        }
+    };
+
+    for &chunk in &state.generated {
+        paint(chunk, Rgb([40, 40, 40]));
+    }
+    for (&chunk, &scans) in &state.charted {
+        // Log scale keeps rarely scanned chunks visible next to radar-covered ones
+        let heat = ((scans as f64).ln_1p() / max_scans.ln_1p()).clamp(0.0, 1.0);
+        paint(
+            chunk,
+            Rgb([(80.0 + 175.0 * heat) as u8, (160.0 * heat) as u8, 60]),
+        );
+    }
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode heatmap: {}", e))?;
+    Ok(png)
+}
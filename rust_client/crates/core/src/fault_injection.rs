@@ -0,0 +1,76 @@
+use crate::config::FaultSettings;
+use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// What to do with one submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    None,
+    /// Drop the submission and report a failure, without touching the network
+    Fail,
+    /// Hold the submission back before sending it
+    Delay(Duration),
+}
+
+/// Chaos-testing hook that makes a sink randomly fail or delay a percentage of its
+/// submissions, to exercise retry and replay paths against a live game
+#[derive(Debug)]
+pub struct FaultInjector {
+    sink: &'static str,
+    fail_percent: f64,
+    delay_percent: f64,
+    delay: Duration,
+    injected: AtomicU64,
+}
+
+impl FaultInjector {
+    /// Builds the injector of `sink`, or None when faults are off for it
+    pub fn for_sink(sink: &'static str, settings: &FaultSettings) -> Option<Self> {
+        let enabled = settings.fail_percent > 0.0 || settings.delay_percent > 0.0;
+        if !enabled || !settings.sinks.iter().any(|s| s == sink) {
+            return None;
+        }
+
+        println!(
+            "💥 Fault injection on {} sink: {}% failures, {}% delayed by {}ms",
+            sink, settings.fail_percent, settings.delay_percent, settings.delay_ms
+        );
+        Some(Self {
+            sink,
+            fail_percent: settings.fail_percent.clamp(0.0, 100.0),
+            delay_percent: settings.delay_percent.clamp(0.0, 100.0),
+            delay: Duration::from_millis(settings.delay_ms),
+            injected: AtomicU64::new(0),
+        })
+    }
+
+    /// Rolls the fault of the next submission
+    pub fn roll(&self) -> Fault {
+        let roll = rand::thread_rng().gen_range(0.0..100.0);
+        let fault = if roll < self.fail_percent {
+            Fault::Fail
+        } else if roll < self.fail_percent + self.delay_percent {
+            Fault::Delay(self.delay)
+        } else {
+            return Fault::None;
+        };
+
+        let injected = self.injected.fetch_add(1, Ordering::Relaxed) + 1;
+        println!("💥 Injected {:?} into {} sink ({} total)", fault, self.sink, injected);
+        fault
+    }
+
+    /// Applies the next fault to an async submission: sleeps for delays and
+    /// returns an error for failures
+    pub async fn inject(&self) -> Result<(), String> {
+        match self.roll() {
+            Fault::None => Ok(()),
+            Fault::Fail => Err(format!("Injected failure in {} sink", self.sink)),
+            Fault::Delay(delay) => {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+        }
+    }
+}
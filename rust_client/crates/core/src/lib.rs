@@ -0,0 +1,28 @@
+//! Event parsing, the mediator and the sink, recorder and run index traits it routes
+//! events through, with the config they share. Depends on no sink backend: neither the
+//! wandb crate, the Weave sidecar, Kafka nor SQLite.
+
+pub mod assemblers;
+pub mod bottleneck;
+pub mod capture_log;
+pub mod client_events;
+pub mod config;
+pub mod correlation;
+pub mod event_mediator;
+pub mod event_sink;
+pub mod event_writer;
+pub mod exploration;
+pub mod fault_injection;
+pub mod pollution;
+pub mod probes;
+pub mod project_routing;
+pub mod prototypes;
+pub mod rocket_silo;
+pub mod rotation;
+pub mod run_index;
+pub mod run_metadata;
+pub mod run_report;
+pub mod science;
+pub mod spool;
+pub mod units;
+pub mod upload_budget;
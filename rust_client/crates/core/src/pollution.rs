@@ -0,0 +1,179 @@
+use crate::config::MapSettings;
+#[cfg(feature = "maps")]
+use image::{ImageFormat, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "maps")]
+use std::io::Cursor;
+
+/// Pixels per chunk in the rendered pollution map
+#[cfg(feature = "maps")]
+const MAP_CHUNK_PX: u32 = 4;
+
+/// Pollution of a single chunk, in chunk coordinates
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChunkPollution {
+    pub x: i32,
+    pub y: i32,
+    pub pollution: f64,
+}
+
+/// Aggregates of the latest pollution sample of a surface
+struct SurfacePollution {
+    chunks: Vec<ChunkPollution>,
+    centroid: Option<(f64, f64)>,
+    /// Centroid of the previous sample, to measure drift
+    previous_centroid: Option<(f64, f64)>,
+    /// Whether the sample arrived since metrics were last taken
+    fresh: bool,
+}
+
+/// Summarizes per-chunk pollution samples into spread metrics: polluted chunk
+/// count, max chunk value, total, and how far the pollution-weighted centroid moved
+#[derive(Default)]
+pub struct PollutionTracker {
+    surfaces: HashMap<String, SurfacePollution>,
+    /// Render a map every this many stats cycles; None disables maps
+    map_every: Option<u64>,
+}
+
+impl PollutionTracker {
+    /// Maps are enabled by `maps.pollution_map_cycles`
+    pub fn from_settings(settings: &MapSettings) -> Self {
+        Self {
+            surfaces: HashMap::new(),
+            map_every: settings.pollution_map_cycles.filter(|&cycles| cycles > 0),
+        }
+    }
+
+    /// Forgets all samples, for a new session
+    pub fn reset(&mut self) {
+        self.surfaces.clear();
+    }
+
+    /// Replaces the sample of a surface
+    pub fn record(&mut self, surface: &str, chunks: Vec<ChunkPollution>) {
+        let total: f64 = chunks.iter().map(|c| c.pollution.max(0.0)).sum();
+        let centroid = (total > 0.0).then(|| {
+            let x = chunks.iter().map(|c| c.x as f64 * c.pollution.max(0.0)).sum::<f64>();
+            let y = chunks.iter().map(|c| c.y as f64 * c.pollution.max(0.0)).sum::<f64>();
+            (x / total, y / total)
+        });
+
+        let previous_centroid = self.surfaces.get(surface).and_then(|s| s.centroid);
+        self.surfaces.insert(
+            surface.to_string(),
+            SurfacePollution {
+                chunks,
+                centroid,
+                previous_centroid,
+                fresh: true,
+            },
+        );
+    }
+
+    /// Returns spread metrics of samples received since the last call
+    pub fn take_metrics(&mut self) -> HashMap<String, f64> {
+        let mut metrics = HashMap::new();
+        for (surface, state) in self.surfaces.iter_mut().filter(|(_, s)| s.fresh) {
+            state.fresh = false;
+            let polluted = state.chunks.iter().filter(|c| c.pollution > 0.0).count();
+            let max = state
+                .chunks
+                .iter()
+                .map(|c| c.pollution)
+                .fold(0.0, f64::max);
+            let total: f64 = state.chunks.iter().map(|c| c.pollution.max(0.0)).sum();
+
+            metrics.insert(format!("pollution/{}/polluted_chunks", surface), polluted as f64);
+            metrics.insert(format!("pollution/{}/max_chunk", surface), max);
+            metrics.insert(format!("pollution/{}/total", surface), total);
+
+            if let Some((x, y)) = state.centroid {
+                metrics.insert(format!("pollution/{}/centroid_x", surface), x);
+                metrics.insert(format!("pollution/{}/centroid_y", surface), y);
+                if let Some((px, py)) = state.previous_centroid {
+                    // Drift in chunks between the last two samples
+                    metrics.insert(
+                        format!("pollution/{}/centroid_drift", surface),
+                        ((x - px).powi(2) + (y - py).powi(2)).sqrt(),
+                    );
+                }
+            }
+        }
+        metrics
+    }
+
+    /// Whether a map should be rendered for this cycle; never without the `maps` feature
+    pub fn map_due(&self, cycle: u64) -> bool {
+        cfg!(feature = "maps")
+            && self
+                .map_every
+                .is_some_and(|every| cycle % every == 0 && !self.surfaces.is_empty())
+    }
+
+    /// Renders a PNG pollution map per surface, shaded relative to the most polluted
+    /// chunk. Returns (surface, polluted chunks, PNG).
+    pub fn render_maps(&self) -> Vec<(String, usize, Vec<u8>)> {
+        self.surfaces
+            .iter()
+            .filter(|(_, state)| !state.chunks.is_empty())
+            .filter_map(|(surface, state)| match render_map(&state.chunks) {
+                Ok(png) => Some((
+                    surface.clone(),
+                    state.chunks.iter().filter(|c| c.pollution > 0.0).count(),
+                    png,
+                )),
+                Err(e) => {
+                    eprintln!("⚠️  Failed to render pollution map for {}: {}", surface, e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(feature = "maps"))]
+fn render_map(_chunks: &[ChunkPollution]) -> Result<Vec<u8>, String> {
+    Err("built without the `maps` feature".to_string())
+}
+
+#[cfg(feature = "maps")]
+fn render_map(chunks: &[ChunkPollution]) -> Result<Vec<u8>, String> {
+    let min_x = chunks.iter().map(|c| c.x).min().unwrap_or(0);
+    let min_y = chunks.iter().map(|c| c.y).min().unwrap_or(0);
+    let max_x = chunks.iter().map(|c| c.x).max().unwrap_or(0);
+    let max_y = chunks.iter().map(|c| c.y).max().unwrap_or(0);
+    let max = chunks.iter().map(|c| c.pollution).fold(0.0, f64::max);
+
+    let width = (max_x - min_x + 1) as u32 * MAP_CHUNK_PX;
+    let height = (max_y - min_y + 1) as u32 * MAP_CHUNK_PX;
+    let mut image = RgbImage::from_pixel(width, height, Rgb([20, 40, 20]));
+
+    for chunk in chunks.iter().filter(|c| c.pollution > 0.0) {
+        // Log scale so light smog at the edge of the cloud stays visible
+        let level = if max > 0.0 {
+            (chunk.pollution.ln_1p() / max.ln_1p()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let color = Rgb([
+            (60.0 + 195.0 * level) as u8,
+            (60.0 * (1.0 - level)) as u8,
+            (120.0 * (1.0 - level)) as u8,
+        ]);
+        let px = (chunk.x - min_x) as u32 * MAP_CHUNK_PX;
+        let py = (chunk.y - min_y) as u32 * MAP_CHUNK_PX;
+        for dy in 0..MAP_CHUNK_PX {
+            for dx in 0..MAP_CHUNK_PX {
+                image.put_pixel(px + dx, py + dy, color);
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode pollution map: {}", e))?;
+    Ok(png)
+}
@@ -0,0 +1,95 @@
+use crate::event_mediator::Position;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A measurement point registered in-game (a tagged belt, inserter, ...)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProbeInfo {
+    pub id: String,
+    pub name: String,
+    /// What is measured, e.g. "belt" or "inserter"
+    pub kind: String,
+    pub position: Position,
+}
+
+impl ProbeInfo {
+    fn same_as(&self, other: &ProbeInfo) -> bool {
+        self.name == other.name
+            && self.kind == other.kind
+            && self.position.x == other.position.x
+            && self.position.y == other.position.y
+    }
+}
+
+/// Difference between two registry versions
+#[derive(Debug, Default, Serialize)]
+pub struct RegistryChanges {
+    pub added: Vec<ProbeInfo>,
+    pub removed: Vec<ProbeInfo>,
+    /// Probes whose name, kind or position changed (new version)
+    pub changed: Vec<ProbeInfo>,
+}
+
+impl RegistryChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Probe registry synchronized from the game. The mod sends the full registry
+/// whenever it changes and readings reference probes by id; the registry turns
+/// ids into metric names.
+#[derive(Default)]
+pub struct ProbeRegistry {
+    probes: HashMap<String, ProbeInfo>,
+}
+
+impl ProbeRegistry {
+    /// Forgets all probes, for a new session
+    pub fn reset(&mut self) {
+        self.probes.clear();
+    }
+
+    /// Replaces the registry with the game's current version, returning what changed
+    pub fn sync(&mut self, probes: Vec<ProbeInfo>) -> RegistryChanges {
+        let mut changes = RegistryChanges::default();
+        let mut next: HashMap<String, ProbeInfo> = HashMap::new();
+
+        for probe in probes {
+            match self.probes.get(&probe.id) {
+                None => changes.added.push(probe.clone()),
+                Some(previous) if !previous.same_as(&probe) => changes.changed.push(probe.clone()),
+                Some(_) => {}
+            }
+            next.insert(probe.id.clone(), probe);
+        }
+        for (id, probe) in self.probes.drain() {
+            if !next.contains_key(&id) {
+                changes.removed.push(probe);
+            }
+        }
+
+        self.probes = next;
+        changes
+    }
+
+    /// Metric name of a reading: belts and inserters report `throughput/<name>`, other
+    /// kinds `probe/<kind>/<name>`. Unregistered ids fall back to `throughput/<id>`.
+    pub fn metric_name(&self, id: &str) -> String {
+        match self.probes.get(id) {
+            Some(probe) if probe.kind == "belt" || probe.kind == "inserter" => {
+                format!("throughput/{}", probe.name)
+            }
+            Some(probe) => format!("probe/{}/{}", probe.kind, probe.name),
+            None => format!("throughput/{}", id),
+        }
+    }
+
+    /// Names a batch of readings keyed by probe id
+    pub fn name_readings(&self, readings: HashMap<String, f64>) -> HashMap<String, f64> {
+        readings
+            .into_iter()
+            .map(|(id, value)| (self.metric_name(&id), value))
+            .collect()
+    }
+}
@@ -0,0 +1,119 @@
+use crate::config::ProjectRouteSettings;
+use glob::Pattern;
+
+/// A project route with its patterns compiled
+struct Route {
+    session_id: Option<Pattern>,
+    level_name: Option<Pattern>,
+    settings: ProjectRouteSettings,
+}
+
+impl Route {
+    fn from_settings(settings: &ProjectRouteSettings) -> Option<Self> {
+        if settings.session_id.is_none() && settings.level_name.is_none() {
+            eprintln!("⚠️  Project route without session_id or level_name pattern, skipping it");
+            return None;
+        }
+        let compile = |pattern: &Option<String>| -> Result<Option<Pattern>, String> {
+            pattern
+                .as_deref()
+                .map(|pattern| {
+                    Pattern::new(pattern)
+                        .map_err(|e| format!("Invalid project route pattern {:?}: {}", pattern, e))
+                })
+                .transpose()
+        };
+        match (compile(&settings.session_id), compile(&settings.level_name)) {
+            (Ok(session_id), Ok(level_name)) => Some(Self {
+                session_id,
+                level_name,
+                settings: settings.clone(),
+            }),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("⚠️  {}, skipping its route", e);
+                None
+            }
+        }
+    }
+
+    /// Every pattern of the route matches
+    fn matches(&self, session_id: &str, level_name: &str) -> bool {
+        let matches = |pattern: &Option<Pattern>, value: &str| match pattern {
+            Some(pattern) => pattern.matches(value),
+            None => true,
+        };
+        matches(&self.session_id, session_id) && matches(&self.level_name, level_name)
+    }
+}
+
+/// Picks the WandB and Weave projects of a session from the configured routes, so
+/// different experiment families played on one machine land in different projects.
+/// The first route whose patterns all match wins; sessions matching none, and projects
+/// a route leaves unset, use the default `wandb` and `weave` settings.
+#[derive(Default)]
+pub struct ProjectRouter {
+    routes: Vec<Route>,
+}
+
+impl ProjectRouter {
+    pub fn from_settings(settings: &[ProjectRouteSettings]) -> Self {
+        let routes: Vec<Route> = settings.iter().filter_map(Route::from_settings).collect();
+        if !routes.is_empty() {
+            println!("🔀 Routing sessions to projects with {} rule(s)", routes.len());
+        }
+        Self { routes }
+    }
+
+    fn route(&self, session_id: &str, level_name: &str) -> Option<&ProjectRouteSettings> {
+        self.routes
+            .iter()
+            .find(|route| route.matches(session_id, level_name))
+            .map(|route| &route.settings)
+    }
+
+    /// (entity, project) of the session's WandB runs, when a route moves them
+    pub fn wandb_project(
+        &self,
+        session_id: &str,
+        level_name: &str,
+        default_entity: &str,
+        default_project: &str,
+    ) -> Option<(String, String)> {
+        let route = self.route(session_id, level_name)?;
+        if route.wandb_entity.is_none() && route.wandb_project.is_none() {
+            return None;
+        }
+        Some((
+            route.wandb_entity.clone().unwrap_or_else(|| default_entity.to_string()),
+            route.wandb_project.clone().unwrap_or_else(|| default_project.to_string()),
+        ))
+    }
+
+    /// `entity/project` of the session's Weave calls, when a route moves them
+    pub fn weave_project_id(
+        &self,
+        session_id: &str,
+        level_name: &str,
+        default_entity: &str,
+        default_project: &str,
+    ) -> Option<String> {
+        let route = self.route(session_id, level_name)?;
+        if route.weave_entity.is_none() && route.weave_project.is_none() {
+            return None;
+        }
+        Some(format!(
+            "{}/{}",
+            route.weave_entity.as_deref().unwrap_or(default_entity),
+            route.weave_project.as_deref().unwrap_or(default_project)
+        ))
+    }
+}
+
+/// (entity, project) of a WandB run URL, `https://wandb.ai/<entity>/<project>/runs/<id>`
+pub fn wandb_project_of_url(url: &str) -> Option<(String, String)> {
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let mut parts = path.split('/').skip(1);
+    let entity = parts.next().filter(|part| !part.is_empty())?;
+    let project = parts.next().filter(|part| !part.is_empty())?;
+    (parts.next() == Some("runs")).then(|| (entity.to_string(), project.to_string()))
+}
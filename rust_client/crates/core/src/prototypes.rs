@@ -0,0 +1,186 @@
+use crate::science::SCIENCE_PACKS;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Item prototype as the mod sends it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ItemPrototype {
+    pub stack_size: u32,
+    #[serde(default)]
+    pub subgroup: Option<String>,
+}
+
+/// Ingredient or product of a recipe; products with a random amount or a probability
+/// carry their expected amount
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecipeAmount {
+    pub name: String,
+    pub amount: f64,
+}
+
+/// Recipe prototype as the mod sends it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecipePrototype {
+    #[serde(default)]
+    pub ingredients: Vec<RecipeAmount>,
+    #[serde(default)]
+    pub products: Vec<RecipeAmount>,
+}
+
+/// Item and recipe prototypes of the game, sent once by the mod at session start
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Prototypes {
+    #[serde(default)]
+    pub items: HashMap<String, ItemPrototype>,
+    #[serde(default)]
+    pub recipes: HashMap<String, RecipePrototype>,
+}
+
+impl Prototypes {
+    /// (item, subgroup) of every item with a subgroup
+    pub fn subgroups(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.items.iter().filter_map(|(name, item)| {
+            item.subgroup
+                .as_deref()
+                .map(|subgroup| (name.as_str(), subgroup))
+        })
+    }
+
+    /// Recipes producing each item
+    fn producers(&self) -> Producers<'_> {
+        let mut producers: Producers = HashMap::new();
+        for (name, recipe) in &self.recipes {
+            for product in &recipe.products {
+                if product.amount > 0.0 {
+                    producers
+                        .entry(product.name.as_str())
+                        .or_default()
+                        .push((name.as_str(), recipe));
+                }
+            }
+        }
+        producers
+    }
+
+    /// Ingredients per unit of each item made by a recipe, with the recipe the raw
+    /// costs use
+    pub fn ingredient_graph(&self) -> HashMap<String, Vec<RecipeAmount>> {
+        let producers = self.producers();
+        let mut graph = HashMap::new();
+        for item in producers.keys() {
+            let Some(recipe) = recipe_for(item, &producers) else {
+                continue;
+            };
+            let produced = produced_amount(recipe, item);
+            let ingredients = recipe
+                .ingredients
+                .iter()
+                .map(|ingredient| RecipeAmount {
+                    name: ingredient.name.clone(),
+                    amount: ingredient.amount / produced,
+                })
+                .collect();
+            graph.insert(item.to_string(), ingredients);
+        }
+        graph
+    }
+
+    /// Expands every item of the recipe graph into the raw resources it is made of
+    pub fn raw_costs(&self) -> RawCosts {
+        let producers = self.producers();
+        let mut costs = RawCosts::default();
+        let mut visiting = HashSet::new();
+        for item in producers.keys() {
+            costs.resolve(item, &producers, &mut visiting);
+        }
+        costs
+    }
+}
+
+/// Raw resources (items without a recipe of their own, like ores, crude oil and water)
+/// needed per unit of each item
+#[derive(Debug, Default)]
+pub struct RawCosts {
+    costs: HashMap<String, HashMap<String, f64>>,
+}
+
+/// Recipes producing each item, with their names
+type Producers<'a> = HashMap<&'a str, Vec<(&'a str, &'a RecipePrototype)>>;
+
+/// The recipe an item is made with: the one named after it, or else the first
+/// single-product recipe, so barreling, recycling and byproducts don't count
+fn recipe_for<'a>(item: &str, producers: &Producers<'a>) -> Option<&'a RecipePrototype> {
+    let mut candidates = producers.get(item)?.clone();
+    candidates.sort_by_key(|(name, _)| *name);
+    candidates
+        .iter()
+        .find(|(name, _)| *name == item)
+        .or_else(|| candidates.iter().find(|(_, recipe)| recipe.products.len() == 1))
+        .map(|(_, recipe)| *recipe)
+}
+
+/// Amount of an item one craft of the recipe yields
+fn produced_amount(recipe: &RecipePrototype, item: &str) -> f64 {
+    recipe
+        .products
+        .iter()
+        .filter(|product| product.name == item)
+        .map(|product| product.amount)
+        .sum()
+}
+
+impl RawCosts {
+    /// Raw resources per unit of an item made by a recipe
+    pub fn of(&self, item: &str) -> Option<&HashMap<String, f64>> {
+        self.costs.get(item)
+    }
+
+    fn resolve(
+        &mut self,
+        item: &str,
+        producers: &Producers,
+        visiting: &mut HashSet<String>,
+    ) -> HashMap<String, f64> {
+        if let Some(cost) = self.costs.get(item) {
+            return cost.clone();
+        }
+        let raw = HashMap::from([(item.to_string(), 1.0)]);
+        let Some(recipe) = recipe_for(item, producers) else {
+            return raw;
+        };
+        // Items of a recipe loop count as raw inside it
+        if !visiting.insert(item.to_string()) {
+            return raw;
+        }
+
+        let produced = produced_amount(recipe, item);
+        let mut cost = HashMap::new();
+        for ingredient in &recipe.ingredients {
+            for (resource, amount) in self.resolve(&ingredient.name, producers, visiting) {
+                *cost.entry(resource).or_insert(0.0) += ingredient.amount * amount / produced;
+            }
+        }
+        visiting.remove(item);
+
+        self.costs.insert(item.to_string(), cost.clone());
+        cost
+    }
+
+    /// Raw resources per minute behind the science pack production, as
+    /// `science/raw/<resource>`
+    pub fn science_metrics(&self, production: &HashMap<String, f64>) -> HashMap<String, f64> {
+        let mut metrics = HashMap::new();
+        for pack in SCIENCE_PACKS {
+            let (Some(produced), Some(cost)) = (production.get(*pack), self.of(pack))
+            else {
+                continue;
+            };
+            for (resource, amount) in cost {
+                *metrics
+                    .entry(format!("science/raw/{}", resource))
+                    .or_insert(0.0) += produced * amount;
+            }
+        }
+        metrics
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+/// Ticks an ordered rocket waits to launch, and a launched one for its cargo pod, before
+/// its trace ends undelivered (rockets without a cargo pod, like satellites of the base
+/// game, never report one)
+const DELIVERY_TIMEOUT_TICKS: u64 = 60 * 60;
+
+/// Stage of the rocket silo pipeline reported by the mod
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SiloStage {
+    /// The rocket parts in the silo changed
+    Parts,
+    LaunchOrdered,
+    Launched,
+    /// The rocket's cargo pod reached its destination
+    CargoDelivered,
+}
+
+impl SiloStage {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "parts" => Some(SiloStage::Parts),
+            "launch_ordered" => Some(SiloStage::LaunchOrdered),
+            "launched" => Some(SiloStage::Launched),
+            "cargo_delivered" => Some(SiloStage::CargoDelivered),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SiloStage::Parts => "parts",
+            SiloStage::LaunchOrdered => "launch_ordered",
+            SiloStage::Launched => "launched",
+            SiloStage::CargoDelivered => "cargo_delivered",
+        }
+    }
+}
+
+/// A stage of one silo, as sent by the mod
+#[derive(Debug, Clone)]
+pub struct SiloReport {
+    /// Unit number of the silo
+    pub silo: u64,
+    pub surface: Option<String>,
+    pub stage: SiloStage,
+    /// Rocket parts in the silo
+    pub parts: Option<u32>,
+    /// Rocket parts a rocket of the silo needs
+    pub parts_required: Option<u32>,
+    /// Items loaded into the rocket, sent with the launch order
+    pub cargo: HashMap<String, u32>,
+}
+
+/// A change to the spans of a launch trace, applied by the Weave manager. Spans are
+/// named by keys; a child span names the key of its parent.
+#[derive(Debug)]
+pub enum SpanChange {
+    Start {
+        key: String,
+        parent: Option<String>,
+        operation: &'static str,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+    },
+    End {
+        key: String,
+        tick: u64,
+        outputs: HashMap<String, serde_json::Value>,
+    },
+}
+
+/// Where a rocket is in its build
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    /// Parts are being added to the silo
+    Loading,
+    /// All parts are in, waiting for the launch order
+    Loaded,
+}
+
+/// The rocket a silo is building or launching
+struct Launch {
+    /// Key of the root span of the launch
+    key: String,
+    phase: Phase,
+    /// Key and operation of the open stage span, if any
+    stage: Option<(String, &'static str)>,
+    started: u64,
+    loaded_at: Option<u64>,
+    ordered_at: Option<u64>,
+    launched_at: Option<u64>,
+    parts: u32,
+    parts_required: Option<u32>,
+    cargo: HashMap<String, u32>,
+}
+
+impl Launch {
+    fn start_stage(
+        &mut self,
+        operation: &'static str,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+    ) -> SpanChange {
+        let key = format!("{}/{}", self.key, operation);
+        self.stage = Some((key.clone(), operation));
+        SpanChange::Start {
+            key,
+            parent: Some(self.key.clone()),
+            operation,
+            tick,
+            inputs,
+        }
+    }
+
+    /// Ends the open stage span, with the outputs of its stage
+    fn end_stage(&mut self, tick: u64, delivered: bool) -> Option<SpanChange> {
+        let (key, operation) = self.stage.take()?;
+        let mut outputs = HashMap::new();
+        match operation {
+            "rocket_parts" => {
+                outputs.insert("parts".to_string(), serde_json::json!(self.parts));
+                outputs.insert("parts_required".to_string(), serde_json::json!(self.parts_required));
+                outputs.insert("loaded".to_string(), serde_json::json!(self.loaded_at.is_some()));
+            }
+            "rocket_liftoff" => {
+                outputs.insert("launched".to_string(), serde_json::json!(self.launched_at.is_some()));
+            }
+            _ => {
+                outputs.insert("delivered".to_string(), serde_json::json!(delivered));
+            }
+        }
+        Some(SpanChange::End { key, tick, outputs })
+    }
+
+    /// Ends the open stage span and the root span with the launch summary
+    fn finish(mut self, tick: u64, delivered: bool) -> Vec<SpanChange> {
+        let mut changes: Vec<SpanChange> = self.end_stage(tick, delivered).into_iter().collect();
+
+        let between = |from: Option<u64>, to: Option<u64>| match (from, to) {
+            (Some(from), Some(to)) => serde_json::json!(to.saturating_sub(from)),
+            _ => serde_json::Value::Null,
+        };
+        let mut outputs = HashMap::new();
+        outputs.insert("parts".to_string(), serde_json::json!(self.parts));
+        outputs.insert("parts_required".to_string(), serde_json::json!(self.parts_required));
+        outputs.insert(
+            "loading_ticks".to_string(),
+            between(Some(self.started), self.loaded_at),
+        );
+        outputs.insert(
+            "waiting_ticks".to_string(),
+            between(self.loaded_at, self.ordered_at),
+        );
+        outputs.insert(
+            "liftoff_ticks".to_string(),
+            between(self.ordered_at, self.launched_at),
+        );
+        outputs.insert(
+            "delivery_ticks".to_string(),
+            between(self.launched_at, delivered.then_some(tick)),
+        );
+        outputs.insert(
+            "total_ticks".to_string(),
+            serde_json::json!(tick.saturating_sub(self.started)),
+        );
+        outputs.insert("launched".to_string(), serde_json::json!(self.launched_at.is_some()));
+        outputs.insert("delivered".to_string(), serde_json::json!(delivered));
+        outputs.insert("cargo".to_string(), serde_json::json!(self.cargo));
+        changes.push(SpanChange::End {
+            key: self.key,
+            tick,
+            outputs,
+        });
+        changes
+    }
+}
+
+/// Follows every rocket silo through its launch pipeline (parts loaded, launch
+/// ordered, launched, cargo delivered) and turns each launch into a trace: a
+/// `rocket_launch` span with a child span per stage. A client started mid-pipeline
+/// traces the launch from the first stage it sees.
+#[derive(Default)]
+pub struct SiloPipeline {
+    /// Rocket each silo is building, by silo
+    building: HashMap<u64, Launch>,
+    /// Rockets ordered to launch, oldest first, with their silo: lifting off, then
+    /// waiting for their cargo pod. The silo builds its next rocket meanwhile.
+    in_flight: Vec<(u64, Launch)>,
+    /// Launches traced so far, numbering their span keys
+    traced: u64,
+}
+
+impl SiloPipeline {
+    /// Forgets the launches of the previous session; their spans are force-ended with it
+    pub fn reset(&mut self) {
+        self.building.clear();
+        self.in_flight.clear();
+    }
+
+    /// Advances the silo of the report, returning the span changes of the step
+    pub fn advance(&mut self, tick: u64, report: &SiloReport) -> Vec<SpanChange> {
+        let mut changes = self.expire(tick);
+
+        match report.stage {
+            SiloStage::Parts => {
+                let parts = report.parts.unwrap_or(0);
+                // The parts are used up by the finished rocket
+                if parts == 0 {
+                    return changes;
+                }
+                if !self.building.contains_key(&report.silo) {
+                    let (start, mut launch) = self.open(tick, report);
+                    let mut inputs = silo_inputs(report);
+                    if let Some(required) = report.parts_required {
+                        inputs.insert("parts_required".to_string(), serde_json::json!(required));
+                    }
+                    changes.push(start);
+                    changes.push(launch.start_stage("rocket_parts", tick, inputs));
+                    self.building.insert(report.silo, launch);
+                }
+                let launch = self.building.get_mut(&report.silo).expect("the silo has a launch");
+                launch.parts = parts;
+                launch.parts_required = report.parts_required.or(launch.parts_required);
+                if launch.phase == Phase::Loading
+                    && launch.parts_required.is_some_and(|required| parts >= required)
+                {
+                    launch.phase = Phase::Loaded;
+                    launch.loaded_at = Some(tick);
+                    changes.extend(launch.end_stage(tick, false));
+                }
+            }
+            SiloStage::LaunchOrdered => {
+                let mut launch = match self.building.remove(&report.silo) {
+                    Some(launch) => launch,
+                    None => {
+                        let (start, launch) = self.open(tick, report);
+                        changes.push(start);
+                        launch
+                    }
+                };
+                // Parts sampled too rarely to see the silo fill up
+                if launch.phase == Phase::Loading && launch.stage.is_some() {
+                    launch.parts = report.parts_required.unwrap_or(launch.parts);
+                    launch.loaded_at = Some(tick);
+                }
+                launch.parts_required = report.parts_required.or(launch.parts_required);
+                changes.extend(launch.end_stage(tick, false));
+                launch.ordered_at = Some(tick);
+                launch.cargo = report.cargo.clone();
+
+                let mut inputs = silo_inputs(report);
+                inputs.insert("cargo".to_string(), serde_json::json!(report.cargo));
+                changes.push(launch.start_stage("rocket_liftoff", tick, inputs));
+                self.in_flight.push((report.silo, launch));
+            }
+            SiloStage::Launched => {
+                let index = match self.oldest_in_flight(report.silo, false) {
+                    Some(index) => index,
+                    // Ordered before the client started
+                    None => {
+                        let (start, launch) = self.open(tick, report);
+                        changes.push(start);
+                        self.in_flight.push((report.silo, launch));
+                        self.in_flight.len() - 1
+                    }
+                };
+                let launch = &mut self.in_flight[index].1;
+                launch.launched_at = Some(tick);
+                changes.extend(launch.end_stage(tick, false));
+                changes.push(launch.start_stage("cargo_delivery", tick, silo_inputs(report)));
+            }
+            SiloStage::CargoDelivered => {
+                if let Some(index) = self.oldest_in_flight(report.silo, true) {
+                    let (_, launch) = self.in_flight.remove(index);
+                    changes.extend(launch.finish(tick, true));
+                }
+            }
+        }
+        changes
+    }
+
+    /// Ends the traces of rockets that never left the silo or whose cargo pod never
+    /// reported back
+    pub fn expire(&mut self, tick: u64) -> Vec<SpanChange> {
+        let (expired, in_flight): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.in_flight).into_iter().partition(|(_, launch)| {
+                launch
+                    .launched_at
+                    .or(launch.ordered_at)
+                    .is_some_and(|since| tick.saturating_sub(since) > DELIVERY_TIMEOUT_TICKS)
+            });
+        self.in_flight = in_flight;
+
+        expired
+            .into_iter()
+            .flat_map(|(_, launch)| launch.finish(tick, false))
+            .collect()
+    }
+
+    /// Position of the silo's oldest rocket in flight that was, or wasn't yet, launched
+    fn oldest_in_flight(&self, silo: u64, launched: bool) -> Option<usize> {
+        self.in_flight
+            .iter()
+            .position(|(of, launch)| *of == silo && launch.launched_at.is_some() == launched)
+    }
+
+    /// Starts the trace of a new launch of the report's silo
+    fn open(&mut self, tick: u64, report: &SiloReport) -> (SpanChange, Launch) {
+        self.traced += 1;
+        let key = format!("rocket_launch:{}:{}", report.silo, self.traced);
+        let start = SpanChange::Start {
+            key: key.clone(),
+            parent: None,
+            operation: "rocket_launch",
+            tick,
+            inputs: silo_inputs(report),
+        };
+        let launch = Launch {
+            key,
+            phase: Phase::Loading,
+            stage: None,
+            started: tick,
+            loaded_at: None,
+            ordered_at: None,
+            launched_at: None,
+            parts: 0,
+            parts_required: report.parts_required,
+            cargo: HashMap::new(),
+        };
+        (start, launch)
+    }
+}
+
+fn silo_inputs(report: &SiloReport) -> HashMap<String, serde_json::Value> {
+    let mut inputs = HashMap::new();
+    inputs.insert("silo".to_string(), serde_json::json!(report.silo));
+    if let Some(ref surface) = report.surface {
+        inputs.insert("surface".to_string(), serde_json::json!(surface));
+    }
+    inputs
+}
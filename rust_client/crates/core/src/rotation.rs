@@ -0,0 +1,60 @@
+use crate::config::RotationSettings;
+use std::time::{Duration, Instant};
+
+/// Automatic run rotation schedule: a new run every N game ticks and/or every
+/// N seconds of wall-clock time, whichever comes first
+#[derive(Debug, Clone, Default)]
+pub struct RotationPolicy {
+    pub every_ticks: Option<u64>,
+    pub every: Option<Duration>,
+}
+
+impl RotationPolicy {
+    /// Creates the configured schedule; a trigger that is unset or 0 is off
+    pub fn from_settings(settings: &RotationSettings) -> Self {
+        let every_ticks = settings.every_ticks.filter(|&ticks| ticks > 0);
+        let every = settings
+            .every_secs
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs);
+
+        if every_ticks.is_some() || every.is_some() {
+            println!(
+                "🔄 Scheduled run rotation: every {:?} ticks / {:?}",
+                every_ticks, every
+            );
+        }
+
+        Self { every_ticks, every }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.every_ticks.is_some() || self.every.is_some()
+    }
+}
+
+/// When the current run started, in game ticks and wall-clock time
+#[derive(Debug, Clone, Copy)]
+pub struct RunClock {
+    pub started_tick: u64,
+    pub started_at: Instant,
+}
+
+impl RunClock {
+    pub fn start(tick: u64) -> Self {
+        Self {
+            started_tick: tick,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Whether the run has lasted long enough to rotate at `tick`
+    pub fn is_due(&self, policy: &RotationPolicy, tick: u64) -> bool {
+        policy
+            .every_ticks
+            .is_some_and(|every| tick.saturating_sub(self.started_tick) >= every)
+            || policy
+                .every
+                .is_some_and(|every| self.started_at.elapsed() >= every)
+    }
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+/// Artifacts published for one run of a game session
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    pub session_id: String,
+    pub run_name: String,
+    pub wandb_run_id: Option<String>,
+    pub wandb_url: Option<String>,
+    pub weave_trace_id: Option<String>,
+    /// Capture log holding the raw events of the run
+    pub archive_path: Option<String>,
+    pub created_at: String,
+    /// Fingerprint of the save the run played
+    pub save_id: Option<String>,
+    /// Game tick at which the run started
+    pub start_tick: Option<u64>,
+}
+
+/// Index of session_id -> published run/trace identifiers, which the mediator records
+/// every run in and finds parent runs and warm starts with
+pub trait RunIndex: Send + Sync {
+    /// Records the artifacts of a newly started run
+    fn record(
+        &self,
+        session_id: &str,
+        run_name: &str,
+        wandb_run: Option<(String, String)>,
+        weave_trace_id: Option<String>,
+        save_id: Option<&str>,
+        start_tick: u64,
+    ) -> Result<(), String>;
+
+    /// Records that a run finished; runs never marked were cut short by a crash
+    fn mark_finished(&self, run_name: &str) -> Result<(), String>;
+
+    /// Returns the latest run of this process's capture log that never finished
+    fn latest_unfinished(&self) -> Result<Option<RunRecord>, String>;
+
+    /// Returns all runs of a session, oldest first
+    fn find(&self, session_id: &str) -> Result<Vec<RunRecord>, String>;
+
+    /// Returns the run a new session of `save_id` starting at `tick` continues: the
+    /// latest run of the same save, from another session, that started no later
+    fn find_parent(
+        &self,
+        save_id: &str,
+        session_id: &str,
+        tick: u64,
+    ) -> Result<Option<RunRecord>, String>;
+}
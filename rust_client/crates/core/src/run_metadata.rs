@@ -0,0 +1,63 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Custom `key=value` metadata given at launch, attached to every WandB run and
+/// every Weave call for the lifetime of the process so experiment scripts can
+/// label their runs.
+#[derive(Debug, Clone, Default)]
+pub struct RunMetadata {
+    entries: BTreeMap<String, String>,
+}
+
+impl RunMetadata {
+    /// Loads the `metadata` of the config and the `--meta key=value` pairs, which take
+    /// precedence
+    pub fn from_settings_and_pairs(settings: &HashMap<String, String>, pairs: &[String]) -> Self {
+        let mut metadata = Self {
+            entries: settings.clone().into_iter().collect(),
+        };
+        for (key, value) in pairs.iter().filter_map(|pair| parse_pair(pair)) {
+            metadata.entries.insert(key, value);
+        }
+
+        if !metadata.is_empty() {
+            println!("🏷️  Run metadata: {}", metadata.to_lines().join(", "));
+        }
+
+        metadata
+    }
+
+    /// Sets one entry, replacing any value given at launch
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.entries.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the metadata as `key=value` strings, sorted by key
+    pub fn to_lines(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect()
+    }
+
+    /// Returns the metadata as a JSON object, for call attributes
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.entries)
+    }
+}
+
+/// Splits a `key=value` pair, warning about (and skipping) one without a key
+pub fn parse_pair(pair: &str) -> Option<(String, String)> {
+    match pair.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Some((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => {
+            eprintln!("⚠️  Ignoring metadata without key=value form: {}", pair);
+            None
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::event_mediator::{parse_event, FactorioEvent};
+use crate::event_sink::apply_stats;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// Summary of a session rebuilt from a capture log
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    pub session_id: String,
+    pub cycles: u64,
+    pub last_tick: u64,
+    pub research_finished: u64,
+    /// Mean production rate per item over all stats cycles
+    pub production: HashMap<String, f64>,
+    /// Mean consumption rate per item over all stats cycles
+    pub consumption: HashMap<String, f64>,
+}
+
+impl RunSummary {
+    /// Loads a session from a capture log (FACTORIO_LOG_PATH). `spec` is a log path,
+    /// optionally followed by `@<session_id>`; without it the last session in the log is used.
+    pub fn load(spec: &str) -> Result<Self, String> {
+        let (path, wanted) = match spec.rsplit_once('@') {
+            Some((path, session)) => (path, Some(session)),
+            None => (spec, None),
+        };
+
+        let file = File::open(path).map_err(|e| format!("No local archive at {}: {}", path, e))?;
+        let mut sessions: Vec<SessionAccumulator> = Vec::new();
+
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let Ok(event) = parse_event(&line) else {
+                continue;
+            };
+            let session_id = match &event {
+                FactorioEvent::SessionInit { session_id, .. }
+                | FactorioEvent::Stats { session_id, .. }
+                | FactorioEvent::Pollution { session_id, .. }
+                | FactorioEvent::ProbeRegistry { session_id, .. }
+                | FactorioEvent::Prototypes { session_id, .. }
+                | FactorioEvent::RocketSilo { session_id, .. }
+                | FactorioEvent::GameEvent { session_id, .. } => session_id.clone(),
+            };
+            if wanted.is_some_and(|wanted| wanted != session_id) {
+                continue;
+            }
+            if sessions.last().map(|s| &s.summary.session_id) != Some(&session_id) {
+                sessions.push(SessionAccumulator::new(session_id));
+            }
+            sessions.last_mut().unwrap().add(event);
+        }
+
+        sessions
+            .pop()
+            .map(SessionAccumulator::finish)
+            .ok_or_else(|| match wanted {
+                Some(session) => format!("Session {} not found in {}", session, path),
+                None => format!("No sessions found in {}", path),
+            })
+    }
+}
+
+/// Accumulates stats of one session while the log is read
+struct SessionAccumulator {
+    summary: RunSummary,
+    production_state: HashMap<String, f64>,
+    consumption_state: HashMap<String, f64>,
+    production_sum: HashMap<String, f64>,
+    consumption_sum: HashMap<String, f64>,
+}
+
+impl SessionAccumulator {
+    fn new(session_id: String) -> Self {
+        Self {
+            summary: RunSummary {
+                session_id,
+                ..RunSummary::default()
+            },
+            production_state: HashMap::new(),
+            consumption_state: HashMap::new(),
+            production_sum: HashMap::new(),
+            consumption_sum: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, event: FactorioEvent) {
+        match event {
+            FactorioEvent::Stats {
+                tick,
+                delta,
+                products_production,
+                materials_consumption,
+                ..
+            } => {
+                apply_stats(&mut self.production_state, products_production, delta);
+                apply_stats(&mut self.consumption_state, materials_consumption, delta);
+                for (item, value) in &self.production_state {
+                    *self.production_sum.entry(item.clone()).or_insert(0.0) += value;
+                }
+                for (item, value) in &self.consumption_state {
+                    *self.consumption_sum.entry(item.clone()).or_insert(0.0) += value;
+                }
+                self.summary.cycles += 1;
+                self.summary.last_tick = self.summary.last_tick.max(tick);
+            }
+            FactorioEvent::GameEvent {
+                event_name, tick, ..
+            } => {
+                if event_name == "on_research_finished" {
+                    self.summary.research_finished += 1;
+                }
+                self.summary.last_tick = self.summary.last_tick.max(tick);
+            }
+            FactorioEvent::SessionInit { tick, .. }
+            | FactorioEvent::Pollution { tick, .. }
+            | FactorioEvent::ProbeRegistry { tick, .. }
+            | FactorioEvent::Prototypes { tick, .. }
+            | FactorioEvent::RocketSilo { tick, .. } => {
+                self.summary.last_tick = self.summary.last_tick.max(tick);
+            }
+        }
+    }
+
+    fn finish(mut self) -> RunSummary {
+        let cycles = self.summary.cycles.max(1) as f64;
+        self.summary.production = self
+            .production_sum
+            .into_iter()
+            .map(|(item, sum)| (item, sum / cycles))
+            .collect();
+        self.summary.consumption = self
+            .consumption_sum
+            .into_iter()
+            .map(|(item, sum)| (item, sum / cycles))
+            .collect();
+        self.summary
+    }
+}
+
+/// Formats a percentage delta from `a` to `b`
+fn percent_delta(a: f64, b: f64) -> String {
+    if a == 0.0 {
+        if b == 0.0 {
+            "0.0%".to_string()
+        } else {
+            "new".to_string()
+        }
+    } else {
+        format!("{:+.1}%", (b - a) / a.abs() * 100.0)
+    }
+}
+
+/// Renders a KPI comparison table of two runs, items ordered by the largest
+/// production rate in either run
+pub fn comparison_table(a: &RunSummary, b: &RunSummary, rows: usize) -> String {
+    let mut out = String::new();
+    let row = |out: &mut String, name: &str, va: f64, vb: f64| {
+        out.push_str(&format!(
+            "{:<36} {:>14.2} {:>14.2} {:>10}\n",
+            name,
+            va,
+            vb,
+            percent_delta(va, vb)
+        ));
+    };
+
+    out.push_str(&format!(
+        "{:<36} {:>14} {:>14} {:>10}\n",
+        "KPI", a.session_id, b.session_id, "delta"
+    ));
+    out.push_str(&format!("{}\n", "-".repeat(77)));
+    row(&mut out, "cycles", a.cycles as f64, b.cycles as f64);
+    row(&mut out, "last tick", a.last_tick as f64, b.last_tick as f64);
+    row(
+        &mut out,
+        "research finished",
+        a.research_finished as f64,
+        b.research_finished as f64,
+    );
+
+    for (section, map_a, map_b) in [
+        ("production", &a.production, &b.production),
+        ("consumption", &a.consumption, &b.consumption),
+    ] {
+        let items: BTreeSet<&String> = map_a.keys().chain(map_b.keys()).collect();
+        let mut items: Vec<(&String, f64, f64)> = items
+            .into_iter()
+            .map(|item| {
+                (
+                    item,
+                    map_a.get(item).copied().unwrap_or(0.0),
+                    map_b.get(item).copied().unwrap_or(0.0),
+                )
+            })
+            .collect();
+        items.sort_by(|x, y| x.1.max(x.2).total_cmp(&y.1.max(y.2)).reverse());
+
+        for (item, va, vb) in items.into_iter().take(rows) {
+            row(&mut out, &format!("{}/{}", section, item), va, vb);
+        }
+    }
+
+    out
+}
+
+/// Runs `diff-runs <runA> <runB> [--rows N]`, printing the comparison table
+pub fn diff_runs(run_a: &str, run_b: &str, rows: usize) -> Result<(), String> {
+    let a = RunSummary::load(run_a)?;
+    let b = RunSummary::load(run_b)?;
+    print!("{}", comparison_table(&a, &b, rows));
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+/// Science packs consumed by labs, in tech tree order
+pub const SCIENCE_PACKS: &[&str] = &[
+    "automation-science-pack",
+    "logistic-science-pack",
+    "military-science-pack",
+    "chemical-science-pack",
+    "production-science-pack",
+    "utility-science-pack",
+    "space-science-pack",
+    "metallurgic-science-pack",
+    "electromagnetic-science-pack",
+    "agricultural-science-pack",
+    "cryogenic-science-pack",
+    "promethium-science-pack",
+];
+
+/// Computes the signed surplus (production minus lab consumption) of every science
+/// pack that is produced or consumed; the bottleneck science is the most negative one
+pub fn balance_metrics(
+    production: &HashMap<String, f64>,
+    consumption: &HashMap<String, f64>,
+) -> HashMap<String, f64> {
+    let mut metrics = HashMap::new();
+
+    for pack in SCIENCE_PACKS {
+        let produced = production.get(*pack).copied();
+        let consumed = consumption.get(*pack).copied();
+        if produced.is_none() && consumed.is_none() {
+            continue;
+        }
+
+        let surplus = produced.unwrap_or(0.0) - consumed.unwrap_or(0.0);
+        metrics.insert(format!("science/{}/surplus", pack), surplus);
+    }
+
+    metrics
+}
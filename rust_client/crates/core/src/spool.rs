@@ -0,0 +1,262 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Extension of the segment currently being written
+const OPEN_EXT: &str = "open";
+/// Extension of sealed segments waiting to be delivered
+const SEALED_EXT: &str = "seg";
+/// Extension of segments whose records have all been delivered
+const ACKED_EXT: &str = "acked";
+
+/// Extension of a merged segment being written by compaction
+const MERGE_EXT: &str = "merge";
+
+/// Outcome of a compaction pass
+#[derive(Debug, Default)]
+pub struct CompactionReport {
+    /// Delivered segments deleted because they were past retention
+    pub expired_segments: usize,
+    /// Small sealed segments folded into larger ones
+    pub merged_segments: usize,
+    /// Bytes freed by deleting expired segments
+    pub reclaimed_bytes: u64,
+}
+
+impl CompactionReport {
+    pub fn is_empty(&self) -> bool {
+        self.expired_segments == 0 && self.merged_segments == 0
+    }
+}
+
+/// Segment currently accepting appends
+struct OpenSegment {
+    path: PathBuf,
+    file: File,
+    bytes: u64,
+}
+
+/// Durable on-disk spool of JSON records, stored as numbered JSONL segments.
+/// Records are appended to an open segment which is sealed once it grows past the
+/// size limit; sealed segments are read back in order and acknowledged once delivered.
+pub struct Spool {
+    dir: PathBuf,
+    max_segment_bytes: u64,
+    current: Option<OpenSegment>,
+    next_seq: u64,
+}
+
+impl Spool {
+    /// Opens (or creates) a spool directory. Segments left open by a previous
+    /// process are sealed so their records are delivered, and merges it left unfinished
+    /// are discarded (the merged segments are all still in place).
+    pub fn open(dir: PathBuf, max_segment_bytes: u64) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create spool {:?}: {}", dir, e))?;
+
+        let mut next_seq = 0;
+        for path in list_segments(&dir)? {
+            if let Some(seq) = segment_seq(&path) {
+                next_seq = next_seq.max(seq + 1);
+            }
+            if has_ext(&path, OPEN_EXT) {
+                fs::rename(&path, path.with_extension(SEALED_EXT))
+                    .map_err(|e| format!("Failed to seal segment {:?}: {}", path, e))?;
+            }
+            if has_ext(&path, MERGE_EXT) {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to delete {:?}: {}", path, e))?;
+            }
+        }
+
+        Ok(Self {
+            dir,
+            max_segment_bytes,
+            current: None,
+            next_seq,
+        })
+    }
+
+    /// Appends a record to the open segment, rotating it when full
+    pub fn append(&mut self, record: &serde_json::Value) -> Result<(), String> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize spool record: {}", e))?;
+        line.push('\n');
+
+        if self.current.is_none() {
+            let path = self
+                .dir
+                .join(format!("{:010}.{}", self.next_seq, OPEN_EXT));
+            self.next_seq += 1;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open segment {:?}: {}", path, e))?;
+            self.current = Some(OpenSegment {
+                path,
+                file,
+                bytes: 0,
+            });
+        }
+
+        let segment = self.current.as_mut().unwrap();
+        segment
+            .file
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("Failed to append to segment {:?}: {}", segment.path, e))?;
+        segment.bytes += line.len() as u64;
+
+        if segment.bytes >= self.max_segment_bytes {
+            self.seal()?;
+        }
+
+        Ok(())
+    }
+
+    /// Seals the open segment so it becomes available for delivery, returning its path
+    pub fn seal(&mut self) -> Result<Option<PathBuf>, String> {
+        let Some(segment) = self.current.take() else {
+            return Ok(None);
+        };
+        segment
+            .file
+            .sync_all()
+            .map_err(|e| format!("Failed to sync segment {:?}: {}", segment.path, e))?;
+        let sealed = segment.path.with_extension(SEALED_EXT);
+        fs::rename(&segment.path, &sealed)
+            .map_err(|e| format!("Failed to seal segment {:?}: {}", segment.path, e))?;
+        Ok(Some(sealed))
+    }
+
+    /// Returns sealed, undelivered segments oldest first, sealing the open one first
+    pub fn pending_segments(&mut self) -> Result<Vec<PathBuf>, String> {
+        self.seal()?;
+        Ok(list_segments(&self.dir)?
+            .into_iter()
+            .filter(|path| has_ext(path, SEALED_EXT))
+            .collect())
+    }
+
+    /// Reads all records of a segment, skipping lines that fail to parse
+    pub fn read_segment(path: &Path) -> Result<Vec<serde_json::Value>, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+
+    /// Marks a segment as delivered
+    pub fn ack(&self, path: &Path) -> Result<(), String> {
+        fs::rename(path, path.with_extension(ACKED_EXT))
+            .map_err(|e| format!("Failed to ack segment {:?}: {}", path, e))
+    }
+
+    /// Deletes a delivered segment right away, for spools that keep no history
+    pub fn discard(&self, path: &Path) -> Result<(), String> {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete segment {:?}: {}", path, e))
+    }
+
+    /// Deletes delivered segments older than `retention` and merges runs of small
+    /// sealed segments into segments of up to the size limit, so long-lived deployments
+    /// don't accumulate thousands of tiny files. A crash mid-merge can only duplicate
+    /// records (delivered at least once), never lose them.
+    pub fn compact(&mut self, retention: Duration) -> Result<CompactionReport, String> {
+        let mut report = CompactionReport::default();
+        let segments = list_segments(&self.dir)?;
+
+        for path in segments.iter().filter(|path| has_ext(path, ACKED_EXT)) {
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            let age = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .unwrap_or_default();
+            if age >= retention {
+                fs::remove_file(path)
+                    .map_err(|e| format!("Failed to delete segment {:?}: {}", path, e))?;
+                report.expired_segments += 1;
+                report.reclaimed_bytes += metadata.len();
+            }
+        }
+
+        // Group consecutive sealed segments that fit together under the size limit
+        let mut group: Vec<(PathBuf, u64)> = Vec::new();
+        let mut group_bytes = 0;
+        for path in segments.into_iter().filter(|path| has_ext(path, SEALED_EXT)) {
+            let bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if group_bytes + bytes > self.max_segment_bytes {
+                report.merged_segments += merge_segments(&group)?;
+                group.clear();
+                group_bytes = 0;
+            }
+            group_bytes += bytes;
+            group.push((path, bytes));
+        }
+        report.merged_segments += merge_segments(&group)?;
+
+        Ok(report)
+    }
+
+    /// Returns the spool directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Merges a group of sealed segments into its last one, returning how many segments were
+/// folded in. The merged file replaces the last segment atomically before the others are
+/// deleted, so records are never lost.
+fn merge_segments(group: &[(PathBuf, u64)]) -> Result<usize, String> {
+    let Some(((last, _), earlier)) = group.split_last() else {
+        return Ok(0);
+    };
+    if earlier.is_empty() {
+        return Ok(0);
+    }
+
+    let merge_path = last.with_extension(MERGE_EXT);
+    let mut merged = File::create(&merge_path)
+        .map_err(|e| format!("Failed to create {:?}: {}", merge_path, e))?;
+    for (path, _) in group {
+        let mut segment =
+            File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+        io::copy(&mut segment, &mut merged)
+            .map_err(|e| format!("Failed to merge {:?}: {}", path, e))?;
+    }
+    merged
+        .sync_all()
+        .map_err(|e| format!("Failed to sync {:?}: {}", merge_path, e))?;
+    fs::rename(&merge_path, last)
+        .map_err(|e| format!("Failed to replace {:?}: {}", last, e))?;
+
+    for (path, _) in earlier {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete {:?}: {}", path, e))?;
+    }
+    Ok(earlier.len())
+}
+
+/// Lists segment files of a spool directory in sequence order
+fn list_segments(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to list spool {:?}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| segment_seq(path).is_some())
+        .collect();
+    segments.sort();
+    Ok(segments)
+}
+
+/// Parses the sequence number from a segment file name
+fn segment_seq(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+fn has_ext(path: &Path, ext: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(ext)
+}
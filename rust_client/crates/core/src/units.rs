@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+/// Unit of each known metric family. A pattern ending in `/` matches every key under
+/// it; `*` matches one path segment (a surface, recipe or item name). The first
+/// matching pattern wins, so specific patterns come before general ones.
+const UNITS: &[(&str, &str)] = &[
+    ("production/", "items/min"),
+    ("consumption/", "items/min"),
+    ("production_by_category/", "items/min"),
+    ("consumption_by_category/", "items/min"),
+    ("throughput/", "items/min"),
+    ("probe/", "items/min"),
+    ("science/labs", "labs"),
+    ("science/lab_utilization", "fraction"),
+    ("science/*/surplus", "items/min"),
+    ("science/raw/", "items/min"),
+    ("bottleneck/item/", "fraction"),
+    ("bottleneck/short_items", "items"),
+    ("utilization/", "fraction"),
+    ("utilization_by_category/", "fraction"),
+    ("machines/", "machines"),
+    ("exploration/*/explored_km2", "km²"),
+    ("exploration/", "chunks"),
+    ("pollution/*/centroid_x", "tiles"),
+    ("pollution/*/centroid_y", "tiles"),
+    ("pollution/*/centroid_drift", "chunks"),
+    ("pollution/*/polluted_chunks", "chunks"),
+    ("pollution/", "pollution"),
+    ("power/", "MW"),
+    ("enemy/evolution", "fraction"),
+    ("rcon/players", "players"),
+    ("rcon/evolution", "fraction"),
+    ("rcon/produced/", "items"),
+    ("ingest/", "events"),
+    ("weave/", "calls"),
+    ("client/overflow_keys", "keys"),
+    ("client/idle_secs", "s"),
+    // Weave call inputs and outputs
+    ("tick", "ticks"),
+    ("duration_ticks", "ticks"),
+    ("position_x", "tiles"),
+    ("position_y", "tiles"),
+];
+
+/// Returns the unit of a metric key or call field, if its family is known
+pub fn unit_of(key: &str) -> Option<&'static str> {
+    UNITS
+        .iter()
+        .find(|(pattern, _)| matches(pattern, key))
+        .map(|&(_, unit)| unit)
+}
+
+/// Units of the given keys that have one, for call attributes
+pub fn units_of<'a>(keys: impl IntoIterator<Item = &'a String>) -> BTreeMap<String, &'static str> {
+    keys.into_iter()
+        .filter_map(|key| unit_of(key).map(|unit| (key.clone(), unit)))
+        .collect()
+}
+
+/// The registry as a `{pattern: unit}` object, for run notes
+pub fn to_json() -> serde_json::Value {
+    let units: BTreeMap<&str, &str> = UNITS.iter().copied().collect();
+    serde_json::json!(units)
+}
+
+/// Whether a metric key matches a registry pattern
+pub fn matches(pattern: &str, key: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix('/') {
+        return key
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'));
+    }
+
+    let mut pattern_parts = pattern.split('/');
+    let mut key_parts = key.split('/');
+    loop {
+        match (pattern_parts.next(), key_parts.next()) {
+            (None, None) => return true,
+            (Some("*"), Some(_)) => {}
+            (Some(expected), Some(actual)) if expected == actual => {}
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::config::UploadBudgetSettings;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Token bucket state, in bytes
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+/// Upload budget for metered connections, shared by the WandB and Weave managers.
+/// Priority traffic (stats, milestones) always goes out but draws down the budget;
+/// deferrable traffic (screenshots, bulk entity traces) only goes out while budget remains.
+pub struct UploadBudget {
+    bucket: Mutex<Bucket>,
+}
+
+impl UploadBudget {
+    /// Creates a budget allowing `mb_per_hour` megabytes per hour
+    pub fn new(mb_per_hour: f64) -> Self {
+        let capacity = mb_per_hour * 1024.0 * 1024.0;
+        Self {
+            bucket: Mutex::new(Bucket {
+                capacity,
+                available: capacity,
+                refill_per_sec: capacity / 3600.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Creates the configured budget; unset means unlimited
+    pub fn from_settings(settings: &UploadBudgetSettings) -> Option<Arc<Self>> {
+        let mb_per_hour = settings.mb_per_hour?;
+        println!("📶 Upload budget enabled: {} MB/hour", mb_per_hour);
+        Some(Arc::new(Self::new(mb_per_hour)))
+    }
+
+    /// Consumes budget for deferrable traffic, returning false if not enough remains
+    pub fn try_consume(&self, bytes: usize) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.refill();
+        if bucket.available >= bytes as f64 {
+            bucket.available -= bytes as f64;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether `bytes` of deferrable traffic would go out now, without consuming budget
+    pub fn can_afford(&self, bytes: usize) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.refill();
+        bucket.available >= bytes as f64
+    }
+
+    /// Consumes budget for priority traffic, which is never deferred.
+    /// The balance may go negative (down to one full bucket) so deferred
+    /// traffic waits until priority traffic has been paid back.
+    pub fn consume(&self, bytes: usize) {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.refill();
+        bucket.available = (bucket.available - bytes as f64).max(-bucket.capacity);
+    }
+
+    /// Whether the bucket is completely refilled. Used to let through single items
+    /// larger than the whole budget, which could otherwise never be sent.
+    pub fn is_full(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.refill();
+        bucket.available >= bucket.capacity
+    }
+}
@@ -0,0 +1,13 @@
+//! Every key of the default settings is described with a type
+
+use factorio_events_core::config::AppConfig;
+
+#[test]
+fn every_default_key_has_a_type() {
+    let schema = AppConfig::schema().expect("every null-default key is in UNSET_KEYS");
+    let keys = schema.as_array().expect("schema is an array");
+    assert!(!keys.is_empty());
+    for key in keys {
+        assert!(key["type"].is_string(), "key without a type: {}", key);
+    }
+}
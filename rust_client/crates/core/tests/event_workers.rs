@@ -0,0 +1,234 @@
+//! Sessions processed side by side on separate workers keep their own runs and traces
+
+use async_trait::async_trait;
+use factorio_events_core::event_mediator::{EventMediator, ProcessSummary, WorkerManagers};
+use factorio_events_core::event_sink::{
+    CallTracer, EventSink, GameEvent, RunTracker, SessionStart, StatsCycle,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// What a fake manager was handed, with the session it was in at the time
+#[derive(Default)]
+struct Handled {
+    current: Option<String>,
+    sessions: Vec<String>,
+    /// (current session, session of the stats cycle)
+    stats: Vec<(Option<String>, String)>,
+    /// (current session, researched tech)
+    calls: Vec<(Option<String>, String)>,
+}
+
+struct FakeManager {
+    name: &'static str,
+    handled: Arc<Mutex<Handled>>,
+}
+
+#[async_trait]
+impl EventSink for FakeManager {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        let mut handled = self.handled.lock().unwrap();
+        handled.current = Some(session.session_id.clone());
+        handled.sessions.push(session.session_id.clone());
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        // Let the other worker run in between, as a slow upload would
+        tokio::task::yield_now().await;
+        let mut handled = self.handled.lock().unwrap();
+        let current = handled.current.clone();
+        handled.stats.push((current, stats.session_id.clone()));
+    }
+
+    async fn handle_game_event(&self, _tick: u64, event: &GameEvent) {
+        tokio::task::yield_now().await;
+        if let GameEvent::ResearchStarted { tech_name, .. } = event {
+            let mut handled = self.handled.lock().unwrap();
+            let current = handled.current.clone();
+            handled.calls.push((current, tech_name.clone()));
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn shutdown(&self) {}
+}
+
+impl RunTracker for FakeManager {
+    fn disabled_reason(&self) -> Option<&str> {
+        None
+    }
+
+    fn current_run_info(&self) -> Option<(String, String)> {
+        None
+    }
+
+    fn resume_run(
+        &self,
+        _run_name: String,
+        _run_id: Option<String>,
+        _project: Option<(String, String)>,
+    ) {
+    }
+
+    fn link_next_run(&self) {}
+
+    fn log_custom(&self, _metrics: HashMap<String, f64>, _step: Option<u64>) {}
+
+    fn mark_crashed(&self) {}
+}
+
+#[async_trait]
+impl CallTracer for FakeManager {
+    fn disabled_reason(&self) -> Option<&str> {
+        None
+    }
+
+    async fn preflight(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn active_calls(&self) -> Vec<(String, u64)> {
+        Vec::new()
+    }
+
+    async fn abort_orphaned_calls(&self, _keep: Option<&str>) {}
+
+    async fn set_wandb_run(&self, _run_info: Option<(String, String)>) {}
+
+    async fn resume_session(&self, _session_id: String, _source_session_id: String) -> bool {
+        false
+    }
+
+    async fn current_trace_id(&self) -> Option<String> {
+        None
+    }
+
+    async fn retry_failed(&self) {}
+
+    async fn upload_deferred(&self) {}
+
+    async fn begin_batch(&self) {}
+
+    async fn end_batch(&self) {}
+
+    async fn handle_drain_summary(
+        &self,
+        _summary: &ProcessSummary,
+        _carried: usize,
+        _latency: Duration,
+    ) {
+    }
+
+    async fn retry_metrics(&self) -> Option<HashMap<String, f64>> {
+        None
+    }
+
+    async fn handle_status(&self, _tick: u64, _idle: Duration) {}
+
+    async fn abort_active_calls(&self, _panic_message: &str) {}
+}
+
+/// What the (WandB, Weave) fakes of each worker were handed, in worker order
+type Registry = Arc<Mutex<Vec<(Arc<Mutex<Handled>>, Arc<Mutex<Handled>>)>>>;
+
+fn fakes(registry: &Registry) -> (FakeManager, FakeManager) {
+    let wandb = Arc::new(Mutex::new(Handled::default()));
+    let weave = Arc::new(Mutex::new(Handled::default()));
+    registry
+        .lock()
+        .unwrap()
+        .push((wandb.clone(), weave.clone()));
+    (
+        FakeManager {
+            name: "wandb",
+            handled: wandb,
+        },
+        FakeManager {
+            name: "weave",
+            handled: weave,
+        },
+    )
+}
+
+fn session_init(session: &str) -> String {
+    format!(
+        r#"{{"type":"session_init","session_id":"{}","tick":0,"level_name":"nauvis"}}"#,
+        session
+    )
+}
+
+fn stats(session: &str, cycle: u64) -> String {
+    format!(
+        r#"{{"type":"stats","session_id":"{}","cycle":{},"tick":{},"products_production":{{"iron-plate":{}}},"materials_consumption":{{}}}}"#,
+        session,
+        cycle,
+        cycle * 600,
+        cycle * 10
+    )
+}
+
+fn research(session: &str, cycle: u64) -> String {
+    format!(
+        r#"{{"type":"event","event_name":"on_research_started","session_id":"{}","tick":{},"tech_name":"{}-tech-{}","tech_level":1}}"#,
+        session,
+        cycle * 600 + 1,
+        session,
+        cycle
+    )
+}
+
+#[tokio::test]
+async fn sessions_on_separate_workers_keep_their_runs_and_calls() {
+    let registry = Registry::default();
+    let (wandb, weave) = fakes(&registry);
+    let factory_registry = registry.clone();
+    let mediator = EventMediator::new(wandb, weave).with_workers(2, move |_| {
+        let (wandb, weave) = fakes(&factory_registry);
+        WorkerManagers::new(wandb, weave)
+    });
+
+    let sessions = ["alpha", "beta"];
+    let mut lines: Vec<String> = sessions
+        .iter()
+        .map(|session| session_init(session))
+        .collect();
+    for cycle in 1..=3 {
+        for session in sessions {
+            lines.push(stats(session, cycle));
+            lines.push(research(session, cycle));
+        }
+    }
+    let summary = mediator.process_events(lines).await;
+    assert_eq!(summary.routed, 14);
+    assert!(summary.dropped.is_empty());
+
+    let registry = registry.lock().unwrap();
+    assert_eq!(registry.len(), 2, "one set of managers per worker");
+    for (session, (wandb, weave)) in sessions.iter().zip(registry.iter()) {
+        let session = session.to_string();
+        let wandb = wandb.lock().unwrap();
+        assert_eq!(wandb.sessions, vec![session.clone()]);
+        assert_eq!(wandb.stats.len(), 3);
+        for (current, stats_session) in &wandb.stats {
+            assert_eq!(current.as_ref(), Some(&session));
+            assert_eq!(stats_session, &session);
+        }
+
+        let weave = weave.lock().unwrap();
+        assert_eq!(weave.sessions, vec![session.clone()]);
+        let techs: Vec<String> = (1..=3)
+            .map(|cycle| format!("{}-tech-{}", session, cycle))
+            .collect();
+        assert_eq!(
+            weave.calls,
+            techs
+                .into_iter()
+                .map(|tech| (Some(session.clone()), tech))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,4 @@
+//! The session export sink: the production and consumption time series of every session
+//! written to CSV or Parquet files.
+
+pub mod session_export;
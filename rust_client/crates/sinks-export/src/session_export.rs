@@ -0,0 +1,297 @@
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use async_trait::async_trait;
+use factorio_events_core::config::ExportSettings;
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Suffix of an export still being written; dropped when the session ends
+const PARTIAL_SUFFIX: &str = ".partial";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Parquet,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Parquet => "parquet",
+        }
+    }
+}
+
+/// Sink exporting the production and consumption time series of every session to
+/// CSV and/or Parquet files, one row per cycle, kind and item:
+/// `cycle, tick, kind, item, value`. Rows are written as cycles arrive; the files get
+/// their final `<run_name>.<format>` name when the session ends.
+#[derive(Clone)]
+pub struct SessionExporter {
+    dir: PathBuf,
+    formats: Vec<Format>,
+    /// Export of the current session
+    open: Arc<Mutex<Option<OpenExport>>>,
+}
+
+struct OpenExport {
+    run_name: String,
+    csv: Option<(PathBuf, BufWriter<File>)>,
+    parquet: Option<(PathBuf, ArrowWriter<File>)>,
+}
+
+impl SessionExporter {
+    /// Starts exporting when an output directory is configured
+    pub fn from_settings(settings: &ExportSettings) -> Option<Self> {
+        let dir = settings.dir.as_ref()?;
+        let mut formats = Vec::new();
+        for name in &settings.formats {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "csv" => formats.push(Format::Csv),
+                "parquet" => formats.push(Format::Parquet),
+                other => eprintln!("⚠️  Unknown export format '{}', skipping it", other),
+            }
+        }
+        if formats.is_empty() {
+            eprintln!("⚠️  No export format configured, session export is off");
+            return None;
+        }
+        formats.dedup();
+
+        println!(
+            "💾 Exporting session metrics to {} ({})",
+            dir,
+            formats
+                .iter()
+                .map(|format| format.extension())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        Some(Self {
+            dir: PathBuf::from(dir),
+            formats,
+            open: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// An exporter of its own session files, in a subdirectory named after the
+    /// given game instance
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        let dir = match instance {
+            Some(instance) => self.dir.join(instance),
+            None => self.dir.clone(),
+        };
+        Self {
+            dir,
+            formats: self.formats.clone(),
+            open: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn start(&self, run_name: &str) -> Result<OpenExport, String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", self.dir, e))?;
+        let mut export = OpenExport {
+            run_name: run_name.to_string(),
+            csv: None,
+            parquet: None,
+        };
+        for format in &self.formats {
+            let path = self.dir.join(format!(
+                "{}.{}{}",
+                run_name,
+                format.extension(),
+                PARTIAL_SUFFIX
+            ));
+            let file =
+                File::create(&path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+            match format {
+                Format::Csv => {
+                    let mut writer = BufWriter::new(file);
+                    writeln!(writer, "cycle,tick,kind,item,value")
+                        .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+                    export.csv = Some((path, writer));
+                }
+                Format::Parquet => {
+                    let properties = WriterProperties::builder()
+                        .set_compression(Compression::SNAPPY)
+                        .build();
+                    let writer = ArrowWriter::try_new(file, schema(), Some(properties))
+                        .map_err(|e| format!("Failed to start {:?}: {}", path, e))?;
+                    export.parquet = Some((path, writer));
+                }
+            }
+        }
+        Ok(export)
+    }
+
+    /// Finishes the export of the current session, if any
+    fn finish(&self) {
+        let Some(export) = self.open.lock().unwrap().take() else {
+            return;
+        };
+        let run_name = export.run_name.clone();
+        match export.finish() {
+            Ok(paths) => {
+                for path in paths {
+                    println!("💾 Exported {} to {:?}", run_name, path);
+                }
+            }
+            Err(e) => eprintln!("❌ Failed to export {}: {}", run_name, e),
+        }
+    }
+}
+
+impl OpenExport {
+    fn append(&mut self, stats: &StatsCycle) -> Result<(), String> {
+        let series = [
+            ("production", &stats.production),
+            ("consumption", &stats.consumption),
+        ];
+
+        if let Some((ref path, ref mut writer)) = self.csv {
+            for (kind, rates) in series {
+                for (item, value) in rates {
+                    writeln!(
+                        writer,
+                        "{},{},{},{},{}",
+                        stats.cycle,
+                        stats.tick,
+                        kind,
+                        csv_field(item),
+                        value
+                    )
+                    .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+                }
+            }
+        }
+
+        if let Some((ref path, ref mut writer)) = self.parquet {
+            let mut kinds = Vec::new();
+            let mut items = Vec::new();
+            let mut values = Vec::new();
+            for (kind, rates) in series {
+                for (item, value) in rates {
+                    kinds.push(kind);
+                    items.push(item.as_str());
+                    values.push(*value);
+                }
+            }
+            if !values.is_empty() {
+                let rows = values.len();
+                let columns: Vec<ArrayRef> = vec![
+                    Arc::new(UInt64Array::from(vec![stats.cycle; rows])),
+                    Arc::new(UInt64Array::from(vec![stats.tick; rows])),
+                    Arc::new(StringArray::from(kinds)),
+                    Arc::new(StringArray::from(items)),
+                    Arc::new(Float64Array::from(values)),
+                ];
+                let batch = RecordBatch::try_new(schema(), columns)
+                    .map_err(|e| format!("Failed to build rows of {:?}: {}", path, e))?;
+                writer
+                    .write(&batch)
+                    .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the files and gives them their final names
+    fn finish(self) -> Result<Vec<PathBuf>, String> {
+        let mut paths = Vec::new();
+        if let Some((path, writer)) = self.csv {
+            writer
+                .into_inner()
+                .map_err(|e| format!("Failed to flush {:?}: {}", path, e.error()))?;
+            paths.push(complete(&path)?);
+        }
+        if let Some((path, writer)) = self.parquet {
+            writer
+                .close()
+                .map_err(|e| format!("Failed to close {:?}: {}", path, e))?;
+            paths.push(complete(&path)?);
+        }
+        Ok(paths)
+    }
+}
+
+#[async_trait]
+impl EventSink for SessionExporter {
+    async fn handle_session_init(&self, _session: &mut SessionStart) {
+        // A new session ends the previous one
+        self.finish();
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        // Opened at the first cycle, so a run resumed by a warm start is exported too
+        let current = self
+            .open
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|export| export.run_name.clone());
+        if current.as_deref() != Some(stats.run_name.as_str()) {
+            self.finish();
+            match self.start(&stats.run_name) {
+                Ok(export) => *self.open.lock().unwrap() = Some(export),
+                Err(e) => {
+                    eprintln!("❌ Failed to start export of {}: {}", stats.run_name, e);
+                    return;
+                }
+            }
+        }
+
+        let mut open = self.open.lock().unwrap();
+        if let Some(export) = open.as_mut() {
+            if let Err(e) = export.append(stats) {
+                eprintln!("❌ Stopped exporting {}: {}", export.run_name, e);
+                *open = None;
+            }
+        }
+    }
+
+    async fn handle_game_event(&self, _tick: u64, _event: &GameEvent) {}
+
+    async fn shutdown(&self) {
+        self.finish();
+    }
+
+    fn name(&self) -> &'static str {
+        "export"
+    }
+}
+
+fn schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("cycle", DataType::UInt64, false),
+        Field::new("tick", DataType::UInt64, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("item", DataType::Utf8, false),
+        Field::new("value", DataType::Float64, false),
+    ]))
+}
+
+/// Renames a finished `.partial` file to its final name
+fn complete(path: &Path) -> Result<PathBuf, String> {
+    let name = path.to_string_lossy();
+    let done = PathBuf::from(name.strip_suffix(PARTIAL_SUFFIX).unwrap_or(&name));
+    std::fs::rename(path, &done)
+        .map_err(|e| format!("Failed to rename {:?} to {:?}: {}", path, done, e))?;
+    Ok(done)
+}
+
+/// Quotes an item name holding a comma or quote
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::InfluxSettings;
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot};
+
+/// Points buffered for the writer before the sink drops new ones
+const QUEUE_BATCHES: usize = 64;
+
+/// First retry delay of a failed write, doubled per attempt
+const RETRY_BASE: Duration = Duration::from_secs(1);
+
+enum WriterMessage {
+    Points(Vec<String>),
+    /// Writes everything buffered, then acknowledges
+    Flush(oneshot::Sender<()>),
+}
+
+/// Sink writing production and consumption rates to InfluxDB as line protocol: one
+/// `factorio_production`/`factorio_consumption` point per item and cycle, tagged with
+/// the session id, run and item, with the game tick as a field. A background writer
+/// batches points and retries failed writes with backoff.
+#[derive(Clone)]
+pub struct InfluxSink {
+    sender: mpsc::Sender<WriterMessage>,
+    instance: Option<String>,
+}
+
+impl InfluxSink {
+    /// Starts the writer when a URL is configured
+    pub fn from_settings(settings: &InfluxSettings) -> Option<Self> {
+        let url = settings.url.as_ref()?;
+        let write_url = format!(
+            "{}/api/v2/write?org={}&bucket={}&precision=ms",
+            url.trim_end_matches('/'),
+            settings.org,
+            settings.bucket
+        );
+        let token = std::env::var("INFLUX_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+        let (sender, receiver) = mpsc::channel(QUEUE_BATCHES);
+        println!(
+            "📈 Writing metrics to InfluxDB bucket {} at {}",
+            settings.bucket, url
+        );
+        let writer = InfluxWriter {
+            http: reqwest::Client::new(),
+            write_url,
+            token,
+            max_attempts: settings.max_attempts.max(1),
+        };
+        tokio::spawn(run_writer(writer, settings.clone(), receiver));
+        Some(Self {
+            sender,
+            instance: None,
+        })
+    }
+
+    /// A handle on the same writer whose points carry the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            instance,
+        }
+    }
+
+    fn point(
+        &self,
+        measurement: &str,
+        stats: &StatsCycle,
+        item: &str,
+        value: f64,
+        time_ms: u128,
+    ) -> String {
+        let mut tags = format!(
+            "session_id={},run_name={},item={}",
+            escape_tag(&stats.session_id),
+            escape_tag(&stats.run_name),
+            escape_tag(item)
+        );
+        if let Some(ref instance) = self.instance {
+            tags.push_str(&format!(",instance={}", escape_tag(instance)));
+        }
+        format!(
+            "{},{} value={},tick={}i,cycle={}i {}",
+            measurement, tags, value, stats.tick, stats.cycle, time_ms
+        )
+    }
+}
+
+#[async_trait]
+impl EventSink for InfluxSink {
+    async fn handle_session_init(&self, _session: &mut SessionStart) {}
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        let time_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis());
+        let mut points = Vec::with_capacity(stats.production.len() + stats.consumption.len());
+        let measurements = [
+            ("factorio_production", &stats.production),
+            ("factorio_consumption", &stats.consumption),
+        ];
+        for (measurement, rates) in measurements {
+            // Line protocol has no NaN or infinity
+            for (item, value) in rates.iter().filter(|(_, value)| value.is_finite()) {
+                points.push(self.point(measurement, stats, item, *value, time_ms));
+            }
+        }
+        if points.is_empty() {
+            return;
+        }
+
+        // Never block the event path on a slow database
+        if self.sender.try_send(WriterMessage::Points(points)).is_err() {
+            eprintln!(
+                "⚠️  InfluxDB writer is backed up, dropped the points of cycle {}",
+                stats.cycle
+            );
+        }
+    }
+
+    async fn handle_game_event(&self, _tick: u64, _event: &GameEvent) {}
+
+    async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.sender.send(WriterMessage::Flush(done)).await.is_ok() {
+            flushed.await.ok();
+        }
+    }
+
+    async fn shutdown(&self) {
+        self.flush().await;
+    }
+
+    fn name(&self) -> &'static str {
+        "influx"
+    }
+}
+
+/// Collects points into batches of `batch_size`, writing a batch when it is full or
+/// `flush_interval_secs` after its first point
+async fn run_writer(
+    writer: InfluxWriter,
+    settings: InfluxSettings,
+    mut receiver: mpsc::Receiver<WriterMessage>,
+) {
+    let flush_interval = Duration::from_secs(settings.flush_interval_secs.max(1));
+    let batch_size = settings.batch_size.max(1);
+    let mut batch: Vec<String> = Vec::new();
+    let mut deadline = tokio::time::Instant::now() + flush_interval;
+
+    loop {
+        let message = if batch.is_empty() {
+            receiver.recv().await
+        } else {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(message) => message,
+                Err(_) => {
+                    writer.write(std::mem::take(&mut batch)).await;
+                    continue;
+                }
+            }
+        };
+
+        match message {
+            Some(WriterMessage::Points(points)) => {
+                if batch.is_empty() {
+                    deadline = tokio::time::Instant::now() + flush_interval;
+                }
+                batch.extend(points);
+                while batch.len() >= batch_size {
+                    let rest = batch.split_off(batch_size);
+                    writer.write(std::mem::replace(&mut batch, rest)).await;
+                }
+            }
+            Some(WriterMessage::Flush(done)) => {
+                if !batch.is_empty() {
+                    writer.write(std::mem::take(&mut batch)).await;
+                }
+                done.send(()).ok();
+            }
+            None => {
+                if !batch.is_empty() {
+                    writer.write(batch).await;
+                }
+                return;
+            }
+        }
+    }
+}
+
+struct InfluxWriter {
+    http: reqwest::Client,
+    write_url: String,
+    token: Option<String>,
+    max_attempts: u32,
+}
+
+impl InfluxWriter {
+    /// Writes one batch, retrying server errors and network failures with exponential
+    /// backoff; client errors (a malformed point, a bad token) are not retried
+    async fn write(&self, batch: Vec<String>) {
+        let body = batch.join("\n");
+        let mut delay = RETRY_BASE;
+        for attempt in 1..=self.max_attempts {
+            let mut request = self.http.post(&self.write_url).body(body.clone());
+            if let Some(ref token) = self.token {
+                request = request.header("Authorization", format!("Token {}", token));
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) if response.status().is_client_error() => {
+                    let status = response.status();
+                    let detail = response.text().await.unwrap_or_default();
+                    report_dropped(batch.len(), &format!("{} {}", status, detail));
+                    return;
+                }
+                Ok(response) => format!("status {}", response.status()),
+                Err(e) => e.to_string(),
+            };
+            if attempt < self.max_attempts {
+                eprintln!(
+                    "⚠️  InfluxDB write failed (attempt {}), retrying in {:?}: {}",
+                    attempt, delay, error
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            } else {
+                report_dropped(batch.len(), &error);
+            }
+        }
+    }
+}
+
+fn report_dropped(points: usize, error: &str) {
+    eprintln!("❌ Dropped {} InfluxDB points: {}", points, error);
+    client_events::emit(ClientEvent::Error {
+        component: "influx".to_string(),
+        message: format!("Dropped {} points: {}", points, error),
+    });
+}
+
+/// Escapes commas, spaces and equals signs in tag values
+fn escape_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(' ', "\\ ")
+        .replace('=', "\\=")
+}
@@ -0,0 +1,4 @@
+//! The InfluxDB sink: production and consumption rates written as line protocol, in
+//! batches.
+
+pub mod influx_sink;
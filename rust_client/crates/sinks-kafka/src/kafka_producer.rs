@@ -0,0 +1,129 @@
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::KafkaSettings;
+use factorio_events_core::event_sink::{EventRecorder, RecordedEvent};
+use rdkafka::config::ClientConfig;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer};
+use rdkafka::ClientContext;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest the producer waits at shutdown for queued events to be delivered
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reports events Kafka failed to deliver after its own retries
+struct DeliveryReporter;
+
+impl ClientContext for DeliveryReporter {}
+
+impl ProducerContext for DeliveryReporter {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _opaque: ()) {
+        if let Err((e, _)) = result {
+            eprintln!("❌ Failed to deliver event to Kafka: {}", e);
+            client_events::emit(ClientEvent::Error {
+                component: "kafka".to_string(),
+                message: format!("Failed to deliver event: {}", e),
+            });
+        }
+    }
+}
+
+/// Publishes every parsed event, as the JSON line the mod wrote, to a Kafka topic.
+/// Messages are keyed by the Factorio session id, so the events of a session stay
+/// ordered within their partition, and carry `type` and `instance` headers.
+#[derive(Clone)]
+pub struct KafkaProducer {
+    producer: Arc<ThreadedProducer<DeliveryReporter>>,
+    topic: String,
+    instance: Option<String>,
+}
+
+impl KafkaProducer {
+    /// Connects when brokers are configured. `properties` are passed to librdkafka
+    /// as is; SASL credentials are only read from KAFKA_USERNAME and KAFKA_PASSWORD.
+    pub fn from_settings(settings: &KafkaSettings) -> Option<Self> {
+        let brokers = settings.brokers.as_ref()?;
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", brokers)
+            .set("client.id", &settings.client_id);
+        for (key, value) in &settings.properties {
+            config.set(key, value);
+        }
+        if let Ok(username) = std::env::var("KAFKA_USERNAME") {
+            config.set("sasl.username", username).set(
+                "sasl.password",
+                std::env::var("KAFKA_PASSWORD").unwrap_or_default(),
+            );
+        }
+
+        match config.create_with_context(DeliveryReporter) {
+            Ok(producer) => {
+                println!("📨 Publishing events to Kafka topic {} at {}", settings.topic, brokers);
+                Some(Self {
+                    producer: Arc::new(producer),
+                    topic: settings.topic.clone(),
+                    instance: None,
+                })
+            }
+            Err(e) => {
+                eprintln!("⚠️  Kafka producer disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// A handle on the same producer whose messages carry the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            producer: self.producer.clone(),
+            topic: self.topic.clone(),
+            instance,
+        }
+    }
+
+    /// Queues a parsed event with its JSON line as the payload
+    pub fn publish(&self, event: &RecordedEvent) -> Result<(), String> {
+        let mut headers = OwnedHeaders::new().insert(Header {
+            key: "type",
+            value: Some(event.kind.as_str()),
+        });
+        if let Some(ref instance) = self.instance {
+            headers = headers.insert(Header {
+                key: "instance",
+                value: Some(instance.as_str()),
+            });
+        }
+        let record = BaseRecord::to(&self.topic)
+            .key(&event.session_id)
+            .payload(&event.payload)
+            .headers(headers);
+        // Never blocks: a full local queue fails right away
+        self.producer
+            .send(record)
+            .map_err(|(e, _)| format!("Failed to queue event for Kafka: {}", e))
+    }
+
+    /// Waits for queued events to be delivered
+    pub fn flush(&self) {
+        if let Err(e) = self.producer.flush(FLUSH_TIMEOUT) {
+            eprintln!("⚠️  Kafka events still queued at shutdown: {}", e);
+        }
+    }
+}
+
+impl EventRecorder for KafkaProducer {
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+
+    fn record(&mut self, event: &RecordedEvent) -> Result<(), String> {
+        self.publish(event)
+    }
+
+    fn flush(&mut self) {
+        KafkaProducer::flush(self);
+    }
+}
@@ -0,0 +1,3 @@
+//! The Kafka recorder: every parsed event published to a topic, keyed by session.
+
+pub mod kafka_producer;
@@ -0,0 +1,256 @@
+use async_trait::async_trait;
+use factorio_events_core::config::AdminSettings;
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use image::ImageFormat;
+use std::collections::{HashMap, VecDeque};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Events kept per instance for the live stream
+const RECENT_EVENTS: usize = 100;
+
+/// Screenshot thumbnails kept per instance
+const THUMBNAILS: usize = 8;
+
+/// Bounds of a thumbnail, in pixels; the aspect ratio is kept
+const THUMBNAIL_WIDTH: u32 = 320;
+const THUMBNAIL_HEIGHT: u32 = 180;
+
+/// A downscaled screenshot
+struct Thumbnail {
+    id: u64,
+    tick: u64,
+    surface: String,
+    png: Arc<Vec<u8>>,
+}
+
+/// What the dashboard shows of one game instance
+#[derive(Default)]
+struct InstanceView {
+    run_name: Option<String>,
+    session_id: Option<String>,
+    cycle: u64,
+    tick: u64,
+    production: HashMap<String, f64>,
+    consumption: HashMap<String, f64>,
+    scalars: HashMap<String, f64>,
+    /// Newest last
+    events: VecDeque<serde_json::Value>,
+    /// Newest last
+    thumbnails: VecDeque<Thumbnail>,
+}
+
+impl InstanceView {
+    fn record(&mut self, event: &str, tick: u64, fields: serde_json::Map<String, serde_json::Value>) {
+        self.events.push_back(serde_json::json!({
+            "event": event,
+            "tick": tick,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "fields": fields,
+        }));
+        if self.events.len() > RECENT_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "run_name": self.run_name,
+            "session_id": self.session_id,
+            "cycle": self.cycle,
+            "tick": self.tick,
+            "production": self.production,
+            "consumption": self.consumption,
+            "scalars": self.scalars,
+            "events": self.events,
+            "thumbnails": self
+                .thumbnails
+                .iter()
+                .map(|thumbnail| serde_json::json!({
+                    "id": thumbnail.id,
+                    "tick": thumbnail.tick,
+                    "surface": thumbnail.surface,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[derive(Default)]
+struct DashboardState {
+    /// Keyed by game instance id
+    instances: HashMap<Option<String>, InstanceView>,
+    next_thumbnail: u64,
+}
+
+/// Sink keeping the latest state of every instance for the admin server's web
+/// dashboard: recent events, the current production and consumption, the cycle's
+/// derived metrics and thumbnails of the latest screenshots. Thumbnails are made in the
+/// background so the event path never waits on image work.
+#[derive(Clone)]
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+    instance: Option<String>,
+    /// Directory the screenshot paths of the stats events are relative to
+    output_path: Option<PathBuf>,
+}
+
+impl Dashboard {
+    /// Enabled by `admin.dashboard`, on an admin server with a bind address. Thumbnails
+    /// are made from the screenshots under `output_path`.
+    pub fn from_settings(settings: &AdminSettings, output_path: Option<&str>) -> Option<Self> {
+        if !settings.dashboard {
+            return None;
+        }
+        if settings.bind.is_none() {
+            eprintln!("⚠️  admin.dashboard needs admin.bind, the dashboard is off");
+            return None;
+        }
+        Some(Self {
+            state: Arc::new(Mutex::new(DashboardState::default())),
+            instance: None,
+            output_path: output_path.map(PathBuf::from),
+        })
+    }
+
+    /// A handle on the same dashboard recording the given game instance
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            state: self.state.clone(),
+            instance,
+            output_path: self.output_path.clone(),
+        }
+    }
+
+    /// State of a game instance as served to the dashboard page, once it sent an event
+    pub fn view(&self, instance: &Option<String>) -> Option<serde_json::Value> {
+        let state = self.state.lock().unwrap();
+        state.instances.get(instance).map(InstanceView::to_json)
+    }
+
+    /// PNG of a thumbnail still kept by any instance
+    pub fn thumbnail(&self, id: u64) -> Option<Arc<Vec<u8>>> {
+        let state = self.state.lock().unwrap();
+        state
+            .instances
+            .values()
+            .flat_map(|view| view.thumbnails.iter())
+            .find(|thumbnail| thumbnail.id == id)
+            .map(|thumbnail| thumbnail.png.clone())
+    }
+
+    fn with_view<T>(&self, f: impl FnOnce(&mut InstanceView) -> T) -> T {
+        let mut state = self.state.lock().unwrap();
+        f(state.instances.entry(self.instance.clone()).or_default())
+    }
+
+    /// Reads and downscales a screenshot in the background, then adds it
+    fn add_thumbnail(&self, tick: u64, surface: String, path: String) {
+        let dashboard = self.clone();
+        tokio::spawn(async move {
+            let png = match make_thumbnail(dashboard.output_path.as_deref(), &path).await {
+                Ok(png) => png,
+                Err(e) => {
+                    eprintln!("⚠️  Dashboard thumbnail skipped: {}", e);
+                    return;
+                }
+            };
+            let mut state = dashboard.state.lock().unwrap();
+            state.next_thumbnail += 1;
+            let id = state.next_thumbnail;
+            let view = state.instances.entry(dashboard.instance.clone()).or_default();
+            view.thumbnails.push_back(Thumbnail {
+                id,
+                tick,
+                surface,
+                png: Arc::new(png),
+            });
+            if view.thumbnails.len() > THUMBNAILS {
+                view.thumbnails.pop_front();
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl EventSink for Dashboard {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        self.with_view(|view| {
+            *view = InstanceView {
+                run_name: Some(session.run_name.clone()),
+                session_id: Some(session.session_id.clone()),
+                tick: session.tick,
+                ..InstanceView::default()
+            };
+            let mut fields = serde_json::Map::new();
+            fields.insert("run_name".to_string(), serde_json::json!(session.run_name));
+            fields.insert("level_name".to_string(), serde_json::json!(session.level_name));
+            view.record("session_init", session.tick, fields);
+        });
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        self.with_view(|view| {
+            if view.run_name.as_deref() != Some(stats.run_name.as_str()) {
+                view.run_name = Some(stats.run_name.clone());
+                view.production.clear();
+                view.consumption.clear();
+            }
+            view.session_id = Some(stats.session_id.clone());
+            view.cycle = stats.cycle;
+            view.tick = stats.tick;
+            // Delta cycles only carry the items that changed
+            if !stats.delta {
+                view.production.clear();
+                view.consumption.clear();
+            }
+            view.production.extend(stats.production.iter().map(|(k, v)| (k.clone(), *v)));
+            view.consumption.extend(stats.consumption.iter().map(|(k, v)| (k.clone(), *v)));
+            view.scalars = stats.scalars.clone();
+
+            let mut fields = serde_json::Map::new();
+            fields.insert("cycle".to_string(), serde_json::json!(stats.cycle));
+            view.record("stats", stats.tick, fields);
+        });
+        if let Some((ref player, ref path)) = stats.snapshot {
+            self.add_thumbnail(stats.tick, player.surface.clone(), path.clone());
+        }
+    }
+
+    async fn handle_game_event(&self, tick: u64, event: &GameEvent) {
+        self.with_view(|view| {
+            view.tick = view.tick.max(tick);
+            view.record(event.name(), tick, event.fields());
+        });
+    }
+
+    async fn shutdown(&self) {}
+
+    fn name(&self) -> &'static str {
+        "dashboard"
+    }
+}
+
+/// Reads a screenshot the mod wrote under `output_path` and downscales it to a PNG
+async fn make_thumbnail(output_path: Option<&Path>, path: &str) -> Result<Vec<u8>, String> {
+    let output_path =
+        output_path.ok_or_else(|| "output_path (FACTORIO_OUTPUT_PATH) not set".to_string())?;
+    let full_path = output_path.join(path);
+    let bytes = tokio::fs::read(&full_path)
+        .await
+        .map_err(|e| format!("Failed to read file {:?}: {}", full_path, e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| format!("Failed to decode screenshot: {}", e))?
+            .thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+        Ok(png)
+    })
+    .await
+    .map_err(|e| format!("Thumbnail task failed: {}", e))?
+}
@@ -0,0 +1,5 @@
+//! The live sinks behind the client's own servers: the WebSocket metrics stream and the
+//! admin dashboard.
+
+pub mod dashboard;
+pub mod metrics_stream;
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+
+/// Frames buffered per subscriber before a slow one starts skipping
+const STREAM_CAPACITY: usize = 256;
+
+/// Sink broadcasting every stats cycle as a JSON frame to the WebSocket subscribers of
+/// `/metrics`, so live dashboards can mirror the WandB charts. Keys match the WandB run.
+/// Nothing is serialized while no one is subscribed.
+#[derive(Clone)]
+pub struct MetricsStream {
+    sender: broadcast::Sender<String>,
+    instance: Option<String>,
+}
+
+impl MetricsStream {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(STREAM_CAPACITY);
+        Self {
+            sender,
+            instance: None,
+        }
+    }
+
+    /// A handle on the same stream whose frames carry the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            instance,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for MetricsStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSink for MetricsStream {
+    async fn handle_session_init(&self, _session: &mut SessionStart) {}
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        let mut metrics: HashMap<String, f64> = stats.scalars.clone();
+        for (item, value) in &stats.production {
+            metrics.insert(format!("production/{}", item), *value);
+        }
+        for (item, value) in &stats.consumption {
+            metrics.insert(format!("consumption/{}", item), *value);
+        }
+        let frame = serde_json::json!({
+            "instance": self.instance,
+            "run_name": stats.run_name,
+            "cycle": stats.cycle,
+            "tick": stats.tick,
+            "delta": stats.delta,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "metrics": metrics,
+        });
+        // Fails only when the last subscriber just left
+        let _ = self.sender.send(frame.to_string());
+    }
+
+    async fn handle_game_event(&self, _tick: u64, _event: &GameEvent) {}
+
+    async fn shutdown(&self) {}
+
+    fn name(&self) -> &'static str {
+        "stream"
+    }
+}
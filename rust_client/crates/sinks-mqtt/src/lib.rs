@@ -0,0 +1,4 @@
+//! The MQTT sink: selected metrics and milestones published to a broker, for home
+//! automation and stream overlays.
+
+pub mod mqtt_sink;
@@ -0,0 +1,272 @@
+use async_trait::async_trait;
+use factorio_events_core::config::MqttSettings;
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use factorio_events_core::units;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Requests buffered for the connection before the sink drops new ones
+const QUEUE_REQUESTS: usize = 256;
+
+/// Delay before reconnecting to a broker that dropped the connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Sink publishing selected metrics and milestones to an MQTT broker, for home
+/// automation. Each selected metric is published, retained, as a plain number to
+/// `<metrics_topic>/<key>`; research completions, rocket launches and stalls of a
+/// selected production rate are published as JSON to `<events_topic>/<event>`. In live
+/// mode, every value of the session is also published, retained, under
+/// `<live_topic>/<session>/` for dashboards and stream overlays.
+#[derive(Clone)]
+pub struct MqttSink {
+    client: AsyncClient,
+    settings: Arc<MqttSettings>,
+    instance: Option<String>,
+    /// Run name of the current session
+    run_name: Arc<Mutex<Option<String>>>,
+    /// Whether each selected production key was above zero last cycle
+    flowing: Arc<Mutex<HashMap<String, bool>>>,
+}
+
+impl MqttSink {
+    /// Connects to the broker when one is configured
+    pub fn from_settings(settings: &MqttSettings) -> Option<Self> {
+        let broker = settings.broker.as_ref()?;
+        let (host, port) = match broker.rsplit_once(':') {
+            Some((host, port)) => match port.parse() {
+                Ok(port) => (host.to_string(), port),
+                Err(_) => {
+                    eprintln!("⚠️  Invalid MQTT broker address {}, MQTT is off", broker);
+                    return None;
+                }
+            },
+            None => (broker.clone(), 1883),
+        };
+
+        let mut options = MqttOptions::new(&settings.client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Ok(username) = std::env::var("MQTT_USERNAME") {
+            let password = std::env::var("MQTT_PASSWORD").unwrap_or_default();
+            options.set_credentials(username, password);
+        }
+        let (client, mut eventloop) = AsyncClient::new(options, QUEUE_REQUESTS);
+        println!("📡 Publishing to MQTT broker {}", broker);
+
+        // The event loop drives the connection and reconnects on the next poll
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        println!("📡 Connected to MQTT broker");
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!(
+                            "⚠️  MQTT connection failed, retrying in {:?}: {}",
+                            RECONNECT_DELAY, e
+                        );
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        Some(Self {
+            client,
+            settings: Arc::new(settings.clone()),
+            instance: None,
+            run_name: Arc::new(Mutex::new(None)),
+            flowing: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// A handle on the same connection whose topics carry the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            client: self.client.clone(),
+            settings: self.settings.clone(),
+            instance,
+            run_name: Arc::new(Mutex::new(None)),
+            flowing: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn topic(&self, base: &str, name: &str) -> String {
+        match self.instance {
+            Some(ref instance) => format!("{}/{}/{}", base, instance, name),
+            None => format!("{}/{}", base, name),
+        }
+    }
+
+    fn selected(&self, key: &str) -> bool {
+        self.settings
+            .metrics
+            .iter()
+            .any(|pattern| units::matches(pattern, key))
+    }
+
+    fn publish(&self, topic: String, retain: bool, payload: String) {
+        // Never block the event path on a slow broker
+        if let Err(e) = self
+            .client
+            .try_publish(&topic, QoS::AtLeastOnce, retain, payload)
+        {
+            eprintln!("⚠️  Dropped MQTT message to {}: {}", topic, e);
+        }
+    }
+
+    fn publish_event(&self, event: &str, tick: u64, mut fields: serde_json::Value) {
+        if let Some(object) = fields.as_object_mut() {
+            object.insert("event".to_string(), serde_json::json!(event));
+            object.insert("tick".to_string(), serde_json::json!(tick));
+            object.insert("instance".to_string(), serde_json::json!(self.instance));
+            object.insert(
+                "run_name".to_string(),
+                serde_json::json!(*self.run_name.lock().unwrap()),
+            );
+            object.insert(
+                "timestamp".to_string(),
+                serde_json::json!(chrono::Utc::now().to_rfc3339()),
+            );
+        }
+        let topic = self.topic(&self.settings.events_topic, event);
+        self.publish(topic, false, fields.to_string());
+    }
+
+    /// Publishes the latest production and consumption values and the player position
+    /// under `<live_topic>/<session>/`
+    fn publish_live(&self, stats: &StatsCycle) {
+        let base = &self.settings.live_topic;
+        let series = [
+            ("production", &stats.production),
+            ("consumption", &stats.consumption),
+        ];
+        for (kind, rates) in series {
+            // Delta cycles only carry changes; retained messages keep the rest current
+            for (item, value) in rates.iter().filter(|(_, value)| value.is_finite()) {
+                let name = format!("{}/{}/{}", stats.session_id, kind, item);
+                self.publish(self.topic(base, &name), true, value.to_string());
+            }
+        }
+
+        if let Some((ref player, _)) = stats.snapshot {
+            let position = serde_json::json!({
+                "x": player.position.x,
+                "y": player.position.y,
+                "surface": player.surface,
+                "health": player.health,
+                "tick": stats.tick,
+            });
+            let name = format!("{}/player", stats.session_id);
+            self.publish(self.topic(base, &name), true, position.to_string());
+        }
+    }
+
+    /// Reports selected production rates that dropped to zero or recovered since
+    /// the last cycle
+    fn report_stalls(&self, stats: &StatsCycle) {
+        let mut changes = Vec::new();
+        {
+            let mut flowing = self.flowing.lock().unwrap();
+            let mut rates: HashMap<String, f64> = stats
+                .production
+                .iter()
+                .map(|(item, value)| (format!("production/{}", item), *value))
+                .filter(|(key, _)| self.selected(key))
+                .collect();
+            // A full cycle omits items that are no longer produced
+            if !stats.delta {
+                for key in flowing.keys() {
+                    rates.entry(key.clone()).or_insert(0.0);
+                }
+            }
+            for (key, value) in rates {
+                let now = value > 0.0;
+                let before = flowing.insert(key.clone(), now);
+                if before.is_some_and(|before| before != now) {
+                    changes.push((key, now));
+                }
+            }
+        }
+
+        for (key, now) in changes {
+            let item = key.trim_start_matches("production/");
+            let event = if now {
+                "production_resumed"
+            } else {
+                "production_stalled"
+            };
+            self.publish_event(event, stats.tick, serde_json::json!({ "item": item }));
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for MqttSink {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        *self.run_name.lock().unwrap() = Some(session.run_name.clone());
+        self.flowing.lock().unwrap().clear();
+        if self.settings.live {
+            // Lets subscribers find the topics of the current session
+            let topic = self.topic(&self.settings.live_topic, "session");
+            self.publish(topic, true, session.session_id.clone());
+        }
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        let production = stats
+            .production
+            .iter()
+            .map(|(item, value)| (format!("production/{}", item), *value));
+        let consumption = stats
+            .consumption
+            .iter()
+            .map(|(item, value)| (format!("consumption/{}", item), *value));
+        let scalars = stats
+            .scalars
+            .iter()
+            .map(|(key, value)| (key.clone(), *value));
+        for (key, value) in production.chain(consumption).chain(scalars) {
+            if value.is_finite() && self.selected(&key) {
+                let topic = self.topic(&self.settings.metrics_topic, &key);
+                self.publish(topic, true, value.to_string());
+            }
+        }
+
+        if self.settings.live {
+            self.publish_live(stats);
+        }
+        self.report_stalls(stats);
+    }
+
+    async fn handle_game_event(&self, tick: u64, event: &GameEvent) {
+        match event {
+            GameEvent::ResearchFinished {
+                tech_name,
+                tech_level,
+                ..
+            } => self.publish_event(
+                "research_finished",
+                tick,
+                serde_json::json!({ "tech_name": tech_name, "tech_level": tech_level }),
+            ),
+            GameEvent::RocketLaunched { surface, count } => self.publish_event(
+                "rocket_launched",
+                tick,
+                serde_json::json!({ "surface": surface, "count": count }),
+            ),
+            _ => {}
+        }
+    }
+
+    async fn shutdown(&self) {
+        // Queued publishes go out before the disconnect
+        self.client.disconnect().await.ok();
+    }
+
+    fn name(&self) -> &'static str {
+        "mqtt"
+    }
+}
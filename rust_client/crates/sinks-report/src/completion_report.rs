@@ -0,0 +1,375 @@
+use async_trait::async_trait;
+use factorio_events_core::config::CompletionReportSettings;
+use factorio_events_core::correlation;
+use factorio_events_core::event_sink::{
+    apply_stats, EventSink, GameEvent, SessionStart, StatsCycle,
+};
+use factorio_events_core::science::SCIENCE_PACKS;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Items listed in the report, by final production rate
+const TOP_ITEMS: usize = 5;
+
+/// Attempts per webhook delivery of a report
+const WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// What a run did, collected while it is current
+struct RunTally {
+    run_name: String,
+    session_id: String,
+    started: Instant,
+    started_at: chrono::DateTime<chrono::Utc>,
+    first_tick: Option<u64>,
+    last_tick: u64,
+    cycles: u64,
+    wandb_url: Option<String>,
+    production: HashMap<String, f64>,
+    research_finished: Vec<String>,
+    rockets_launched: u64,
+    entities_built: u64,
+    entities_mined: u64,
+    crashes: u64,
+    last_crash: Option<String>,
+}
+
+impl RunTally {
+    fn new(run_name: String, session_id: String) -> Self {
+        Self {
+            run_name,
+            session_id,
+            started: Instant::now(),
+            started_at: chrono::Utc::now(),
+            first_tick: None,
+            last_tick: 0,
+            cycles: 0,
+            wandb_url: None,
+            production: HashMap::new(),
+            research_finished: Vec::new(),
+            rockets_launched: 0,
+            entities_built: 0,
+            entities_mined: 0,
+            crashes: 0,
+            last_crash: None,
+        }
+    }
+
+    /// The report as a JSON object, for webhooks
+    fn to_json(&self, instance: &Option<String>) -> serde_json::Value {
+        let game_ticks = self
+            .first_tick
+            .map_or(0, |first| self.last_tick.saturating_sub(first));
+        let mut top: Vec<(&String, &f64)> = self
+            .production
+            .iter()
+            .filter(|(_, rate)| **rate > 0.0)
+            .collect();
+        top.sort_by(|a, b| b.1.total_cmp(a.1));
+        top.truncate(TOP_ITEMS);
+        let science: f64 = SCIENCE_PACKS
+            .iter()
+            .filter_map(|pack| self.production.get(*pack))
+            .sum();
+
+        serde_json::json!({
+            "run_name": self.run_name,
+            "session_id": self.session_id,
+            "instance": instance,
+            "status": if self.crashes == 0 { "clean" } else { "crashed" },
+            "crashes": self.crashes,
+            "last_crash": self.last_crash,
+            "started_at": self.started_at.to_rfc3339(),
+            "finished_at": chrono::Utc::now().to_rfc3339(),
+            "wall_secs": self.started.elapsed().as_secs(),
+            "game_ticks": game_ticks,
+            "cycles": self.cycles,
+            "science_per_min": science,
+            "top_production": top
+                .iter()
+                .map(|(item, rate)| serde_json::json!({ "item": item, "per_min": rate }))
+                .collect::<Vec<_>>(),
+            "research_finished": self.research_finished,
+            "rockets_launched": self.rockets_launched,
+            "entities_built": self.entities_built,
+            "entities_mined": self.entities_mined,
+            "wandb_run_url": self.wandb_url,
+            "weave_thread": correlation::control_thread_id(&self.run_name),
+        })
+    }
+}
+
+/// Renders the report as the plain-text body of an email
+fn to_text(report: &serde_json::Value) -> String {
+    let field = |name: &str| match report.get(name) {
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(serde_json::Value::Null) | None => "-".to_string(),
+        Some(value) => value.to_string(),
+    };
+    let wall_secs = report["wall_secs"].as_u64().unwrap_or(0);
+    let game_secs = report["game_ticks"].as_u64().unwrap_or(0) / 60;
+    let mut lines = vec![
+        format!("Run {} finished: {}", field("run_name"), field("status")),
+        String::new(),
+        format!(
+            "Duration: {}h{:02}m wall clock, {}h{:02}m game time ({} cycles)",
+            wall_secs / 3600,
+            wall_secs / 60 % 60,
+            game_secs / 3600,
+            game_secs / 60 % 60,
+            field("cycles")
+        ),
+        format!("Science: {:.1} packs/min", report["science_per_min"].as_f64().unwrap_or(0.0)),
+        format!(
+            "Research finished: {}, rockets launched: {}",
+            report["research_finished"].as_array().map_or(0, |techs| techs.len()),
+            field("rockets_launched")
+        ),
+        format!(
+            "Entities built: {}, mined: {}",
+            field("entities_built"),
+            field("entities_mined")
+        ),
+    ];
+    if let Some(items) = report["top_production"].as_array() {
+        lines.push("Top production (items/min):".to_string());
+        for item in items {
+            lines.push(format!(
+                "  {}: {:.1}",
+                item["item"].as_str().unwrap_or("?"),
+                item["per_min"].as_f64().unwrap_or(0.0)
+            ));
+        }
+    }
+    if report["crashes"].as_u64().unwrap_or(0) > 0 {
+        lines.push(format!(
+            "Client crashes: {} (last: {})",
+            field("crashes"),
+            field("last_crash")
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!("WandB run: {}", field("wandb_run_url")));
+    lines.push(format!("Weave thread: {}", field("weave_thread")));
+    lines.join("\n")
+}
+
+/// Sink sending a short completion report of every run when it finishes (a new
+/// session starts or the client shuts down), by email over SMTP and/or to a webhook,
+/// for long headless experiments nobody watches.
+#[derive(Clone)]
+pub struct CompletionReporter {
+    settings: Arc<CompletionReportSettings>,
+    webhook_url: Option<String>,
+    http: reqwest::Client,
+    instance: Option<String>,
+    current: Arc<Mutex<Option<RunTally>>>,
+}
+
+impl CompletionReporter {
+    /// Enabled when a webhook or an SMTP server with recipients is configured
+    pub fn from_settings(settings: &CompletionReportSettings) -> Option<Self> {
+        let webhook_url = match settings.webhook_url_env {
+            Some(ref name) => std::env::var(name).ok(),
+            None => settings.webhook_url.clone(),
+        };
+        let email = settings.smtp_server.is_some() && !settings.to.is_empty();
+        if webhook_url.is_none() && !email {
+            return None;
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .ok()?;
+        println!("📬 Sending a completion report of every run");
+        Some(Self {
+            settings: Arc::new(settings.clone()),
+            webhook_url,
+            http,
+            instance: None,
+            current: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// A reporter of its own runs, reported with the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            settings: self.settings.clone(),
+            webhook_url: self.webhook_url.clone(),
+            http: self.http.clone(),
+            instance,
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Takes the current run's report, if a run is current
+    fn take_report(&self) -> Option<serde_json::Value> {
+        let tally = self.current.lock().unwrap().take()?;
+        Some(tally.to_json(&self.instance))
+    }
+
+    async fn send(&self, report: serde_json::Value) {
+        let run_name = report["run_name"].as_str().unwrap_or_default().to_string();
+        if let Some(ref url) = self.webhook_url {
+            self.post(url, &report).await;
+        }
+        if self.settings.smtp_server.is_some() && !self.settings.to.is_empty() {
+            let subject = format!(
+                "Factorio run {} finished ({})",
+                run_name,
+                report["status"].as_str().unwrap_or("unknown")
+            );
+            match self.email(&subject, to_text(&report)).await {
+                Ok(()) => println!("📬 Emailed the completion report of {}", run_name),
+                Err(e) => eprintln!("⚠️  Failed to email the completion report: {}", e),
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, report: &serde_json::Value) {
+        let mut delay = Duration::from_secs(1);
+        for attempt in 1..=WEBHOOK_ATTEMPTS {
+            let request = self
+                .http
+                .post(url)
+                .header("Content-Type", "application/json")
+                .body(report.to_string());
+            match request.send().await {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) if response.status().is_client_error() => {
+                    eprintln!("⚠️  Completion report rejected: {}", response.status());
+                    return;
+                }
+                Ok(response) => eprintln!(
+                    "⚠️  Completion report failed (attempt {}): {}",
+                    attempt,
+                    response.status()
+                ),
+                Err(e) => eprintln!("⚠️  Completion report failed (attempt {}): {}", attempt, e),
+            }
+            if attempt < WEBHOOK_ATTEMPTS {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+
+    /// Sends the report over SMTP with STARTTLS; credentials are only read from
+    /// SMTP_USERNAME and SMTP_PASSWORD
+    async fn email(&self, subject: &str, body: String) -> Result<(), String> {
+        let server = self.settings.smtp_server.as_deref().unwrap_or_default();
+        let from = self
+            .settings
+            .from
+            .parse()
+            .map_err(|e| format!("Invalid sender {}: {}", self.settings.from, e))?;
+        let mut message = Message::builder().from(from).subject(subject);
+        for to in &self.settings.to {
+            let mailbox = to
+                .parse()
+                .map_err(|e| format!("Invalid recipient {}: {}", to, e))?;
+            message = message.to(mailbox);
+        }
+        let message = message
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| format!("Failed to build the email: {}", e))?;
+
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(server)
+            .map_err(|e| format!("Invalid SMTP server {}: {}", server, e))?
+            .port(self.settings.smtp_port);
+        if let Ok(username) = std::env::var("SMTP_USERNAME") {
+            let password = std::env::var("SMTP_PASSWORD").unwrap_or_default();
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+        transport
+            .build()
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Reports the finished run in the background, so the next run isn't held up
+    fn report_in_background(&self) {
+        if let Some(report) = self.take_report() {
+            let reporter = self.clone();
+            tokio::spawn(async move { reporter.send(report).await });
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for CompletionReporter {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        self.report_in_background();
+        let mut tally = RunTally::new(session.run_name.clone(), session.session_id.clone());
+        tally.first_tick = Some(session.tick);
+        tally.last_tick = session.tick;
+        tally.wandb_url = session.wandb_run.as_ref().map(|(_, url)| url.clone());
+        *self.current.lock().unwrap() = Some(tally);
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        // A run resumed by a warm start has no session init
+        let known = self
+            .current
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|tally| tally.run_name == stats.run_name);
+        if !known {
+            self.report_in_background();
+            *self.current.lock().unwrap() = Some(RunTally::new(
+                stats.run_name.clone(),
+                stats.session_id.clone(),
+            ));
+        }
+
+        let mut current = self.current.lock().unwrap();
+        if let Some(tally) = current.as_mut() {
+            tally.first_tick.get_or_insert(stats.tick);
+            tally.last_tick = stats.tick;
+            tally.cycles += 1;
+            apply_stats(&mut tally.production, stats.production.clone(), stats.delta);
+        }
+    }
+
+    async fn handle_game_event(&self, _tick: u64, event: &GameEvent) {
+        let mut current = self.current.lock().unwrap();
+        let Some(tally) = current.as_mut() else {
+            return;
+        };
+        match event {
+            GameEvent::ResearchFinished { tech_name, .. } => {
+                tally.research_finished.push(tech_name.clone())
+            }
+            GameEvent::RocketLaunched { .. } => tally.rockets_launched += 1,
+            GameEvent::EntityBuilt { .. } => tally.entities_built += 1,
+            GameEvent::EntityMined { .. } => tally.entities_mined += 1,
+            _ => {}
+        }
+    }
+
+    async fn handle_crash(&self, message: &str) {
+        if let Some(tally) = self.current.lock().unwrap().as_mut() {
+            tally.crashes += 1;
+            tally.last_crash = Some(message.to_string());
+        }
+    }
+
+    async fn shutdown(&self) {
+        // The process is about to exit, so wait for the delivery
+        if let Some(report) = self.take_report() {
+            self.send(report).await;
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "completion_report"
+    }
+}
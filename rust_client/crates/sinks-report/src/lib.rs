@@ -0,0 +1,3 @@
+//! The completion report sink: a short report of every finished run, by email or webhook.
+
+pub mod completion_report;
@@ -0,0 +1,116 @@
+use factorio_events_core::config::DownsampleSettings;
+use std::collections::HashMap;
+
+/// Which stats cycles of a run are logged
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownsamplePolicy {
+    /// Every cycle
+    Off,
+    /// Every `n`th cycle
+    Every(u64),
+    /// Every cycle in which a metric moved by more than `threshold` (relative to its
+    /// last logged value, at least 1.0), and every `max_gap`th cycle while flat
+    Adaptive { threshold: f64, max_gap: u64 },
+}
+
+impl DownsamplePolicy {
+    pub fn from_settings(settings: &DownsampleSettings) -> Self {
+        let every = settings.every.max(1);
+        match settings.mode.trim().to_ascii_lowercase().as_str() {
+            "off" | "" => DownsamplePolicy::Off,
+            "every" if every == 1 => DownsamplePolicy::Off,
+            "every" => DownsamplePolicy::Every(every),
+            "adaptive" => DownsamplePolicy::Adaptive {
+                threshold: settings.change_threshold.max(0.0),
+                max_gap: every,
+            },
+            other => {
+                eprintln!("⚠️  Unknown downsampling mode '{}', logging every cycle", other);
+                DownsamplePolicy::Off
+            }
+        }
+    }
+
+    /// The policy as recorded in run notes, None when off
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            DownsamplePolicy::Off => None,
+            DownsamplePolicy::Every(n) => Some(format!("every:{}", n)),
+            DownsamplePolicy::Adaptive { threshold, max_gap } => {
+                Some(format!("adaptive:threshold={},max_gap={}", threshold, max_gap))
+            }
+        }
+    }
+}
+
+/// Decides per stats cycle whether a run logs it. Item state is still updated on
+/// skipped cycles, so the next logged cycle carries the current values.
+pub struct Downsampler {
+    policy: DownsamplePolicy,
+    /// Cycles seen since the last logged one
+    since_logged: u64,
+    /// Metrics of the last logged cycle, for the adaptive policy
+    last_logged: HashMap<String, f64>,
+    /// Cycle of the last decision and whether it was logged
+    last_decision: Option<(u64, bool)>,
+}
+
+impl Downsampler {
+    pub fn new(policy: DownsamplePolicy) -> Self {
+        Self {
+            policy,
+            since_logged: 0,
+            last_logged: HashMap::new(),
+            last_decision: None,
+        }
+    }
+
+    pub fn policy(&self) -> &DownsamplePolicy {
+        &self.policy
+    }
+
+    /// Starts over for a new run, whose first cycle is always logged
+    pub fn reset(&mut self) {
+        self.since_logged = 0;
+        self.last_logged.clear();
+        self.last_decision = None;
+    }
+
+    /// Decides whether `cycle`, with the full metrics of the run, is logged
+    pub fn keep(&mut self, cycle: u64, metrics: &HashMap<String, f64>) -> bool {
+        let first = self.last_decision.is_none();
+        self.since_logged += 1;
+        let keep = first
+            || match self.policy {
+                DownsamplePolicy::Off => true,
+                DownsamplePolicy::Every(n) => self.since_logged >= n,
+                DownsamplePolicy::Adaptive { threshold, max_gap } => {
+                    self.since_logged >= max_gap || self.changed(metrics, threshold)
+                }
+            };
+
+        if keep {
+            self.since_logged = 0;
+            if matches!(self.policy, DownsamplePolicy::Adaptive { .. }) {
+                self.last_logged = metrics.clone();
+            }
+        }
+        self.last_decision = Some((cycle, keep));
+        keep
+    }
+
+    /// Whether `cycle` was logged; cycles not decided yet count as logged
+    pub fn kept(&self, cycle: u64) -> bool {
+        match self.last_decision {
+            Some((decided, keep)) if decided == cycle => keep,
+            _ => true,
+        }
+    }
+
+    fn changed(&self, metrics: &HashMap<String, f64>, threshold: f64) -> bool {
+        metrics.iter().any(|(key, value)| match self.last_logged.get(key) {
+            Some(last) => (value - last).abs() > threshold * last.abs().max(1.0),
+            None => true,
+        })
+    }
+}
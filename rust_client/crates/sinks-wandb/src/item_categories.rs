@@ -0,0 +1,161 @@
+use factorio_events_core::config::ItemCategorySettings;
+use factorio_events_core::science::SCIENCE_PACKS;
+use std::collections::HashMap;
+
+/// Plates smelted from ores, including Space Age's
+const PLATES: &[&str] = &[
+    "iron-plate",
+    "copper-plate",
+    "steel-plate",
+    "tungsten-plate",
+    "holmium-plate",
+];
+
+/// Ammunition, weapons, armor and defenses
+const MILITARY: &[&str] = &[
+    "firearm-magazine",
+    "piercing-rounds-magazine",
+    "uranium-rounds-magazine",
+    "shotgun-shell",
+    "piercing-shotgun-shell",
+    "cannon-shell",
+    "explosive-cannon-shell",
+    "uranium-cannon-shell",
+    "explosive-uranium-cannon-shell",
+    "artillery-shell",
+    "rocket",
+    "explosive-rocket",
+    "atomic-bomb",
+    "flamethrower-ammo",
+    "grenade",
+    "cluster-grenade",
+    "poison-capsule",
+    "slowdown-capsule",
+    "defender-capsule",
+    "distractor-capsule",
+    "destroyer-capsule",
+    "pistol",
+    "submachine-gun",
+    "shotgun",
+    "combat-shotgun",
+    "rocket-launcher",
+    "flamethrower",
+    "light-armor",
+    "heavy-armor",
+    "modular-armor",
+    "power-armor",
+    "power-armor-mk2",
+    "stone-wall",
+    "gate",
+    "land-mine",
+    "gun-turret",
+    "laser-turret",
+    "flamethrower-turret",
+    "artillery-turret",
+    "tank",
+];
+
+/// Maps items to the categories their production and consumption roll up into: the
+/// built-in `plates`, `science` and `military`, the custom categories of the settings,
+/// and each item's prototype subgroup, from a Factorio data dump or the mod's
+/// `prototypes` event (so modded items are covered too). An item can be in several
+/// categories.
+pub struct ItemCategories {
+    /// Item name -> its categories
+    categories: HashMap<String, Vec<String>>,
+}
+
+impl ItemCategories {
+    pub fn from_settings(settings: &ItemCategorySettings) -> Self {
+        let mut categories = Self {
+            categories: HashMap::new(),
+        };
+        categories.extend("plates", PLATES.iter().copied());
+        categories.extend("science", SCIENCE_PACKS.iter().copied());
+        categories.extend("military", MILITARY.iter().copied());
+        for (category, items) in &settings.custom {
+            categories.extend(category, items.iter().map(String::as_str));
+        }
+
+        if let Some(ref path) = settings.data_dump {
+            match load_subgroups(path) {
+                Ok(subgroups) => {
+                    println!(
+                        "📊 Loaded the subgroups of {} items from {}",
+                        subgroups.len(),
+                        path.display()
+                    );
+                    categories.add_subgroups(
+                        subgroups
+                            .iter()
+                            .map(|(item, subgroup)| (item.as_str(), subgroup.as_str())),
+                    );
+                }
+                Err(e) => eprintln!("⚠️  Item subgroups not loaded: {}", e),
+            }
+        }
+        categories
+    }
+
+    /// Rolls every (item, subgroup) pair up into the subgroup
+    pub fn add_subgroups<'a>(&mut self, subgroups: impl Iterator<Item = (&'a str, &'a str)>) {
+        for (item, subgroup) in subgroups {
+            self.extend(subgroup, std::iter::once(item));
+        }
+    }
+
+    fn extend<'a>(&mut self, category: &str, items: impl Iterator<Item = &'a str>) {
+        for item in items {
+            let categories = self.categories.entry(item.to_string()).or_default();
+            if !categories.iter().any(|existing| existing == category) {
+                categories.push(category.to_string());
+            }
+        }
+    }
+
+    /// Totals of every category with a produced or consumed item, as
+    /// `production_by_category/<category>` and `consumption_by_category/<category>`
+    pub fn rollup_metrics(
+        &self,
+        production: &HashMap<String, f64>,
+        consumption: &HashMap<String, f64>,
+    ) -> HashMap<String, f64> {
+        let mut metrics = HashMap::new();
+        for (prefix, values) in [
+            ("production_by_category", production),
+            ("consumption_by_category", consumption),
+        ] {
+            for (item, value) in values {
+                for category in self.categories.get(item).into_iter().flatten() {
+                    *metrics
+                        .entry(format!("{}/{}", prefix, category))
+                        .or_insert(0.0) += value;
+                }
+            }
+        }
+        metrics
+    }
+}
+
+/// Reads the subgroup of every item prototype of a `factorio --dump-data` output
+/// (`script-output/data-raw-dump.json`). Items are the prototypes with a stack size,
+/// whatever their type (`item`, `ammo`, `tool`, ...).
+fn load_subgroups(path: &std::path::Path) -> Result<Vec<(String, String)>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read data dump {}: {}", path.display(), e))?;
+    let dump: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse data dump {}: {}", path.display(), e))?;
+
+    let mut subgroups = Vec::new();
+    for prototypes in dump.as_object().into_iter().flat_map(|types| types.values()) {
+        for (name, prototype) in prototypes.as_object().into_iter().flatten() {
+            if prototype.get("stack_size").is_none() {
+                continue;
+            }
+            if let Some(subgroup) = prototype.get("subgroup").and_then(|s| s.as_str()) {
+                subgroups.push((name.clone(), subgroup.to_string()));
+            }
+        }
+    }
+    Ok(subgroups)
+}
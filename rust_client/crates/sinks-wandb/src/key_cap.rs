@@ -0,0 +1,78 @@
+use std::collections::{HashMap, HashSet};
+
+/// Key reporting how many distinct keys of the run were folded into `other` buckets
+pub const OVERFLOW_METRIC: &str = "client/overflow_keys";
+
+/// Caps the distinct metric keys of a run, so a mod adding thousands of item
+/// prototypes can't explode the run. Keys seen first are admitted up to the cap; later
+/// keys are summed into the `<family>/other` bucket of their first path segment
+/// (`production/other`, ...).
+pub struct KeyCap {
+    /// 0 disables the cap
+    max_keys: usize,
+    admitted: HashSet<String>,
+    overflowed: HashSet<String>,
+}
+
+impl KeyCap {
+    pub fn new(max_keys: usize) -> Self {
+        Self {
+            max_keys,
+            admitted: HashSet::new(),
+            overflowed: HashSet::new(),
+        }
+    }
+
+    /// Forgets the keys of the previous run
+    pub fn reset(&mut self) {
+        self.admitted.clear();
+        self.overflowed.clear();
+    }
+
+    /// Folds keys beyond the cap into their `other` bucket. Once a run overflows,
+    /// every batch also carries OVERFLOW_METRIC.
+    pub fn apply(&mut self, metrics: HashMap<String, f64>) -> HashMap<String, f64> {
+        if self.max_keys == 0 {
+            return metrics;
+        }
+
+        let mut capped = HashMap::with_capacity(metrics.len());
+        for (key, value) in metrics {
+            if self.admit(&key) {
+                capped.insert(key, value);
+            } else {
+                *capped.entry(other_bucket(&key)).or_insert(0.0) += value;
+            }
+        }
+        if !self.overflowed.is_empty() {
+            capped.insert(OVERFLOW_METRIC.to_string(), self.overflowed.len() as f64);
+        }
+        capped
+    }
+
+    fn admit(&mut self, key: &str) -> bool {
+        if self.admitted.contains(key) {
+            return true;
+        }
+        if self.admitted.len() < self.max_keys {
+            self.admitted.insert(key.to_string());
+            return true;
+        }
+        if self.overflowed.is_empty() {
+            eprintln!(
+                "⚠️  Run reached {} distinct metric keys, folding new keys into `other` buckets",
+                self.max_keys
+            );
+        }
+        self.overflowed.insert(key.to_string());
+        false
+    }
+}
+
+/// `production/iron-plate` -> `production/other`
+fn other_bucket(key: &str) -> String {
+    match key.split_once('/') {
+        Some((family, _)) => format!("{}/other", family),
+        None => "other".to_string(),
+    }
+}
@@ -0,0 +1,9 @@
+//! The WandB sink: a run per Factorio session, with its metrics, tables and the tournament
+//! leaderboard.
+
+pub mod downsample;
+pub mod item_categories;
+pub mod key_cap;
+pub mod tournament;
+pub mod wandb_manager;
+pub mod wandb_settings;
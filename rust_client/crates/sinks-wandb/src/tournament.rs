@@ -0,0 +1,235 @@
+use crate::wandb_settings::{RunSettings, SettingsBuilder};
+use factorio_events_core::config::TournamentSettings;
+use factorio_events_core::event_sink::Leaderboard;
+use std::collections::HashMap;
+use wandb;
+
+/// A KPI contributing to the tournament score
+#[derive(Debug, Clone)]
+pub struct Kpi {
+    pub item: String,
+    pub weight: f64,
+}
+
+/// Tournament/leaderboard mode: tracks named participants (one per server or force),
+/// scores them on standardized KPIs and logs the leaderboard to a shared WandB run.
+pub struct Tournament {
+    name: String,
+    kpis: Vec<Kpi>,
+    /// Latest production rate per KPI item for each participant
+    participants: HashMap<String, HashMap<String, f64>>,
+    run: Option<wandb::run::Run>,
+    step: i64,
+}
+
+/// A participant's standing on the leaderboard
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub participant: String,
+    pub score: f64,
+    pub rank: usize,
+}
+
+impl Tournament {
+    /// Creates the configured tournament. Enabled by `name`; `kpis` are
+    /// `item[:weight]` entries, e.g. `electronic-circuit:2`.
+    pub fn from_settings(settings: &TournamentSettings) -> Option<Self> {
+        let name = settings.name.clone()?;
+        let kpis = parse_kpis(&settings.kpis);
+
+        if kpis.is_empty() {
+            eprintln!("⚠️  Tournament name set but no KPIs, tournament mode disabled");
+            return None;
+        }
+
+        println!(
+            "🏆 Tournament mode '{}' enabled with KPIs: {}",
+            name,
+            kpis.iter()
+                .map(|kpi| format!("{}x{}", kpi.item, kpi.weight))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        Some(Self {
+            name,
+            kpis,
+            participants: HashMap::new(),
+            run: None,
+            step: 0,
+        })
+    }
+
+    /// Records the latest production rates for a participant.
+    /// Delta updates leave KPIs they don't mention at their previous value.
+    pub fn record(&mut self, participant: &str, production: &HashMap<String, f64>, delta: bool) {
+        let values = self
+            .participants
+            .entry(participant.to_string())
+            .or_default();
+
+        for kpi in &self.kpis {
+            match production.get(&kpi.item) {
+                Some(value) => {
+                    values.insert(kpi.item.clone(), *value);
+                }
+                None if !delta => {
+                    values.insert(kpi.item.clone(), 0.0);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Computes standings: each KPI is standardized (z-score across participants),
+    /// weighted and summed, so KPIs with very different magnitudes contribute fairly
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut scores: HashMap<&str, f64> = self
+            .participants
+            .keys()
+            .map(|name| (name.as_str(), 0.0))
+            .collect();
+
+        for kpi in &self.kpis {
+            let values: Vec<f64> = self
+                .participants
+                .values()
+                .map(|values| values.get(&kpi.item).copied().unwrap_or(0.0))
+                .collect();
+            let count = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / count;
+            let std_dev =
+                (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count).sqrt();
+
+            for (name, values) in &self.participants {
+                let value = values.get(&kpi.item).copied().unwrap_or(0.0);
+                let z = if std_dev > 0.0 {
+                    (value - mean) / std_dev
+                } else {
+                    0.0
+                };
+                *scores.get_mut(name.as_str()).unwrap() += kpi.weight * z;
+            }
+        }
+
+        let mut standings: Vec<Standing> = scores
+            .into_iter()
+            .map(|(participant, score)| Standing {
+                participant: participant.to_string(),
+                score,
+                rank: 0,
+            })
+            .collect();
+        standings.sort_by(|a, b| b.score.total_cmp(&a.score));
+        for (i, standing) in standings.iter_mut().enumerate() {
+            standing.rank = i + 1;
+        }
+
+        standings
+    }
+
+    /// Logs the current leaderboard to the shared tournament run
+    pub fn log_leaderboard(&mut self) {
+        if self.participants.is_empty() {
+            return;
+        }
+
+        if self.run.is_none() {
+            let run = SettingsBuilder::new("wandb", "factorio-tournaments")
+                .run_name(format!("tournament_{}", self.name))
+                .build()
+                .and_then(RunSettings::init);
+            match run {
+                Ok(run) => {
+                    println!("🏆 Tournament run initialized: {}", self.name);
+                    self.run = Some(run);
+                }
+                Err(e) => {
+                    eprintln!("❌ Failed to initialize tournament run: {}", e);
+                    return;
+                }
+            }
+        }
+
+        let standings = self.standings();
+        let mut metrics = HashMap::new();
+
+        println!("🏆 Leaderboard '{}':", self.name);
+        for standing in &standings {
+            println!(
+                "   #{} {} (score {:.3})",
+                standing.rank, standing.participant, standing.score
+            );
+            metrics.insert(
+                format!("leaderboard/{}/score", standing.participant),
+                wandb::run::Value::Float(standing.score),
+            );
+            metrics.insert(
+                format!("leaderboard/{}/rank", standing.participant),
+                wandb::run::Value::Float(standing.rank as f64),
+            );
+            for kpi in &self.kpis {
+                let value = self.participants[&standing.participant]
+                    .get(&kpi.item)
+                    .copied()
+                    .unwrap_or(0.0);
+                metrics.insert(
+                    format!("kpi/{}/{}", kpi.item, standing.participant),
+                    wandb::run::Value::Float(value),
+                );
+            }
+        }
+
+        if let Some(ref run) = self.run {
+            run.log(metrics, Some(self.step));
+            self.step += 1;
+        }
+    }
+
+    /// Finishes the shared tournament run
+    pub fn finish(&mut self) {
+        if let Some(mut run) = self.run.take() {
+            println!("🏆 Finishing tournament run: {}", self.name);
+            run.finish();
+        }
+    }
+}
+
+impl Leaderboard for Tournament {
+    fn record(&mut self, participant: &str, production: &HashMap<String, f64>, delta: bool) {
+        Tournament::record(self, participant, production, delta);
+    }
+
+    fn log_leaderboard(&mut self) {
+        Tournament::log_leaderboard(self);
+    }
+
+    fn finish(&mut self) {
+        Tournament::finish(self);
+    }
+}
+
+/// Parses `item[:weight]` KPI entries
+fn parse_kpis(entries: &[String]) -> Vec<Kpi> {
+    entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| match entry.split_once(':') {
+            Some((item, weight)) => match weight.parse() {
+                Ok(weight) => Some(Kpi {
+                    item: item.to_string(),
+                    weight,
+                }),
+                Err(_) => {
+                    eprintln!("⚠️  Ignoring KPI with invalid weight: {}", entry);
+                    None
+                }
+            },
+            None => Some(Kpi {
+                item: entry.to_string(),
+                weight: 1.0,
+            }),
+        })
+        .collect()
+}
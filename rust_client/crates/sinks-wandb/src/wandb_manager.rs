@@ -0,0 +1,669 @@
+use crate::downsample::{DownsamplePolicy, Downsampler};
+use crate::item_categories::ItemCategories;
+use crate::key_cap::KeyCap;
+use crate::wandb_settings::{RunMode, RunSettings, SettingsBuilder};
+use async_trait::async_trait;
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::{AppConfig, WandbSettings};
+use factorio_events_core::correlation::{self, ClosedStep, StepCorrelation};
+use factorio_events_core::event_sink::{
+    apply_stats, EventSink, GameEvent, RunTracker, SessionStart, StatsCycle,
+};
+use factorio_events_core::fault_injection::{Fault, FaultInjector};
+use factorio_events_core::project_routing::ProjectRouter;
+use factorio_events_core::prototypes::RawCosts;
+use factorio_events_core::run_index::RunRecord;
+use factorio_events_core::run_metadata::RunMetadata;
+use factorio_events_core::science;
+use factorio_events_core::units;
+use factorio_events_core::upload_budget::UploadBudget;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use wandb;
+
+/// Batches of custom metrics kept while no run is active
+const MAX_PENDING_CUSTOM: usize = 1000;
+
+/// Logs the Weave call counts of a closed step at that step
+fn log_closed_step(run: &wandb::run::Run, closed: ClosedStep) {
+    let mut metrics = HashMap::new();
+    metrics.insert("weave/calls".to_string(), wandb::run::Value::Float(closed.calls as f64));
+    metrics.insert(
+        "weave/late_calls".to_string(),
+        wandb::run::Value::Float(closed.late_calls as f64),
+    );
+    run.log(metrics, Some(closed.step as i64));
+}
+
+/// Generates an 8-character run id in the style WandB uses
+fn generate_run_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(8)
+        .map(|c| (c as char).to_ascii_lowercase())
+        .collect()
+}
+
+/// A singleton service that manages WandB sessions for Factorio events.
+/// Handles session initialization, metrics logging, and session cleanup.
+/// Tracks the last value of every seen item to report zeros for inactive
+/// production/consumption, and to rebuild full stats from delta-encoded updates.
+pub struct WandbManager {
+    current_run: Arc<Mutex<Option<wandb::run::Run>>>,
+    current_session_id: Arc<Mutex<Option<String>>>,
+    production_state: Arc<Mutex<HashMap<String, f64>>>,
+    consumption_state: Arc<Mutex<HashMap<String, f64>>>,
+    /// Optional shared upload budget; stats always go out but are charged against it
+    upload_budget: Option<Arc<UploadBudget>>,
+    /// Launch metadata recorded on every run
+    metadata: RunMetadata,
+    /// (run id, url) of the current run
+    current_run_info: Arc<Mutex<Option<(String, String)>>>,
+    /// Link to the run the next run continues, set when rotating
+    previous_run: Arc<Mutex<Option<String>>>,
+    /// Weave thread of the next run, recorded for deep-linking
+    weave_thread: Arc<Mutex<Option<String>>>,
+    /// Lineage notes of the next run when it continues an earlier save
+    parent_run: Arc<Mutex<Vec<String>>>,
+    /// Step alignment shared with the Weave manager
+    correlation: Arc<StepCorrelation>,
+    settings: WandbSettings,
+    /// Mode of new runs from `settings.mode`, when valid
+    mode: Option<RunMode>,
+    /// Chaos-testing faults applied to logged metrics
+    faults: Option<FaultInjector>,
+    /// Custom metrics logged while no run was active, with their steps
+    pending_custom: Arc<Mutex<VecDeque<(HashMap<String, f64>, Option<u64>)>>>,
+    /// Distinct metric keys of the current run, capped at `settings.max_metric_keys`
+    key_cap: Arc<Mutex<KeyCap>>,
+    /// Stats cycles of the current run that are logged
+    downsampler: Arc<Mutex<Downsampler>>,
+    /// Categories item metrics roll up into
+    item_categories: Arc<Mutex<ItemCategories>>,
+    /// Raw resource costs of the items, once the mod sent its prototypes
+    raw_costs: Arc<Mutex<Option<RawCosts>>>,
+    /// Why WandB is off, when no credentials are available
+    disabled: Option<String>,
+    /// Routes sessions to other projects than `settings.project`
+    router: Arc<ProjectRouter>,
+    /// (entity, project) the next and current runs were routed to, when not the defaults
+    routed_project: Arc<Mutex<Option<(String, String)>>>,
+}
+
+/// Why runs can't be logged: WandB was turned off with `wandb.mode` (or WANDB_MODE)
+/// set to disabled, or, unless it runs offline, there is no API key in WANDB_API_KEY or
+/// in a `wandb login` entry of ~/.netrc
+fn disabled_reason(mode: Option<&str>) -> Option<String> {
+    match mode.map(RunMode::parse) {
+        Some(Ok(RunMode::Disabled)) => return Some("WandB mode is disabled".to_string()),
+        Some(Ok(RunMode::Offline)) => return None,
+        _ => {}
+    }
+    if std::env::var("WANDB_API_KEY").is_ok_and(|key| !key.is_empty()) {
+        return None;
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let netrc = std::fs::read_to_string(std::path::Path::new(&home).join(".netrc")).unwrap_or_default();
+    if netrc.contains("api.wandb.ai") {
+        return None;
+    }
+    Some("WANDB_API_KEY environment variable not set".to_string())
+}
+
+impl WandbManager {
+    /// Creates a new WandB manager instance
+    pub fn new(config: &AppConfig) -> Self {
+        WandbManager {
+            current_run: Arc::new(Mutex::new(None)),
+            current_session_id: Arc::new(Mutex::new(None)),
+            production_state: Arc::new(Mutex::new(HashMap::new())),
+            consumption_state: Arc::new(Mutex::new(HashMap::new())),
+            upload_budget: None,
+            metadata: RunMetadata::default(),
+            current_run_info: Arc::new(Mutex::new(None)),
+            previous_run: Arc::new(Mutex::new(None)),
+            weave_thread: Arc::new(Mutex::new(None)),
+            parent_run: Arc::new(Mutex::new(Vec::new())),
+            correlation: Arc::new(StepCorrelation::new()),
+            settings: config.wandb.clone(),
+            mode: config.wandb.mode.as_deref().and_then(|mode| {
+                RunMode::parse(mode)
+                    .map_err(|e| eprintln!("⚠️  {}, using the default mode", e))
+                    .ok()
+            }),
+            faults: FaultInjector::for_sink("wandb", &config.faults),
+            pending_custom: Arc::new(Mutex::new(VecDeque::new())),
+            key_cap: Arc::new(Mutex::new(KeyCap::new(config.wandb.max_metric_keys))),
+            downsampler: Arc::new(Mutex::new(Downsampler::new(DownsamplePolicy::from_settings(
+                &config.wandb.downsample,
+            )))),
+            item_categories: Arc::new(Mutex::new(ItemCategories::from_settings(
+                &config.item_categories,
+            ))),
+            raw_costs: Arc::new(Mutex::new(None)),
+            disabled: disabled_reason(config.wandb.mode.as_deref()),
+            router: Arc::new(ProjectRouter::default()),
+            routed_project: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Why WandB is off, or None when it has credentials. A disabled manager is left
+    /// out of the mediator's sinks.
+    pub fn disabled_reason(&self) -> Option<&str> {
+        self.disabled.as_deref()
+    }
+
+    /// Charges logged metrics against a shared upload budget
+    pub fn with_upload_budget(mut self, budget: Option<Arc<UploadBudget>>) -> Self {
+        self.upload_budget = budget;
+        self
+    }
+
+    /// Routes the runs of matching sessions to other projects
+    pub fn with_project_router(mut self, router: Arc<ProjectRouter>) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Picks the project of the session's next run from the project routes
+    pub fn route_session(&self, session_id: &str, level_name: &str) {
+        *self.routed_project.lock().unwrap() = self.router.wandb_project(
+            session_id,
+            level_name,
+            &self.settings.entity,
+            &self.settings.project,
+        );
+    }
+
+    /// Records launch metadata on every run this manager starts
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Shares step alignment with the Weave manager, logging its call counts per step
+    pub fn with_correlation(mut self, correlation: Arc<StepCorrelation>) -> Self {
+        self.correlation = correlation;
+        self
+    }
+
+    /// Handles a session_init event. Closes any existing session and starts a new one.
+    pub fn handle_session_init(&self, session_id: String, tick: u64, level_name: String) {
+        println!("📍 Session init received: {}", session_id);
+
+        // Close existing session if any
+        self.finish_current_session();
+
+        // Clear seen items for new session
+        self.production_state.lock().unwrap().clear();
+        self.consumption_state.lock().unwrap().clear();
+        self.key_cap.lock().unwrap().reset();
+        self.downsampler.lock().unwrap().reset();
+
+        // Start new session
+        self.start_new_session(session_id, tick, level_name, None);
+    }
+
+    /// Reopens a run left unfinished by a client crash, so its history continues.
+    /// Without the run's id (its init had failed) a new run of the same name starts.
+    /// `project` is the (entity, project) the run was routed to, if not the defaults.
+    pub fn resume_run(
+        &self,
+        run_name: String,
+        run_id: Option<String>,
+        project: Option<(String, String)>,
+    ) {
+        println!("📍 Resuming run: {}", run_name);
+        self.finish_current_session();
+        *self.routed_project.lock().unwrap() = project;
+        self.production_state.lock().unwrap().clear();
+        self.consumption_state.lock().unwrap().clear();
+        self.key_cap.lock().unwrap().reset();
+        self.downsampler.lock().unwrap().reset();
+        self.start_new_session(run_name, 0, String::new(), run_id);
+    }
+
+    /// Handles a stats event and logs metrics.
+    /// Note: run_name should be provided by EventMediator (with random suffix).
+    /// EventMediator ensures the session is initialized before calling this.
+    /// With `delta` set, the maps only contain items whose values changed.
+    pub fn handle_stats_event(
+        &self,
+        run_name: String,
+        cycle: u64,
+        tick: u64,
+        products_production: HashMap<String, f64>,
+        materials_consumption: HashMap<String, f64>,
+        delta: bool,
+    ) {
+        // Verify we have an active session
+        let current_session = self.current_session_id.lock().unwrap().clone();
+
+        if current_session.as_ref() != Some(&run_name) {
+            eprintln!(
+                "⚠️  Session mismatch: expected '{}', got '{:?}'. This should not happen!",
+                run_name, current_session
+            );
+            return;
+        }
+
+        // Log metrics
+        self.log_metrics(cycle, tick, products_production, materials_consumption, delta);
+    }
+
+    /// Starts a new WandB session, or resumes the run `resume_id`
+    /// Note: run_name should be the enhanced session ID (with random suffix) from EventMediator
+    fn start_new_session(
+        &self,
+        run_name: String,
+        _tick: u64,
+        _level_name: String,
+        resume_id: Option<String>,
+    ) {
+        println!("🚀 Starting new WandB run: {}", run_name);
+
+        // Configure WandB settings
+        let (entity, project) = self
+            .routed_project
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| (self.settings.entity.clone(), self.settings.project.clone()));
+        let resume = resume_id.is_some();
+        let run_id = resume_id.unwrap_or_else(generate_run_id);
+        // Runs are configured through settings only, so metadata, the continuity
+        // links of rotated and continued runs, the Weave thread and the metric units
+        // go in the notes, along with the downsampling policy
+        let mut notes = self.metadata.to_lines();
+        if let Some(previous) = self.previous_run.lock().unwrap().take() {
+            notes.push(format!("rotated_from={}", previous));
+        }
+        notes.append(&mut self.parent_run.lock().unwrap());
+        if let Some(thread_id) = self.weave_thread.lock().unwrap().take() {
+            notes.push(format!("weave_thread={}", thread_id));
+        }
+        notes.push(format!("metric_units={}", units::to_json()));
+        if let Some(policy) = self.downsampler.lock().unwrap().policy().describe() {
+            notes.push(format!("metric_downsampling={}", policy));
+        }
+        let url = format!("https://wandb.ai/{}/{}/runs/{}", entity, project, run_id);
+
+        // Initialize run
+        let run = SettingsBuilder::new(entity, project)
+            .run_name(run_name.clone())
+            .run_id(run_id.clone())
+            .resume(resume)
+            .mode(self.mode)
+            .tags(self.settings.tags.clone())
+            .notes(notes)
+            .build()
+            .and_then(RunSettings::init);
+        match run {
+            Ok(run) => {
+                // Store the run and use run_name as the session_id
+                let mut run_guard = self.current_run.lock().unwrap();
+                self.flush_pending_custom(&run);
+                *run_guard = Some(run);
+                drop(run_guard);
+                *self.current_session_id.lock().unwrap() = Some(run_name.clone());
+
+                *self.current_run_info.lock().unwrap() = Some((run_id.clone(), url.clone()));
+
+                println!("✅ WandB run initialized successfully: {}", url);
+                client_events::emit(ClientEvent::RunStarted {
+                    run_name,
+                    run_id,
+                    url,
+                });
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to initialize WandB run: {}", e);
+                client_events::emit(ClientEvent::Error {
+                    component: "wandb".to_string(),
+                    message: format!("Failed to initialize run: {}", e),
+                });
+            }
+        }
+    }
+
+    /// Logs metrics to the current WandB session
+    fn log_metrics(
+        &self,
+        cycle: u64,
+        tick: u64,
+        products_production: HashMap<String, f64>,
+        materials_consumption: HashMap<String, f64>,
+        delta: bool,
+    ) {
+        let run_guard = self.current_run.lock().unwrap();
+
+        if let Some(ref run) = *run_guard {
+            // This cycle starts a new step; the previous one gets its Weave call count
+            if let Some(closed) = self.correlation.begin_step(cycle, tick) {
+                log_closed_step(run, closed);
+            }
+
+            // Update item state and build complete metrics with zeros for inactive items
+            let mut prod_state = self.production_state.lock().unwrap();
+            let mut cons_state = self.consumption_state.lock().unwrap();
+
+            let active_prod = products_production.len();
+            let active_cons = materials_consumption.len();
+            apply_stats(&mut prod_state, products_production, delta);
+            apply_stats(&mut cons_state, materials_consumption, delta);
+
+            let mut metrics = HashMap::new();
+
+            // Add production metrics (with zeros for inactive items)
+            for (item_name, value) in prod_state.iter() {
+                metrics.insert(format!("production/{}", item_name), *value);
+            }
+
+            // Add consumption metrics (with zeros for inactive items)
+            for (item_name, value) in cons_state.iter() {
+                metrics.insert(format!("consumption/{}", item_name), *value);
+            }
+
+            // Science balance is computed on the full state so delta updates work too
+            metrics.extend(science::balance_metrics(&prod_state, &cons_state));
+            metrics.extend(
+                self.item_categories
+                    .lock()
+                    .unwrap()
+                    .rollup_metrics(&prod_state, &cons_state),
+            );
+            if let Some(ref raw_costs) = *self.raw_costs.lock().unwrap() {
+                metrics.extend(raw_costs.science_metrics(&prod_state));
+            }
+
+            // Skipped cycles still updated the item state above
+            if !self.downsampler.lock().unwrap().keep(cycle, &metrics) {
+                return;
+            }
+
+            let metrics: HashMap<String, wandb::run::Value> = self
+                .key_cap
+                .lock()
+                .unwrap()
+                .apply(metrics)
+                .into_iter()
+                .map(|(key, value)| (key, wandb::run::Value::Float(value)))
+                .collect();
+
+            let total_metrics = prod_state.len() + cons_state.len();
+            // Log metrics with step
+            if !metrics.is_empty() {
+                if let Some(ref budget) = self.upload_budget {
+                    // Rough per-metric size of the key and value on the wire
+                    let bytes: usize = metrics.keys().map(|key| key.len() + 16).sum();
+                    budget.consume(bytes);
+                }
+                if !self.fault_allows_submit() {
+                    return;
+                }
+                run.log(metrics, Some(cycle as i64));
+                println!(
+                    "📊 Logged {} total metrics ({} active: {}p/{}c) at step {}",
+                    total_metrics, active_prod + active_cons, active_prod, active_cons, cycle
+                );
+            }
+        } else {
+            eprintln!("⚠️  Attempted to log metrics but no active run exists");
+        }
+    }
+
+    /// Applies the next injected fault to a submission, returning whether to send it.
+    /// The SDK call is synchronous, so delays block the caller like a slow sink would.
+    fn fault_allows_submit(&self) -> bool {
+        match self.faults.as_ref().map(FaultInjector::roll) {
+            Some(Fault::Fail) => {
+                eprintln!("⚠️  Dropped WandB metrics (injected failure)");
+                false
+            }
+            Some(Fault::Delay(delay)) => {
+                std::thread::sleep(delay);
+                true
+            }
+            Some(Fault::None) | None => true,
+        }
+    }
+
+    /// Records the current run as the predecessor of the next one, for rotation
+    pub fn link_next_run(&self) {
+        let run_name = self.current_session_id.lock().unwrap().clone();
+        let run_info = self.current_run_info.lock().unwrap().clone();
+        if let (Some(run_name), Some((_, url))) = (run_name, run_info) {
+            *self.previous_run.lock().unwrap() = Some(format!("{} {}", run_name, url));
+        }
+    }
+
+    /// Records the run whose save the next run continues, as its parent
+    pub fn link_parent_run(&self, parent: &RunRecord) {
+        let mut notes = vec![format!("parent_run={}", parent.run_name)];
+        if let Some(ref run_id) = parent.wandb_run_id {
+            notes.push(format!("parent_run_id={}", run_id));
+        }
+        if let Some(ref url) = parent.wandb_url {
+            notes.push(format!("parent_run_url={}", url));
+        }
+        *self.parent_run.lock().unwrap() = notes;
+    }
+
+    /// Records the Weave thread of the next run in its notes
+    pub fn link_weave_thread(&self, thread_id: String) {
+        *self.weave_thread.lock().unwrap() = Some(thread_id);
+    }
+
+    /// Returns the (run id, url) of the current run
+    pub fn current_run_info(&self) -> Option<(String, String)> {
+        self.current_run_info.lock().unwrap().clone()
+    }
+
+    /// Logs derived scalar metrics (exploration, pollution, ...) at a stats cycle step
+    pub fn log_scalars(&self, cycle: u64, scalars: HashMap<String, f64>) {
+        if scalars.is_empty() {
+            return;
+        }
+
+        // Scalars follow the downsampling decision of their cycle
+        if !self.downsampler.lock().unwrap().kept(cycle) {
+            return;
+        }
+
+        let run_guard = self.current_run.lock().unwrap();
+        if let Some(ref run) = *run_guard {
+            self.submit_scalars(run, scalars, Some(cycle));
+        }
+    }
+
+    /// Logs metrics from code embedding the library into the current run, at `step`
+    /// or the current step. Metrics logged while no run is active are buffered (up to
+    /// MAX_PENDING_CUSTOM batches) and logged as soon as the next run starts.
+    pub fn log_custom(&self, metrics: HashMap<String, f64>, step: Option<u64>) {
+        if metrics.is_empty() {
+            return;
+        }
+
+        let run_guard = self
+            .current_run
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match *run_guard {
+            Some(ref run) => self.submit_scalars(run, metrics, step),
+            None => {
+                let mut pending = self
+                    .pending_custom
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                pending.push_back((metrics, step));
+                if pending.len() > MAX_PENDING_CUSTOM {
+                    pending.pop_front();
+                    eprintln!("⚠️  Custom metrics buffer full, dropped the oldest batch");
+                }
+            }
+        }
+    }
+
+    /// Logs custom metrics buffered while no run was active
+    fn flush_pending_custom(&self, run: &wandb::run::Run) {
+        let pending: Vec<_> = self
+            .pending_custom
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .drain(..)
+            .collect();
+        if !pending.is_empty() {
+            println!("📊 Logging {} buffered custom metric batches", pending.len());
+        }
+        for (metrics, step) in pending {
+            self.submit_scalars(run, metrics, step);
+        }
+    }
+
+    /// Charges scalars against the budget and logs them, subject to fault injection
+    fn submit_scalars(&self, run: &wandb::run::Run, scalars: HashMap<String, f64>, step: Option<u64>) {
+        let metrics: HashMap<String, wandb::run::Value> = self
+            .key_cap
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .apply(scalars)
+            .into_iter()
+            .map(|(key, value)| (key, wandb::run::Value::Float(value)))
+            .collect();
+        if let Some(ref budget) = self.upload_budget {
+            let bytes: usize = metrics.keys().map(|key| key.len() + 16).sum();
+            budget.consume(bytes);
+        }
+        if self.fault_allows_submit() {
+            run.log(metrics, step.map(|step| step as i64));
+        }
+    }
+
+    /// Finishes the current WandB session if one exists
+    fn finish_current_session(&self) {
+        let mut run_guard = self.current_run.lock().unwrap();
+        let session_id = self.current_session_id.lock().unwrap().clone();
+
+        if let Some(mut run) = run_guard.take() {
+            println!("🏁 Finishing WandB run for session: {:?}", session_id);
+            if let Some(closed) = self.correlation.finish() {
+                log_closed_step(&run, closed);
+            }
+            run.finish();
+            *self.current_session_id.lock().unwrap() = None;
+            *self.current_run_info.lock().unwrap() = None;
+            println!("✅ WandB run finished");
+            if let Some(run_name) = session_id {
+                client_events::emit(ClientEvent::RunFinished { run_name });
+            }
+        }
+    }
+
+    /// Marks the current run as crashed. WandB keeps the last logged value of a key
+    /// as its summary, so the run ends up with `client/crashed = 1`.
+    /// Tolerates locks poisoned by the panic being reported.
+    pub fn mark_crashed(&self) {
+        let run_guard = self
+            .current_run
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some(ref run) = *run_guard {
+            let mut metrics = HashMap::new();
+            metrics.insert("client/crashed".to_string(), wandb::run::Value::Float(1.0));
+            run.log(metrics, None);
+            println!("📊 Marked WandB run as crashed");
+        }
+    }
+
+    /// Public method to explicitly finish the current session (e.g., on shutdown)
+    pub fn shutdown(&self) {
+        println!("🔚 Shutting down WandB manager...");
+        self.finish_current_session();
+    }
+}
+
+#[async_trait]
+impl EventSink for WandbManager {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        if let Some(ref parent) = session.parent {
+            self.link_parent_run(parent);
+        }
+        self.link_weave_thread(correlation::control_thread_id(&session.run_name));
+        self.route_session(&session.session_id, &session.level_name);
+        self.handle_session_init(session.run_name.clone(), session.tick, session.level_name.clone());
+        session.wandb_run = self.current_run_info();
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        self.handle_stats_event(
+            stats.run_name.clone(),
+            stats.cycle,
+            stats.tick,
+            stats.production.clone(),
+            stats.consumption.clone(),
+            stats.delta,
+        );
+        self.log_scalars(stats.cycle, stats.scalars.clone());
+    }
+
+    /// In-game events are traced in Weave; runs only carry the stats, and the prototypes
+    /// the derived metrics are computed with
+    async fn handle_game_event(&self, _tick: u64, event: &GameEvent) {
+        if let GameEvent::PrototypesLoaded { prototypes } = event {
+            self.item_categories
+                .lock()
+                .unwrap()
+                .add_subgroups(prototypes.subgroups());
+            *self.raw_costs.lock().unwrap() = Some(prototypes.raw_costs());
+        }
+    }
+
+    async fn shutdown(&self) {
+        WandbManager::shutdown(self);
+    }
+
+    fn name(&self) -> &'static str {
+        "wandb"
+    }
+}
+
+impl RunTracker for WandbManager {
+    fn disabled_reason(&self) -> Option<&str> {
+        WandbManager::disabled_reason(self)
+    }
+
+    fn current_run_info(&self) -> Option<(String, String)> {
+        WandbManager::current_run_info(self)
+    }
+
+    fn resume_run(
+        &self,
+        run_name: String,
+        run_id: Option<String>,
+        project: Option<(String, String)>,
+    ) {
+        WandbManager::resume_run(self, run_name, run_id, project);
+    }
+
+    fn link_next_run(&self) {
+        WandbManager::link_next_run(self);
+    }
+
+    fn log_custom(&self, metrics: HashMap<String, f64>, step: Option<u64>) {
+        WandbManager::log_custom(self, metrics, step);
+    }
+
+    fn mark_crashed(&self) {
+        WandbManager::mark_crashed(self);
+    }
+}
+
+impl Drop for WandbManager {
+    fn drop(&mut self) {
+        // Ensure session is closed when manager is dropped
+        self.finish_current_session();
+    }
+}
@@ -0,0 +1,198 @@
+/// Characters WandB rejects in entity and project names
+const FORBIDDEN_NAME_CHARS: &[char] = &['/', '\\', '#', '?', '%', ':'];
+
+/// Longest entity or project name WandB accepts
+const MAX_NAME_LEN: usize = 128;
+
+/// Longest run id WandB accepts
+const MAX_RUN_ID_LEN: usize = 64;
+
+/// Longest tag WandB accepts
+const MAX_TAG_LEN: usize = 64;
+
+/// Where a run's data goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    Online,
+    /// Kept on disk for a later `wandb sync`
+    Offline,
+    /// Nothing is logged
+    Disabled,
+}
+
+impl RunMode {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "online" => Ok(RunMode::Online),
+            "offline" => Ok(RunMode::Offline),
+            "disabled" => Ok(RunMode::Disabled),
+            other => Err(format!(
+                "Invalid WandB mode '{}': expected online, offline or disabled",
+                other
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunMode::Online => "online",
+            RunMode::Offline => "offline",
+            RunMode::Disabled => "disabled",
+        }
+    }
+}
+
+/// Validated settings of a run, ready for `wandb::init`
+pub struct RunSettings {
+    pub project: String,
+    pub settings: wandb::settings::Settings,
+}
+
+impl RunSettings {
+    /// Starts the run
+    pub fn init(self) -> Result<wandb::run::Run, String> {
+        wandb::init(Some(self.project), Some(self.settings)).map_err(|e| format!("{:?}", e))
+    }
+}
+
+/// Typed builder of the settings of a WandB run, keeping the wandb crate's proto fields
+/// out of the rest of the client. `build` checks the values against WandB's naming
+/// rules, so a bad config fails with a clear message instead of a rejected upload.
+#[derive(Debug, Clone)]
+pub struct SettingsBuilder {
+    entity: String,
+    project: String,
+    run_name: Option<String>,
+    run_id: Option<String>,
+    resume: bool,
+    mode: Option<RunMode>,
+    tags: Vec<String>,
+    notes: Vec<String>,
+}
+
+impl SettingsBuilder {
+    pub fn new(entity: impl Into<String>, project: impl Into<String>) -> Self {
+        Self {
+            entity: entity.into(),
+            project: project.into(),
+            run_name: None,
+            run_id: None,
+            resume: false,
+            mode: None,
+            tags: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn run_name(mut self, name: impl Into<String>) -> Self {
+        self.run_name = Some(name.into());
+        self
+    }
+
+    pub fn run_id(mut self, id: impl Into<String>) -> Self {
+        self.run_id = Some(id.into());
+        self
+    }
+
+    /// Continues the run of `run_id` if it exists
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Mode of the run; without one, the SDK's default (or WANDB_MODE) applies
+    pub fn mode(mut self, mode: Option<RunMode>) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn tags(mut self, tags: impl IntoIterator<Item = String>) -> Self {
+        self.tags.extend(tags);
+        self
+    }
+
+    /// Notes lines, joined into the run's notes
+    pub fn notes(mut self, notes: impl IntoIterator<Item = String>) -> Self {
+        self.notes.extend(notes);
+        self
+    }
+
+    pub fn build(self) -> Result<RunSettings, String> {
+        check_name("entity", &self.entity)?;
+        check_name("project", &self.project)?;
+        if let Some(ref name) = self.run_name {
+            if name.trim().is_empty() {
+                return Err("Invalid WandB run name: it is empty".to_string());
+            }
+        }
+        if let Some(ref id) = self.run_id {
+            if id.is_empty() || id.len() > MAX_RUN_ID_LEN {
+                return Err(format!(
+                    "Invalid WandB run id '{}': it must have 1 to {} characters",
+                    id, MAX_RUN_ID_LEN
+                ));
+            }
+            if let Some(c) = id
+                .chars()
+                .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+            {
+                return Err(format!(
+                    "Invalid WandB run id '{}': '{}' is not allowed",
+                    id, c
+                ));
+            }
+        } else if self.resume {
+            return Err("Can't resume a WandB run without its run id".to_string());
+        }
+        for tag in &self.tags {
+            if tag.trim().is_empty() || tag.len() > MAX_TAG_LEN {
+                return Err(format!(
+                    "Invalid WandB tag '{}': it must have 1 to {} characters",
+                    tag, MAX_TAG_LEN
+                ));
+            }
+        }
+
+        let mut settings = wandb::settings::Settings::default();
+        settings.proto.entity = Some(self.entity);
+        settings.proto.run_name = self.run_name;
+        settings.proto.run_id = self.run_id;
+        if self.resume {
+            settings.proto.resume = Some("allow".to_string());
+        }
+        if let Some(mode) = self.mode {
+            settings.proto.mode = Some(mode.name().to_string());
+        }
+        if !self.tags.is_empty() {
+            let mut tags = settings.proto.run_tags.take().unwrap_or_default();
+            tags.value = self.tags;
+            settings.proto.run_tags = Some(tags);
+        }
+        if !self.notes.is_empty() {
+            settings.proto.run_notes = Some(self.notes.join("\n"));
+        }
+        Ok(RunSettings {
+            project: self.project,
+            settings,
+        })
+    }
+}
+
+fn check_name(what: &str, name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(format!("Invalid WandB {}: it is empty", what));
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Invalid WandB {} '{}': it is longer than {} characters",
+            what, name, MAX_NAME_LEN
+        ));
+    }
+    if let Some(c) = name.chars().find(|c| FORBIDDEN_NAME_CHARS.contains(c)) {
+        return Err(format!(
+            "Invalid WandB {} '{}': '{}' is not allowed",
+            what, name, c
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use factorio_events_core::config::CallBatchSettings;
+use std::collections::HashMap;
+
+/// Suffix of the op of a merged call, after the op of its calls
+pub const BATCH_SUFFIX: &str = "_batch";
+
+/// One instant call held for its batch
+struct BatchedCall {
+    tick: u64,
+    inputs: HashMap<String, serde_json::Value>,
+    outputs: HashMap<String, serde_json::Value>,
+}
+
+/// Calls of one op and player within one tick window
+struct Bucket {
+    /// Index of the tick window, `tick / window_ticks`
+    window: u64,
+    window_ticks: u64,
+    calls: Vec<BatchedCall>,
+}
+
+impl Bucket {
+    /// First tick after the window
+    fn end(&self) -> u64 {
+        (self.window + 1) * self.window_ticks
+    }
+}
+
+/// Instant calls of one op merged into a single call
+pub struct Batch {
+    pub operation: String,
+    pub player_index: Option<u32>,
+    /// Tick of the first call
+    pub tick: u64,
+    pub inputs: HashMap<String, serde_json::Value>,
+    pub outputs: HashMap<String, serde_json::Value>,
+}
+
+impl Batch {
+    fn from_calls(operation: &str, player_index: Option<u32>, calls: Vec<BatchedCall>) -> Self {
+        let first = calls.first().map_or(0, |call| call.tick);
+        let last = calls.last().map_or(0, |call| call.tick);
+
+        let mut inputs = HashMap::new();
+        if let Some(player_index) = player_index {
+            inputs.insert("player_index".to_string(), serde_json::json!(player_index));
+        }
+        inputs.insert("tick_start".to_string(), serde_json::json!(first));
+        inputs.insert("tick_end".to_string(), serde_json::json!(last));
+        inputs.insert("count".to_string(), serde_json::json!(calls.len()));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("count".to_string(), serde_json::json!(calls.len()));
+
+        let mut input_items = Vec::with_capacity(calls.len());
+        let mut output_items = Vec::with_capacity(calls.len());
+        for mut call in calls {
+            call.inputs.insert("tick".to_string(), serde_json::json!(call.tick));
+            input_items.push(serde_json::json!(call.inputs));
+            output_items.push(serde_json::json!(call.outputs));
+        }
+        inputs.insert("items".to_string(), serde_json::Value::Array(input_items));
+        outputs.insert("items".to_string(), serde_json::Value::Array(output_items));
+
+        Self {
+            operation: format!("{}{}", operation, BATCH_SUFFIX),
+            player_index,
+            tick: first,
+            inputs,
+            outputs,
+        }
+    }
+}
+
+/// Merges the instant calls of the configured ops that fall into the same tick window
+/// (and player thread) into one `<op>_batch` call whose inputs and outputs list every
+/// call, e.g. 40 crafted items between two ticks. A batch goes out when its window
+/// closes, when it is full, or when the session ends.
+#[derive(Default)]
+pub struct CallBatcher {
+    settings: HashMap<String, CallBatchSettings>,
+    buckets: HashMap<(String, Option<u32>), Bucket>,
+}
+
+impl CallBatcher {
+    pub fn new(settings: HashMap<String, CallBatchSettings>) -> Self {
+        Self {
+            settings,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Whether calls of the op are batched
+    pub fn batches(&self, operation: &str) -> bool {
+        self.settings.contains_key(operation)
+    }
+
+    /// Adds a call to its batch, returning the batches it completed: the op's batch of
+    /// an earlier window, or its own batch once full
+    pub fn push(
+        &mut self,
+        operation: &str,
+        player_index: Option<u32>,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+        outputs: HashMap<String, serde_json::Value>,
+    ) -> Vec<Batch> {
+        let Some(settings) = self.settings.get(operation) else {
+            return Vec::new();
+        };
+        let window_ticks = settings.window_ticks.max(1);
+        let max_calls = settings.max_calls.max(1);
+        let window = tick / window_ticks;
+        let key = (operation.to_string(), player_index);
+
+        let mut batches = Vec::new();
+        if self.buckets.get(&key).is_some_and(|bucket| bucket.window != window) {
+            batches.extend(self.take(&key));
+        }
+        let bucket = self.buckets.entry(key.clone()).or_insert_with(|| Bucket {
+            window,
+            window_ticks,
+            calls: Vec::new(),
+        });
+        bucket.calls.push(BatchedCall {
+            tick,
+            inputs,
+            outputs,
+        });
+        if bucket.calls.len() >= max_calls {
+            batches.extend(self.take(&key));
+        }
+        batches
+    }
+
+    /// Takes the batches whose window closed before the tick
+    pub fn due(&mut self, tick: u64) -> Vec<Batch> {
+        let keys: Vec<_> = self
+            .buckets
+            .iter()
+            .filter(|(_, bucket)| bucket.end() <= tick)
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut batches: Vec<Batch> = keys.iter().filter_map(|key| self.take(key)).collect();
+        batches.sort_by_key(|batch| batch.tick);
+        batches
+    }
+
+    /// Takes every open batch, oldest first
+    pub fn drain(&mut self) -> Vec<Batch> {
+        let mut batches: Vec<Batch> = self
+            .buckets
+            .drain()
+            .map(|((operation, player_index), bucket)| {
+                Batch::from_calls(&operation, player_index, bucket.calls)
+            })
+            .collect();
+        batches.sort_by_key(|batch| batch.tick);
+        batches
+    }
+
+    fn take(&mut self, key: &(String, Option<u32>)) -> Option<Batch> {
+        let bucket = self.buckets.remove(key)?;
+        Some(Batch::from_calls(&key.0, key.1, bucket.calls))
+    }
+}
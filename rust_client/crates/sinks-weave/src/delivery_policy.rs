@@ -0,0 +1,135 @@
+use crate::call_batching::BATCH_SUFFIX;
+use factorio_events_core::config::DeliverySettings;
+
+/// Attempts of a `retry` policy given without a count
+const DEFAULT_ATTEMPTS: u32 = 3;
+
+/// What the Weave delivery layer does with a call it failed to send
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeliveryPolicy {
+    /// Park the call in the spool and keep replaying it until it is delivered
+    Durable,
+    /// Send up to this many times, then drop the call
+    Retry(u32),
+    /// Send once and drop the call on failure
+    BestEffort,
+}
+
+impl DeliveryPolicy {
+    /// Parses `wal`, `retry`, `retry:<attempts>` or `best_effort`
+    fn parse(class: &str, value: &str, fallback: DeliveryPolicy) -> Self {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "wal" | "durable" => return DeliveryPolicy::Durable,
+            "retry" => return DeliveryPolicy::Retry(DEFAULT_ATTEMPTS),
+            "best_effort" | "best-effort" => return DeliveryPolicy::BestEffort,
+            _ => {}
+        }
+        match value.strip_prefix("retry:").map(|n| n.trim().parse::<u32>()) {
+            Some(Ok(attempts)) if attempts > 1 => DeliveryPolicy::Retry(attempts),
+            Some(Ok(_)) => DeliveryPolicy::BestEffort,
+            _ => {
+                eprintln!(
+                    "⚠️  Unknown delivery policy '{}' for {} calls, using {}",
+                    value,
+                    class,
+                    fallback.describe()
+                );
+                fallback
+            }
+        }
+    }
+
+    /// Number of sends before the call is spooled or dropped
+    pub fn attempts(&self) -> u32 {
+        match self {
+            DeliveryPolicy::Retry(attempts) => *attempts,
+            DeliveryPolicy::Durable | DeliveryPolicy::BestEffort => 1,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            DeliveryPolicy::Durable => "wal".to_string(),
+            DeliveryPolicy::Retry(attempts) => format!("retry:{}", attempts),
+            DeliveryPolicy::BestEffort => "best_effort".to_string(),
+        }
+    }
+}
+
+/// Class of a Weave call, which decides its delivery policy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventClass {
+    /// Aggregates: surface maps, probe registry changes, drain summaries, status heartbeats
+    /// and bottlenecks
+    Stats,
+    /// Per-player entity and crafting events, the bulk of the calls
+    Entity,
+    /// Player snapshots, the largest calls
+    Screenshot,
+    /// Everything else: session starts, research, rocket launches, lineage, custom ops
+    Session,
+}
+
+impl EventClass {
+    /// Class of an op; a batch of calls has the class of their op
+    pub fn of(operation: &str) -> Self {
+        match operation.strip_suffix(BATCH_SUFFIX).unwrap_or(operation) {
+            "exploration_heatmap" | "pollution_map" | "probe_registry" | "drain_summary"
+            | "bottleneck" | "status" => EventClass::Stats,
+            "on_built_entity" | "on_player_mined_entity" | "on_player_crafted_item" => {
+                EventClass::Entity
+            }
+            "player_snapshot" => EventClass::Screenshot,
+            _ => EventClass::Session,
+        }
+    }
+}
+
+/// Delivery policy of each event class
+#[derive(Debug, Clone)]
+pub struct DeliveryPolicies {
+    stats: DeliveryPolicy,
+    entity: DeliveryPolicy,
+    screenshot: DeliveryPolicy,
+    session: DeliveryPolicy,
+}
+
+impl DeliveryPolicies {
+    pub fn from_settings(settings: &DeliverySettings) -> Self {
+        Self {
+            stats: DeliveryPolicy::parse("stats", &settings.stats, DeliveryPolicy::Durable),
+            entity: DeliveryPolicy::parse(
+                "entity",
+                &settings.entity,
+                DeliveryPolicy::Retry(DEFAULT_ATTEMPTS),
+            ),
+            screenshot: DeliveryPolicy::parse(
+                "screenshot",
+                &settings.screenshot,
+                DeliveryPolicy::BestEffort,
+            ),
+            session: DeliveryPolicy::parse("session", &settings.session, DeliveryPolicy::Durable),
+        }
+    }
+
+    /// Policy of the calls of an operation
+    pub fn for_operation(&self, operation: &str) -> DeliveryPolicy {
+        match EventClass::of(operation) {
+            EventClass::Stats => self.stats,
+            EventClass::Entity => self.entity,
+            EventClass::Screenshot => self.screenshot,
+            EventClass::Session => self.session,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "stats={} entity={} screenshot={} session={}",
+            self.stats.describe(),
+            self.entity.describe(),
+            self.screenshot.describe(),
+            self.session.describe()
+        )
+    }
+}
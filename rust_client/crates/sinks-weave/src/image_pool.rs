@@ -0,0 +1,53 @@
+use factorio_events_core::config::ImageSettings;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Bounded pool for CPU-heavy image work (hashing, overlays, base64 encoding). At most
+/// `workers` jobs run on the blocking thread pool at once and at most `queue` more wait
+/// for a worker, so a burst of screenshots can't starve event routing; jobs beyond that
+/// are rejected and the image is skipped.
+pub struct ImagePool {
+    workers: Arc<Semaphore>,
+    /// Slots for running plus waiting jobs
+    slots: Arc<Semaphore>,
+    rejected: AtomicU64,
+}
+
+impl ImagePool {
+    pub fn new(settings: &ImageSettings) -> Self {
+        let workers = settings.workers.max(1);
+        Self {
+            workers: Arc::new(Semaphore::new(workers)),
+            slots: Arc::new(Semaphore::new(workers + settings.queue)),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Runs `job` on a worker, or fails at once when the queue is full
+    pub async fn run<T, F>(&self, job: F) -> Result<T, String>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let Ok(_slot) = self.slots.clone().try_acquire_owned() else {
+            let rejected = self.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+            return Err(format!("Image queue full ({} jobs rejected so far)", rejected));
+        };
+        let _worker = self
+            .workers
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| format!("Image pool closed: {}", e))?;
+
+        tokio::task::spawn_blocking(job)
+            .await
+            .map_err(|e| format!("Image task failed: {}", e))
+    }
+
+    /// Number of jobs rejected because the queue was full
+    pub fn rejected_count(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
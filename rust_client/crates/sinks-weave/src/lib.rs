@@ -0,0 +1,15 @@
+//! The Weave sink: a trace per Factorio session, posted to the trace server over HTTP
+//! or delivered through the Go `weave-sender` sidecar.
+
+pub mod call_batching;
+pub mod delivery_policy;
+pub mod image_pool;
+pub mod op_attributes;
+pub mod op_schema;
+pub mod screenshot_hash;
+pub mod screenshot_overlay;
+pub mod trace_store;
+pub mod weave_client;
+pub mod weave_http;
+pub mod weave_manager;
+pub mod weave_retry;
@@ -0,0 +1,38 @@
+use crate::call_batching::BATCH_SUFFIX;
+use crate::op_schema;
+use std::collections::HashMap;
+
+/// Attributes every call of an op carries, so Weave filters and saved views can group
+/// the ops: the `category` of the op's schema (`construction`, `research`, `rocket`...),
+/// overridden or extended by the `[weave.op_attributes.<op>]` tables of the config.
+/// A batch of calls carries the attributes of its op, then those configured for the
+/// `<op>_batch` op itself.
+#[derive(Debug, Clone, Default)]
+pub struct OpAttributes {
+    configured: HashMap<String, HashMap<String, serde_json::Value>>,
+}
+
+impl OpAttributes {
+    pub fn new(configured: HashMap<String, HashMap<String, serde_json::Value>>) -> Self {
+        Self { configured }
+    }
+
+    /// Attributes of the calls of an operation
+    pub fn for_operation(&self, operation: &str) -> HashMap<String, serde_json::Value> {
+        let op = operation.strip_suffix(BATCH_SUFFIX).unwrap_or(operation);
+
+        let mut attributes = HashMap::new();
+        if let Some(schema) = op_schema::all().iter().find(|schema| schema.op_name == op) {
+            attributes.insert("category".to_string(), serde_json::json!(schema.category));
+        }
+        if let Some(configured) = self.configured.get(op) {
+            attributes.extend(configured.clone());
+        }
+        if op != operation {
+            if let Some(configured) = self.configured.get(operation) {
+                attributes.extend(configured.clone());
+            }
+        }
+        attributes
+    }
+}
@@ -0,0 +1,401 @@
+use crate::call_batching::BATCH_SUFFIX;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// JSON type expected for a call input field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Object,
+    Array,
+}
+
+impl FieldType {
+    /// Checks whether a JSON value has this type
+    fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_u64() || value.is_i64(),
+            FieldType::Number => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Object => value.is_object(),
+            FieldType::Array => value.is_array(),
+        }
+    }
+}
+
+/// Description of a single input field of an op.
+/// A name ending in `/*` stands for a family of keys sharing that prefix,
+/// e.g. `screenshot/*` for the per-surface `screenshot/nauvis`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    pub required: bool,
+}
+
+impl FieldSchema {
+    /// Whether an input key is described by this field
+    fn matches_key(&self, key: &str) -> bool {
+        match self.name.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == self.name,
+        }
+    }
+}
+
+/// Input schema for a Weave op emitted by the client.
+/// Published once per session so the expected shape of every op is visible in Weave,
+/// and checked against outgoing inputs in debug builds to catch mod/client drift.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpSchema {
+    pub op_name: &'static str,
+    pub description: &'static str,
+    /// Group of the op in the Weave UI, sent as the `category` attribute of its calls
+    pub category: &'static str,
+    pub inputs: Vec<FieldSchema>,
+}
+
+fn required(name: &'static str, field_type: FieldType) -> FieldSchema {
+    FieldSchema {
+        name,
+        field_type,
+        required: true,
+    }
+}
+
+fn optional(name: &'static str, field_type: FieldType) -> FieldSchema {
+    FieldSchema {
+        name,
+        field_type,
+        required: false,
+    }
+}
+
+impl OpSchema {
+    /// Validates call inputs against the schema, returning one message per problem
+    pub fn validate(&self, inputs: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for field in &self.inputs {
+            let mut present = false;
+            for (key, value) in inputs.iter().filter(|(key, _)| field.matches_key(key)) {
+                present = true;
+                if !field.field_type.matches(value) {
+                    problems.push(format!(
+                        "field '{}' expected {:?}, got {}",
+                        key, field.field_type, value
+                    ));
+                }
+            }
+            if !present && field.required {
+                problems.push(format!("missing required field '{}'", field.name));
+            }
+        }
+
+        for key in inputs.keys() {
+            if !self.inputs.iter().any(|field| field.matches_key(key)) {
+                problems.push(format!("unexpected field '{}'", key));
+            }
+        }
+
+        problems
+    }
+
+    /// Renders the schema as a JSON-schema-like Weave object
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let mut pattern_properties = serde_json::Map::new();
+        for field in &self.inputs {
+            let schema = serde_json::json!({ "type": field.field_type });
+            match field.name.strip_suffix('*') {
+                Some(prefix) => pattern_properties.insert(format!("^{}", prefix), schema),
+                None => properties.insert(field.name.to_string(), schema),
+            };
+        }
+        let required: Vec<&str> = self
+            .inputs
+            .iter()
+            .filter(|field| field.required)
+            .map(|field| field.name)
+            .collect();
+
+        serde_json::json!({
+            "_type": "OpSchema",
+            "op_name": self.op_name,
+            "description": self.description,
+            "category": self.category,
+            "type": "object",
+            "properties": properties,
+            "patternProperties": pattern_properties,
+            "required": required,
+        })
+    }
+}
+
+/// Returns the schemas of all ops the client emits
+pub fn all() -> &'static [OpSchema] {
+    static SCHEMAS: OnceLock<Vec<OpSchema>> = OnceLock::new();
+    SCHEMAS.get_or_init(|| {
+        use FieldType::*;
+
+        let entity_fields = || {
+            vec![
+                required("session_id", String),
+                required("player_index", Integer),
+                required("entity", String),
+                required("position_x", Number),
+                required("position_y", Number),
+                required("surface", String),
+            ]
+        };
+
+        let map_fields = || {
+            vec![
+                required("session_id", String),
+                required("surface", String),
+                required("chunks", Integer),
+                required("map", Object),
+            ]
+        };
+
+        vec![
+            OpSchema {
+                op_name: "session_init",
+                description: "A new game session was started or a save was loaded",
+                category: "session",
+                inputs: vec![
+                    required("session_id", String),
+                    required("tick", Integer),
+                    required("level_name", String),
+                ],
+            },
+            OpSchema {
+                op_name: "research",
+                description: "A technology research span from start to finish",
+                category: "research",
+                inputs: vec![
+                    required("session_id", String),
+                    required("tech_name", String),
+                    required("tech_level", Integer),
+                ],
+            },
+            OpSchema {
+                op_name: "research_progress",
+                description: "The levels of an infinite tech researched in a session",
+                category: "research",
+                inputs: vec![
+                    required("session_id", String),
+                    required("tech_name", String),
+                    required("first_level", Integer),
+                ],
+            },
+            OpSchema {
+                op_name: "on_built_entity",
+                description: "A player placed an entity",
+                category: "construction",
+                inputs: entity_fields(),
+            },
+            OpSchema {
+                op_name: "on_player_mined_entity",
+                description: "A player mined an entity",
+                category: "construction",
+                inputs: entity_fields(),
+            },
+            OpSchema {
+                op_name: "on_player_crafted_item",
+                description: "A player finished hand-crafting an item",
+                category: "crafting",
+                inputs: vec![
+                    required("session_id", String),
+                    required("player_index", Integer),
+                    required("item", String),
+                    required("count", Integer),
+                ],
+            },
+            OpSchema {
+                op_name: "batch",
+                description: "Instant calls of one op within a tick window, as `<op>_batch`",
+                category: "batch",
+                inputs: vec![
+                    required("session_id", String),
+                    optional("player_index", Integer),
+                    required("tick_start", Integer),
+                    required("tick_end", Integer),
+                    required("count", Integer),
+                    required("items", Array),
+                ],
+            },
+            OpSchema {
+                op_name: "on_rocket_launched",
+                description: "A rocket was launched from a silo",
+                category: "rocket",
+                inputs: vec![
+                    required("session_id", String),
+                    optional("surface", String),
+                    optional("count", Integer),
+                ],
+            },
+            OpSchema {
+                op_name: "rocket_launch",
+                description: "A rocket silo's launch, from its first part to the cargo delivery",
+                category: "rocket",
+                inputs: vec![
+                    required("session_id", String),
+                    required("silo", Integer),
+                    optional("surface", String),
+                ],
+            },
+            OpSchema {
+                op_name: "rocket_parts",
+                description: "Rocket parts loaded into a silo until the rocket is complete",
+                category: "rocket",
+                inputs: vec![
+                    required("session_id", String),
+                    required("silo", Integer),
+                    optional("surface", String),
+                    optional("parts_required", Integer),
+                ],
+            },
+            OpSchema {
+                op_name: "rocket_liftoff",
+                description: "A rocket from its launch order until it left the silo",
+                category: "rocket",
+                inputs: vec![
+                    required("session_id", String),
+                    required("silo", Integer),
+                    optional("surface", String),
+                    required("cargo", Object),
+                ],
+            },
+            OpSchema {
+                op_name: "cargo_delivery",
+                description: "A launched rocket's cargo pod until it reached its destination",
+                category: "rocket",
+                inputs: vec![
+                    required("session_id", String),
+                    required("silo", Integer),
+                    optional("surface", String),
+                ],
+            },
+            OpSchema {
+                op_name: "on_player_died",
+                description: "A player died",
+                category: "player",
+                inputs: vec![
+                    required("session_id", String),
+                    required("player_index", Integer),
+                    optional("cause", String),
+                    optional("surface", String),
+                ],
+            },
+            OpSchema {
+                op_name: "player_snapshot",
+                description: "Periodic player state with a screenshot",
+                category: "player",
+                inputs: vec![
+                    required("session_id", String),
+                    required("position_x", Number),
+                    required("position_y", Number),
+                    required("surface", String),
+                    required("health", Number),
+                    required("screenshot/*", Object),
+                ],
+            },
+            OpSchema {
+                op_name: "exploration_heatmap",
+                description: "Periodic map of charted chunks on a surface",
+                category: "map",
+                inputs: map_fields(),
+            },
+            OpSchema {
+                op_name: "probe_registry",
+                description: "Probes were added, removed or changed in-game",
+                category: "production",
+                inputs: vec![
+                    required("session_id", String),
+                    required("added", Array),
+                    required("removed", Array),
+                    required("changed", Array),
+                ],
+            },
+            OpSchema {
+                op_name: "bottleneck",
+                description: "The production bottleneck changed",
+                category: "production",
+                inputs: vec![
+                    required("session_id", String),
+                    required("chain", Array),
+                    required("explains", Array),
+                ],
+            },
+            OpSchema {
+                op_name: "prototypes",
+                description: "The mod sent the game's item and recipe prototypes",
+                category: "session",
+                inputs: vec![
+                    required("session_id", String),
+                    required("items", Object),
+                    required("recipes", Object),
+                ],
+            },
+            OpSchema {
+                op_name: "run_lineage",
+                description: "The session continues a save played in an earlier run",
+                category: "session",
+                inputs: vec![
+                    required("session_id", String),
+                    required("parent_run_name", String),
+                    required("parent_session_id", String),
+                    required("save_id", String),
+                ],
+            },
+            OpSchema {
+                op_name: "pollution_map",
+                description: "Periodic map of polluted chunks on a surface",
+                category: "map",
+                inputs: map_fields(),
+            },
+            OpSchema {
+                op_name: "status",
+                description: "Heartbeat of a client that received no events for a while",
+                category: "client",
+                inputs: vec![
+                    required("session_id", String),
+                    required("idle_secs", Integer),
+                    required("active_calls", Integer),
+                ],
+            },
+            OpSchema {
+                op_name: "drain_summary",
+                description: "Heartbeat of a drain cycle: events per type, drops and latency",
+                category: "client",
+                inputs: vec![
+                    required("session_id", String),
+                    required("events", Object),
+                    required("routed", Integer),
+                    required("dropped", Object),
+                    required("parse_errors", Integer),
+                    required("carried", Integer),
+                    required("latency_ms", Number),
+                ],
+            },
+        ]
+    })
+}
+
+/// Looks up the schema for an op by name; every `<op>_batch` op shares the `batch` one
+pub fn find(op_name: &str) -> Option<&'static OpSchema> {
+    let op_name = if op_name.ends_with(BATCH_SUFFIX) {
+        "batch"
+    } else {
+        op_name
+    };
+    all().iter().find(|schema| schema.op_name == op_name)
+}
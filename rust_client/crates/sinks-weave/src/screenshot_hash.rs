@@ -0,0 +1,25 @@
+use image::imageops::FilterType;
+
+/// Computes a 64-bit difference hash (dHash) of an encoded image: the image is
+/// shrunk to 9x8 grayscale and each bit records whether a pixel is brighter than
+/// its right neighbour. Nearly identical screenshots yield hashes a few bits apart.
+pub fn dhash(bytes: &[u8]) -> Result<u64, String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| format!("Failed to decode screenshot: {}", e))?;
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+    Ok(hash)
+}
+
+/// Number of differing bits between two hashes
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
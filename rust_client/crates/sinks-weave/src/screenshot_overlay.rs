@@ -0,0 +1,109 @@
+use ab_glyph::{FontArc, PxScale};
+use factorio_events_core::config::ScreenshotSettings;
+use factorio_events_core::event_mediator::PlayerInfo;
+use image::{ImageFormat, Rgba};
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut, text_size};
+use imageproc::rect::Rect;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// Number of top production items written on the overlay
+const OVERLAY_KPIS: usize = 3;
+const MARGIN: i32 = 6;
+
+/// Text overlay drawn on snapshots before upload so the image describes itself
+/// (tick, top production, player position) when browsed outside of its call.
+pub struct ScreenshotOverlay {
+    font: FontArc,
+    scale: PxScale,
+}
+
+impl ScreenshotOverlay {
+    /// Enabled by `overlay_font`, the path of a TTF/OTF font, drawn `overlay_size`
+    /// pixels high
+    pub fn from_settings(settings: &ScreenshotSettings) -> Option<Self> {
+        let path = settings.overlay_font.as_ref()?;
+
+        let font = match std::fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| FontArc::try_from_vec(bytes).map_err(|e| e.to_string()))
+        {
+            Ok(font) => font,
+            Err(e) => {
+                eprintln!(
+                    "⚠️  Failed to load overlay font {}: {}, overlay disabled",
+                    path.display(),
+                    e
+                );
+                return None;
+            }
+        };
+
+        println!("🔷 Screenshot overlay enabled with font {}", path.display());
+        Some(Self {
+            font,
+            scale: PxScale::from(settings.overlay_size),
+        })
+    }
+
+    /// Builds the overlay text for a snapshot
+    pub fn lines(
+        tick: u64,
+        player_info: &PlayerInfo,
+        production: &HashMap<String, f64>,
+    ) -> Vec<String> {
+        let mut lines = vec![
+            format!("tick {}", tick),
+            format!(
+                "{} ({:.0}, {:.0})",
+                player_info.surface, player_info.position.x, player_info.position.y
+            ),
+        ];
+
+        let mut top: Vec<(&String, &f64)> = production.iter().collect();
+        top.sort_by(|a, b| b.1.total_cmp(a.1));
+        for (item, rate) in top.into_iter().take(OVERLAY_KPIS) {
+            lines.push(format!("{}: {:.1}", item, rate));
+        }
+
+        lines
+    }
+
+    /// Draws the lines in the top-left corner of an encoded image, returning a PNG
+    pub fn annotate(&self, bytes: &[u8], lines: &[String]) -> Result<Vec<u8>, String> {
+        let mut image = image::load_from_memory(bytes)
+            .map_err(|e| format!("Failed to decode screenshot: {}", e))?
+            .to_rgba8();
+
+        let line_height = self.scale.y.ceil() as i32 + 2;
+        let width = lines
+            .iter()
+            .map(|line| text_size(self.scale, &self.font, line).0)
+            .max()
+            .unwrap_or(0);
+        let height = line_height * lines.len() as i32;
+
+        draw_filled_rect_mut(
+            &mut image,
+            Rect::at(0, 0).of_size(width + 2 * MARGIN as u32, (height + 2 * MARGIN) as u32),
+            Rgba([0, 0, 0, 255]),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            draw_text_mut(
+                &mut image,
+                Rgba([255, 255, 255, 255]),
+                MARGIN,
+                MARGIN + i as i32 * line_height,
+                self.scale,
+                &self.font,
+                line,
+            );
+        }
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode annotated screenshot: {}", e))?;
+        Ok(png)
+    }
+}
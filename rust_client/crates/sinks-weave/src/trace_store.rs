@@ -0,0 +1,112 @@
+use crate::weave_manager::CallContext;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// How long a stored session trace can be re-adopted after its last use
+const RETENTION_DAYS: i64 = 30;
+
+/// Root trace context of a Weave session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTrace {
+    pub trace_id: String,
+    pub root_call_id: String,
+    pub updated_at: DateTime<Utc>,
+    /// Calls open in the session, keyed like the manager's active calls
+    #[serde(default)]
+    pub(crate) open_calls: HashMap<String, CallContext>,
+    /// `entity/project` the session was routed to, when not the default one
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+/// File-backed store of session root traces, keyed by Factorio session_id.
+/// Lets a restarted client keep adding calls to the session's original trace
+/// instead of starting a disconnected one.
+pub struct TraceStore {
+    path: PathBuf,
+    traces: HashMap<String, SessionTrace>,
+}
+
+impl TraceStore {
+    /// Loads the store from disk, starting empty if the file is missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let traces = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("⚠️  Ignoring unreadable trace store {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self { path, traces }
+    }
+
+    /// Returns the stored trace for a session if it is still within retention
+    pub fn get(&self, session_id: &str) -> Option<&SessionTrace> {
+        self.traces
+            .get(session_id)
+            .filter(|trace| trace.updated_at > Utc::now() - Duration::days(RETENTION_DAYS))
+    }
+
+    /// Stores the trace for a session and persists the store
+    pub fn insert(&mut self, session_id: String, trace: SessionTrace) -> Result<(), String> {
+        self.traces.insert(session_id, trace);
+
+        let cutoff = Utc::now() - Duration::days(RETENTION_DAYS);
+        self.traces.retain(|_, trace| trace.updated_at > cutoff);
+
+        self.save()
+    }
+
+    /// Stores the trace of a session exported from another machine, so calls backfilled
+    /// from the session join it. Its open calls are left behind, and a trace the session
+    /// already has here is kept. Returns whether the trace was stored.
+    pub fn import(&mut self, session_id: String, mut trace: SessionTrace) -> Result<bool, String> {
+        if self.get(&session_id).is_some() {
+            return Ok(false);
+        }
+        trace.open_calls.clear();
+        trace.updated_at = Utc::now();
+        self.insert(session_id, trace)?;
+        Ok(true)
+    }
+
+    /// Takes the open calls of every stored trace but `keep`'s, persisting the store,
+    /// and returns them with their session ids and traces
+    pub fn take_open_calls(
+        &mut self,
+        keep: Option<&str>,
+    ) -> Result<Vec<(String, SessionTrace, Vec<CallContext>)>, String> {
+        let mut taken = Vec::new();
+        for (session_id, trace) in self.traces.iter_mut() {
+            if trace.open_calls.is_empty() || keep == Some(session_id.as_str()) {
+                continue;
+            }
+            let calls = trace.open_calls.drain().map(|(_, context)| context).collect();
+            taken.push((session_id.clone(), trace.clone(), calls));
+        }
+        if !taken.is_empty() {
+            self.save()?;
+        }
+        Ok(taken)
+    }
+
+    /// Writes the store atomically via a temporary file
+    fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+
+        let contents = serde_json::to_string_pretty(&self.traces)
+            .map_err(|e| format!("Failed to serialize trace store: {}", e))?;
+
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, contents)
+            .map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .map_err(|e| format!("Failed to replace {:?}: {}", self.path, e))
+    }
+}
@@ -0,0 +1,1257 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::{state_dir, AppConfig};
+use factorio_events_core::fault_injection::FaultInjector;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::codec::{FramedRead, LinesCodec};
+
+/// How the calls reach the trace server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Through the Go weave-sender over its Unix socket
+    Sidecar,
+    /// Straight to the trace server's HTTP API
+    Http,
+}
+
+impl Transport {
+    /// Resolves `weave.transport`; `auto` picks the sidecar when its binary is configured
+    fn from_settings(transport: &str, has_binary: bool) -> Result<Self, String> {
+        match transport {
+            "auto" if has_binary => Ok(Transport::Sidecar),
+            "auto" | "http" => Ok(Transport::Http),
+            "sidecar" => Ok(Transport::Sidecar),
+            other => Err(format!(
+                "Unknown Weave transport '{}' (expected auto, http or sidecar)",
+                other
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transport::Sidecar => "sidecar",
+            Transport::Http => "http",
+        }
+    }
+}
+
+/// Configuration for the Weave client
+#[derive(Debug, Clone)]
+pub struct WeaveConfig {
+    pub entity: String,
+    pub project: String,
+    pub base_url: String,
+    pub api_key: String,
+    pub transport: Transport,
+    /// The `weave-sender` binary, empty with the HTTP transport
+    pub binary_path: PathBuf,
+    pub socket_path: PathBuf,
+    /// Request gzip framing on the socket protocol (negotiated at init)
+    pub compression: bool,
+    /// Interval between health-check pings on the sidecar connection
+    pub ping_interval: Duration,
+    /// Time allowed for a request to the sidecar, waiting for the connection included
+    pub request_timeout: Duration,
+    /// Time allowed for the sidecar's flush, wait and shutdown requests
+    pub drain_timeout: Duration,
+    /// File where session root traces are persisted across restarts
+    pub trace_store_path: PathBuf,
+    /// Directory of the spool holding calls deferred by the upload budget or failed sends
+    pub spool_dir: PathBuf,
+    /// Age after which delivered spool segments are deleted
+    pub spool_retention: Duration,
+    /// Interval between spool compaction passes
+    pub spool_compact_interval: Duration,
+    /// Chaos-testing faults applied to enqueued items
+    pub faults: Option<Arc<FaultInjector>>,
+    /// Items of a batch sent per enqueue request, 0 to send every item on its own
+    pub enqueue_batch_items: usize,
+}
+
+impl WeaveConfig {
+    /// Builds the client configuration from the app config
+    pub fn from_app(config: &AppConfig) -> Result<Self, String> {
+        let settings = &config.weave;
+        let entity = settings
+            .entity
+            .clone()
+            .ok_or("Weave entity not configured (weave.entity or WEAVE_ENTITY)")?;
+        let project = settings
+            .project
+            .clone()
+            .ok_or("Weave project not configured (weave.project or WEAVE_PROJECT)")?;
+        let base_url = settings.base_url.clone();
+        let api_key = env::var("WANDB_API_KEY")
+            .map_err(|_| "WANDB_API_KEY environment variable not set")?;
+
+        let transport =
+            Transport::from_settings(&settings.transport, settings.binary_path.is_some())?;
+
+        let binary_path = match transport {
+            Transport::Sidecar => {
+                let binary_path = settings
+                    .binary_path
+                    .as_ref()
+                    .ok_or(
+                        "Weave binary path not configured (weave.binary_path or WEAVE_BINARY_PATH)",
+                    )?
+                    .join("weave-sender");
+                if !binary_path.exists() {
+                    return Err(format!("Weave binary not found at {:?}", binary_path));
+                }
+                binary_path
+            }
+            Transport::Http => PathBuf::new(),
+        };
+
+        // Prefer a per-user runtime directory over the shared /tmp on multi-user machines
+        let socket_dir = settings
+            .socket_dir
+            .clone()
+            .or_else(|| env::var("XDG_RUNTIME_DIR").ok().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("/tmp"));
+
+        // Each game instance served by this process runs its own sidecar
+        let socket_name = match config.instance {
+            Some(ref instance) => format!(
+                "weave-sender-factorio-{}-{}.sock",
+                std::process::id(),
+                instance
+            ),
+            None => format!("weave-sender-factorio-{}.sock", std::process::id()),
+        };
+        let socket_path = socket_dir.join(socket_name);
+
+        let compression = settings.compression;
+        let ping_interval = Duration::from_secs(settings.ping_interval_secs.max(1));
+        let request_timeout = Duration::from_secs(settings.request_timeout_secs.max(1));
+        let drain_timeout = Duration::from_secs(settings.drain_timeout_secs.max(1));
+
+        let trace_store_path = trace_store_path(config);
+        let spool_dir = instance_state_dir(config).join("spool");
+
+        Ok(Self {
+            entity,
+            project,
+            base_url,
+            api_key,
+            transport,
+            binary_path,
+            socket_path,
+            compression,
+            ping_interval,
+            request_timeout,
+            drain_timeout,
+            trace_store_path,
+            spool_dir,
+            spool_retention: Duration::from_secs(settings.spool_retention_hours * 3600),
+            spool_compact_interval: Duration::from_secs(settings.spool_compact_interval_secs.max(1)),
+            faults: FaultInjector::for_sink("weave", &config.faults).map(Arc::new),
+            enqueue_batch_items: settings.enqueue_batch_items,
+        })
+    }
+
+    /// Get the project_id in the format "entity/project"
+    pub fn project_id(&self) -> String {
+        format!("{}/{}", self.entity, self.project)
+    }
+}
+
+/// State directory of the Weave client, one per game instance when the client serves several
+fn instance_state_dir(config: &AppConfig) -> PathBuf {
+    match config.instance {
+        Some(ref instance) => state_dir().join("instances").join(instance),
+        None => state_dir(),
+    }
+}
+
+/// File where the Weave client persists session root traces
+pub fn trace_store_path(config: &AppConfig) -> PathBuf {
+    instance_state_dir(config).join("weave_traces.json")
+}
+
+/// JSON-RPC request structure
+#[derive(Debug, Serialize)]
+struct JsonRpcRequest {
+    id: i32,
+    method: String,
+    params: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    no_reply: Option<bool>,
+}
+
+/// JSON-RPC response structure
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    id: i32,
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// Why a request to the sender failed
+#[derive(Debug)]
+pub enum RequestError {
+    /// No reply within the request's timeout. The sender may be hung, but the
+    /// connection stays usable: the late reply is discarded when it arrives.
+    Timeout { method: String, after: Duration },
+    /// The connection failed or the request couldn't be framed
+    Transport(String),
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout { method, after } => {
+                write!(
+                    f,
+                    "Weave-sender didn't answer {} within {:?}",
+                    method, after
+                )
+            }
+            Self::Transport(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<RequestError> for String {
+    fn from(e: RequestError) -> Self {
+        e.to_string()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i32,
+    message: String,
+}
+
+/// Auth params for the weave-sender
+#[derive(Debug, Serialize)]
+struct AuthParams {
+    username: String,
+    password: String,
+}
+
+/// Init params for the weave-sender
+#[derive(Debug, Serialize)]
+struct InitParams {
+    server_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth: Option<AuthParams>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HashMap<String, String>>,
+    /// Compression the client would like to use; the sender echoes it back in the
+    /// init result if supported, otherwise requests stay plain JSON lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compression: Option<String>,
+}
+
+/// Enqueue params for the weave-sender
+#[derive(Debug, Serialize)]
+struct EnqueueParams {
+    items: Vec<EnqueueItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct EnqueueItem {
+    #[serde(rename = "type")]
+    pub(crate) item_type: String, // "start" or "end"
+    pub(crate) payload: serde_json::Value,
+    /// Id of the call the item starts or ends
+    #[serde(skip)]
+    pub(crate) call_id: Option<String>,
+}
+
+impl EnqueueItem {
+    /// Builds the start item of a call
+    pub(crate) fn start(start: StartedCallSchemaForInsert) -> Result<Self, String> {
+        let call_id = start.id.clone();
+        let req = CallStartReq { start };
+        let payload = serde_json::to_value(req)
+            .map_err(|e| format!("Failed to serialize start call: {}", e))?;
+        Ok(Self {
+            item_type: "start".to_string(),
+            payload,
+            call_id,
+        })
+    }
+
+    /// Builds the end item of a call
+    pub(crate) fn end(end: EndedCallSchemaForInsert) -> Result<Self, String> {
+        let call_id = Some(end.id.clone());
+        let req = CallEndReq { end };
+        let payload = serde_json::to_value(req)
+            .map_err(|e| format!("Failed to serialize end call: {}", e))?;
+        Ok(Self {
+            item_type: "end".to_string(),
+            payload,
+            call_id,
+        })
+    }
+
+    /// Identifies the item among those sent: its type and call id
+    pub(crate) fn key(&self) -> Option<(String, String)> {
+        self.call_id
+            .as_ref()
+            .map(|call_id| (self.item_type.clone(), call_id.clone()))
+    }
+
+    pub(crate) fn has_key(&self, key: &(String, String)) -> bool {
+        self.item_type == key.0 && self.call_id.as_deref() == Some(key.1.as_str())
+    }
+}
+
+/// Starts and ends written to the sender, oldest first, remembered to suppress copies
+#[derive(Default)]
+pub(crate) struct DeliveredItems {
+    pub(crate) keys: HashSet<(String, String)>,
+    order: VecDeque<(String, String)>,
+}
+
+impl DeliveredItems {
+    pub(crate) fn insert(&mut self, key: (String, String)) {
+        if self.keys.insert(key.clone()) {
+            self.order.push_back(key);
+        }
+        while self.order.len() > DELIVERED_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// StartedCallSchemaForInsert as per Weave trace server interface
+#[derive(Debug, Clone, Serialize)]
+pub struct StartedCallSchemaForInsert {
+    pub project_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub op_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub attributes: HashMap<String, serde_json::Value>,
+    pub inputs: HashMap<String, serde_json::Value>,
+}
+
+/// EndedCallSchemaForInsert as per Weave trace server interface
+#[derive(Debug, Clone, Serialize)]
+pub struct EndedCallSchemaForInsert {
+    pub project_id: String,
+    pub id: String,
+    pub ended_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exception: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+    pub summary: HashMap<String, serde_json::Value>,
+}
+
+/// CallStartReq wrapper
+#[derive(Debug, Serialize)]
+struct CallStartReq {
+    start: StartedCallSchemaForInsert,
+}
+
+/// CallEndReq wrapper
+#[derive(Debug, Serialize)]
+struct CallEndReq {
+    end: EndedCallSchemaForInsert,
+}
+
+/// Compresses a request line into a gzip frame: a `GZ <length>\n` header followed
+/// by the gzip bytes. Responses are always plain JSON lines.
+fn gzip_frame(line: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    encoder
+        .write_all(line)
+        .map_err(|e| format!("Failed to compress request: {}", e))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress request: {}", e))?;
+
+    let mut frame = format!("GZ {}\n", compressed.len()).into_bytes();
+    frame.extend_from_slice(&compressed);
+    Ok(frame)
+}
+
+/// Maximum number of unacknowledged items kept for replay after a reconnect
+pub(crate) const OUTBOX_CAPACITY: usize = 10_000;
+
+/// Number of written items remembered to suppress their copies
+const DELIVERED_CAPACITY: usize = 50_000;
+
+/// Longest reply line accepted from the sender
+const MAX_REPLY_BYTES: usize = 1 << 20;
+
+/// Frames queued for the writer task before senders wait for it
+const WRITE_QUEUE_FRAMES: usize = 1024;
+
+/// Time the sender gets to exit after a kill, or after the shutdown request before it
+/// is killed
+const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Shortest time between two restarts of the sender, so one that crashes at launch
+/// isn't respawned for every call
+const RESTART_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Socket connection to the weave-sender. Frames are written by a dedicated task in the
+/// order they were queued; replies are read back as JSON lines.
+struct Connection {
+    frames: mpsc::Sender<Vec<u8>>,
+    replies: FramedRead<OwnedReadHalf, LinesCodec>,
+}
+
+impl Connection {
+    fn new(stream: UnixStream) -> Self {
+        let (read_half, write_half) = stream.into_split();
+        let (frames, queued) = mpsc::channel(WRITE_QUEUE_FRAMES);
+        tokio::spawn(write_frames(write_half, queued));
+
+        Self {
+            frames,
+            replies: FramedRead::new(read_half, LinesCodec::new_with_max_length(MAX_REPLY_BYTES)),
+        }
+    }
+
+    /// Queues a frame for the writer task
+    async fn send(&self, frame: Vec<u8>) -> Result<(), String> {
+        self.frames
+            .send(frame)
+            .await
+            .map_err(|_| "Failed to write request: connection closed".to_string())
+    }
+
+    /// Reads the next reply line; the request's timeout bounds the wait
+    async fn read_reply(&mut self) -> Result<String, String> {
+        match self.replies.next().await {
+            None => Err("Connection closed by weave-sender".to_string()),
+            Some(line) => line.map_err(|e| format!("Failed to read response: {}", e)),
+        }
+    }
+}
+
+/// Writes the queued frames to the socket until the connection is dropped or a write
+/// fails; senders then see the queue closed
+async fn write_frames(mut socket: OwnedWriteHalf, mut queued: mpsc::Receiver<Vec<u8>>) {
+    while let Some(frame) = queued.recv().await {
+        if let Err(e) = socket.write_all(&frame).await {
+            eprintln!("⚠️  Failed to write to weave-sender socket: {}", e);
+            return;
+        }
+    }
+}
+
+/// Weave client that communicates with the Go weave-sender via Unix socket
+pub struct WeaveClient {
+    config: WeaveConfig,
+    process: Arc<Mutex<Option<Child>>>,
+    connection: Arc<Mutex<Option<Connection>>>,
+    request_id: Arc<Mutex<i32>>,
+    /// Whether the sender accepted gzip framing at init
+    gzip_enabled: Arc<AtomicBool>,
+    /// Enqueued items not yet acknowledged by a reply. The socket is ordered, so any
+    /// successful reply means every item written before it reached the sender.
+    outbox: Arc<Mutex<VecDeque<EnqueueItem>>>,
+    /// Items written to the current sender process. Call ids are derived from the
+    /// event, so a start or end submitted again (by a retry of an item that did reach
+    /// the sender, or while its copy waits in the outbox) is dropped instead of
+    /// doubling the call.
+    delivered: Arc<Mutex<DeliveredItems>>,
+    /// Items held by the open batch, None outside of one
+    batch: Arc<Mutex<Option<Vec<EnqueueItem>>>>,
+    /// When the sender was last respawned
+    last_restart: Arc<Mutex<Option<Instant>>>,
+    restarts: Arc<AtomicU64>,
+}
+
+impl WeaveClient {
+    /// Create a new Weave client
+    pub fn new(config: WeaveConfig) -> Self {
+        Self {
+            config,
+            process: Arc::new(Mutex::new(None)),
+            connection: Arc::new(Mutex::new(None)),
+            request_id: Arc::new(Mutex::new(1)),
+            gzip_enabled: Arc::new(AtomicBool::new(false)),
+            outbox: Arc::new(Mutex::new(VecDeque::new())),
+            delivered: Arc::new(Mutex::new(DeliveredItems::default())),
+            batch: Arc::new(Mutex::new(None)),
+            last_restart: Arc::new(Mutex::new(None)),
+            restarts: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Initialize the client by starting the Go sender and establishing connection
+    pub async fn init(&self) -> Result<(), String> {
+        // Start the weave-sender process; a new one has received nothing yet
+        self.start_sender_process().await?;
+        *self.delivered.lock().await = DeliveredItems::default();
+
+        // Wait for socket to be available
+        self.wait_for_socket().await?;
+
+        // Connect to the socket
+        self.connect_to_socket().await?;
+
+        // Initialize the sender
+        self.send_init().await?;
+
+        println!("✅ Weave client initialized successfully");
+        Ok(())
+    }
+
+    /// Start the weave-sender process
+    async fn start_sender_process(&self) -> Result<(), String> {
+        let mut process_guard = self.process.lock().await;
+
+        if process_guard.is_some() {
+            return Ok(());
+        }
+
+        println!(
+            "🚀 Starting weave-sender: {:?}",
+            self.config.binary_path
+        );
+
+        let child = Command::new(&self.config.binary_path)
+            .arg("-socket")
+            .arg(&self.config.socket_path)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::inherit())
+            .stderr(std::process::Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to start weave-sender: {}", e))?;
+
+        *process_guard = Some(child);
+        Ok(())
+    }
+
+    /// Wait for the socket file to be created
+    async fn wait_for_socket(&self) -> Result<(), String> {
+        for _ in 0..50 {
+            if self.config.socket_path.exists() {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Err("Weave-sender socket did not become available".to_string())
+    }
+
+    /// Restrict the socket to the current user and verify nobody else can hijack it
+    fn secure_socket(path: &Path) -> Result<(), String> {
+        let metadata = std::fs::symlink_metadata(path)
+            .map_err(|e| format!("Failed to stat socket {:?}: {}", path, e))?;
+
+        if !metadata.file_type().is_socket() {
+            return Err(format!("{:?} is not a Unix socket", path));
+        }
+
+        let uid = unsafe { libc::geteuid() };
+        if metadata.uid() != uid {
+            return Err(format!(
+                "Refusing to use socket {:?} owned by uid {} (expected {})",
+                path,
+                metadata.uid(),
+                uid
+            ));
+        }
+
+        if metadata.mode() & 0o777 != 0o600 {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to set socket permissions: {}", e))?;
+        }
+
+        // A directory anyone can write to without the sticky bit lets others swap the socket
+        if let Some(parent) = path.parent() {
+            let dir_mode = std::fs::metadata(parent)
+                .map_err(|e| format!("Failed to stat socket directory {:?}: {}", parent, e))?
+                .mode();
+            if dir_mode & 0o002 != 0 && dir_mode & 0o1000 == 0 {
+                return Err(format!(
+                    "Refusing to use socket in world-writable directory {:?}",
+                    parent
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Connect to the Unix socket
+    async fn connect_to_socket(&self) -> Result<(), String> {
+        Self::secure_socket(&self.config.socket_path)?;
+
+        let stream = UnixStream::connect(&self.config.socket_path)
+            .await
+            .map_err(|e| format!("Failed to connect to socket: {}", e))?;
+
+        let mut conn_guard = self.connection.lock().await;
+        *conn_guard = Some(Connection::new(stream));
+
+        Ok(())
+    }
+
+    /// Send init command to the weave-sender
+    async fn send_init(&self) -> Result<(), String> {
+        let params = InitParams {
+            server_url: self.config.base_url.clone(),
+            auth: Some(AuthParams {
+                username: String::new(), // W&B uses API key as username
+                password: self.config.api_key.clone(), // Empty password
+            }),
+            headers: None,
+            compression: if self.config.compression {
+                Some("gzip".to_string())
+            } else {
+                None
+            },
+        };
+
+        let response = self
+            .send_request("init", serde_json::to_value(params).unwrap(), false)
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Init failed: {}", error.message));
+        }
+
+        if self.config.compression {
+            let accepted = response
+                .result
+                .as_ref()
+                .and_then(|result| result.get("compression"))
+                .and_then(|value| value.as_str())
+                == Some("gzip");
+
+            if accepted {
+                println!("🔷 Weave-sender accepted gzip framing");
+            } else {
+                println!("🔷 Weave-sender does not support gzip framing, using plain JSON lines");
+            }
+            self.gzip_enabled.store(accepted, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// Send a JSON-RPC request and wait for its response, giving up after the request
+    /// timeout (the drain timeout for requests that wait for deliveries)
+    async fn send_request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        no_reply: bool,
+    ) -> Result<JsonRpcResponse, RequestError> {
+        let after = match method {
+            "flush" | "wait_queue_empty" | "wait_idle" | "shutdown" => self.config.drain_timeout,
+            _ => self.config.request_timeout,
+        };
+        // Cancelling the exchange is safe: frames are queued whole, and unread replies
+        // are told apart by their id
+        match tokio::time::timeout(after, self.exchange(method, params, no_reply)).await {
+            Ok(result) => result,
+            Err(_) => Err(RequestError::Timeout {
+                method: method.to_string(),
+                after,
+            }),
+        }
+    }
+
+    async fn exchange(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        no_reply: bool,
+    ) -> Result<JsonRpcResponse, RequestError> {
+        // The connection stays locked until the reply, and ids are taken under the lock,
+        // so requests go out in id order and replies pair up
+        let mut conn_guard = self.connection.lock().await;
+        let conn = conn_guard
+            .as_mut()
+            .ok_or_else(|| RequestError::Transport("Not connected".to_string()))?;
+
+        let mut id_guard = self.request_id.lock().await;
+        let id = *id_guard;
+        *id_guard += 1;
+        drop(id_guard);
+
+        let request = JsonRpcRequest {
+            id,
+            method: method.to_string(),
+            params,
+            no_reply: if no_reply { Some(true) } else { None },
+        };
+
+        let mut request_json = serde_json::to_string(&request)
+            .map_err(|e| RequestError::Transport(format!("Failed to serialize request: {}", e)))?;
+        request_json.push('\n');
+
+        let frame = if self.gzip_enabled.load(Ordering::Relaxed) {
+            gzip_frame(request_json.as_bytes()).map_err(RequestError::Transport)?
+        } else {
+            request_json.into_bytes()
+        };
+
+        conn.send(frame).await.map_err(RequestError::Transport)?;
+
+        if no_reply {
+            // Don't wait for response
+            return Ok(JsonRpcResponse {
+                id,
+                result: None,
+                error: None,
+            });
+        }
+
+        // Read the response, skipping the late replies to requests that timed out
+        let response = loop {
+            let line = match conn.read_reply().await {
+                Ok(line) => line,
+                Err(e) => {
+                    *conn_guard = None;
+                    return Err(RequestError::Transport(e));
+                }
+            };
+            let response: JsonRpcResponse = serde_json::from_str(&line)
+                .map_err(|e| RequestError::Transport(format!("Failed to parse response: {}", e)))?;
+            if response.id > id {
+                // Replies come in request order, so the framing is lost
+                *conn_guard = None;
+                return Err(RequestError::Transport(format!(
+                    "Reply to request {} arrived before the reply to {}",
+                    response.id, id
+                )));
+            }
+            if response.id == id {
+                break response;
+            }
+            eprintln!(
+                "⚠️  Discarded late weave-sender reply to request {}",
+                response.id
+            );
+        };
+        drop(conn_guard);
+
+        // Everything written before this reply has been received by the sender
+        self.outbox.lock().await.clear();
+
+        Ok(response)
+    }
+
+    /// Enqueue new items, subject to fault injection, dropping the copies of items
+    /// already written, waiting in the outbox for a replay or held by the open batch.
+    /// While a batch is open the items join it, and go out once it is full or closed.
+    async fn send_items(&self, items: Vec<EnqueueItem>) -> Result<(), String> {
+        // Injected failures happen before the outbox so nothing is replayed for them
+        if let Some(ref faults) = self.config.faults {
+            faults.inject().await?;
+        }
+
+        let mut batch = self.batch.lock().await;
+        let items: Vec<EnqueueItem> = {
+            let delivered = self.delivered.lock().await;
+            let outbox = self.outbox.lock().await;
+            let held = batch.as_deref().unwrap_or_default();
+            items
+                .into_iter()
+                .filter(|item| {
+                    let Some(key) = item.key() else {
+                        return true;
+                    };
+                    let duplicate = delivered.keys.contains(&key)
+                        || outbox.iter().any(|queued| queued.has_key(&key))
+                        || held.iter().any(|queued| queued.has_key(&key));
+                    if duplicate {
+                        println!("🔷 Suppressed duplicate Weave {} of call {}", key.0, key.1);
+                    }
+                    !duplicate
+                })
+                .collect()
+        };
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let ready = match batch.as_mut() {
+            None => Some(items),
+            Some(held) => {
+                held.extend(items);
+                (held.len() >= self.config.enqueue_batch_items).then(|| std::mem::take(held))
+            }
+        };
+        drop(batch);
+        let Some(items) = ready else {
+            return Ok(());
+        };
+        let result = self.enqueue_items(items).await;
+        // A sender that died is respawned right away rather than at the next health check
+        if let Err(ref e) = result {
+            if let Some(status) = self.exit_status().await {
+                let failure = format!("Weave-sender exited with {} ({})", status, e);
+                self.recover(&failure, false).await;
+            }
+        }
+        result
+    }
+
+    /// Opens a batch: the starts and ends sent until `end_batch` go out together, in
+    /// one enqueue request per `enqueue_batch_items` items
+    pub async fn begin_batch(&self) {
+        if self.config.enqueue_batch_items > 0 {
+            self.batch.lock().await.get_or_insert_with(Vec::new);
+        }
+    }
+
+    /// Closes the open batch and enqueues the items it holds
+    pub async fn end_batch(&self) -> Result<(), String> {
+        let held = self.batch.lock().await.take().unwrap_or_default();
+        if held.is_empty() {
+            return Ok(());
+        }
+        self.enqueue_items(held).await
+    }
+
+    /// Enqueues the items held by the open batch, keeping it open, so a request
+    /// after them (a flush, or the shutdown) finds them at the sender
+    async fn send_held(&self) -> Result<(), String> {
+        let held = match self.batch.lock().await.as_mut() {
+            Some(held) => std::mem::take(held),
+            None => return Ok(()),
+        };
+        if held.is_empty() {
+            return Ok(());
+        }
+        self.enqueue_items(held).await
+    }
+
+    /// Record items in the outbox and enqueue them (fire-and-forget)
+    async fn enqueue_items(&self, items: Vec<EnqueueItem>) -> Result<(), String> {
+        {
+            let mut outbox = self.outbox.lock().await;
+            outbox.extend(items.iter().cloned());
+            while outbox.len() > OUTBOX_CAPACITY {
+                outbox.pop_front();
+            }
+        }
+
+        let keys: Vec<(String, String)> = items.iter().filter_map(EnqueueItem::key).collect();
+        let params = EnqueueParams { items };
+
+        // Fire-and-forget for performance
+        self.send_request("enqueue", serde_json::to_value(params).unwrap(), true)
+            .await?;
+
+        let mut delivered = self.delivered.lock().await;
+        for key in keys {
+            delivered.insert(key);
+        }
+        Ok(())
+    }
+
+    /// Ping the sender over the existing connection
+    pub async fn ping(&self) -> Result<(), String> {
+        let response = self
+            .send_request("stats", serde_json::json!({}), false)
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Ping failed: {}", error.message));
+        }
+
+        Ok(())
+    }
+
+    /// Supervises the sender: respawns it when the process died or hangs, and
+    /// reconnects (or, if that fails, respawns it) when its connection failed
+    pub async fn health_check(&self) {
+        if let Some(status) = self.exit_status().await {
+            let failure = format!("Weave-sender exited with {}", status);
+            self.recover(&failure, false).await;
+            return;
+        }
+
+        let ping = self
+            .send_request("stats", serde_json::json!({}), false)
+            .await;
+        let (failure, hung) = match ping {
+            Ok(JsonRpcResponse { error: None, .. }) => return,
+            Ok(JsonRpcResponse {
+                error: Some(error), ..
+            }) => (
+                format!("Weave-sender ping failed: {}", error.message),
+                false,
+            ),
+            Err(e @ RequestError::Timeout { .. }) => (e.to_string(), true),
+            Err(e) => (format!("Weave-sender ping failed: {}", e), false),
+        };
+        self.recover(&failure, hung).await;
+    }
+
+    /// Exit status of the sender process, None while it runs (or before it started)
+    async fn exit_status(&self) -> Option<ExitStatus> {
+        self.process
+            .lock()
+            .await
+            .as_mut()?
+            .try_wait()
+            .ok()
+            .flatten()
+    }
+
+    /// Brings the sender back after a failure: a live process is reconnected to first,
+    /// a dead or hung one (or one whose socket can't be re-established) is respawned
+    async fn recover(&self, failure: &str, hung: bool) {
+        if !hung && self.exit_status().await.is_none() {
+            eprintln!("⚠️  {}, reconnecting...", failure);
+            match self.reconnect().await {
+                Ok(()) => return,
+                Err(e) => eprintln!("⚠️  Weave-sender reconnect failed: {}", e),
+            }
+        }
+
+        {
+            let mut last_restart = self.last_restart.lock().await;
+            if last_restart.is_some_and(|at| at.elapsed() < RESTART_BACKOFF) {
+                return;
+            }
+            *last_restart = Some(Instant::now());
+        }
+        eprintln!("⚠️  {}, restarting it", failure);
+        if let Err(e) = self.restart().await {
+            eprintln!("❌ Failed to restart weave-sender: {}", e);
+            client_events::emit(ClientEvent::Error {
+                component: "weave".to_string(),
+                message: format!("Failed to restart weave-sender: {}", e),
+            });
+        }
+    }
+
+    /// Kills what is left of the sender, spawns a new one, re-runs init and replays the
+    /// items the old one never acknowledged
+    async fn restart(&self) -> Result<(), String> {
+        *self.connection.lock().await = None;
+        let child = self.process.lock().await.take();
+        if let Some(mut child) = child {
+            let _ = child.start_kill();
+            let exited = tokio::time::timeout(EXIT_TIMEOUT, child.wait()).await;
+            if exited.is_err() {
+                eprintln!(
+                    "⚠️  Weave-sender did not exit within {:?} of being killed",
+                    EXIT_TIMEOUT
+                );
+            }
+        }
+        // The old socket file would pass for the new sender's before it listens
+        if self.config.socket_path.exists() {
+            std::fs::remove_file(&self.config.socket_path)
+                .map_err(|e| format!("Failed to remove stale socket: {}", e))?;
+        }
+
+        // Take pending items before init, whose reply would clear the outbox
+        let pending: Vec<EnqueueItem> = self.outbox.lock().await.drain(..).collect();
+        let init = self.init().await;
+        if let Err(e) = init {
+            // Keep the items for the next attempt
+            let mut outbox = self.outbox.lock().await;
+            for item in pending.into_iter().rev() {
+                outbox.push_front(item);
+            }
+            return Err(e);
+        }
+
+        let restarts = self.restarts.fetch_add(1, Ordering::Relaxed) + 1;
+        println!("✅ Weave-sender restarted (restart #{})", restarts);
+        if !pending.is_empty() {
+            println!(
+                "🔷 Replaying {} unacknowledged Weave items after restart",
+                pending.len()
+            );
+            self.enqueue_items(pending).await?;
+        }
+        Ok(())
+    }
+
+    /// Re-establish the socket connection, re-run init and replay unacknowledged items
+    async fn reconnect(&self) -> Result<(), String> {
+        *self.connection.lock().await = None;
+
+        // Take pending items before init, whose reply would clear the outbox
+        let pending: Vec<EnqueueItem> = self.outbox.lock().await.drain(..).collect();
+
+        self.connect_to_socket().await?;
+        self.send_init().await?;
+
+        if !pending.is_empty() {
+            println!(
+                "🔷 Replaying {} unacknowledged Weave items after reconnect",
+                pending.len()
+            );
+            self.enqueue_items(pending).await?;
+        }
+
+        println!("✅ Weave-sender connection re-established");
+        Ok(())
+    }
+
+    /// Start a call (send to queue with fire-and-forget)
+    pub async fn start_call(
+        &self,
+        start: StartedCallSchemaForInsert,
+    ) -> Result<(), String> {
+        self.send_items(vec![EnqueueItem::start(start)?]).await
+    }
+
+    /// End a call (send to queue with fire-and-forget)
+    pub async fn end_call(&self, end: EndedCallSchemaForInsert) -> Result<(), String> {
+        self.send_items(vec![EnqueueItem::end(end)?]).await
+    }
+
+    /// Flush all pending items
+    pub async fn flush(&self) -> Result<(), String> {
+        self.send_held().await?;
+        let response = self
+            .send_request("flush", serde_json::json!({}), false)
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Flush failed: {}", error.message));
+        }
+
+        Ok(())
+    }
+
+    /// Wait for queue to be empty
+    pub async fn wait_queue_empty(&self) -> Result<(), String> {
+        self.send_held().await?;
+        let response = self
+            .send_request("wait_queue_empty", serde_json::json!({}), false)
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Wait queue empty failed: {}", error.message));
+        }
+
+        Ok(())
+    }
+
+    /// Wait for all in-flight requests to complete
+    pub async fn wait_idle(&self) -> Result<(), String> {
+        // Check if connection exists before trying to send
+        {
+            let conn_guard = self.connection.lock().await;
+            if conn_guard.is_none() {
+                return Err("Connection already closed".to_string());
+            }
+        }
+
+        self.send_held().await?;
+        let response = self
+            .send_request("wait_idle", serde_json::json!({}), false)
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Wait idle failed: {}", error.message));
+        }
+
+        Ok(())
+    }
+
+    /// Get statistics from the sender
+    pub async fn stats(&self) -> Result<serde_json::Value, String> {
+        let response = self
+            .send_request("stats", serde_json::json!({}), false)
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Stats failed: {}", error.message));
+        }
+
+        Ok(response.result.unwrap_or(serde_json::json!({})))
+    }
+
+    /// Shutdown the weave-sender
+    pub async fn shutdown(&self) -> Result<(), String> {
+        // Check if process is still running before attempting communication
+        let process_alive = {
+            let mut process_guard = self.process.lock().await;
+            if let Some(child) = process_guard.as_mut() {
+                // Check if process has already exited
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        println!("🔷 Weave-sender already exited with status: {}", status);
+                        false
+                    }
+                    Ok(None) => true, // Still running
+                    Err(e) => {
+                        eprintln!("⚠️  Failed to check process status: {}", e);
+                        false
+                    }
+                }
+            } else {
+                false
+            }
+        };
+
+        // Only try to send shutdown command if process is alive
+        if process_alive {
+            if let Err(e) = self.end_batch().await {
+                eprintln!("⚠️  Failed to send the open batch of Weave items: {}", e);
+            }
+            if let Err(e) = self
+                .send_request("shutdown", serde_json::json!({}), false)
+                .await
+            {
+                eprintln!("⚠️  Shutdown command failed (process may have died): {}", e);
+            }
+        }
+
+        // Close connection
+        let mut conn_guard = self.connection.lock().await;
+        *conn_guard = None;
+        drop(conn_guard);
+
+        // Wait for process to exit
+        let child = self.process.lock().await.take();
+        if let Some(mut child) = child {
+            match child.try_wait() {
+                Ok(Some(_)) => {
+                    // Already exited, nothing to do
+                }
+                Ok(None) => {
+                    // Still running, wait for it, and kill it if it hangs
+                    match tokio::time::timeout(EXIT_TIMEOUT, child.wait()).await {
+                        Ok(Ok(_)) => {}
+                        Ok(Err(e)) => {
+                            eprintln!("⚠️  Failed to wait for weave-sender process: {}", e)
+                        }
+                        Err(_) => {
+                            eprintln!(
+                                "⚠️  Weave-sender did not exit within {:?}, killing it",
+                                EXIT_TIMEOUT
+                            );
+                            let _ = child.start_kill();
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("⚠️  Failed to check process status: {}", e);
+                }
+            }
+        }
+
+        // Clean up socket file
+        if self.config.socket_path.exists() {
+            if let Err(e) = std::fs::remove_file(&self.config.socket_path) {
+                eprintln!("⚠️  Failed to remove socket file: {}", e);
+            }
+        }
+
+        println!("✅ Weave client shutdown complete");
+        Ok(())
+    }
+}
+
+/// Delivers the calls of the manager to Weave, through the sidecar or over HTTP
+#[async_trait]
+pub trait CallSender: Send + Sync {
+    async fn init(&self) -> Result<(), String>;
+    async fn ping(&self) -> Result<(), String>;
+    /// Checks the connection, recovering it and resending what it holds
+    async fn health_check(&self);
+    async fn start_call(&self, start: StartedCallSchemaForInsert) -> Result<(), String>;
+    async fn end_call(&self, end: EndedCallSchemaForInsert) -> Result<(), String>;
+    async fn begin_batch(&self);
+    async fn end_batch(&self) -> Result<(), String>;
+    async fn flush(&self) -> Result<(), String>;
+    async fn wait_idle(&self) -> Result<(), String>;
+    async fn shutdown(&self) -> Result<(), String>;
+}
+
+#[async_trait]
+impl CallSender for WeaveClient {
+    async fn init(&self) -> Result<(), String> {
+        WeaveClient::init(self).await
+    }
+
+    async fn ping(&self) -> Result<(), String> {
+        WeaveClient::ping(self).await
+    }
+
+    async fn health_check(&self) {
+        WeaveClient::health_check(self).await
+    }
+
+    async fn start_call(&self, start: StartedCallSchemaForInsert) -> Result<(), String> {
+        WeaveClient::start_call(self, start).await
+    }
+
+    async fn end_call(&self, end: EndedCallSchemaForInsert) -> Result<(), String> {
+        WeaveClient::end_call(self, end).await
+    }
+
+    async fn begin_batch(&self) {
+        WeaveClient::begin_batch(self).await
+    }
+
+    async fn end_batch(&self) -> Result<(), String> {
+        WeaveClient::end_batch(self).await
+    }
+
+    async fn flush(&self) -> Result<(), String> {
+        WeaveClient::flush(self).await
+    }
+
+    async fn wait_idle(&self) -> Result<(), String> {
+        WeaveClient::wait_idle(self).await
+    }
+
+    async fn shutdown(&self) -> Result<(), String> {
+        WeaveClient::shutdown(self).await
+    }
+}
+
+impl Drop for WeaveClient {
+    fn drop(&mut self) {
+        // Try to clean up, but don't panic if it fails
+        // Note: The process is only signalled here, nothing waits for it to exit
+        // In production, you should call shutdown() explicitly before dropping
+        if let Some(mut child) = self.process.try_lock().ok().and_then(|mut g| g.take()) {
+            let _ = child.start_kill();
+        }
+
+        if self.config.socket_path.exists() {
+            let _ = std::fs::remove_file(&self.config.socket_path);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use crate::weave_client::{
+    CallSender, DeliveredItems, EndedCallSchemaForInsert, EnqueueItem, StartedCallSchemaForInsert,
+    WeaveConfig, OUTBOX_CAPACITY,
+};
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+/// Weave client that posts the calls straight to the trace server: a start or end on
+/// its own to `/call/start` or `/call/end`, the items of a batch together to
+/// `/call/upsert_batch`. Every request is acknowledged by its response, so there is
+/// no sidecar process to supervise and nothing to replay after a reconnect. Each
+/// request may take `request_timeout`; a flush or shutdown stops posting once
+/// `drain_timeout` has passed and keeps the rest unsent.
+pub struct WeaveHttpClient {
+    config: WeaveConfig,
+    http: reqwest::Client,
+    /// Items the server accepted. Call ids are derived from the event, so a start or
+    /// end submitted again is dropped instead of doubling the call.
+    delivered: Arc<Mutex<DeliveredItems>>,
+    /// Items held by the open batch, None outside of one
+    batch: Arc<Mutex<Option<Vec<EnqueueItem>>>>,
+    /// Items of batch requests that failed, sent again ahead of the next batch and by
+    /// the health check
+    unsent: Arc<Mutex<VecDeque<EnqueueItem>>>,
+}
+
+impl WeaveHttpClient {
+    pub fn new(config: WeaveConfig) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .build()
+            .map_err(|e| format!("Failed to build Weave HTTP client: {}", e))?;
+
+        Ok(Self {
+            config,
+            http,
+            delivered: Arc::new(Mutex::new(DeliveredItems::default())),
+            batch: Arc::new(Mutex::new(None)),
+            unsent: Arc::new(Mutex::new(VecDeque::new())),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Sends new items, subject to fault injection, dropping the copies of items
+    /// already accepted, waiting to be resent or held by the open batch. While a
+    /// batch is open the items join it, and go out once it is full or closed.
+    async fn send_items(&self, items: Vec<EnqueueItem>) -> Result<(), String> {
+        if let Some(ref faults) = self.config.faults {
+            faults.inject().await?;
+        }
+
+        let mut batch = self.batch.lock().await;
+        let items: Vec<EnqueueItem> = {
+            let delivered = self.delivered.lock().await;
+            let unsent = self.unsent.lock().await;
+            let held = batch.as_deref().unwrap_or_default();
+            items
+                .into_iter()
+                .filter(|item| {
+                    let Some(key) = item.key() else {
+                        return true;
+                    };
+                    let duplicate = delivered.keys.contains(&key)
+                        || unsent.iter().any(|queued| queued.has_key(&key))
+                        || held.iter().any(|queued| queued.has_key(&key));
+                    if duplicate {
+                        println!("🔷 Suppressed duplicate Weave {} of call {}", key.0, key.1);
+                    }
+                    !duplicate
+                })
+                .collect()
+        };
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        if batch.is_none() {
+            drop(batch);
+            // Outside of a batch a failed item is the caller's to retry
+            return self.post(&items).await;
+        }
+        let ready = batch.as_mut().and_then(|held| {
+            held.extend(items);
+            (held.len() >= self.config.enqueue_batch_items).then(|| std::mem::take(held))
+        });
+        drop(batch);
+        match ready {
+            Some(items) => self.send_batch(items, None).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Enqueues the items held by the open batch, keeping it open, within the drain
+    /// timeout
+    async fn send_held(&self) -> Result<(), String> {
+        let held = match self.batch.lock().await.as_mut() {
+            Some(held) => std::mem::take(held),
+            None => Vec::new(),
+        };
+        self.send_batch(held, Some(Instant::now() + self.config.drain_timeout))
+            .await
+    }
+
+    /// Posts the unsent items of earlier batches followed by these, one upsert request
+    /// per `enqueue_batch_items` items, starting no request after `deadline`. Whatever
+    /// could not be posted stays unsent.
+    async fn send_batch(
+        &self,
+        items: Vec<EnqueueItem>,
+        deadline: Option<Instant>,
+    ) -> Result<(), String> {
+        let mut pending: VecDeque<EnqueueItem> = self.unsent.lock().await.drain(..).collect();
+        pending.extend(items);
+
+        let chunk = self.config.enqueue_batch_items.max(1);
+        while !pending.is_empty() {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                let left = pending.len();
+                self.keep_unsent(pending).await;
+                return Err(format!(
+                    "Weave delivery timed out after {:?} with {} items left",
+                    self.config.drain_timeout, left
+                ));
+            }
+            let items: Vec<EnqueueItem> = pending.drain(..chunk.min(pending.len())).collect();
+            if let Err(e) = self.post(&items).await {
+                self.keep_unsent(items.into_iter().chain(pending)).await;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Queues items to be sent again, dropping the oldest beyond the outbox capacity
+    async fn keep_unsent(&self, items: impl IntoIterator<Item = EnqueueItem>) {
+        let mut unsent = self.unsent.lock().await;
+        unsent.extend(items);
+        while unsent.len() > OUTBOX_CAPACITY {
+            unsent.pop_front();
+        }
+    }
+
+    /// Posts the items in one request and records them as accepted
+    async fn post(&self, items: &[EnqueueItem]) -> Result<(), String> {
+        let (url, body) = match items {
+            [] => return Ok(()),
+            [item] => (
+                self.url(&format!("/call/{}", item.item_type)),
+                item.payload.clone(),
+            ),
+            _ => {
+                let batch: Vec<serde_json::Value> = items
+                    .iter()
+                    .map(|item| serde_json::json!({"mode": item.item_type, "req": item.payload}))
+                    .collect();
+                (
+                    self.url("/call/upsert_batch"),
+                    serde_json::json!({ "batch": batch }),
+                )
+            }
+        };
+        let body = serde_json::to_string(&body)
+            .map_err(|e| format!("Failed to serialize request: {}", e))?;
+
+        let response = self
+            .http
+            .post(&url)
+            .basic_auth("api", Some(&self.config.api_key))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    format!("Weave request to {} timed out", url)
+                } else {
+                    format!("Weave request to {} failed: {}", url, e)
+                }
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(format!(
+                "Weave server returned {} for {}: {}",
+                status, url, detail
+            ));
+        }
+
+        let mut delivered = self.delivered.lock().await;
+        for key in items.iter().filter_map(EnqueueItem::key) {
+            delivered.insert(key);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CallSender for WeaveHttpClient {
+    /// Checks the trace server is reachable before the first calls
+    async fn init(&self) -> Result<(), String> {
+        self.ping().await?;
+        println!(
+            "✅ Weave HTTP client initialized ({})",
+            self.config.base_url
+        );
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<(), String> {
+        let response = self
+            .http
+            .get(self.url("/health"))
+            .send()
+            .await
+            .map_err(|e| format!("Ping failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ping failed: status {}", response.status()));
+        }
+        Ok(())
+    }
+
+    /// Pings the trace server and resends the items of failed batches once it answers
+    async fn health_check(&self) {
+        if let Err(e) = self.ping().await {
+            eprintln!("⚠️  Weave server unreachable: {}", e);
+            return;
+        }
+
+        let unsent = self.unsent.lock().await.len();
+        if unsent == 0 {
+            return;
+        }
+        println!("🔷 Resending {} unsent Weave items", unsent);
+        if let Err(e) = self.send_batch(Vec::new(), None).await {
+            eprintln!("⚠️  Failed to resend Weave items: {}", e);
+        }
+    }
+
+    async fn start_call(&self, start: StartedCallSchemaForInsert) -> Result<(), String> {
+        self.send_items(vec![EnqueueItem::start(start)?]).await
+    }
+
+    async fn end_call(&self, end: EndedCallSchemaForInsert) -> Result<(), String> {
+        self.send_items(vec![EnqueueItem::end(end)?]).await
+    }
+
+    /// Opens a batch: the starts and ends sent until `end_batch` go out together, in
+    /// one upsert request per `enqueue_batch_items` items
+    async fn begin_batch(&self) {
+        if self.config.enqueue_batch_items > 0 {
+            self.batch.lock().await.get_or_insert_with(Vec::new);
+        }
+    }
+
+    async fn end_batch(&self) -> Result<(), String> {
+        let held = self.batch.lock().await.take().unwrap_or_default();
+        self.send_batch(held, None).await
+    }
+
+    /// Sends the held and unsent items; every request already waited for its response
+    async fn flush(&self) -> Result<(), String> {
+        self.send_held().await
+    }
+
+    async fn wait_idle(&self) -> Result<(), String> {
+        self.send_held().await
+    }
+
+    async fn shutdown(&self) -> Result<(), String> {
+        let held = self.batch.lock().await.take().unwrap_or_default();
+        let deadline = Instant::now() + self.config.drain_timeout;
+        if let Err(e) = self.send_batch(held, Some(deadline)).await {
+            eprintln!("⚠️  Failed to send the open batch of Weave items: {}", e);
+        }
+
+        let unsent = self.unsent.lock().await.len();
+        if unsent > 0 {
+            eprintln!(
+                "⚠️  {} Weave items could not be sent before shutdown",
+                unsent
+            );
+        }
+
+        println!("✅ Weave client shutdown complete");
+        Ok(())
+    }
+}
@@ -0,0 +1,2655 @@
+use crate::call_batching::{Batch, CallBatcher, BATCH_SUFFIX};
+use crate::delivery_policy::{DeliveryPolicies, DeliveryPolicy};
+use crate::image_pool::ImagePool;
+use crate::op_attributes::OpAttributes;
+use crate::op_schema;
+use crate::screenshot_hash;
+use crate::screenshot_overlay::ScreenshotOverlay;
+use crate::trace_store::{SessionTrace, TraceStore};
+use crate::weave_client::{
+    CallSender, EndedCallSchemaForInsert, StartedCallSchemaForInsert, Transport, WeaveClient,
+    WeaveConfig,
+};
+use crate::weave_http::WeaveHttpClient;
+use crate::weave_retry::RetryQueue;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Utc;
+use factorio_events_core::bottleneck::Bottleneck;
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::AppConfig;
+use factorio_events_core::correlation::{thread_id_for, StepCorrelation};
+use factorio_events_core::event_mediator::{PlayerInfo, ProcessSummary};
+use factorio_events_core::event_sink::{
+    CallTracer, EventSink, GameEvent, SessionStart, StatsCycle,
+};
+use factorio_events_core::probes::RegistryChanges;
+use factorio_events_core::project_routing::ProjectRouter;
+use factorio_events_core::prototypes::Prototypes;
+use factorio_events_core::rocket_silo::{SiloPipeline, SiloReport, SpanChange};
+use factorio_events_core::run_index::RunRecord;
+use factorio_events_core::run_metadata::RunMetadata;
+use factorio_events_core::science::SCIENCE_PACKS;
+use factorio_events_core::spool::Spool;
+use factorio_events_core::units;
+use factorio_events_core::upload_budget::UploadBudget;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// A singleton service that manages Weave sessions for Factorio events.
+/// Handles trace logging via start_call() and end_call() operations.
+/// Weave sessions map 1:1 with WandB sessions using the same session_id.
+pub struct WeaveManager {
+    current_session_id: Arc<Mutex<Option<String>>>,
+    /// Factorio session id of the current session, the key of its stored trace
+    source_session_id: Arc<Mutex<Option<String>>>,
+    active_calls: Arc<Mutex<HashMap<String, CallContext>>>,
+    /// Cache for research events: key is "tech_name:tech_level", value is the call_id
+    research_cache: Arc<Mutex<HashMap<String, String>>>,
+    /// Levels researched this session of every infinite tech, by tech name
+    research_progress: Arc<Mutex<HashMap<String, ResearchProgress>>>,
+    client: Arc<Mutex<Option<Arc<dyn CallSender>>>>,
+    config: WeaveConfig,
+    /// Root trace of the current session; every call in the session joins it
+    session_trace: Arc<Mutex<Option<SessionTrace>>>,
+    trace_store: Arc<Mutex<TraceStore>>,
+    /// Optional upload budget; calls of deferrable ops that exceed it go to the spool
+    upload_budget: Option<Arc<UploadBudget>>,
+    spool: Arc<Mutex<Option<Spool>>>,
+    /// Launch metadata attached to every call
+    metadata: RunMetadata,
+    /// Max hash distance at which a snapshot counts as a duplicate; None disables dedupe
+    snapshot_dedupe_distance: Option<u32>,
+    /// Perceptual hash of the last uploaded screenshot per surface
+    last_snapshot_hash: Arc<Mutex<HashMap<String, u64>>>,
+    skipped_duplicate_snapshots: Arc<AtomicU64>,
+    /// Optional text overlay drawn on screenshots before upload
+    overlay: Option<Arc<ScreenshotOverlay>>,
+    /// Runs hashing, overlays and encoding of images off the event path
+    image_pool: Arc<ImagePool>,
+    /// Directory the screenshot paths of the stats events are relative to
+    output_path: Option<PathBuf>,
+    /// Step alignment shared with the WandB manager
+    correlation: Arc<StepCorrelation>,
+    /// (run id, url) of the WandB run of the current session
+    wandb_run: Arc<Mutex<Option<(String, String)>>>,
+    /// Event calls this many ticks after a screenshot reference it; 0 disables links
+    screenshot_link_ticks: u64,
+    /// Log a call per drain cycle
+    drain_summary: bool,
+    /// Latest screenshot, referenced by the event calls shortly after it
+    last_snapshot: Arc<Mutex<Option<SnapshotRef>>>,
+    /// What happens to instant calls that fail to send, per event class
+    delivery: DeliveryPolicies,
+    /// Calls that failed to send, waiting for their next attempt
+    retries: Arc<Mutex<RetryQueue<RetryCall>>>,
+    /// Attempts at the start or end of a span before it is given up
+    span_attempts: u32,
+    /// Why Weave is off, when its configuration is incomplete
+    disabled: Option<String>,
+    /// Routes sessions to other projects than the configured one
+    router: Arc<ProjectRouter>,
+    /// `entity/project` the current session was routed to, when not the default one
+    routed_project: Arc<Mutex<Option<String>>>,
+    /// Launch pipeline of every rocket silo, traced per launch
+    silos: Arc<Mutex<SiloPipeline>>,
+    /// Instant calls of the batched ops, waiting for their tick window to close
+    batcher: Arc<Mutex<CallBatcher>>,
+    /// Instant calls of recent ticks, numbering the calls of an op at the same tick
+    call_occurrences: Arc<Mutex<CallOccurrences>>,
+    /// Grouping attributes (category, ...) added to the calls of each op
+    op_attributes: OpAttributes,
+}
+
+/// Namespace of the call ids derived by `call_id_for`
+const CALL_ID_NAMESPACE: Uuid = Uuid::from_u128(0x6d1f_3a52_c8e4_4b07_9a2e_f05c_71b3_d468);
+
+/// Ticks the instant calls of a tick are counted for, so calls of events reordered by
+/// the readers keep apart
+const CALL_OCCURRENCE_TICKS: u64 = 60 * 60;
+
+/// Id of a call, derived from its session, op, tick and a key telling apart the calls
+/// of the op at that tick. The same event always gets the same call id, e.g. when a
+/// crashed drain replays its write-ahead log, so a call submitted twice is one call in
+/// Weave and the client's outbox can spot the copy.
+fn call_id_for(session_id: &str, operation: &str, tick: u64, key: &str) -> String {
+    let name = format!("{}\n{}\n{}\n{}", session_id, operation, tick, key);
+    Uuid::new_v5(&CALL_ID_NAMESPACE, name.as_bytes()).to_string()
+}
+
+/// How many instant calls of each op and player were made at the recent ticks
+#[derive(Default)]
+struct CallOccurrences {
+    latest_tick: u64,
+    counts: HashMap<(String, Option<u32>, u64), u32>,
+}
+
+impl CallOccurrences {
+    /// Key of a new call of the op and player at the tick: the player and how many
+    /// such calls came before it
+    fn next_key(&mut self, operation: &str, player_index: Option<u32>, tick: u64) -> String {
+        if tick > self.latest_tick {
+            self.latest_tick = tick;
+            self.counts
+                .retain(|(_, _, seen), _| seen + CALL_OCCURRENCE_TICKS >= tick);
+        }
+        let count = self
+            .counts
+            .entry((operation.to_string(), player_index, tick))
+            .or_insert(0);
+        let key = match player_index {
+            Some(index) => format!("{}#{}", index, count),
+            None => format!("#{}", count),
+        };
+        *count += 1;
+        key
+    }
+}
+
+/// The levels of an infinite tech researched in the current session, traced as
+/// `research` calls under one `research_progress` span
+#[derive(Debug, Clone)]
+struct ResearchProgress {
+    first_level: u32,
+    last_level: Option<u32>,
+    levels_completed: u32,
+    /// Tick of the latest level event, when the span ends with the session
+    last_tick: u64,
+}
+
+/// Key of the `research_progress` span of an infinite tech
+fn research_progress_key(tech_name: &str) -> String {
+    format!("research_progress:{}", tech_name)
+}
+
+/// A captured screenshot and the call that uploaded it
+#[derive(Debug, Clone)]
+struct SnapshotRef {
+    tick: u64,
+    surface: String,
+    path: String,
+    /// `player_snapshot` call holding the image
+    call_id: Option<String>,
+}
+
+/// Ops whose calls may be deferred when the upload budget is exhausted
+const DEFERRABLE_OPS: &[&str] = &[
+    "player_snapshot",
+    "on_built_entity",
+    "on_player_mined_entity",
+    "on_player_crafted_item",
+    "exploration_heatmap",
+    "pollution_map",
+];
+
+/// Ops whose calls reference a screenshot taken shortly before them
+const SCREENSHOT_LINKED_OPS: &[&str] = &[
+    "research",
+    "on_built_entity",
+    "on_player_mined_entity",
+    "on_player_crafted_item",
+    "on_rocket_launched",
+    "on_player_died",
+];
+
+/// Exception of the calls a crashed client left open, ended after its restart
+const ABORTED_EXCEPTION: &str = "Aborted: the client stopped before the call ended";
+
+/// Size limit of a spool segment holding deferred calls
+const SPOOL_SEGMENT_BYTES: u64 = 4 * 1024 * 1024;
+
+/// A fully resolved instant call, ready to send or to park in the spool
+#[derive(Debug, Serialize, Deserialize)]
+struct InstantCall {
+    call_id: String,
+    trace_id: String,
+    parent_id: Option<String>,
+    thread_id: String,
+    operation: String,
+    tick: u64,
+    inputs: HashMap<String, serde_json::Value>,
+    outputs: HashMap<String, serde_json::Value>,
+    /// Whether the start already went out, so only the end is left to send
+    #[serde(default)]
+    started: bool,
+}
+
+/// A call waiting in the retry queue: an instant call, or the start or end of a span
+/// (session roots, research and custom calls), whose halves are sent apart
+enum RetryCall {
+    Instant(InstantCall),
+    Start(StartedCallSchemaForInsert),
+    End(EndedCallSchemaForInsert),
+}
+
+impl InstantCall {
+    /// Approximate upload size of the call payload
+    fn estimated_bytes(&self) -> usize {
+        serde_json::to_vec(&self.inputs).map(|v| v.len()).unwrap_or(0)
+            + serde_json::to_vec(&self.outputs).map(|v| v.len()).unwrap_or(0)
+    }
+}
+
+/// Encodes PNG bytes as a data URI for Weave Image objects
+fn png_data_uri(png: &[u8]) -> String {
+    format!("data:image/png;base64,{}", BASE64.encode(png))
+}
+
+/// Context for an active Weave call/trace, persisted with the session trace so a warm
+/// start can end calls opened before a crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CallContext {
+    call_id: String,
+    trace_id: String,
+    session_id: String,
+    start_tick: u64,
+    inputs: HashMap<String, serde_json::Value>,
+}
+
+impl WeaveManager {
+    /// Creates a new Weave manager instance
+    pub fn new(app_config: &AppConfig) -> Self {
+        let mut disabled = None;
+        let config = match WeaveConfig::from_app(app_config) {
+            Ok(cfg) => {
+                println!(
+                    "✅ Weave config loaded: entity={}, project={}, transport={}",
+                    cfg.entity,
+                    cfg.project,
+                    cfg.transport.name()
+                );
+                cfg
+            }
+            Err(e) => {
+                eprintln!("⚠️  Failed to load Weave config: {}", e);
+                eprintln!("⚠️  Weave integration will be disabled");
+                disabled = Some(e);
+                // Create a dummy config - client won't be initialized
+                WeaveConfig {
+                    entity: "unknown".to_string(),
+                    project: "unknown".to_string(),
+                    base_url: "https://trace.wandb.ai".to_string(),
+                    api_key: "dummy".to_string(),
+                    transport: Transport::Http,
+                    binary_path: std::path::PathBuf::from("/dev/null"),
+                    socket_path: std::path::PathBuf::from("/dev/null"),
+                    compression: false,
+                    ping_interval: std::time::Duration::from_secs(30),
+                    request_timeout: std::time::Duration::from_secs(10),
+                    drain_timeout: std::time::Duration::from_secs(120),
+                    trace_store_path: std::path::PathBuf::from("/dev/null"),
+                    spool_dir: std::path::PathBuf::from("/dev/null"),
+                    spool_retention: std::time::Duration::from_secs(24 * 3600),
+                    spool_compact_interval: std::time::Duration::from_secs(600),
+                    faults: None,
+                    enqueue_batch_items: 0,
+                }
+            }
+        };
+
+        let trace_store = TraceStore::load(config.trace_store_path.clone());
+        let delivery = DeliveryPolicies::from_settings(&app_config.weave.delivery);
+        let screenshots = &app_config.weave.screenshots;
+        println!("🔷 Weave delivery policies: {}", delivery.describe());
+
+        WeaveManager {
+            current_session_id: Arc::new(Mutex::new(None)),
+            source_session_id: Arc::new(Mutex::new(None)),
+            active_calls: Arc::new(Mutex::new(HashMap::new())),
+            research_cache: Arc::new(Mutex::new(HashMap::new())),
+            research_progress: Arc::new(Mutex::new(HashMap::new())),
+            client: Arc::new(Mutex::new(None)),
+            config,
+            session_trace: Arc::new(Mutex::new(None)),
+            trace_store: Arc::new(Mutex::new(trace_store)),
+            upload_budget: None,
+            spool: Arc::new(Mutex::new(None)),
+            metadata: RunMetadata::default(),
+            snapshot_dedupe_distance: screenshots.dedupe.then_some(screenshots.dedupe_distance),
+            last_snapshot_hash: Arc::new(Mutex::new(HashMap::new())),
+            skipped_duplicate_snapshots: Arc::new(AtomicU64::new(0)),
+            overlay: ScreenshotOverlay::from_settings(screenshots).map(Arc::new),
+            image_pool: Arc::new(ImagePool::new(&app_config.images)),
+            output_path: app_config.output_path.as_ref().map(PathBuf::from),
+            correlation: Arc::new(StepCorrelation::new()),
+            wandb_run: Arc::new(Mutex::new(None)),
+            screenshot_link_ticks: app_config.weave.screenshot_link_ticks,
+            drain_summary: app_config.weave.drain_summary,
+            last_snapshot: Arc::new(Mutex::new(None)),
+            delivery,
+            retries: Arc::new(Mutex::new(RetryQueue::new(&app_config.weave.retry))),
+            span_attempts: app_config.weave.retry.max_attempts.max(1),
+            disabled,
+            router: Arc::new(ProjectRouter::default()),
+            routed_project: Arc::new(Mutex::new(None)),
+            silos: Arc::new(Mutex::new(SiloPipeline::default())),
+            batcher: Arc::new(Mutex::new(CallBatcher::new(app_config.weave.batching.clone()))),
+            call_occurrences: Arc::new(Mutex::new(CallOccurrences::default())),
+            op_attributes: OpAttributes::new(app_config.weave.op_attributes.clone()),
+        }
+    }
+
+    /// Why Weave is off, or None when it is configured. A disabled manager is left out
+    /// of the mediator's sinks.
+    pub fn disabled_reason(&self) -> Option<&str> {
+        self.disabled.as_deref()
+    }
+
+    /// Limits uploads to a shared budget, deferring screenshots and entity traces
+    pub fn with_upload_budget(mut self, budget: Option<Arc<UploadBudget>>) -> Self {
+        self.upload_budget = budget;
+        self
+    }
+
+    /// Routes the calls of matching sessions to other projects
+    pub fn with_project_router(mut self, router: Arc<ProjectRouter>) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Project the calls of the current session go to
+    async fn project_id(&self) -> String {
+        match *self.routed_project.lock().await {
+            Some(ref project_id) => project_id.clone(),
+            None => self.config.project_id(),
+        }
+    }
+
+    /// Attaches launch metadata to the attributes of every call
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Shares step alignment with the WandB manager, tagging every call with its step
+    pub fn with_correlation(mut self, correlation: Arc<StepCorrelation>) -> Self {
+        self.correlation = correlation;
+        self
+    }
+
+    /// Initialize the Weave client connection
+    async fn ensure_client(&self) -> Result<(), String> {
+        let mut client_guard = self.client.lock().await;
+
+        if client_guard.is_some() {
+            return Ok(());
+        }
+
+        let client: Arc<dyn CallSender> = match self.config.transport {
+            Transport::Sidecar => Arc::new(WeaveClient::new(self.config.clone())),
+            Transport::Http => Arc::new(WeaveHttpClient::new(self.config.clone())?),
+        };
+        client.init().await?;
+
+        *client_guard = Some(client);
+        drop(client_guard);
+
+        self.start_health_check();
+        self.start_spool_compaction();
+        Ok(())
+    }
+
+    /// Launches the sidecar (or reaches the trace server over HTTP) and runs its init now
+    /// instead of at the first event, so a misconfiguration shows up at startup and the
+    /// first calls don't wait for it
+    pub async fn preflight(&self) -> Result<(), String> {
+        let started = std::time::Instant::now();
+        self.ensure_client().await?;
+        if let Some(client) = self.client.lock().await.as_ref() {
+            client.ping().await?;
+        }
+
+        println!(
+            "✅ Weave {} transport ready in {:.1}s",
+            self.config.transport.name(),
+            started.elapsed().as_secs_f64()
+        );
+        client_events::emit(ClientEvent::SinkReady {
+            component: "weave".to_string(),
+        });
+        Ok(())
+    }
+
+    /// Spawns a background task that periodically pings the transport and recovers it on failure
+    fn start_health_check(&self) {
+        let client = self.client.clone();
+        let interval = self.config.ping_interval;
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // first tick completes immediately
+
+            loop {
+                ticker.tick().await;
+
+                // A recovery may restart the sidecar, so the check runs on a handle of
+                // its own rather than holding up every call behind the lock
+                let Some(client) = client.lock().await.clone() else {
+                    break;
+                };
+                client.health_check().await;
+            }
+        });
+    }
+
+    /// Spawns a background task that periodically compacts the spool, merging small
+    /// segments and deleting delivered ones past retention
+    fn start_spool_compaction(&self) {
+        if self.upload_budget.is_none() {
+            return;
+        }
+        let spool = self.spool.clone();
+        let dir = self.config.spool_dir.clone();
+        let interval = self.config.spool_compact_interval;
+        let retention = self.config.spool_retention;
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                let mut spool_guard = spool.lock().await;
+                // Also clean up segments left behind by earlier runs
+                if spool_guard.is_none() && dir.is_dir() {
+                    match Spool::open(dir.clone(), SPOOL_SEGMENT_BYTES) {
+                        Ok(opened) => *spool_guard = Some(opened),
+                        Err(e) => eprintln!("⚠️  Failed to open spool for compaction: {}", e),
+                    }
+                }
+                let Some(spool) = spool_guard.as_mut() else {
+                    continue;
+                };
+
+                match spool.compact(retention) {
+                    Ok(report) if report.is_empty() => {}
+                    Ok(report) => println!(
+                        "📶 Compacted spool: merged {} segments, deleted {} expired, reclaimed {} bytes",
+                        report.merged_segments, report.expired_segments, report.reclaimed_bytes
+                    ),
+                    Err(e) => eprintln!("⚠️  Spool compaction failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Sets the WandB run every following call links to
+    pub async fn set_wandb_run(&self, run_info: Option<(String, String)>) {
+        *self.wandb_run.lock().await = run_info;
+    }
+
+    /// Handles a session_init event. Creates a new Weave session matching WandB.
+    /// `source_session_id` is the Factorio session id, used to re-adopt the session's
+    /// root trace when the client restarts mid-session.
+    pub async fn handle_session_init(
+        &self,
+        session_id: String,
+        source_session_id: String,
+        tick: u64,
+        level_name: String,
+    ) {
+        println!("🔷 Weave session init: {}", session_id);
+
+        // The previous session's batches and research progress go out with it
+        let open = self.batcher.lock().await.drain();
+        self.log_batches(open).await;
+        self.end_research_progress().await;
+
+        // End any active calls from previous session
+        self.end_all_calls("Session changed").await;
+        *self.session_trace.lock().await = None;
+        // Calls a crash left open in other sessions won't be resumed anymore
+        self.abort_orphaned_calls(Some(&source_session_id)).await;
+
+        // Calls of the new session go to the project it is routed to
+        let routed_project = self.router.weave_project_id(
+            &source_session_id,
+            &level_name,
+            &self.config.entity,
+            &self.config.project,
+        );
+        *self.routed_project.lock().await = routed_project.clone();
+
+        // Clear research cache for new session
+        self.research_cache.lock().await.clear();
+        println!("🔷 Research cache cleared for new session");
+        self.silos.lock().await.reset();
+
+        // Store new session ID
+        *self.current_session_id.lock().await = Some(session_id.clone());
+        *self.source_session_id.lock().await = Some(source_session_id.clone());
+
+        // Ensure client is initialized
+        if let Err(e) = self.ensure_client().await {
+            eprintln!("⚠️  Failed to initialize Weave client: {}", e);
+            client_events::emit(ClientEvent::Error {
+                component: "weave".to_string(),
+                message: format!("Failed to initialize client: {}", e),
+            });
+            return;
+        }
+
+        println!(
+            "🔷 Weave session created: {} (tick: {}, level: {})",
+            session_id, tick, level_name
+        );
+
+        // Log the session_init event as an atomic call
+        let mut inputs = HashMap::new();
+        inputs.insert("session_id".to_string(), serde_json::json!(&session_id));
+        inputs.insert("tick".to_string(), serde_json::json!(tick));
+        inputs.insert("level_name".to_string(), serde_json::json!(&level_name));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("session_id".to_string(), serde_json::json!(&session_id));
+        outputs.insert("level_name".to_string(), serde_json::json!(level_name));
+
+        let stored_trace = self.trace_store.lock().await.get(&source_session_id).cloned();
+
+        match stored_trace {
+            Some(trace) => {
+                // Client restarted mid-session: rejoin the original trace
+                self.adopt_trace(source_session_id, trace).await;
+                self.log_call("session_init".to_string(), None, tick, inputs, outputs)
+                    .await;
+            }
+            None => {
+                // The session_init call becomes the root of the session's trace
+                let trace = SessionTrace {
+                    trace_id: Uuid::now_v7().to_string(),
+                    root_call_id: call_id_for(&session_id, "session_init", tick, ""),
+                    updated_at: Utc::now(),
+                    open_calls: HashMap::new(),
+                    project_id: routed_project,
+                };
+
+                if let Err(e) = self
+                    .send_start_call(
+                        trace.root_call_id.clone(),
+                        trace.trace_id.clone(),
+                        None,
+                        thread_id_for(&session_id, None),
+                        "session_init".to_string(),
+                        tick,
+                        inputs,
+                    )
+                    .await
+                {
+                    eprintln!("⚠️  Failed to send start call to Weave: {}", e);
+                }
+                if let Err(e) = self
+                    .send_end_call(trace.root_call_id.clone(), tick, 0, outputs, None)
+                    .await
+                {
+                    eprintln!("⚠️  Failed to send end call to Weave: {}", e);
+                }
+
+                *self.session_trace.lock().await = Some(trace.clone());
+                if let Err(e) = self
+                    .trace_store
+                    .lock()
+                    .await
+                    .insert(source_session_id, trace)
+                {
+                    eprintln!("⚠️  Failed to persist Weave session trace: {}", e);
+                }
+            }
+        }
+
+        self.publish_op_schemas(tick).await;
+    }
+
+    /// Resumes a session after a client crash without a new session_init: calls join
+    /// the session's stored trace again, and the calls open at the crash can still end.
+    /// Returns false when no trace of the session is stored; new calls then start one.
+    pub async fn resume_session(&self, session_id: String, source_session_id: String) -> bool {
+        *self.current_session_id.lock().await = Some(session_id);
+        *self.source_session_id.lock().await = Some(source_session_id.clone());
+        if let Err(e) = self.ensure_client().await {
+            eprintln!("⚠️  Failed to initialize Weave client: {}", e);
+        }
+
+        let stored_trace = self.trace_store.lock().await.get(&source_session_id).cloned();
+        match stored_trace {
+            Some(trace) => {
+                self.adopt_trace(source_session_id, trace).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes a stored trace the current one, restoring the calls it had open
+    async fn adopt_trace(&self, source_session_id: String, mut trace: SessionTrace) {
+        println!(
+            "🔷 Resuming Weave trace {} for session {} ({} open calls)",
+            trace.trace_id,
+            source_session_id,
+            trace.open_calls.len()
+        );
+        trace.updated_at = Utc::now();
+        // The trace stays in the project it started in
+        *self.routed_project.lock().await = trace.project_id.clone();
+        self.active_calls
+            .lock()
+            .await
+            .extend(trace.open_calls.clone());
+        *self.session_trace.lock().await = Some(trace.clone());
+        if let Err(e) = self
+            .trace_store
+            .lock()
+            .await
+            .insert(source_session_id, trace)
+        {
+            eprintln!("⚠️  Failed to persist Weave session trace: {}", e);
+        }
+    }
+
+    /// Ends the calls a crashed client left open in the stored traces of other sessions
+    /// than `keep` with an "aborted" exception, so they don't dangle in the Weave UI.
+    /// The calls of `keep` stay open, to be resumed when its session goes on.
+    pub async fn abort_orphaned_calls(&self, keep: Option<&str>) {
+        let orphaned = match self.trace_store.lock().await.take_open_calls(keep) {
+            Ok(orphaned) => orphaned,
+            Err(e) => {
+                eprintln!("⚠️  Failed to persist Weave session trace: {}", e);
+                return;
+            }
+        };
+        if orphaned.is_empty() {
+            return;
+        }
+        if let Err(e) = self.ensure_client().await {
+            eprintln!("⚠️  Failed to ensure Weave client: {}", e);
+            return;
+        }
+
+        for (source_session_id, trace, calls) in orphaned {
+            println!(
+                "🔷 Aborting {} Weave calls left open by session {}",
+                calls.len(),
+                source_session_id
+            );
+            let project_id = trace
+                .project_id
+                .clone()
+                .unwrap_or_else(|| self.config.project_id());
+            for context in calls {
+                let mut outputs = HashMap::new();
+                outputs.insert("session_id".to_string(), serde_json::json!(context.session_id));
+                let mut end = self
+                    .end_payload(
+                        context.call_id,
+                        context.start_tick,
+                        0,
+                        outputs,
+                        Some(ABORTED_EXCEPTION.to_string()),
+                    )
+                    .await;
+                // The call ends in the project its trace started in
+                end.project_id = project_id.clone();
+                if let Err(e) = self.submit_end(&end).await {
+                    if let Err(e) = self.retry_span(RetryCall::End(end), e).await {
+                        eprintln!("⚠️  Failed to abort call: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Records the open calls with the stored trace of the session
+    async fn persist_open_calls(&self) {
+        let Some(source_session_id) = self.source_session_id.lock().await.clone() else {
+            return;
+        };
+        let open_calls = self.active_calls.lock().await.clone();
+        let trace = {
+            let mut trace_guard = self.session_trace.lock().await;
+            let Some(trace) = trace_guard.as_mut() else {
+                return;
+            };
+            trace.open_calls = open_calls;
+            trace.updated_at = Utc::now();
+            trace.clone()
+        };
+        if let Err(e) = self
+            .trace_store
+            .lock()
+            .await
+            .insert(source_session_id, trace)
+        {
+            eprintln!("⚠️  Failed to persist Weave session trace: {}", e);
+        }
+    }
+
+    /// Returns the trace_id and parent call for a new call in the current session
+    async fn trace_context(&self) -> (String, Option<String>) {
+        match self.session_trace.lock().await.as_ref() {
+            Some(trace) => (trace.trace_id.clone(), Some(trace.root_call_id.clone())),
+            None => (Uuid::now_v7().to_string(), None),
+        }
+    }
+
+    /// Returns the trace id of the current session
+    pub async fn current_trace_id(&self) -> Option<String> {
+        self.session_trace
+            .lock()
+            .await
+            .as_ref()
+            .map(|trace| trace.trace_id.clone())
+    }
+
+    /// Publishes the input schema of every op as Weave objects for the new session
+    async fn publish_op_schemas(&self, tick: u64) {
+        let mut outputs = HashMap::new();
+        for schema in op_schema::all() {
+            outputs.insert(schema.op_name.to_string(), schema.to_json());
+        }
+
+        self.log_call("op_schemas".to_string(), None, tick, HashMap::new(), outputs)
+            .await;
+    }
+
+    /// Starts a new Weave call/trace
+    pub async fn start_call(
+        &self,
+        call_id: String,
+        operation: String,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+    ) {
+        self.start_child_call(call_id, None, operation, tick, inputs)
+            .await;
+    }
+
+    /// Starts a new Weave call under the active call `parent`, or under the session
+    /// root when there is none
+    async fn start_child_call(
+        &self,
+        call_id: String,
+        parent: Option<&str>,
+        operation: String,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+    ) {
+        // Ensure client is initialized (creates session if needed)
+        if let Err(e) = self.ensure_client().await {
+            eprintln!("⚠️  Failed to ensure Weave client: {}", e);
+            return;
+        }
+
+        // Get active session
+        let session_id = {
+            let session_guard = self.current_session_id.lock().await;
+            match session_guard.as_ref() {
+                Some(id) => id.clone(),
+                None => {
+                    eprintln!("⚠️  Cannot start call '{}': no active Weave session", operation);
+                    return;
+                }
+            }
+        };
+
+        // Now we're guaranteed to have a session_id
+        // Derive the call id from the span's key and join the session trace
+        let weave_call_id = call_id_for(&session_id, &operation, tick, &call_id);
+        let (trace_id, mut parent_id) = self.trace_context().await;
+
+        let context = CallContext {
+            call_id: weave_call_id.clone(),
+            trace_id: trace_id.clone(),
+            session_id: session_id.clone(),
+            start_tick: tick,
+            inputs: inputs.clone(),
+        };
+
+        {
+            let mut active_calls = self.active_calls.lock().await;
+            if let Some(parent) = parent.and_then(|parent| active_calls.get(parent)) {
+                parent_id = Some(parent.call_id.clone());
+            }
+            active_calls.insert(call_id.clone(), context);
+        }
+        self.persist_open_calls().await;
+
+        println!(
+            "🔷 Weave call started: '{}' operation='{}' tick={} session={} weave_id={}",
+            call_id, operation, tick, session_id, weave_call_id
+        );
+
+        // Add session_id to the inputs, keeping their JSON types
+        let mut inputs_json = inputs;
+        inputs_json.insert("session_id".to_string(), serde_json::json!(&session_id));
+
+        // Send to Weave
+        if let Err(e) = self
+            .send_start_call(
+                weave_call_id,
+                trace_id,
+                parent_id,
+                thread_id_for(&session_id, None),
+                operation,
+                tick,
+                inputs_json,
+            )
+            .await
+        {
+            eprintln!("⚠️  Failed to send start call to Weave: {}", e);
+        }
+    }
+
+    /// Sends the start of a span to Weave; a failed send is queued for retry
+    async fn send_start_call(
+        &self,
+        call_id: String,
+        trace_id: String,
+        parent_id: Option<String>,
+        thread_id: String,
+        operation: String,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+    ) -> Result<(), String> {
+        let start = self
+            .start_payload(call_id, trace_id, parent_id, thread_id, operation, tick, inputs)
+            .await;
+        match self.submit_start(&start).await {
+            Ok(()) => Ok(()),
+            Err(e) => self.retry_span(RetryCall::Start(start), e).await,
+        }
+    }
+
+    /// Builds the start of a call
+    async fn start_payload(
+        &self,
+        call_id: String,
+        trace_id: String,
+        parent_id: Option<String>,
+        thread_id: String,
+        operation: String,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+    ) -> StartedCallSchemaForInsert {
+        // Catch schema drift between the mod and the client early
+        #[cfg(debug_assertions)]
+        if let Some(schema) = op_schema::find(&operation) {
+            for problem in schema.validate(&inputs) {
+                eprintln!("⚠️  Schema drift in op '{}': {}", operation, problem);
+            }
+        }
+
+        // Build attributes (metadata about the call)
+        let mut attributes: HashMap<String, serde_json::Value> = HashMap::new();
+        attributes.insert("tick".to_string(), serde_json::json!(tick));
+        if let Some(step) = self.correlation.record_call(tick) {
+            attributes.insert("wandb_step".to_string(), serde_json::json!(step));
+        }
+        if let Some((run_id, url)) = self.wandb_run.lock().await.clone() {
+            attributes.insert("wandb_run_id".to_string(), serde_json::json!(run_id));
+            attributes.insert("wandb_run_url".to_string(), serde_json::json!(url));
+        }
+        if !self.metadata.is_empty() {
+            attributes.insert("meta".to_string(), self.metadata.to_json());
+        }
+        let units = units::units_of(inputs.keys());
+        if !units.is_empty() {
+            attributes.insert("units".to_string(), serde_json::json!(units));
+        }
+        if SCREENSHOT_LINKED_OPS.contains(&operation.as_str()) {
+            let surface = inputs.get("surface").and_then(|value| value.as_str());
+            if let Some(screenshot) = self.screenshot_near(tick, surface).await {
+                attributes.insert("screenshot".to_string(), screenshot);
+            }
+        }
+        // The op's grouping attributes never replace those of the call itself
+        for (key, value) in self.op_attributes.for_operation(&operation) {
+            attributes.entry(key).or_insert(value);
+        }
+
+        StartedCallSchemaForInsert {
+            project_id: self.project_id().await,
+            id: Some(call_id.clone()),
+            op_name: operation,
+            display_name: None,
+            trace_id: Some(trace_id),
+            parent_id,
+            thread_id: Some(thread_id),
+            turn_id: Some(call_id),
+            started_at: Utc::now(),
+            attributes,
+            inputs,
+        }
+    }
+
+    async fn submit_start(&self, start: &StartedCallSchemaForInsert) -> Result<(), String> {
+        let client_guard = self.client.lock().await;
+        let client = client_guard
+            .as_ref()
+            .ok_or_else(|| "Weave client not initialized".to_string())?;
+        client.start_call(start.clone()).await
+    }
+
+    async fn submit_end(&self, end: &EndedCallSchemaForInsert) -> Result<(), String> {
+        let client_guard = self.client.lock().await;
+        let client = client_guard
+            .as_ref()
+            .ok_or_else(|| "Weave client not initialized".to_string())?;
+        client.end_call(end.clone()).await
+    }
+
+    /// Queues a span half that failed to send, returning the error when it can't wait
+    async fn retry_span(&self, call: RetryCall, error: String) -> Result<(), String> {
+        let queued = self.retries.lock().await.push(call, self.span_attempts);
+        match queued {
+            Ok(()) => {
+                eprintln!("⚠️  Failed to send span to Weave, retrying it: {}", error);
+                Ok(())
+            }
+            Err(entry) => {
+                self.give_up(entry.item, &error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Reference to the latest screenshot when it was taken at most
+    /// `screenshot_link_ticks` before `tick` (on `surface`, for events that have one)
+    async fn screenshot_near(&self, tick: u64, surface: Option<&str>) -> Option<serde_json::Value> {
+        if self.screenshot_link_ticks == 0 {
+            return None;
+        }
+        let snapshot = self.last_snapshot.lock().await.clone()?;
+        let apart = tick.checked_sub(snapshot.tick)?;
+        if apart > self.screenshot_link_ticks || surface.is_some_and(|s| s != snapshot.surface) {
+            return None;
+        }
+        let project_id = self.project_id().await;
+        let call_ref = snapshot
+            .call_id
+            .as_ref()
+            .map(|call_id| format!("weave:///{}/call/{}", project_id, call_id));
+        Some(serde_json::json!({
+            "path": snapshot.path,
+            "tick": snapshot.tick,
+            "ticks_before": apart,
+            "call": call_ref,
+        }))
+    }
+
+    /// Ends an active Weave call/trace
+    pub async fn end_call(
+        &self,
+        call_id: String,
+        tick: u64,
+        outputs: HashMap<String, serde_json::Value>,
+        success: bool,
+    ) {
+        let mut active_calls = self.active_calls.lock().await;
+
+        match active_calls.remove(&call_id) {
+            None => {
+                eprintln!("⚠️  Cannot end Weave call '{}': call not found", call_id);
+            }
+            Some(context) => {
+                let duration_ticks = tick.saturating_sub(context.start_tick);
+
+                println!(
+                    "🔷 Weave call ended: '{}' duration={} ticks success={} session={} weave_id={}",
+                    call_id, duration_ticks, success, context.session_id, context.call_id
+                );
+
+                // Add session_id to the outputs, keeping their JSON types
+                let mut outputs_json = outputs;
+                outputs_json.insert("session_id".to_string(), serde_json::json!(&context.session_id));
+
+                // Send to Weave
+                drop(active_calls); // Release lock before async call
+                self.persist_open_calls().await;
+                if let Err(e) = self
+                    .send_end_call(
+                        context.call_id,
+                        tick,
+                        duration_ticks,
+                        outputs_json,
+                        (!success).then(|| "Call failed".to_string()),
+                    )
+                    .await
+                {
+                    eprintln!("⚠️  Failed to send end call to Weave: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Sends the end of a span to Weave; a failed send is queued for retry
+    async fn send_end_call(
+        &self,
+        call_id: String,
+        tick: u64,
+        duration_ticks: u64,
+        outputs: HashMap<String, serde_json::Value>,
+        exception: Option<String>,
+    ) -> Result<(), String> {
+        let end = self
+            .end_payload(call_id, tick, duration_ticks, outputs, exception)
+            .await;
+        match self.submit_end(&end).await {
+            Ok(()) => Ok(()),
+            Err(e) => self.retry_span(RetryCall::End(end), e).await,
+        }
+    }
+
+    /// Builds the end of a call
+    async fn end_payload(
+        &self,
+        call_id: String,
+        tick: u64,
+        duration_ticks: u64,
+        outputs: HashMap<String, serde_json::Value>,
+        exception: Option<String>,
+    ) -> EndedCallSchemaForInsert {
+        // Build output
+        let mut output_map = outputs;
+        output_map.insert(
+            "success".to_string(),
+            serde_json::json!(exception.is_none()),
+        );
+        output_map.insert("tick".to_string(), serde_json::json!(tick));
+
+        // Build summary
+        let mut summary: HashMap<String, serde_json::Value> = HashMap::new();
+        summary.insert(
+            "duration_ticks".to_string(),
+            serde_json::json!(duration_ticks),
+        );
+
+        EndedCallSchemaForInsert {
+            project_id: self.project_id().await,
+            id: call_id,
+            ended_at: Utc::now(),
+            exception,
+            output: Some(serde_json::to_value(output_map).unwrap()),
+            summary,
+        }
+    }
+
+    /// Logs an atomic call to Weave (start and end at the same time).
+    /// Useful for instant events that don't have duration.
+    /// Calls with a player_index are placed on that player's thread.
+    /// Returns the id of the call, unless it could not be logged or was held for a
+    /// batch (see `weave.batching`).
+    pub async fn log_call(
+        &self,
+        operation: String,
+        player_index: Option<u32>,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+        outputs: HashMap<String, serde_json::Value>,
+    ) -> Option<String> {
+        let batches = {
+            let mut batcher = self.batcher.lock().await;
+            if !batcher.batches(&operation) {
+                drop(batcher);
+                return self
+                    .log_instant_call(operation, player_index, tick, inputs, outputs)
+                    .await;
+            }
+            batcher.push(&operation, player_index, tick, inputs, outputs)
+        };
+        self.log_batches(batches).await;
+        None
+    }
+
+    /// Logs merged calls as one instant call each
+    async fn log_batches(&self, batches: Vec<Batch>) {
+        for batch in batches {
+            self.log_instant_call(
+                batch.operation,
+                batch.player_index,
+                batch.tick,
+                batch.inputs,
+                batch.outputs,
+            )
+            .await;
+        }
+    }
+
+    async fn log_instant_call(
+        &self,
+        operation: String,
+        player_index: Option<u32>,
+        tick: u64,
+        inputs: HashMap<String, serde_json::Value>,
+        outputs: HashMap<String, serde_json::Value>,
+    ) -> Option<String> {
+        // Ensure client is initialized
+        if let Err(e) = self.ensure_client().await {
+            eprintln!("⚠️  Failed to ensure Weave client: {}", e);
+            return None;
+        }
+
+        // Get active session
+        let session_id = {
+            let session_guard = self.current_session_id.lock().await;
+            match session_guard.as_ref() {
+                Some(id) => id.clone(),
+                None => {
+                    eprintln!("⚠️  Cannot log Weave call '{}': no active session", operation);
+                    return None;
+                }
+            }
+        };
+
+        // Derive the call id and join the session trace
+        let weave_call_id = self
+            .instant_call_id(&session_id, &operation, player_index, tick)
+            .await;
+        let (trace_id, parent_id) = self.trace_context().await;
+
+        println!(
+            "🔷 Weave instant call: operation='{}' tick={} session={} weave_id={}",
+            operation, tick, session_id, weave_call_id
+        );
+
+        // Add session_id to inputs and outputs
+        let mut inputs_with_session = inputs;
+        inputs_with_session.insert("session_id".to_string(), serde_json::json!(&session_id));
+
+        let mut outputs_with_session = outputs;
+        outputs_with_session.insert("session_id".to_string(), serde_json::json!(&session_id));
+
+        let call = InstantCall {
+            call_id: weave_call_id.clone(),
+            trace_id,
+            parent_id,
+            thread_id: thread_id_for(&session_id, player_index),
+            operation,
+            tick,
+            inputs: inputs_with_session,
+            outputs: outputs_with_session,
+            started: false,
+        };
+
+        // Charge the upload budget, deferring low-priority calls once it runs out
+        if let Some(ref budget) = self.upload_budget {
+            let bytes = call.estimated_bytes();
+            let op = call.operation.strip_suffix(BATCH_SUFFIX).unwrap_or(&call.operation);
+            if DEFERRABLE_OPS.contains(&op) {
+                if !budget.try_consume(bytes) {
+                    println!(
+                        "📶 Upload budget exhausted, deferring '{}' call weave_id={}",
+                        call.operation, call.call_id
+                    );
+                    self.defer_call(call).await;
+                    return Some(weave_call_id);
+                }
+            } else {
+                budget.consume(bytes);
+            }
+        }
+
+        self.deliver_instant_call(call).await;
+        Some(weave_call_id)
+    }
+
+    /// Id of a new instant or custom call, numbered among the calls of its op and player
+    /// at the tick
+    async fn instant_call_id(
+        &self,
+        session_id: &str,
+        operation: &str,
+        player_index: Option<u32>,
+        tick: u64,
+    ) -> String {
+        let key = self
+            .call_occurrences
+            .lock()
+            .await
+            .next_key(operation, player_index, tick);
+        call_id_for(session_id, operation, tick, &key)
+    }
+
+    /// Sends an instant call under the delivery policy of its class: durable calls that
+    /// fail go to the spool, retried ones to the retry queue
+    async fn deliver_instant_call(&self, mut call: InstantCall) {
+        let error = match self.send_instant_call(&mut call).await {
+            Ok(()) => return,
+            Err(e) => e,
+        };
+        let policy = self.delivery.for_operation(&call.operation);
+        if policy == DeliveryPolicy::Durable {
+            eprintln!(
+                "⚠️  Failed to send '{}' call to Weave, spooling it for replay: {}",
+                call.operation, error
+            );
+            self.defer_call(call).await;
+            return;
+        }
+
+        let operation = call.operation.clone();
+        let queued = self
+            .retries
+            .lock()
+            .await
+            .push(RetryCall::Instant(call), policy.attempts());
+        match queued {
+            Ok(()) => eprintln!(
+                "⚠️  Failed to send '{}' call to Weave, retrying it: {}",
+                operation, error
+            ),
+            Err(entry) => self.give_up(entry.item, &error),
+        }
+    }
+
+    /// Sends the start (unless it already went out) and end of an instant call
+    async fn send_instant_call(&self, call: &mut InstantCall) -> Result<(), String> {
+        if !call.started {
+            let start = self
+                .start_payload(
+                    call.call_id.clone(),
+                    call.trace_id.clone(),
+                    call.parent_id.clone(),
+                    call.thread_id.clone(),
+                    call.operation.clone(),
+                    call.tick,
+                    call.inputs.clone(),
+                )
+                .await;
+            self.submit_start(&start)
+                .await
+                .map_err(|e| format!("start call: {}", e))?;
+            call.started = true;
+        }
+
+        let end = self
+            .end_payload(call.call_id.clone(), call.tick, 0, call.outputs.clone(), None)
+            .await;
+        self.submit_end(&end)
+            .await
+            .map_err(|e| format!("end call: {}", e))
+    }
+
+    /// Sends the calls of the retry queue that are due, once the weave-sender answers;
+    /// `all` sends every queued call regardless of its backoff. The end of a span waits
+    /// for its start, and calls out of attempts are dropped.
+    async fn retry_calls(&self, all: bool) {
+        let due = self.retries.lock().await.take_due(all);
+        if due.is_empty() {
+            return;
+        }
+
+        // Attempts aren't spent while the sidecar is down
+        let reachable = match self.client.lock().await.as_ref() {
+            Some(client) => client.ping().await.is_ok(),
+            None => false,
+        };
+        if !reachable {
+            let mut retries = self.retries.lock().await;
+            for entry in due {
+                retries.hold(entry);
+            }
+            return;
+        }
+
+        let mut starts: HashSet<String> = HashSet::new();
+        for call in self.retries.lock().await.pending() {
+            if let RetryCall::Start(ref start) = call {
+                starts.extend(start.id.clone());
+            }
+        }
+
+        println!("🔷 Retrying {} Weave calls", due.len());
+        for mut entry in due {
+            if matches!(entry.item, RetryCall::End(ref end) if starts.contains(&end.id)) {
+                self.retries.lock().await.hold(entry);
+                continue;
+            }
+            let result = match entry.item {
+                RetryCall::Instant(ref mut call) => self.send_instant_call(call).await,
+                RetryCall::Start(ref start) => self.submit_start(start).await,
+                RetryCall::End(ref end) => self.submit_end(end).await,
+            };
+
+            let mut retries = self.retries.lock().await;
+            let Err(error) = result else {
+                retries.recovered();
+                continue;
+            };
+            if let RetryCall::Start(ref start) = entry.item {
+                starts.extend(start.id.clone());
+            }
+            entry.attempts += 1;
+            if let Err(entry) = retries.schedule(entry) {
+                drop(retries);
+                self.give_up(entry.item, &error);
+            }
+        }
+    }
+
+    /// Sends the calls of the retry queue that are due
+    pub async fn retry_failed(&self) {
+        self.retry_calls(false).await;
+    }
+
+    /// Counters of the retry queue as `weave/*` metrics, when they changed since the
+    /// last call
+    pub async fn retry_metrics(&self) -> Option<HashMap<String, f64>> {
+        let stats = self.retries.lock().await.changed_stats()?;
+        Some(HashMap::from([
+            ("weave/retry_queue".to_string(), stats.queued as f64),
+            ("weave/recovered_calls".to_string(), stats.recovered as f64),
+            ("weave/failed_calls".to_string(), stats.failed as f64),
+            ("weave/overflowed_calls".to_string(), stats.overflowed as f64),
+        ]))
+    }
+
+    /// Reports a call that won't be sent again
+    fn give_up(&self, call: RetryCall, error: &str) {
+        match call {
+            RetryCall::Instant(call) => self.report_dropped_call(&call, error),
+            RetryCall::Start(start) => {
+                report_dropped_span("start", start.id.as_deref().unwrap_or("?"), error)
+            }
+            RetryCall::End(end) => report_dropped_span("end", &end.id, error),
+        }
+    }
+
+    fn report_dropped_call(&self, call: &InstantCall, error: &str) {
+        eprintln!(
+            "❌ Dropped '{}' call weave_id={} after failing to send it: {}",
+            call.operation, call.call_id, error
+        );
+        client_events::emit(ClientEvent::Error {
+            component: "weave".to_string(),
+            message: format!("Dropped a '{}' call: {}", call.operation, error),
+        });
+    }
+
+    /// Parks a call in the spool until the upload budget refills or Weave is reachable
+    async fn defer_call(&self, call: InstantCall) {
+        let mut spool_guard = self.spool.lock().await;
+
+        if spool_guard.is_none() {
+            match Spool::open(self.config.spool_dir.clone(), SPOOL_SEGMENT_BYTES) {
+                Ok(spool) => *spool_guard = Some(spool),
+                Err(e) => {
+                    eprintln!("⚠️  Failed to open spool, dropping '{}' call: {}", call.operation, e);
+                    return;
+                }
+            }
+        }
+
+        let record = match serde_json::to_value(&call) {
+            Ok(record) => record,
+            Err(e) => {
+                eprintln!("⚠️  Failed to serialize deferred call: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = spool_guard.as_mut().unwrap().append(&record) {
+            eprintln!("⚠️  Failed to spool deferred call: {}", e);
+        }
+    }
+
+    /// Uploads calls from the spool, oldest segment first: calls deferred by the upload
+    /// budget while budget remains, and calls that failed to send once Weave answers
+    /// again. When a call fails during the upload, durable calls and the rest of the
+    /// segment are spooled anew and the upload stops until the next batch.
+    pub async fn upload_deferred(&self) {
+        let mut spool_guard = self.spool.lock().await;
+
+        // Segments left by a previous process are uploaded too
+        if spool_guard.is_none() && self.config.spool_dir.is_dir() {
+            match Spool::open(self.config.spool_dir.clone(), SPOOL_SEGMENT_BYTES) {
+                Ok(spool) => *spool_guard = Some(spool),
+                Err(e) => eprintln!("⚠️  Failed to open spool: {}", e),
+            }
+        }
+        let Some(spool) = spool_guard.as_mut() else {
+            return;
+        };
+
+        // Nothing goes out while the sidecar is down, and the open segment stays open
+        match self.client.lock().await.as_ref() {
+            Some(client) if client.ping().await.is_ok() => {}
+            _ => return,
+        }
+
+        let segments = match spool.pending_segments() {
+            Ok(segments) => segments,
+            Err(e) => {
+                eprintln!("⚠️  Failed to list spool segments: {}", e);
+                return;
+            }
+        };
+        if segments.is_empty() {
+            return;
+        }
+
+        for segment in segments {
+            if let Some(ref budget) = self.upload_budget {
+                let bytes = std::fs::metadata(&segment)
+                    .map(|m| m.len() as usize)
+                    .unwrap_or(0);
+
+                // A segment larger than the whole budget goes out once the bucket is full
+                if !budget.try_consume(bytes) {
+                    if !budget.is_full() {
+                        break;
+                    }
+                    budget.consume(bytes);
+                }
+            }
+
+            let records = match Spool::read_segment(&segment) {
+                Ok(records) => records,
+                Err(e) => {
+                    eprintln!("⚠️  Failed to read spool segment: {}", e);
+                    break;
+                }
+            };
+
+            println!(
+                "📶 Uploading {} deferred Weave calls from {:?}",
+                records.len(),
+                segment
+            );
+            let mut failed = false;
+            let mut respool = Vec::new();
+            for record in records {
+                let mut call = match serde_json::from_value::<InstantCall>(record) {
+                    Ok(call) => call,
+                    Err(e) => {
+                        eprintln!("⚠️  Skipping malformed deferred call: {}", e);
+                        continue;
+                    }
+                };
+                if failed {
+                    respool.push(call);
+                    continue;
+                }
+                if let Err(e) = self.send_instant_call(&mut call).await {
+                    failed = true;
+                    if self.delivery.for_operation(&call.operation) == DeliveryPolicy::Durable {
+                        respool.push(call);
+                    } else {
+                        self.report_dropped_call(&call, &e);
+                    }
+                }
+            }
+
+            // Spooled again before the ack, so a crash in between only duplicates calls
+            for call in respool {
+                let appended = serde_json::to_value(&call)
+                    .map_err(|e| e.to_string())
+                    .and_then(|record| spool.append(&record));
+                if let Err(e) = appended {
+                    eprintln!("⚠️  Failed to spool '{}' call again: {}", call.operation, e);
+                }
+            }
+            if let Err(e) = spool.ack(&segment) {
+                eprintln!("⚠️  {}", e);
+            }
+            if failed {
+                eprintln!("⚠️  Weave stopped accepting deferred calls, keeping the rest spooled");
+                break;
+            }
+        }
+    }
+
+    /// Logs a link from the new session to the run whose save it continues
+    pub async fn handle_run_lineage(&self, tick: u64, parent: &RunRecord) {
+        let mut inputs = HashMap::new();
+        inputs.insert("parent_run_name".to_string(), serde_json::json!(parent.run_name));
+        inputs.insert("parent_session_id".to_string(), serde_json::json!(parent.session_id));
+        inputs.insert("save_id".to_string(), serde_json::json!(parent.save_id));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("parent_wandb_run_id".to_string(), serde_json::json!(parent.wandb_run_id));
+        outputs.insert("parent_wandb_url".to_string(), serde_json::json!(parent.wandb_url));
+        outputs.insert(
+            "parent_weave_trace_id".to_string(),
+            serde_json::json!(parent.weave_trace_id),
+        );
+        outputs.insert("parent_start_tick".to_string(), serde_json::json!(parent.start_tick));
+
+        self.log_call("run_lineage".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Handles research started event. Each level of an infinite tech is a child of
+    /// the tech's progress span, opened with its first level of the session.
+    pub async fn handle_research_started(
+        &self,
+        tick: u64,
+        tech_name: String,
+        tech_level: u32,
+        infinite: bool,
+    ) {
+        let research_key = format!("{}:{}", tech_name, tech_level);
+        // A catch-up re-sends the current research, which may already be open
+        if self.active_calls.lock().await.contains_key(&research_key) {
+            return;
+        }
+
+        let mut inputs = HashMap::new();
+        inputs.insert("tech_name".to_string(), serde_json::json!(tech_name));
+        inputs.insert("tech_level".to_string(), serde_json::json!(tech_level));
+
+        if !infinite {
+            // Start a call and store the call_id in the research cache
+            self.start_call(
+                research_key.clone(),
+                "research".to_string(),
+                tick,
+                inputs,
+            )
+            .await;
+            return;
+        }
+
+        let progress_key = research_progress_key(&tech_name);
+        self.research_progress
+            .lock()
+            .await
+            .entry(tech_name.clone())
+            .or_insert(ResearchProgress {
+                first_level: tech_level,
+                last_level: None,
+                levels_completed: 0,
+                last_tick: tick,
+            })
+            .last_tick = tick;
+        // A warm start resumes the span of the session's earlier levels
+        if !self.is_call_active(&progress_key).await {
+            let mut progress_inputs = HashMap::new();
+            progress_inputs.insert("tech_name".to_string(), serde_json::json!(tech_name));
+            progress_inputs.insert("first_level".to_string(), serde_json::json!(tech_level));
+            self.start_call(
+                progress_key.clone(),
+                "research_progress".to_string(),
+                tick,
+                progress_inputs,
+            )
+            .await;
+        }
+        self.start_child_call(
+            research_key,
+            Some(&progress_key),
+            "research".to_string(),
+            tick,
+            inputs,
+        )
+        .await;
+    }
+
+    /// Handles research finished event
+    pub async fn handle_research_finished(
+        &self,
+        tick: u64,
+        tech_name: String,
+        tech_level: u32,
+        infinite: bool,
+    ) {
+        let research_key = format!("{}:{}", tech_name, tech_level);
+        if infinite {
+            let mut progress = self.research_progress.lock().await;
+            let progress = progress.entry(tech_name.clone()).or_insert(ResearchProgress {
+                first_level: tech_level,
+                last_level: None,
+                levels_completed: 0,
+                last_tick: tick,
+            });
+            progress.last_level = Some(tech_level);
+            progress.levels_completed += 1;
+            progress.last_tick = tick;
+        }
+
+        let mut outputs = HashMap::new();
+        outputs.insert("tech_name".to_string(), serde_json::json!(tech_name));
+        outputs.insert("tech_level".to_string(), serde_json::json!(tech_level));
+        outputs.insert("completed".to_string(), serde_json::json!(true));
+
+        // End the call using the research key as call_id
+        self.end_call(research_key, tick, outputs, true).await;
+    }
+
+    /// Ends the progress span of every infinite tech researched in the session, with the
+    /// levels it completed
+    async fn end_research_progress(&self) {
+        let progress: Vec<(String, ResearchProgress)> =
+            self.research_progress.lock().await.drain().collect();
+        for (tech_name, progress) in progress {
+            let key = research_progress_key(&tech_name);
+            if !self.is_call_active(&key).await {
+                continue;
+            }
+            let mut outputs = HashMap::new();
+            outputs.insert("tech_name".to_string(), serde_json::json!(tech_name));
+            outputs.insert("first_level".to_string(), serde_json::json!(progress.first_level));
+            outputs.insert("last_level".to_string(), serde_json::json!(progress.last_level));
+            outputs.insert(
+                "levels_completed".to_string(),
+                serde_json::json!(progress.levels_completed),
+            );
+            self.end_call(key, progress.last_tick, outputs, true).await;
+        }
+    }
+
+    /// Handles entity built event
+    pub async fn handle_entity_built(
+        &self,
+        tick: u64,
+        player_index: u32,
+        entity: String,
+        position_x: f64,
+        position_y: f64,
+        surface: String,
+    ) {
+        let mut inputs = HashMap::new();
+        inputs.insert("player_index".to_string(), serde_json::json!(player_index));
+        inputs.insert("entity".to_string(), serde_json::json!(entity));
+        inputs.insert("position_x".to_string(), serde_json::json!(position_x));
+        inputs.insert("position_y".to_string(), serde_json::json!(position_y));
+        inputs.insert("surface".to_string(), serde_json::json!(&surface));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("entity".to_string(), serde_json::json!(entity));
+        outputs.insert("surface".to_string(), serde_json::json!(surface));
+
+        self.log_call(
+            "on_built_entity".to_string(),
+            Some(player_index),
+            tick,
+            inputs,
+            outputs,
+        )
+        .await;
+    }
+
+    /// Handles entity mined event
+    pub async fn handle_entity_mined(
+        &self,
+        tick: u64,
+        player_index: u32,
+        entity: String,
+        position_x: f64,
+        position_y: f64,
+        surface: String,
+    ) {
+        let mut inputs = HashMap::new();
+        inputs.insert("player_index".to_string(), serde_json::json!(player_index));
+        inputs.insert("entity".to_string(), serde_json::json!(entity));
+        inputs.insert("position_x".to_string(), serde_json::json!(position_x));
+        inputs.insert("position_y".to_string(), serde_json::json!(position_y));
+        inputs.insert("surface".to_string(), serde_json::json!(&surface));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("entity".to_string(), serde_json::json!(entity));
+        outputs.insert("surface".to_string(), serde_json::json!(surface));
+
+        self.log_call(
+            "on_player_mined_entity".to_string(),
+            Some(player_index),
+            tick,
+            inputs,
+            outputs,
+        )
+        .await;
+    }
+
+    /// Handles player crafted item event
+    pub async fn handle_item_crafted(
+        &self,
+        tick: u64,
+        player_index: u32,
+        item: String,
+        count: u32,
+    ) {
+        let mut inputs = HashMap::new();
+        inputs.insert("player_index".to_string(), serde_json::json!(player_index));
+        inputs.insert("item".to_string(), serde_json::json!(&item));
+        inputs.insert("count".to_string(), serde_json::json!(count));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("item".to_string(), serde_json::json!(item));
+        outputs.insert("count".to_string(), serde_json::json!(count));
+
+        self.log_call(
+            "on_player_crafted_item".to_string(),
+            Some(player_index),
+            tick,
+            inputs,
+            outputs,
+        )
+        .await;
+    }
+
+    /// Handles a rocket launch
+    pub async fn handle_rocket_launched(
+        &self,
+        tick: u64,
+        surface: Option<String>,
+        count: Option<u32>,
+    ) {
+        println!("🔷 Rocket launched (#{})", count.map_or("?".to_string(), |c| c.to_string()));
+
+        let mut inputs = HashMap::new();
+        if let Some(ref surface) = surface {
+            inputs.insert("surface".to_string(), serde_json::json!(surface));
+        }
+        if let Some(count) = count {
+            inputs.insert("count".to_string(), serde_json::json!(count));
+        }
+
+        let mut outputs = HashMap::new();
+        outputs.insert("rockets_launched".to_string(), serde_json::json!(count));
+
+        self.log_call("on_rocket_launched".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Advances the silo's launch pipeline, starting and ending the spans of its trace
+    pub async fn handle_rocket_silo(&self, tick: u64, report: &SiloReport) {
+        let changes = self.silos.lock().await.advance(tick, report);
+        self.apply_span_changes(changes).await;
+    }
+
+    async fn apply_span_changes(&self, changes: Vec<SpanChange>) {
+        for change in changes {
+            match change {
+                SpanChange::Start {
+                    key,
+                    parent,
+                    operation,
+                    tick,
+                    inputs,
+                } => {
+                    self.start_child_call(key, parent.as_deref(), operation.to_string(), tick, inputs)
+                        .await
+                }
+                SpanChange::End { key, tick, outputs } => {
+                    self.end_call(key, tick, outputs, true).await
+                }
+            }
+        }
+    }
+
+    /// Logs a player death on the player's thread
+    pub async fn handle_player_died(
+        &self,
+        tick: u64,
+        player_index: u32,
+        cause: Option<String>,
+        surface: Option<String>,
+    ) {
+        println!(
+            "🔷 Player {} died ({})",
+            player_index,
+            cause.as_deref().unwrap_or("unknown cause")
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert("player_index".to_string(), serde_json::json!(player_index));
+        if let Some(ref cause) = cause {
+            inputs.insert("cause".to_string(), serde_json::json!(cause));
+        }
+        if let Some(ref surface) = surface {
+            inputs.insert("surface".to_string(), serde_json::json!(surface));
+        }
+
+        let mut outputs = HashMap::new();
+        outputs.insert("cause".to_string(), serde_json::json!(cause));
+
+        self.log_call(
+            "on_player_died".to_string(),
+            Some(player_index),
+            tick,
+            inputs,
+            outputs,
+        )
+        .await;
+    }
+
+    /// Logs a change of the in-game probe registry
+    pub async fn handle_probe_registry_changed(
+        &self,
+        tick: u64,
+        total: usize,
+        changes: &RegistryChanges,
+    ) {
+        println!(
+            "🔷 Probe registry changed: +{} -{} ~{}",
+            changes.added.len(),
+            changes.removed.len(),
+            changes.changed.len()
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert("added".to_string(), serde_json::json!(changes.added));
+        inputs.insert("removed".to_string(), serde_json::json!(changes.removed));
+        inputs.insert("changed".to_string(), serde_json::json!(changes.changed));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("probes".to_string(), serde_json::json!(total));
+
+        self.log_call("probe_registry".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Stores the game's item and recipe prototypes in the trace, with the raw resource
+    /// cost of each science pack derived from them
+    pub async fn handle_prototypes(&self, tick: u64, prototypes: &Prototypes) {
+        println!(
+            "🔷 Prototypes: {} items, {} recipes",
+            prototypes.items.len(),
+            prototypes.recipes.len()
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert("items".to_string(), serde_json::json!(prototypes.items));
+        inputs.insert("recipes".to_string(), serde_json::json!(prototypes.recipes));
+
+        let raw_costs = prototypes.raw_costs();
+        let science_costs: HashMap<&str, &HashMap<String, f64>> = SCIENCE_PACKS
+            .iter()
+            .filter_map(|pack| raw_costs.of(pack).map(|cost| (*pack, cost)))
+            .collect();
+
+        let mut outputs = HashMap::new();
+        outputs.insert("items".to_string(), serde_json::json!(prototypes.items.len()));
+        outputs.insert("recipes".to_string(), serde_json::json!(prototypes.recipes.len()));
+        outputs.insert("science_raw_costs".to_string(), serde_json::json!(science_costs));
+
+        self.log_call("prototypes".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Logs a new production bottleneck with the chain of shortages leading to it
+    pub async fn handle_bottleneck(&self, tick: u64, bottleneck: &Bottleneck) {
+        let root = bottleneck.item();
+        println!(
+            "🔷 Bottleneck: {} short by {:.1}/min, explains {} shortages",
+            root.item,
+            root.shortfall,
+            bottleneck.explains.len()
+        );
+
+        let mut inputs = HashMap::new();
+        inputs.insert("chain".to_string(), serde_json::json!(bottleneck.chain));
+        inputs.insert("explains".to_string(), serde_json::json!(bottleneck.explains));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("item".to_string(), serde_json::json!(root.item));
+        outputs.insert("shortfall".to_string(), serde_json::json!(root.shortfall));
+        outputs.insert("ratio".to_string(), serde_json::json!(root.ratio()));
+
+        self.log_call("bottleneck".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Holds the starts and ends sent from now on, so a drain cycle reaches the sidecar
+    /// in one enqueue request instead of one per call
+    pub async fn begin_batch(&self) {
+        if let Some(client) = self.client.lock().await.as_ref() {
+            client.begin_batch().await;
+        }
+    }
+
+    /// Sends the starts and ends held since `begin_batch`. A failed send stays in the
+    /// client's outbox and is replayed once the connection is re-established.
+    pub async fn end_batch(&self) {
+        if let Some(client) = self.client.lock().await.as_ref() {
+            if let Err(e) = client.end_batch().await {
+                eprintln!("⚠️  Failed to send batch of Weave calls: {}", e);
+            }
+        }
+    }
+
+    /// Logs a drain cycle's counts per event type, drops and processing latency, so gaps
+    /// in the pipeline show in the trace. Cycles outside a session are not logged.
+    pub async fn handle_drain_summary(
+        &self,
+        summary: &ProcessSummary,
+        carried: usize,
+        latency: std::time::Duration,
+    ) {
+        if !self.drain_summary || self.current_session_id.lock().await.is_none() {
+            return;
+        }
+
+        let mut inputs = HashMap::new();
+        inputs.insert("events".to_string(), serde_json::json!(summary.kinds));
+        inputs.insert("routed".to_string(), serde_json::json!(summary.routed));
+        inputs.insert("dropped".to_string(), serde_json::json!(summary.dropped));
+        inputs.insert(
+            "parse_errors".to_string(),
+            serde_json::json!(summary.dropped.get("parse_error").copied().unwrap_or(0)),
+        );
+        inputs.insert("carried".to_string(), serde_json::json!(carried));
+        inputs.insert(
+            "latency_ms".to_string(),
+            serde_json::json!(latency.as_secs_f64() * 1000.0),
+        );
+
+        let mut outputs = HashMap::new();
+        outputs.insert("processed".to_string(), serde_json::json!(summary.total()));
+
+        let tick = summary.last_tick.unwrap_or(0);
+        self.log_call("drain_summary".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Logs a status heartbeat of an idle client; calls outside a session are not logged
+    pub async fn handle_status(&self, tick: u64, idle: std::time::Duration) {
+        if self.current_session_id.lock().await.is_none() {
+            return;
+        }
+
+        let mut inputs = HashMap::new();
+        inputs.insert("idle_secs".to_string(), serde_json::json!(idle.as_secs()));
+        inputs.insert(
+            "active_calls".to_string(),
+            serde_json::json!(self.active_call_count().await),
+        );
+
+        let mut outputs = HashMap::new();
+        outputs.insert("alive".to_string(), serde_json::json!(true));
+
+        self.log_call("status".to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Logs a rendered per-surface map image (exploration heatmap, pollution map)
+    pub async fn handle_surface_map(
+        &self,
+        operation: &str,
+        tick: u64,
+        surface: String,
+        chunks: usize,
+        png: Vec<u8>,
+    ) {
+        let data = match self.image_pool.run(move || png_data_uri(&png)).await {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("⚠️  Skipping {} of {}: {}", operation, surface, e);
+                return;
+            }
+        };
+
+        let mut inputs = HashMap::new();
+        inputs.insert("surface".to_string(), serde_json::json!(&surface));
+        inputs.insert("chunks".to_string(), serde_json::json!(chunks));
+        inputs.insert(
+            "map".to_string(),
+            serde_json::json!({
+                "_type": "Image",
+                "data": data
+            }),
+        );
+
+        let mut outputs = HashMap::new();
+        outputs.insert("surface".to_string(), serde_json::json!(surface));
+
+        self.log_call(operation.to_string(), None, tick, inputs, outputs)
+            .await;
+    }
+
+    /// Handles player snapshot event (from Stats).
+    /// Snapshots whose screenshot is nearly identical to the last one uploaded for the
+    /// same surface are skipped and only counted, so AFK stretches don't upload
+    /// hundreds of identical images.
+    pub async fn handle_player_snapshot(
+        &self,
+        tick: u64,
+        player_info: &PlayerInfo,
+        screenshot_path: &str,
+        production: &HashMap<String, f64>,
+    ) {
+        let mut screenshot_bytes = match self.read_screenshot(screenshot_path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!(
+                    "⚠️  Failed to read screenshot at {}: {}",
+                    screenshot_path, e
+                );
+                return;
+            }
+        };
+
+        if self
+            .is_duplicate_snapshot(&player_info.surface, &screenshot_bytes)
+            .await
+        {
+            let skipped = self.skipped_duplicate_snapshots.fetch_add(1, Ordering::Relaxed) + 1;
+            println!(
+                "🔷 Skipped duplicate snapshot {} ({} skipped so far)",
+                screenshot_path, skipped
+            );
+            // The view hasn't changed, so the uploaded image still shows it
+            if let Some(ref mut snapshot) = *self.last_snapshot.lock().await {
+                if snapshot.surface == player_info.surface {
+                    snapshot.tick = tick;
+                    snapshot.path = screenshot_path.to_string();
+                }
+            }
+            return;
+        }
+
+        // Annotate after dedupe so the changing text doesn't defeat the hash
+        if let Some(ref overlay) = self.overlay {
+            let overlay = overlay.clone();
+            let lines = ScreenshotOverlay::lines(tick, player_info, production);
+            let raw = screenshot_bytes.clone();
+            match self.image_pool.run(move || overlay.annotate(&raw, &lines)).await {
+                Ok(Ok(annotated)) => screenshot_bytes = annotated,
+                Ok(Err(e)) => eprintln!("⚠️  Uploading screenshot without overlay: {}", e),
+                Err(e) => eprintln!("⚠️  Screenshot overlay skipped: {}", e),
+            }
+        }
+
+        // Encode the screenshot as a data URI
+        let screenshot_data = match self
+            .image_pool
+            .run(move || png_data_uri(&screenshot_bytes))
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("⚠️  Skipping snapshot {}: {}", screenshot_path, e);
+                return;
+            }
+        };
+
+        // Build inputs with player position and screenshot as data URI
+        let mut inputs: HashMap<String, serde_json::Value> = HashMap::new();
+        inputs.insert("position_x".to_string(), serde_json::json!(player_info.position.x));
+        inputs.insert("position_y".to_string(), serde_json::json!(player_info.position.y));
+        inputs.insert("surface".to_string(), serde_json::json!(player_info.surface));
+        inputs.insert("health".to_string(), serde_json::json!(player_info.health));
+
+        // Create Weave Image object format, keyed by surface so snapshots from
+        // different surfaces show up as separate columns
+        inputs.insert(
+            format!("screenshot/{}", player_info.surface),
+            serde_json::json!({
+                "_type": "Image",
+                "data": screenshot_data
+            })
+        );
+
+        // Build outputs with the same screenshot path and the duplicate counter
+        let mut outputs: HashMap<String, serde_json::Value> = HashMap::new();
+        outputs.insert("screenshot_path".to_string(), serde_json::json!(screenshot_path));
+        outputs.insert(
+            "skipped_duplicates".to_string(),
+            serde_json::json!(self.skipped_duplicate_snapshots.load(Ordering::Relaxed)),
+        );
+
+        // Log the call and let the event calls after it reference it
+        let call_id = self
+            .log_call("player_snapshot".to_string(), None, tick, inputs, outputs)
+            .await;
+        *self.last_snapshot.lock().await = Some(SnapshotRef {
+            tick,
+            surface: player_info.surface.clone(),
+            path: screenshot_path.to_string(),
+            call_id,
+        });
+    }
+
+    /// Compares a screenshot to the last uploaded one for its surface, remembering it
+    /// if it is new. Screenshots that fail to decode are never treated as duplicates.
+    async fn is_duplicate_snapshot(&self, surface: &str, bytes: &[u8]) -> bool {
+        let Some(max_distance) = self.snapshot_dedupe_distance else {
+            return false;
+        };
+
+        let owned = bytes.to_vec();
+        let hash = match self.image_pool.run(move || screenshot_hash::dhash(&owned)).await {
+            Ok(Ok(hash)) => hash,
+            Ok(Err(e)) => {
+                eprintln!("⚠️  {}", e);
+                return false;
+            }
+            Err(e) => {
+                eprintln!("⚠️  Screenshot hashing skipped: {}", e);
+                return false;
+            }
+        };
+
+        let mut last_hashes = self.last_snapshot_hash.lock().await;
+        match last_hashes.get(surface) {
+            Some(&last) if screenshot_hash::distance(last, hash) <= max_distance => true,
+            _ => {
+                last_hashes.insert(surface.to_string(), hash);
+                false
+            }
+        }
+    }
+
+    /// Reads a screenshot file from the Factorio output directory
+    async fn read_screenshot(&self, path: &str) -> Result<Vec<u8>, String> {
+        let output_path = self
+            .output_path
+            .as_ref()
+            .ok_or_else(|| "output_path (FACTORIO_OUTPUT_PATH) not set".to_string())?;
+
+        let full_path = output_path.join(path);
+
+        fs::read(&full_path)
+            .await
+            .map_err(|e| format!("Failed to read file {:?}: {}", full_path, e))
+    }
+
+    /// Ends all active calls (used during session transitions)
+    async fn end_all_calls(&self, exception: &str) {
+        // First, collect all calls to end
+        let calls_to_end: Vec<CallContext> = {
+            let mut active_calls = self.active_calls.lock().await;
+            let call_count = active_calls.len();
+
+            if call_count > 0 {
+                println!(
+                    "🔷 Ending {} active Weave calls: {}",
+                    call_count, exception
+                );
+                active_calls.drain().map(|(_, context)| context).collect()
+            } else {
+                Vec::new()
+            }
+        };
+        if !calls_to_end.is_empty() {
+            self.persist_open_calls().await;
+        }
+
+        // Now end each call without holding the lock
+        for context in calls_to_end {
+            println!(
+                "🔷 Force-ending Weave call: '{}' session={} weave_id={}",
+                context.call_id, context.session_id, context.call_id
+            );
+
+            // Force end the call with failure
+            if let Err(e) = self
+                .send_end_call(
+                    context.call_id,
+                    context.start_tick,
+                    0,
+                    HashMap::new(),
+                    Some(exception.to_string()),
+                )
+                .await
+            {
+                eprintln!("⚠️  Failed to force-end call: {}", e);
+            }
+        }
+    }
+
+    /// Begins a custom call in the current session
+    pub fn call(&self, operation: impl Into<String>) -> CallBuilder<'_> {
+        CallBuilder {
+            manager: self,
+            operation: operation.into(),
+            tick: None,
+            player_index: None,
+            inputs: HashMap::new(),
+        }
+    }
+
+    /// Returns the count of currently active calls
+    pub async fn active_call_count(&self) -> usize {
+        self.active_calls.lock().await.len()
+    }
+
+    /// Returns the ids and start ticks of the active calls, oldest first
+    pub async fn active_calls(&self) -> Vec<(String, u64)> {
+        let mut calls: Vec<(String, u64)> = self
+            .active_calls
+            .lock()
+            .await
+            .iter()
+            .map(|(call_id, context)| (call_id.clone(), context.start_tick))
+            .collect();
+        calls.sort_by_key(|(_, start_tick)| *start_tick);
+        calls
+    }
+
+    /// Checks if a specific call is active
+    pub async fn is_call_active(&self, call_id: &str) -> bool {
+        self.active_calls.lock().await.contains_key(call_id)
+    }
+
+    /// Force-ends all active calls with the panic as their exception and flushes
+    /// the sender, so the trace shows where the client crashed
+    pub async fn abort_active_calls(&self, panic_message: &str) {
+        self.end_all_calls(&format!("Client panicked: {}", panic_message))
+            .await;
+
+        let client_guard = self.client.lock().await;
+        if let Some(client) = client_guard.as_ref() {
+            if let Err(e) = client.flush().await {
+                eprintln!("⚠️  Failed to flush Weave client after panic: {}", e);
+            }
+        }
+    }
+
+    /// Public method to explicitly close the current session (e.g., on shutdown)
+    pub async fn shutdown(&self) {
+        println!("🔷 Shutting down Weave manager...");
+        let open = self.batcher.lock().await.drain();
+        self.log_batches(open).await;
+        self.end_research_progress().await;
+        self.end_all_calls("Client shut down").await;
+        // Last chance for the calls still waiting for a retry
+        self.retry_calls(true).await;
+        let left = self.retries.lock().await.stats().queued;
+        if left > 0 {
+            eprintln!("⚠️  {} Weave calls waiting for a retry are lost", left);
+        }
+        *self.current_session_id.lock().await = None;
+        *self.session_trace.lock().await = None;
+
+        // Flush and shutdown client (taking it also stops the health check)
+        let mut client_guard = self.client.lock().await;
+        if let Some(client) = client_guard.take() {
+            if let Err(e) = client.wait_idle().await {
+                eprintln!("⚠️  Failed to wait for idle: {}", e);
+            }
+            if let Err(e) = client.shutdown().await {
+                eprintln!("⚠️  Failed to shutdown client: {}", e);
+            }
+        }
+        drop(client_guard);
+
+        println!("🔷 Weave manager shutdown complete");
+    }
+}
+
+#[async_trait]
+impl EventSink for WeaveManager {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        self.set_wandb_run(session.wandb_run.clone()).await;
+        self.handle_session_init(
+            session.run_name.clone(),
+            session.session_id.clone(),
+            session.tick,
+            session.level_name.clone(),
+        )
+        .await;
+        if let Some(ref parent) = session.parent {
+            self.handle_run_lineage(session.tick, parent).await;
+        }
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        for map in &stats.maps {
+            self.handle_surface_map(
+                map.kind,
+                stats.tick,
+                map.surface.clone(),
+                map.chunks,
+                map.png.clone(),
+            )
+            .await;
+        }
+        if let Some((ref player_info, ref screenshot_path)) = stats.snapshot {
+            self.handle_player_snapshot(stats.tick, player_info, screenshot_path, &stats.production)
+                .await;
+        }
+        let expired = self.silos.lock().await.expire(stats.tick);
+        self.apply_span_changes(expired).await;
+        let due = self.batcher.lock().await.due(stats.tick);
+        self.log_batches(due).await;
+    }
+
+    async fn handle_game_event(&self, tick: u64, event: &GameEvent) {
+        match event {
+            GameEvent::ResearchStarted {
+                tech_name,
+                tech_level,
+                infinite,
+            } => {
+                self.handle_research_started(tick, tech_name.clone(), *tech_level, *infinite)
+                    .await
+            }
+            GameEvent::ResearchFinished {
+                tech_name,
+                tech_level,
+                infinite,
+            } => {
+                self.handle_research_finished(tick, tech_name.clone(), *tech_level, *infinite)
+                    .await
+            }
+            GameEvent::EntityBuilt {
+                player_index,
+                entity,
+                position,
+                surface,
+            } => {
+                self.handle_entity_built(
+                    tick,
+                    *player_index,
+                    entity.clone(),
+                    position.x,
+                    position.y,
+                    surface.clone(),
+                )
+                .await
+            }
+            GameEvent::EntityMined {
+                player_index,
+                entity,
+                position,
+                surface,
+            } => {
+                self.handle_entity_mined(
+                    tick,
+                    *player_index,
+                    entity.clone(),
+                    position.x,
+                    position.y,
+                    surface.clone(),
+                )
+                .await
+            }
+            GameEvent::ItemCrafted {
+                player_index,
+                item,
+                count,
+            } => {
+                self.handle_item_crafted(tick, *player_index, item.clone(), *count)
+                    .await
+            }
+            GameEvent::RocketLaunched { surface, count } => {
+                self.handle_rocket_launched(tick, surface.clone(), *count)
+                    .await
+            }
+            GameEvent::PlayerDied {
+                player_index,
+                cause,
+                surface,
+            } => {
+                self.handle_player_died(tick, *player_index, cause.clone(), surface.clone())
+                    .await
+            }
+            GameEvent::ProbeRegistryChanged { total, changes } => {
+                self.handle_probe_registry_changed(tick, *total, changes)
+                    .await
+            }
+            GameEvent::PrototypesLoaded { prototypes } => {
+                self.handle_prototypes(tick, prototypes).await
+            }
+            GameEvent::BottleneckChanged { bottleneck } => {
+                self.handle_bottleneck(tick, bottleneck).await
+            }
+            GameEvent::RocketSilo { report } => self.handle_rocket_silo(tick, report).await,
+        }
+    }
+
+    async fn flush(&self) {
+        let client_guard = self.client.lock().await;
+        if let Some(client) = client_guard.as_ref() {
+            if let Err(e) = client.flush().await {
+                eprintln!("⚠️  Failed to flush Weave client: {}", e);
+            }
+        }
+    }
+
+    async fn shutdown(&self) {
+        WeaveManager::shutdown(self).await;
+    }
+
+    fn name(&self) -> &'static str {
+        "weave"
+    }
+}
+
+#[async_trait]
+impl CallTracer for WeaveManager {
+    fn disabled_reason(&self) -> Option<&str> {
+        WeaveManager::disabled_reason(self)
+    }
+
+    async fn preflight(&self) -> Result<(), String> {
+        WeaveManager::preflight(self).await
+    }
+
+    async fn active_calls(&self) -> Vec<(String, u64)> {
+        WeaveManager::active_calls(self).await
+    }
+
+    async fn abort_orphaned_calls(&self, keep: Option<&str>) {
+        WeaveManager::abort_orphaned_calls(self, keep).await;
+    }
+
+    async fn set_wandb_run(&self, run_info: Option<(String, String)>) {
+        WeaveManager::set_wandb_run(self, run_info).await;
+    }
+
+    async fn resume_session(&self, session_id: String, source_session_id: String) -> bool {
+        WeaveManager::resume_session(self, session_id, source_session_id).await
+    }
+
+    async fn current_trace_id(&self) -> Option<String> {
+        WeaveManager::current_trace_id(self).await
+    }
+
+    async fn retry_failed(&self) {
+        WeaveManager::retry_failed(self).await;
+    }
+
+    async fn upload_deferred(&self) {
+        WeaveManager::upload_deferred(self).await;
+    }
+
+    async fn begin_batch(&self) {
+        WeaveManager::begin_batch(self).await;
+    }
+
+    async fn end_batch(&self) {
+        WeaveManager::end_batch(self).await;
+    }
+
+    async fn handle_drain_summary(
+        &self,
+        summary: &ProcessSummary,
+        carried: usize,
+        latency: std::time::Duration,
+    ) {
+        WeaveManager::handle_drain_summary(self, summary, carried, latency).await;
+    }
+
+    async fn retry_metrics(&self) -> Option<HashMap<String, f64>> {
+        WeaveManager::retry_metrics(self).await
+    }
+
+    async fn handle_status(&self, tick: u64, idle: std::time::Duration) {
+        WeaveManager::handle_status(self, tick, idle).await;
+    }
+
+    async fn abort_active_calls(&self, panic_message: &str) {
+        WeaveManager::abort_active_calls(self, panic_message).await;
+    }
+}
+
+/// Builder of a custom call in the current session, for embedders of the library:
+/// `manager.call("my_op").input("k", 1).start().await?` ... `.end().await`
+pub struct CallBuilder<'a> {
+    manager: &'a WeaveManager,
+    operation: String,
+    tick: Option<u64>,
+    player_index: Option<u32>,
+    inputs: HashMap<String, serde_json::Value>,
+}
+
+impl<'a> CallBuilder<'a> {
+    pub fn input(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.inputs.insert(key.into(), value.into());
+        self
+    }
+
+    /// Game tick of the call (default: the tick of the current WandB step)
+    pub fn tick(mut self, tick: u64) -> Self {
+        self.tick = Some(tick);
+        self
+    }
+
+    /// Places the call on a player's thread instead of the control thread
+    pub fn player(mut self, player_index: u32) -> Self {
+        self.player_index = Some(player_index);
+        self
+    }
+
+    fn resolved_tick(&self) -> u64 {
+        self.tick
+            .or_else(|| self.manager.correlation.active_tick())
+            .unwrap_or(0)
+    }
+
+    /// Starts the call; it stays open until `end` or the session changes
+    pub async fn start(self) -> Result<ActiveCall<'a>, String> {
+        let manager = self.manager;
+        let tick = self.resolved_tick();
+        manager.ensure_client().await?;
+        let session_id = manager
+            .current_session_id
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| format!("Cannot start call '{}': no active Weave session", self.operation))?;
+
+        let call_id = manager
+            .instant_call_id(&session_id, &self.operation, self.player_index, tick)
+            .await;
+        let (trace_id, parent_id) = manager.trace_context().await;
+        let context = CallContext {
+            call_id: call_id.clone(),
+            trace_id: trace_id.clone(),
+            session_id: session_id.clone(),
+            start_tick: tick,
+            inputs: self.inputs.clone(),
+        };
+        manager.active_calls.lock().await.insert(call_id.clone(), context);
+        manager.persist_open_calls().await;
+
+        let mut inputs = self.inputs;
+        inputs.insert("session_id".to_string(), serde_json::json!(&session_id));
+        if let Err(e) = manager
+            .send_start_call(
+                call_id.clone(),
+                trace_id,
+                parent_id,
+                thread_id_for(&session_id, self.player_index),
+                self.operation,
+                tick,
+                inputs,
+            )
+            .await
+        {
+            manager.active_calls.lock().await.remove(&call_id);
+            manager.persist_open_calls().await;
+            return Err(e);
+        }
+
+        Ok(ActiveCall {
+            manager,
+            call_id,
+            session_id,
+            start_tick: tick,
+            tick: self.tick,
+            outputs: HashMap::new(),
+        })
+    }
+
+    /// Logs the call as an instant call with the given outputs
+    pub async fn log(self, outputs: HashMap<String, serde_json::Value>) {
+        let tick = self.resolved_tick();
+        self.manager
+            .log_call(self.operation, self.player_index, tick, self.inputs, outputs)
+            .await;
+    }
+}
+
+/// A custom call started with `CallBuilder::start`
+pub struct ActiveCall<'a> {
+    manager: &'a WeaveManager,
+    call_id: String,
+    session_id: String,
+    start_tick: u64,
+    tick: Option<u64>,
+    outputs: HashMap<String, serde_json::Value>,
+}
+
+impl ActiveCall<'_> {
+    pub fn output(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.outputs.insert(key.into(), value.into());
+        self
+    }
+
+    /// Game tick at which the call ends (default: the tick of the current WandB step)
+    pub fn tick(mut self, tick: u64) -> Self {
+        self.tick = Some(tick);
+        self
+    }
+
+    /// Ends the call successfully
+    pub async fn end(self) -> Result<(), String> {
+        self.finish(None).await
+    }
+
+    /// Ends the call with an exception
+    pub async fn fail(self, exception: impl Into<String>) -> Result<(), String> {
+        self.finish(Some(exception.into())).await
+    }
+
+    async fn finish(self, exception: Option<String>) -> Result<(), String> {
+        // Calls are force-ended when their session changes
+        if self.manager.active_calls.lock().await.remove(&self.call_id).is_none() {
+            return Err(format!("Weave call {} already ended", self.call_id));
+        }
+        self.manager.persist_open_calls().await;
+
+        let tick = self
+            .tick
+            .or_else(|| self.manager.correlation.active_tick())
+            .unwrap_or(self.start_tick)
+            .max(self.start_tick);
+        let mut outputs = self.outputs;
+        outputs.insert("session_id".to_string(), serde_json::json!(&self.session_id));
+        self.manager
+            .send_end_call(
+                self.call_id,
+                tick,
+                tick - self.start_tick,
+                outputs,
+                exception,
+            )
+            .await
+    }
+}
+
+fn report_dropped_span(half: &str, call_id: &str, error: &str) {
+    eprintln!(
+        "❌ Dropped the {} of span weave_id={} after failing to send it: {}",
+        half, call_id, error
+    );
+    client_events::emit(ClientEvent::Error {
+        component: "weave".to_string(),
+        message: format!("Dropped the {} of a span: {}", half, error),
+    });
+}
+
+impl Drop for WeaveManager {
+    fn drop(&mut self) {
+        // Note: We can't call async shutdown from Drop
+        // The user should call shutdown() explicitly before dropping
+        println!("⚠️  WeaveManager dropped - ensure shutdown() was called first");
+    }
+}
@@ -0,0 +1,129 @@
+use factorio_events_core::config::WeaveRetrySettings;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A failed item waiting for its next attempt
+pub struct RetryEntry<T> {
+    pub item: T,
+    /// Attempts made so far
+    pub attempts: u32,
+    /// Attempts before the item is given up
+    pub max_attempts: u32,
+    due: Instant,
+}
+
+/// Counters of the retry queue, logged to WandB as `weave/*`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RetryStats {
+    /// Items waiting for their next attempt
+    pub queued: usize,
+    /// Items delivered by a retry
+    pub recovered: u64,
+    /// Items given up after their last attempt
+    pub failed: u64,
+    /// Items dropped because the queue was full
+    pub overflowed: u64,
+}
+
+/// Bounded queue of items that failed to send, each retried after an exponential
+/// backoff with jitter: the n-th retry waits between half and all of
+/// `base * 2^(n-1)`, capped at `max_delay`, so clients that failed together don't
+/// retry in lockstep
+pub struct RetryQueue<T> {
+    entries: VecDeque<RetryEntry<T>>,
+    capacity: usize,
+    base: Duration,
+    max_delay: Duration,
+    stats: RetryStats,
+    /// Stats as of the last `changed_stats`
+    reported: RetryStats,
+}
+
+impl<T> RetryQueue<T> {
+    pub fn new(settings: &WeaveRetrySettings) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: settings.queue_calls,
+            base: Duration::from_millis(settings.base_delay_ms.max(1)),
+            max_delay: Duration::from_millis(settings.max_delay_ms.max(settings.base_delay_ms)),
+            stats: RetryStats::default(),
+            reported: RetryStats::default(),
+        }
+    }
+
+    fn backoff(&self, attempts: u32) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(16);
+        let delay = self.base.saturating_mul(1 << exponent).min(self.max_delay);
+        let half = delay / 2;
+        half + half.mul_f64(rand::thread_rng().gen::<f64>())
+    }
+
+    /// Schedules an item after its `attempts`-th failed send. Returns the entry back
+    /// when its attempts are used up or the queue is full, so the caller can give up.
+    pub fn schedule(&mut self, mut entry: RetryEntry<T>) -> Result<(), RetryEntry<T>> {
+        if entry.attempts >= entry.max_attempts {
+            self.stats.failed += 1;
+            return Err(entry);
+        }
+        if self.entries.len() >= self.capacity {
+            self.stats.overflowed += 1;
+            return Err(entry);
+        }
+        entry.due = Instant::now() + self.backoff(entry.attempts);
+        self.entries.push_back(entry);
+        Ok(())
+    }
+
+    /// Queues an item whose first send failed
+    pub fn push(&mut self, item: T, max_attempts: u32) -> Result<(), RetryEntry<T>> {
+        self.schedule(RetryEntry {
+            item,
+            attempts: 1,
+            max_attempts,
+            due: Instant::now(),
+        })
+    }
+
+    /// Puts back an item that could not be attempted yet, keeping its place and attempts
+    pub fn hold(&mut self, entry: RetryEntry<T>) {
+        self.entries.push_back(entry);
+    }
+
+    /// Takes the items due for an attempt, oldest first; `all` takes every item
+    pub fn take_due(&mut self, all: bool) -> Vec<RetryEntry<T>> {
+        let now = Instant::now();
+        let (due, waiting): (Vec<_>, VecDeque<_>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|entry| all || entry.due <= now);
+        self.entries = waiting;
+        due
+    }
+
+    /// Items waiting for an attempt
+    pub fn pending(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().map(|entry| &entry.item)
+    }
+
+    /// Counts an item delivered by a retry
+    pub fn recovered(&mut self) {
+        self.stats.recovered += 1;
+    }
+
+    pub fn stats(&self) -> RetryStats {
+        RetryStats {
+            queued: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    /// The stats, when they changed since the last call
+    pub fn changed_stats(&mut self) -> Option<RetryStats> {
+        let stats = self.stats();
+        if stats == self.reported {
+            return None;
+        }
+        self.reported = stats;
+        Some(stats)
+    }
+}
@@ -0,0 +1,5 @@
+//! The webhook sinks: events posted to HTTP endpoints, and the chat notifications
+//! (Discord, Slack) delivered through the same queue.
+
+pub mod notifications;
+pub mod webhook_sink;
@@ -0,0 +1,319 @@
+use crate::webhook_sink::DeliveryQueue;
+use async_trait::async_trait;
+use factorio_events_core::config::{NotificationRule, NotificationSettings};
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use std::sync::{Arc, Mutex};
+
+/// Seconds before a chat webhook delivery times out
+const TIMEOUT_SECS: u64 = 10;
+
+/// Attempts per message before it is dropped
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Chat service the messages are formatted for
+#[derive(Debug, Clone, Copy)]
+enum Service {
+    Discord,
+    Slack,
+}
+
+impl Service {
+    /// The configured service, or the one the webhook URL belongs to
+    fn from_settings(service: Option<&str>, url: &str) -> Option<Self> {
+        match service.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+            Some("discord") => Some(Service::Discord),
+            Some("slack") => Some(Service::Slack),
+            Some(other) => {
+                eprintln!("⚠️  Unknown notification service '{}', notifications are off", other);
+                None
+            }
+            None if url.contains("hooks.slack.com") => Some(Service::Slack),
+            None if url.contains("discord.com/") || url.contains("discordapp.com/") => {
+                Some(Service::Discord)
+            }
+            None => {
+                eprintln!(
+                    "⚠️  Can't tell the chat service of the notification webhook, set notifications.service"
+                );
+                None
+            }
+        }
+    }
+
+    fn body(&self, text: &str) -> String {
+        let body = match self {
+            Service::Discord => serde_json::json!({ "content": text }),
+            Service::Slack => serde_json::json!({ "text": text }),
+        };
+        body.to_string()
+    }
+}
+
+/// Milestone a rule announces
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Milestone {
+    /// The force's first rocket
+    FirstRocket,
+    RocketLaunched,
+    ResearchFinished,
+    PlayerDied,
+    /// The enemy evolution factor reached a threshold
+    Evolution,
+}
+
+impl Milestone {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "first_rocket" => Some(Milestone::FirstRocket),
+            "rocket_launched" => Some(Milestone::RocketLaunched),
+            "research_finished" => Some(Milestone::ResearchFinished),
+            "player_died" => Some(Milestone::PlayerDied),
+            "evolution" => Some(Milestone::Evolution),
+            _ => None,
+        }
+    }
+
+    fn default_message(&self) -> &'static str {
+        match self {
+            Milestone::FirstRocket => "🚀 First rocket launched in {{run_name}}!",
+            Milestone::RocketLaunched => "🚀 Rocket #{{count}} launched in {{run_name}}",
+            Milestone::ResearchFinished => {
+                "🔬 Research finished in {{run_name}}: {{tech_name}} (level {{tech_level}})"
+            }
+            Milestone::PlayerDied => {
+                "💀 Player {{player_index}} died in {{run_name}} (killed by {{cause}})"
+            }
+            Milestone::Evolution => {
+                "👾 Enemy evolution reached {{threshold}} in {{run_name}} (now {{evolution}})"
+            }
+        }
+    }
+}
+
+/// A parsed notification rule
+struct Rule {
+    milestone: Milestone,
+    names: Vec<String>,
+    thresholds: Vec<f64>,
+    message: String,
+}
+
+impl Rule {
+    fn from_settings(rule: &NotificationRule) -> Option<Self> {
+        let Some(milestone) = Milestone::parse(&rule.on) else {
+            eprintln!("⚠️  Unknown notification milestone '{}', skipping its rule", rule.on);
+            return None;
+        };
+        if milestone == Milestone::Evolution && rule.thresholds.is_empty() {
+            eprintln!("⚠️  Evolution notification rule without thresholds, skipping it");
+            return None;
+        }
+        Some(Self {
+            milestone,
+            names: rule.names.clone(),
+            thresholds: rule.thresholds.clone(),
+            message: rule
+                .message
+                .clone()
+                .unwrap_or_else(|| milestone.default_message().to_string()),
+        })
+    }
+}
+
+/// Milestones reached by the current run
+#[derive(Default)]
+struct Progress {
+    run_name: Option<String>,
+    /// Rockets launched since the run started
+    rockets: u64,
+    /// Latest evolution factor; thresholds count as reached when it rises past them
+    evolution: Option<f64>,
+}
+
+impl Progress {
+    /// Starts over when the run changes, including runs resumed without a session_init
+    fn enter(&mut self, run_name: &str) {
+        if self.run_name.as_deref() != Some(run_name) {
+            *self = Progress {
+                run_name: Some(run_name.to_string()),
+                ..Progress::default()
+            };
+        }
+    }
+}
+
+/// Sink posting chat messages to a Discord or Slack incoming webhook when the game
+/// reaches the milestones selected by the configured rules. Messages are rendered from
+/// templates whose `{{field}}` placeholders take the event's fields, and delivered in
+/// the background with retries.
+#[derive(Clone)]
+pub struct Notifier {
+    queue: Arc<DeliveryQueue>,
+    service: Service,
+    rules: Arc<Vec<Rule>>,
+    instance: Option<String>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl Notifier {
+    /// Starts the delivery task when a webhook and at least one valid rule are set
+    pub fn from_settings(settings: &NotificationSettings) -> Option<Self> {
+        let url = match settings.webhook_url_env {
+            Some(ref name) => match std::env::var(name) {
+                Ok(url) => url,
+                Err(_) => {
+                    eprintln!("⚠️  {} is not set, notifications are off", name);
+                    return None;
+                }
+            },
+            None => settings.webhook_url.clone()?,
+        };
+        let rules: Vec<Rule> = settings.rules.iter().filter_map(Rule::from_settings).collect();
+        if rules.is_empty() {
+            eprintln!("⚠️  Notification webhook set without rules, notifications are off");
+            return None;
+        }
+        let service = Service::from_settings(settings.service.as_deref(), &url)?;
+        let queue = match DeliveryQueue::start("notifications", url, TIMEOUT_SECS, MAX_ATTEMPTS) {
+            Ok(queue) => queue,
+            Err(e) => {
+                eprintln!("⚠️  Notifications are off: {}", e);
+                return None;
+            }
+        };
+
+        println!("🔔 Sending {} notification rule(s) to {:?}", rules.len(), service);
+        Some(Self {
+            queue: Arc::new(queue),
+            service,
+            rules: Arc::new(rules),
+            instance: None,
+            progress: Arc::new(Mutex::new(Progress::default())),
+        })
+    }
+
+    /// A handle on the same webhook whose messages carry the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            queue: self.queue.clone(),
+            service: self.service,
+            rules: self.rules.clone(),
+            instance,
+            progress: Arc::new(Mutex::new(Progress::default())),
+        }
+    }
+
+    fn notify(
+        &self,
+        rule: &Rule,
+        run_name: Option<&str>,
+        tick: u64,
+        mut fields: serde_json::Map<String, serde_json::Value>,
+    ) {
+        fields.insert("tick".to_string(), serde_json::json!(tick));
+        fields.insert("run_name".to_string(), serde_json::json!(run_name));
+        fields.insert("instance".to_string(), serde_json::json!(self.instance));
+
+        let text = fill(&rule.message, &fields);
+        println!("🔔 {}", text);
+        // Never block the event path on a slow chat service
+        if !self.queue.try_deliver(self.service.body(&text)) {
+            eprintln!("⚠️  Notification queue is full, dropped: {}", text);
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for Notifier {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        self.progress.lock().unwrap().enter(&session.run_name);
+    }
+
+    async fn handle_stats(&self, stats: &StatsCycle) {
+        let Some(&evolution) = stats.scalars.get("enemy/evolution") else {
+            return;
+        };
+        let before = {
+            let mut progress = self.progress.lock().unwrap();
+            progress.enter(&stats.run_name);
+            progress.evolution.replace(evolution)
+        };
+        // The first value of a run is its baseline, not a milestone
+        let Some(before) = before else {
+            return;
+        };
+
+        for rule in self.rules.iter().filter(|rule| rule.milestone == Milestone::Evolution) {
+            for &threshold in &rule.thresholds {
+                if before < threshold && evolution >= threshold {
+                    let mut fields = serde_json::Map::new();
+                    fields.insert("threshold".to_string(), serde_json::json!(threshold));
+                    fields.insert("evolution".to_string(), serde_json::json!(evolution));
+                    self.notify(rule, Some(&stats.run_name), stats.tick, fields);
+                }
+            }
+        }
+    }
+
+    async fn handle_game_event(&self, tick: u64, event: &GameEvent) {
+        let (run_name, first_rocket) = {
+            let mut progress = self.progress.lock().unwrap();
+            let mut first_rocket = false;
+            if let GameEvent::RocketLaunched { count, .. } = event {
+                progress.rockets += 1;
+                // The force's count also covers rockets launched before the client started
+                first_rocket = count.map_or(progress.rockets == 1, |count| count == 1);
+            }
+            (progress.run_name.clone(), first_rocket)
+        };
+
+        for rule in self.rules.iter() {
+            let matches = match (rule.milestone, event) {
+                (Milestone::FirstRocket, GameEvent::RocketLaunched { .. }) => first_rocket,
+                (Milestone::RocketLaunched, GameEvent::RocketLaunched { .. }) => true,
+                (Milestone::ResearchFinished, GameEvent::ResearchFinished { tech_name, .. }) => {
+                    rule.names.is_empty() || rule.names.iter().any(|name| name == tech_name)
+                }
+                (Milestone::PlayerDied, GameEvent::PlayerDied { .. }) => true,
+                _ => false,
+            };
+            if matches {
+                self.notify(rule, run_name.as_deref(), tick, event.fields());
+            }
+        }
+    }
+
+    async fn flush(&self) {
+        self.queue.flush().await;
+    }
+
+    async fn shutdown(&self) {
+        self.flush().await;
+    }
+
+    fn name(&self) -> &'static str {
+        "notifications"
+    }
+}
+
+/// Replaces every `{{field}}` of the message by the field's value, as plain text.
+/// Missing and null fields render as `unknown`.
+fn fill(message: &str, fields: &serde_json::Map<String, serde_json::Value>) -> String {
+    let mut filled = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + end].trim();
+        match fields.get(name) {
+            Some(serde_json::Value::String(value)) => filled.push_str(value),
+            Some(serde_json::Value::Null) | None => filled.push_str("unknown"),
+            Some(value) => filled.push_str(&value.to_string()),
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
@@ -0,0 +1,307 @@
+use async_trait::async_trait;
+use factorio_events_core::client_events::{self, ClientEvent};
+use factorio_events_core::config::WebhookSettings;
+use factorio_events_core::event_sink::{EventSink, GameEvent, SessionStart, StatsCycle};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Deliveries queued per webhook before the sink drops new ones
+const QUEUE_DELIVERIES: usize = 256;
+
+/// First retry delay of a failed delivery, doubled per attempt
+const RETRY_BASE: Duration = Duration::from_secs(1);
+
+enum DeliveryMessage {
+    Body(String),
+    /// Delivers everything queued, then acknowledges
+    Flush(oneshot::Sender<()>),
+}
+
+/// Background task POSTing JSON bodies to one endpoint in order, with a timeout
+/// and retries
+pub(crate) struct DeliveryQueue {
+    sender: mpsc::Sender<DeliveryMessage>,
+}
+
+impl DeliveryQueue {
+    /// Starts the delivery task; `component` names the sender of dropped-delivery errors
+    pub(crate) fn start(
+        component: &'static str,
+        url: String,
+        timeout_secs: u64,
+        max_attempts: u32,
+    ) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout_secs.max(1)))
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let (sender, receiver) = mpsc::channel(QUEUE_DELIVERIES);
+        let delivery = Delivery {
+            component,
+            http,
+            url,
+            max_attempts: max_attempts.max(1),
+        };
+        tokio::spawn(delivery.run(receiver));
+        Ok(Self { sender })
+    }
+
+    /// Queues a body without waiting, returning false when the queue is full
+    pub(crate) fn try_deliver(&self, body: String) -> bool {
+        self.sender.try_send(DeliveryMessage::Body(body)).is_ok()
+    }
+
+    /// Waits until everything queued so far was delivered or dropped
+    pub(crate) async fn flush(&self) {
+        let (done, delivered) = oneshot::channel();
+        if self.sender.send(DeliveryMessage::Flush(done)).await.is_ok() {
+            delivered.await.ok();
+        }
+    }
+}
+
+/// One configured endpoint and its delivery queue
+struct Webhook {
+    /// Event names delivered to it; empty delivers every event
+    events: Vec<String>,
+    template: Option<String>,
+    queue: DeliveryQueue,
+}
+
+impl Webhook {
+    fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|name| name == event)
+    }
+}
+
+/// Sink POSTing selected events to HTTP endpoints, for Zapier/IFTTT-style automations.
+/// Each webhook renders its JSON body from a template whose `{{field}}` placeholders
+/// are replaced by the event's fields, or sends the fields as they are. A background
+/// task per webhook delivers the bodies in order, with a timeout and retries.
+#[derive(Clone)]
+pub struct WebhookSink {
+    hooks: Arc<Vec<Webhook>>,
+    instance: Option<String>,
+    /// (run name, session id) of the current session
+    session: Arc<Mutex<Option<(String, String)>>>,
+}
+
+impl WebhookSink {
+    /// Starts a delivery task per configured webhook, if any
+    pub fn from_settings(settings: &[WebhookSettings]) -> Option<Self> {
+        let mut hooks = Vec::new();
+        for hook in settings {
+            let url = match hook.url_env {
+                Some(ref name) => match std::env::var(name) {
+                    Ok(url) => url,
+                    Err(_) => {
+                        eprintln!("⚠️  {} is not set, skipping its webhook", name);
+                        continue;
+                    }
+                },
+                None => match hook.url {
+                    Some(ref url) => url.clone(),
+                    None => {
+                        eprintln!("⚠️  Webhook without url or url_env, skipping it");
+                        continue;
+                    }
+                },
+            };
+            let queue = match DeliveryQueue::start("webhook", url, hook.timeout_secs, hook.max_attempts)
+            {
+                Ok(queue) => queue,
+                Err(e) => {
+                    eprintln!("⚠️  Skipping webhook: {}", e);
+                    continue;
+                }
+            };
+            hooks.push(Webhook {
+                events: hook.events.clone(),
+                template: hook.template.clone(),
+                queue,
+            });
+        }
+        if hooks.is_empty() {
+            return None;
+        }
+
+        println!("🪝 Delivering events to {} webhook(s)", hooks.len());
+        Some(Self {
+            hooks: Arc::new(hooks),
+            instance: None,
+            session: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// A handle on the same webhooks whose events carry the given game instance id
+    pub fn for_instance(&self, instance: Option<String>) -> Self {
+        Self {
+            hooks: self.hooks.clone(),
+            instance,
+            session: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn deliver(&self, event: &str, tick: u64, mut fields: serde_json::Map<String, serde_json::Value>) {
+        if !self.hooks.iter().any(|hook| hook.wants(event)) {
+            return;
+        }
+
+        fields.insert("event".to_string(), serde_json::json!(event));
+        fields.insert("tick".to_string(), serde_json::json!(tick));
+        fields.insert("instance".to_string(), serde_json::json!(self.instance));
+        if let Some((ref run_name, ref session_id)) = *self.session.lock().unwrap() {
+            fields
+                .entry("run_name")
+                .or_insert_with(|| serde_json::json!(run_name));
+            fields
+                .entry("session_id")
+                .or_insert_with(|| serde_json::json!(session_id));
+        }
+        fields.insert(
+            "timestamp".to_string(),
+            serde_json::json!(chrono::Utc::now().to_rfc3339()),
+        );
+
+        for hook in self.hooks.iter().filter(|hook| hook.wants(event)) {
+            let body = match hook.template {
+                Some(ref template) => render(template, &fields),
+                None => serde_json::Value::Object(fields.clone()).to_string(),
+            };
+            // A template that doesn't render to JSON would be rejected anyway
+            if let Err(e) = serde_json::from_str::<serde_json::Value>(&body) {
+                eprintln!("⚠️  Webhook template for {} is not valid JSON: {}", event, e);
+                continue;
+            }
+            // Never block the event path on a slow endpoint
+            if !hook.queue.try_deliver(body) {
+                eprintln!("⚠️  Webhook queue is full, dropped a {} event", event);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    async fn handle_session_init(&self, session: &mut SessionStart) {
+        *self.session.lock().unwrap() =
+            Some((session.run_name.clone(), session.session_id.clone()));
+
+        let mut fields = serde_json::Map::new();
+        fields.insert("level_name".to_string(), serde_json::json!(session.level_name));
+        if let Some((_, ref url)) = session.wandb_run {
+            fields.insert("wandb_run_url".to_string(), serde_json::json!(url));
+        }
+        self.deliver("session_init", session.tick, fields);
+    }
+
+    async fn handle_stats(&self, _stats: &StatsCycle) {}
+
+    async fn handle_game_event(&self, tick: u64, event: &GameEvent) {
+        self.deliver(event.name(), tick, event.fields());
+    }
+
+    async fn flush(&self) {
+        for hook in self.hooks.iter() {
+            hook.queue.flush().await;
+        }
+    }
+
+    async fn shutdown(&self) {
+        self.flush().await;
+    }
+
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+}
+
+struct Delivery {
+    component: &'static str,
+    http: reqwest::Client,
+    url: String,
+    max_attempts: u32,
+}
+
+impl Delivery {
+    async fn run(self, mut receiver: mpsc::Receiver<DeliveryMessage>) {
+        while let Some(message) = receiver.recv().await {
+            match message {
+                DeliveryMessage::Body(body) => self.post(body).await,
+                DeliveryMessage::Flush(done) => {
+                    done.send(()).ok();
+                }
+            }
+        }
+    }
+
+    /// POSTs one body, retrying timeouts, server errors and network failures with
+    /// exponential backoff; client errors are not retried
+    async fn post(&self, body: String) {
+        let mut delay = RETRY_BASE;
+        for attempt in 1..=self.max_attempts {
+            let request = self
+                .http
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) if response.status().is_client_error() => {
+                    report_dropped(self.component, &format!("status {}", response.status()));
+                    return;
+                }
+                Ok(response) => format!("status {}", response.status()),
+                Err(e) if e.is_timeout() => "timed out".to_string(),
+                Err(e) => e.to_string(),
+            };
+            if attempt < self.max_attempts {
+                eprintln!(
+                    "⚠️  {} delivery failed (attempt {}), retrying in {:?}: {}",
+                    self.component, attempt, delay, error
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            } else {
+                report_dropped(self.component, &error);
+            }
+        }
+    }
+}
+
+fn report_dropped(component: &str, error: &str) {
+    eprintln!("❌ Dropped a {} delivery: {}", component, error);
+    client_events::emit(ClientEvent::Error {
+        component: component.to_string(),
+        message: format!("Dropped a delivery: {}", error),
+    });
+}
+
+/// Replaces every `{{field}}` of the template by the field's value: strings are
+/// JSON-escaped without their quotes (so they can sit inside a template string),
+/// other values are inserted as JSON. Unknown fields render as `null`.
+fn render(template: &str, fields: &serde_json::Map<String, serde_json::Value>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + end].trim();
+        match fields.get(name) {
+            Some(serde_json::Value::String(value)) => {
+                let quoted = serde_json::to_string(value).unwrap_or_default();
+                rendered.push_str(&quoted[1..quoted.len() - 1]);
+            }
+            Some(value) => rendered.push_str(&value.to_string()),
+            None => rendered.push_str("null"),
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
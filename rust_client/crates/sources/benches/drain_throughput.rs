@@ -0,0 +1,136 @@
+//! Drain throughput of the pipe cache with several readers pushing at once, against the
+//! `Mutex<VecDeque<String>>` cache it replaced. Every reader parses its lines into typed
+//! events before handing them over, as the pipe reader does, while the mutex cache moves
+//! the line strings the drain loop used to parse. Run with
+//! `cargo bench --bench drain_throughput`.
+
+use factorio_events_core::event_mediator::{parse_event_bytes, FactorioEvent};
+use factorio_events_sources::pipe_cache::{OverflowPolicy, PipeCache};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Events pushed by every reader
+const EVENTS_PER_READER: usize = 200_000;
+
+/// Reader counts measured, from one game instance to many busy players
+const READERS: &[usize] = &[1, 4, 16];
+
+const CAPACITY: usize = 10_000;
+
+/// The queue the benchmark fills and drains
+trait Queue: Send + Sync + 'static {
+    /// Hands over one line read into the reader's buffer
+    fn push(&self, line: &[u8]);
+    /// Drains the queue, returning how many events it held
+    fn drain_all(&self) -> usize;
+}
+
+impl Queue for PipeCache {
+    fn push(&self, line: &[u8]) {
+        let event: FactorioEvent = parse_event_bytes(line).expect("the line parses");
+        self.push_event(event);
+    }
+
+    fn drain_all(&self) -> usize {
+        PipeCache::drain_all(self).len()
+    }
+}
+
+/// The previous cache: one lock shared by the readers and the drain
+struct MutexQueue {
+    events: Mutex<VecDeque<String>>,
+}
+
+impl Queue for MutexQueue {
+    fn push(&self, line: &[u8]) {
+        // Waits for room like the `block` policy, so both queues deliver every event
+        let mut line = Some(String::from_utf8_lossy(line).into_owned());
+        while let Some(pending) = line.take() {
+            let mut events = self.events.lock().unwrap();
+            if events.len() < CAPACITY {
+                events.push_back(pending);
+            } else {
+                drop(events);
+                line = Some(pending);
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+
+    fn drain_all(&self) -> usize {
+        // The drain loop parsed every line it drained
+        let lines: Vec<String> = self.events.lock().unwrap().drain(..).collect();
+        for line in &lines {
+            parse_event_bytes(line.as_bytes()).expect("the line parses");
+        }
+        lines.len()
+    }
+}
+
+/// Pushes `EVENTS_PER_READER` events from each reader while the drain keeps emptying the
+/// queue, returning the drained events per second
+fn measure<Q: Queue>(queue: Arc<Q>, readers: usize) -> f64 {
+    let total = readers * EVENTS_PER_READER;
+    let line = br#"{"type":"event","event_name":"on_player_crafted_item","session_id":"bench","tick":123456,"player_index":1,"item":"iron-gear-wheel","count":1}"#;
+
+    let started = Instant::now();
+    let pushers: Vec<_> = (0..readers)
+        .map(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for _ in 0..EVENTS_PER_READER {
+                    queue.push(line);
+                }
+            })
+        })
+        .collect();
+
+    let drain = {
+        let queue = queue.clone();
+        thread::spawn(move || {
+            let mut drained = 0;
+            while drained < total {
+                let events = queue.drain_all();
+                if events == 0 {
+                    thread::yield_now();
+                }
+                drained += events;
+            }
+            drained
+        })
+    };
+
+    for pusher in pushers {
+        pusher.join().unwrap();
+    }
+    let drained = drain.join().unwrap();
+    drained as f64 / started.elapsed().as_secs_f64()
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>16} {:>16} {:>8}",
+        "readers", "mutex events/s", "cache events/s", "speedup"
+    );
+    for &readers in READERS {
+        let mutex = measure(
+            Arc::new(MutexQueue {
+                events: Mutex::new(VecDeque::with_capacity(CAPACITY)),
+            }),
+            readers,
+        );
+        let cache = measure(
+            Arc::new(PipeCache::new(CAPACITY, OverflowPolicy::Block)),
+            readers,
+        );
+        println!(
+            "{:>8} {:>16.0} {:>16.0} {:>7.2}x",
+            readers,
+            mutex,
+            cache,
+            cache / mutex
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Prefix of a fragment line, checked before parsing a line as an event
+pub const FRAGMENT_PREFIX: &[u8] = b"{\"chunk\":";
+
+/// Fragments of one event accepted at most, which bounds what a sender can make a reader hold
+const MAX_CHUNKS: usize = 1024;
+
+/// How long the fragments of an event wait for the rest before they are dropped
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Position of a fragment among those of one event line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    /// Shared by the fragments of one event, unique per sender
+    pub id: String,
+    pub index: usize,
+    pub count: usize,
+}
+
+/// A piece of an event line too large to be written at once:
+/// `{"chunk": {"id": "...", "index": 0, "count": 3}, "data": "..."}`, where the `data`
+/// of the fragments, joined in index order, is the event's JSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fragment {
+    pub chunk: Chunk,
+    pub data: String,
+}
+
+impl Fragment {
+    /// Splits a line into fragments of at most `max_bytes` of its text, cut at character
+    /// boundaries
+    pub fn split(id: &str, line: &str, max_bytes: usize) -> Vec<Fragment> {
+        let max_bytes = max_bytes.max(4);
+        let mut pieces = Vec::new();
+        let mut rest = line;
+        while !rest.is_empty() {
+            let mut end = max_bytes.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (piece, tail) = rest.split_at(end);
+            pieces.push(piece.to_string());
+            rest = tail;
+        }
+
+        let count = pieces.len();
+        pieces
+            .into_iter()
+            .enumerate()
+            .map(|(index, data)| Fragment {
+                chunk: Chunk {
+                    id: id.to_string(),
+                    index,
+                    count,
+                },
+                data,
+            })
+            .collect()
+    }
+}
+
+struct Partial {
+    parts: Vec<Option<String>>,
+    received: usize,
+    started: Instant,
+}
+
+/// Reassembles event lines from their fragments. Fragments may arrive out of order and
+/// interleaved with those of other events; an event whose fragments don't all arrive
+/// within the timeout is dropped.
+#[derive(Default)]
+pub struct ChunkAssembler {
+    pending: HashMap<String, Partial>,
+}
+
+impl ChunkAssembler {
+    /// Takes a fragment, returning the event line once its last fragment arrived
+    pub fn accept(&mut self, fragment: Fragment) -> Option<String> {
+        self.expire();
+
+        let Chunk { id, index, count } = fragment.chunk;
+        if count == 0 || count > MAX_CHUNKS || index >= count {
+            eprintln!("⚠️  Ignoring fragment {}/{} of event {}", index, count, id);
+            return None;
+        }
+
+        let partial = self.pending.entry(id.clone()).or_insert_with(|| Partial {
+            parts: vec![None; count],
+            received: 0,
+            started: Instant::now(),
+        });
+        if partial.parts.len() != count {
+            eprintln!("⚠️  Fragments of event {} disagree on their count", id);
+            self.pending.remove(&id);
+            return None;
+        }
+        if partial.parts[index].is_none() {
+            partial.parts[index] = Some(fragment.data);
+            partial.received += 1;
+        }
+        if partial.received < count {
+            return None;
+        }
+
+        let partial = self.pending.remove(&id)?;
+        Some(partial.parts.into_iter().flatten().collect())
+    }
+
+    /// Drops the events still missing fragments after the timeout
+    fn expire(&mut self) {
+        self.pending.retain(|id, partial| {
+            let waiting = partial.started.elapsed() < CHUNK_TIMEOUT;
+            if !waiting {
+                eprintln!(
+                    "⚠️  Dropping event {}: {} of {} fragments arrived",
+                    id,
+                    partial.received,
+                    partial.parts.len()
+                );
+            }
+            waiting
+        });
+    }
+}
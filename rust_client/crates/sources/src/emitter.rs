@@ -0,0 +1,148 @@
+use crate::chunking::Fragment;
+use factorio_events_core::event_mediator::parse_event;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::UdpSocket;
+use std::path::PathBuf;
+
+/// Largest write to a pipe the kernel keeps whole when several writers share it
+const PIPE_BUF: usize = 4096;
+
+/// Largest datagram sent, well below what the UDP reader receives
+const MAX_DATAGRAM_BYTES: usize = 60_000;
+
+/// Text of the event carried by a fragment before it is tried at half the size. JSON
+/// escaping may double it, which still fits a pipe write.
+const FRAGMENT_BYTES: usize = 1800;
+
+/// Where an emitter writes: `udp:<host>:<port>`, or the path of the pipe (or any file
+/// the client reads)
+#[derive(Debug, Clone)]
+pub enum EmitTarget {
+    Udp(String),
+    Pipe(PathBuf),
+}
+
+impl EmitTarget {
+    pub fn parse(target: &str) -> Self {
+        match target.strip_prefix("udp:") {
+            Some(addr) => Self::Udp(addr.to_string()),
+            None => Self::Pipe(PathBuf::from(target)),
+        }
+    }
+}
+
+enum Sink {
+    Udp(UdpSocket),
+    Pipe(File),
+}
+
+/// Writes events into the pipe or UDP source the way the mod does, so scripts and
+/// tools can inject events the client ingests like the game's own. Every event is one
+/// compact JSON line in a single write; one too large to stay whole is split into
+/// fragments the readers reassemble. Over UDP each event carries the next sequence
+/// number of the emitter's `source`, which the reader tracks apart from the mod's.
+pub struct Emitter {
+    sink: Sink,
+    source: String,
+    seq: u64,
+}
+
+impl Emitter {
+    /// Opens the target; a pipe blocks until the client has it open for reading
+    pub fn connect(target: &EmitTarget, source: &str) -> Result<Self, String> {
+        let sink = match target {
+            EmitTarget::Udp(addr) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")
+                    .and_then(|socket| socket.connect(addr).map(|_| socket))
+                    .map_err(|e| format!("Failed to connect to {}: {}", addr, e))?;
+                Sink::Udp(socket)
+            }
+            EmitTarget::Pipe(path) => Sink::Pipe(
+                OpenOptions::new()
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("Failed to open {:?}: {}", path, e))?,
+            ),
+        };
+        Ok(Self {
+            sink,
+            source: source.to_string(),
+            seq: 0,
+        })
+    }
+
+    pub fn emit(&mut self, event: &serde_json::Value) -> Result<(), String> {
+        self.emit_line(&event.to_string())
+    }
+
+    /// Writes one event given as JSON, refusing what the client wouldn't parse as one
+    pub fn emit_line(&mut self, line: &str) -> Result<(), String> {
+        let event: serde_json::Value =
+            serde_json::from_str(line.trim()).map_err(|e| format!("Invalid JSON: {}", e))?;
+        let line = event.to_string();
+        parse_event(&line).map_err(|e| format!("Not an event the client ingests: {}", e))?;
+
+        self.seq += 1;
+        let id = format!("{}-{}-{}", self.source, std::process::id(), self.seq);
+        match &mut self.sink {
+            Sink::Pipe(file) => {
+                let lines = if line.len() < PIPE_BUF {
+                    vec![line]
+                } else {
+                    fragment_lines(&id, &line, PIPE_BUF - 1, |fragment| {
+                        serde_json::to_string(fragment).unwrap_or_default()
+                    })
+                };
+                for mut line in lines {
+                    line.push('\n');
+                    file.write_all(line.as_bytes())
+                        .map_err(|e| format!("Failed to write event: {}", e))?;
+                }
+            }
+            Sink::Udp(socket) => {
+                let envelope =
+                    serde_json::json!({"seq": self.seq, "source": self.source, "event": event})
+                        .to_string();
+                let datagrams = if envelope.len() <= MAX_DATAGRAM_BYTES {
+                    vec![envelope]
+                } else {
+                    let (seq, source) = (self.seq, &self.source);
+                    fragment_lines(&id, &line, MAX_DATAGRAM_BYTES, |fragment| {
+                        serde_json::json!({
+                            "seq": seq,
+                            "source": source,
+                            "chunk": fragment.chunk,
+                            "data": fragment.data,
+                        })
+                        .to_string()
+                    })
+                };
+                for datagram in datagrams {
+                    socket
+                        .send(datagram.as_bytes())
+                        .map_err(|e| format!("Failed to send event: {}", e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a line into fragments whose encoding by `wrap` is at most `limit` bytes,
+/// halving their size while escaping makes one too long
+fn fragment_lines(
+    id: &str,
+    line: &str,
+    limit: usize,
+    wrap: impl Fn(&Fragment) -> String,
+) -> Vec<String> {
+    let mut size = FRAGMENT_BYTES.max(limit / 4);
+    loop {
+        let lines: Vec<String> = Fragment::split(id, line, size).iter().map(&wrap).collect();
+        if size <= 64 || lines.iter().all(|line| line.len() <= limit) {
+            return lines;
+        }
+        size /= 2;
+    }
+}
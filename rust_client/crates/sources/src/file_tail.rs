@@ -0,0 +1,167 @@
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Extension of the event files the mod writes into script-output
+const TAIL_EXT: &str = "jsonl";
+
+/// Directory rescan interval when no change notification arrives (e.g. on network
+/// filesystems that don't deliver them)
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Read position in one tailed file
+struct TailedFile {
+    file: File,
+    inode: u64,
+    offset: u64,
+    /// Bytes after the last newline, completed by the next append
+    partial: Vec<u8>,
+}
+
+impl TailedFile {
+    fn open(path: &Path, from_end: bool) -> Option<Self> {
+        let mut file = File::open(path).ok()?;
+        let metadata = file.metadata().ok()?;
+        let offset = if from_end { metadata.len() } else { 0 };
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        Some(Self {
+            file,
+            inode: metadata.ino(),
+            offset,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Reads everything appended since the last read, calling `on_line` for each
+    /// complete line
+    fn read_appended(&mut self, on_line: &mut impl FnMut(&str)) {
+        let mut appended = Vec::new();
+        match self.file.read_to_end(&mut appended) {
+            Ok(read) => self.offset += read as u64,
+            Err(e) => {
+                eprintln!("Error reading tailed file: {}", e);
+                return;
+            }
+        }
+
+        self.partial.extend_from_slice(&appended);
+        let Some(last_newline) = self.partial.iter().rposition(|&b| b == b'\n') else {
+            return;
+        };
+        let rest = self.partial.split_off(last_newline + 1);
+        for line in String::from_utf8_lossy(&self.partial).lines() {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                on_line(trimmed);
+            }
+        }
+        self.partial = rest;
+    }
+}
+
+/// Tails the JSONL files of a directory, like `tail -F` on each of them. Files present
+/// at startup are read from their end and files created later from their start. A file
+/// rotated away (renamed or moved out) is read to its end while the new one is followed
+/// from its start, and a truncated file is followed again from its start.
+pub struct FileTailSource {
+    dir: PathBuf,
+    files: HashMap<PathBuf, TailedFile>,
+}
+
+impl FileTailSource {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Watches the directory and calls `on_line` for every appended line; never returns
+    /// unless the directory can't be watched
+    pub fn run(mut self, mut on_line: impl FnMut(&str)) -> Result<(), String> {
+        let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+        watcher
+            .watch(&self.dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {:?}: {}", self.dir, e))?;
+        println!("Tailing *.{} files in {:?}", TAIL_EXT, self.dir);
+
+        for path in self.list_files() {
+            if let Some(tailed) = TailedFile::open(&path, true) {
+                self.files.insert(path, tailed);
+            }
+        }
+
+        loop {
+            // Any notification (or the rescan timeout) triggers a pass over all files
+            match rx.recv_timeout(RESCAN_INTERVAL) {
+                Ok(Err(e)) => eprintln!("⚠️  File watcher error: {}", e),
+                Ok(Ok(_)) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err("File watcher stopped".to_string());
+                }
+            }
+            while rx.try_recv().is_ok() {}
+
+            self.poll(&mut on_line);
+        }
+    }
+
+    /// Reads new lines of every file, picking up created, rotated and truncated files.
+    /// Files are matched to their read position by inode, so a file renamed within the
+    /// directory keeps its position under the new name.
+    fn poll(&mut self, on_line: &mut impl FnMut(&str)) {
+        let mut previous: HashMap<u64, TailedFile> = self
+            .files
+            .drain()
+            .map(|(_, tailed)| (tailed.inode, tailed))
+            .collect();
+
+        for path in self.list_files() {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let tailed = match previous.remove(&metadata.ino()) {
+                Some(tailed) => tailed,
+                None => match TailedFile::open(&path, false) {
+                    Some(opened) => opened,
+                    None => continue,
+                },
+            };
+            let tailed = self.files.entry(path.clone()).or_insert(tailed);
+
+            if metadata.len() < tailed.offset {
+                println!("Tailed file {:?} was truncated, reading from start", path);
+                tailed.offset = 0;
+                tailed.partial.clear();
+                if tailed.file.seek(SeekFrom::Start(0)).is_err() {
+                    continue;
+                }
+            }
+            tailed.read_appended(on_line);
+        }
+
+        // Files rotated out of the directory or deleted: read what was appended last
+        for mut tailed in previous.into_values() {
+            tailed.read_appended(on_line);
+        }
+    }
+
+    /// Lists the JSONL files of the directory
+    fn list_files(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == TAIL_EXT))
+            .collect()
+    }
+}
@@ -0,0 +1,112 @@
+use factorio_events_core::config;
+use factorio_events_core::event_mediator::{parse_event, FactorioEvent};
+use factorio_events_core::spool::Spool;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Segments are never rotated mid-batch: each one holds the events of one drain
+const SEGMENT_BYTES: u64 = u64::MAX;
+
+/// Write-ahead log of drained events. Each drained batch is written to its own spool
+/// segment before it is processed, and the segment is deleted once every one of its
+/// events went through the mediator and the sinks were flushed. Segments left behind by
+/// a crash are replayed when the drain loop starts, so events are delivered at least
+/// once: the events of a partly processed batch are processed again.
+pub struct IngestWal {
+    spool: Spool,
+    /// Logged segments oldest first, with how many of their events are unprocessed
+    segments: VecDeque<(PathBuf, usize)>,
+}
+
+impl IngestWal {
+    /// Log directory of a game instance, in `dir` or the state directory
+    pub fn dir_for(dir: Option<&PathBuf>, instance: Option<&str>) -> PathBuf {
+        let base = match dir {
+            Some(dir) => dir.clone(),
+            None => config::state_dir().join("ingest_wal"),
+        };
+        match instance {
+            Some(instance) => base.join("instances").join(instance),
+            None => base,
+        }
+    }
+
+    /// Opens the log, keeping the segments a previous process left unprocessed
+    pub fn open(dir: PathBuf) -> Result<Self, String> {
+        let mut spool = Spool::open(dir, SEGMENT_BYTES)?;
+        let segments = spool
+            .pending_segments()?
+            .into_iter()
+            .map(|path| (path, 0))
+            .collect();
+        Ok(Self { spool, segments })
+    }
+
+    /// Every event not yet processed, oldest first. Partly processed segments are
+    /// returned whole, and segments without events are deleted. Events logged as JSONL
+    /// strings by earlier versions are parsed like a reader would.
+    pub fn pending(&mut self) -> Result<Vec<FactorioEvent>, String> {
+        let mut events = Vec::new();
+        let mut segments = VecDeque::new();
+        for (path, _) in self.segments.drain(..) {
+            let logged: Vec<FactorioEvent> = Spool::read_segment(&path)?
+                .into_iter()
+                .filter_map(|record| {
+                    let event = match record {
+                        serde_json::Value::String(line) => parse_event(&line),
+                        record => serde_json::from_value(record).map_err(|e| e.to_string()),
+                    };
+                    match event {
+                        Ok(event) => Some(event),
+                        Err(e) => {
+                            eprintln!("⚠️  Skipping unreadable write-ahead log event: {}", e);
+                            None
+                        }
+                    }
+                })
+                .collect();
+            if logged.is_empty() {
+                self.spool.discard(&path)?;
+                continue;
+            }
+            segments.push_back((path, logged.len()));
+            events.extend(logged);
+        }
+        self.segments = segments;
+        Ok(events)
+    }
+
+    /// Writes a drained batch to disk before it is processed
+    pub fn append(&mut self, events: &[FactorioEvent]) -> Result<(), String> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        for event in events {
+            let record = serde_json::to_value(event)
+                .map_err(|e| format!("Failed to serialize event: {}", e))?;
+            self.spool.append(&record)?;
+        }
+        if let Some(path) = self.spool.seal()? {
+            self.segments.push_back((path, events.len()));
+        }
+        Ok(())
+    }
+
+    /// Marks the oldest `count` logged events as processed, deleting the segments that
+    /// have no unprocessed event left
+    pub fn commit(&mut self, mut count: usize) -> Result<(), String> {
+        while count > 0 {
+            let Some((path, remaining)) = self.segments.front_mut() else {
+                break;
+            };
+            if count < *remaining {
+                *remaining -= count;
+                break;
+            }
+            count -= *remaining;
+            self.spool.discard(path)?;
+            self.segments.pop_front();
+        }
+        Ok(())
+    }
+}
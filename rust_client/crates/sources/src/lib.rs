@@ -0,0 +1,10 @@
+//! Where events come from: the named pipe, tailed files and UDP, queued for the mediator,
+//! and the emitter other tools use to write events into them.
+
+pub mod chunking;
+pub mod emitter;
+pub mod file_tail;
+pub mod ingest_wal;
+pub mod pipe_cache;
+pub mod rcon_client;
+pub mod udp_ingest;
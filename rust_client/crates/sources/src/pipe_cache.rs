@@ -0,0 +1,400 @@
+use crate::chunking::{ChunkAssembler, Fragment, FRAGMENT_PREFIX};
+use crate::file_tail::FileTailSource;
+use crate::udp_ingest::{decode_datagram, Datagram, Decoded, ReorderBuffer, SequenceTracker};
+use crossbeam_queue::ArrayQueue;
+use factorio_events_core::event_mediator::{parse_event_bytes, FactorioEvent};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// How long datagrams are held back to restore tick order
+const REORDER_WINDOW: Duration = Duration::from_millis(200);
+
+/// How often a reader blocked by a full cache retries
+const BLOCK_RETRY: Duration = Duration::from_millis(1);
+
+/// Opens the capture log for appending
+fn open_log(path: Option<&str>) -> Option<File> {
+    path.map(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("Failed to open log file")
+    })
+}
+
+/// What a reader does with an event when the cache is full
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowPolicy {
+    /// Wait for the drain loop to make room, holding back the game's writes
+    Block,
+    /// Evict the oldest cached event
+    DropOldest,
+    /// Discard the event
+    DropNewest,
+}
+
+impl OverflowPolicy {
+    /// The configured policy; unknown names fall back to `drop_oldest`
+    pub fn from_settings(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "block" => OverflowPolicy::Block,
+            "drop_oldest" => OverflowPolicy::DropOldest,
+            "drop_newest" => OverflowPolicy::DropNewest,
+            other => {
+                eprintln!("⚠️  Unknown cache overflow policy '{}', dropping the oldest events", other);
+                OverflowPolicy::DropOldest
+            }
+        }
+    }
+}
+
+/// The line without its surrounding whitespace
+fn trim_line(line: &[u8]) -> &[u8] {
+    let start = line
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(line.len());
+    let end = line
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |end| end + 1);
+    &line[start..end]
+}
+
+/// Bounded queue handing the events of the background readers to the drain loop.
+/// The queue is lock-free, so readers pushing per-tick events never contend with each
+/// other or with the drain for a lock. Readers parse each line as it arrives, so the
+/// queue holds typed events and no line is kept or copied as a `String`.
+#[derive(Clone)]
+pub struct PipeCache {
+    events: Arc<ArrayQueue<FactorioEvent>>,
+    overflow: OverflowPolicy,
+    /// Number of events discarded because the cache was full
+    dropped: Arc<AtomicU64>,
+    /// Number of events a reader waited to hand over because the cache was full
+    blocked: Arc<AtomicU64>,
+    /// Number of lines the readers discarded because they didn't parse
+    parse_errors: Arc<AtomicU64>,
+    /// Datagrams missing from the UDP sequence
+    udp_lost: Arc<AtomicU64>,
+    /// Signaled by the readers whenever they push an event
+    arrived: Arc<Notify>,
+    /// Event kinds dispatched without waiting for the drain interval
+    immediate: Arc<HashSet<String>>,
+    /// Signaled by the readers when they push an event of an immediate kind
+    urgent: Arc<Notify>,
+}
+
+impl PipeCache {
+    /// Create a new PipeCache holding up to `capacity` events
+    pub fn new(capacity: usize, overflow: OverflowPolicy) -> Self {
+        Self {
+            events: Arc::new(ArrayQueue::new(capacity.max(1))),
+            overflow,
+            dropped: Arc::new(AtomicU64::new(0)),
+            blocked: Arc::new(AtomicU64::new(0)),
+            parse_errors: Arc::new(AtomicU64::new(0)),
+            udp_lost: Arc::new(AtomicU64::new(0)),
+            arrived: Arc::new(Notify::new()),
+            immediate: Arc::new(HashSet::new()),
+            urgent: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Wakes the drain at once for events of these kinds (see `wait_for_urgent`)
+    pub fn with_immediate(mut self, kinds: &[String]) -> Self {
+        self.immediate = Arc::new(kinds.iter().cloned().collect());
+        self
+    }
+
+    /// Start the background reader thread
+    pub fn start_reader(&self, pipe_path: String, log_path: Option<String>) {
+        let cache = self.clone();
+
+        thread::spawn(move || {
+            println!("Pipe reader thread started");
+            println!("Reading from: {}", pipe_path);
+
+            // Open log file if specified
+            let mut log_file = open_log(log_path.as_deref());
+            let mut chunks = ChunkAssembler::default();
+
+            // Open the pipe once and keep reading
+            loop {
+                match File::open(&pipe_path) {
+                    Ok(pipe) => {
+                        println!("Successfully opened pipe");
+                        let mut reader = BufReader::new(pipe);
+                        // Reused for every line, and parsed in place
+                        let mut line = Vec::new();
+
+                        // Keep reading lines from the same pipe
+                        loop {
+                            line.clear();
+                            match reader.read_until(b'\n', &mut line) {
+                                Ok(0) => {
+                                    // EOF reached - writer closed the pipe
+                                    // This is normal, just reopen
+                                    thread::sleep(std::time::Duration::from_millis(100));
+                                    break;
+                                }
+                                Ok(_) => {
+                                    // Successfully read a line
+                                    cache.push(&line, &mut chunks, &mut log_file);
+                                }
+                                Err(e) => {
+                                    eprintln!("Error reading line: {}", e);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to open pipe: {}, retrying in 1 second...", e);
+                        thread::sleep(std::time::Duration::from_secs(1));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Start a background thread receiving one JSON event per UDP datagram instead of
+    /// reading the pipe. Events are reordered by tick within a short window, and gaps in
+    /// the datagram sequence numbers of each sender are counted as lost. Events split
+    /// into fragments are reassembled before they are numbered.
+    pub fn start_udp_reader(&self, bind_addr: String, log_path: Option<String>) {
+        let cache = self.clone();
+
+        thread::spawn(move || {
+            let socket = match UdpSocket::bind(&bind_addr) {
+                Ok(socket) => socket,
+                Err(e) => {
+                    eprintln!("❌ Failed to bind UDP socket {}: {}", bind_addr, e);
+                    return;
+                }
+            };
+            socket.set_read_timeout(Some(REORDER_WINDOW / 2)).ok();
+            println!("UDP reader listening on {}", bind_addr);
+
+            let mut log_file = open_log(log_path.as_deref());
+            let mut reorder = ReorderBuffer::new(REORDER_WINDOW);
+            let mut sequences: HashMap<String, SequenceTracker> = HashMap::new();
+            let mut lost_by_source: HashMap<String, u64> = HashMap::new();
+            let mut chunks = ChunkAssembler::default();
+            let mut buf = vec![0u8; 65536];
+
+            loop {
+                let received = match socket.recv(&mut buf) {
+                    Ok(len) => match decode_datagram(&buf[..len]) {
+                        Some(Decoded::Event(datagram)) => Some(datagram),
+                        Some(Decoded::Fragment {
+                            source,
+                            seq,
+                            fragment,
+                        }) => chunks.accept(fragment).and_then(|line| {
+                            let datagram = Datagram::from_line(source, seq, line);
+                            if datagram.is_none() {
+                                eprintln!("⚠️  Ignoring malformed reassembled UDP event {}", seq);
+                            }
+                            datagram
+                        }),
+                        None => {
+                            eprintln!("⚠️  Ignoring malformed UDP datagram ({} bytes)", len);
+                            None
+                        }
+                    },
+                    Err(e)
+                        if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                    {
+                        None
+                    }
+                    Err(e) => {
+                        eprintln!("Error receiving datagram: {}", e);
+                        None
+                    }
+                };
+                if let Some(datagram) = received {
+                    let lost = sequences
+                        .entry(datagram.source.clone())
+                        .or_default()
+                        .observe(datagram.seq);
+                    lost_by_source.insert(datagram.source.clone(), lost);
+                    cache
+                        .udp_lost
+                        .store(lost_by_source.values().sum(), Ordering::Relaxed);
+                    reorder.push(datagram);
+                }
+
+                for line in reorder.release_due() {
+                    cache.push_line(line.as_bytes(), &mut log_file);
+                }
+            }
+        });
+    }
+
+    /// Start a background thread tailing the JSONL files the mod writes into a directory
+    /// instead of reading the pipe
+    pub fn start_file_tail(&self, dir: String, log_path: Option<String>) {
+        let cache = self.clone();
+
+        thread::spawn(move || {
+            let mut log_file = open_log(log_path.as_deref());
+            let mut chunks = ChunkAssembler::default();
+            let source = FileTailSource::new(PathBuf::from(&dir));
+            if let Err(e) =
+                source.run(|line| cache.push(line.as_bytes(), &mut chunks, &mut log_file))
+            {
+                eprintln!("❌ File tail of {} stopped: {}", dir, e);
+            }
+        });
+    }
+
+    /// Adds a line read from the pipe or a file; the fragments of a large event (see
+    /// `chunking`) are held until the whole event is in
+    fn push(&self, line: &[u8], chunks: &mut ChunkAssembler, log_file: &mut Option<File>) {
+        let line = trim_line(line);
+        if !line.starts_with(FRAGMENT_PREFIX) {
+            self.push_line(line, log_file);
+            return;
+        }
+        match serde_json::from_slice::<Fragment>(line) {
+            Ok(fragment) => {
+                if let Some(event) = chunks.accept(fragment) {
+                    self.push_line(event.as_bytes(), log_file);
+                }
+            }
+            Err(e) => {
+                self.parse_errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("Failed to parse event fragment - Error: {}", e);
+            }
+        }
+    }
+
+    /// Adds a line to the log and, once parsed, to the cache
+    fn push_line(&self, line: &[u8], log_file: &mut Option<File>) {
+        let line = trim_line(line);
+        if line.is_empty() {
+            return;
+        }
+        // Write to log file if specified
+        if let Some(ref mut log) = log_file {
+            log.write_all(line).and_then(|_| log.write_all(b"\n")).ok();
+        }
+        match parse_event_bytes(line) {
+            Ok(event) => self.push_event(event),
+            Err(e) => {
+                self.parse_errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!(
+                    "Failed to parse event: {} - Error: {}",
+                    String::from_utf8_lossy(line),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Adds an event as if a reader received it, applying the overflow policy when the
+    /// cache is full. Under `block`, waits (without a runtime) until the drain makes room.
+    pub fn push_event(&self, event: FactorioEvent) {
+        let urgent = self.immediate.contains(event.kind());
+        match self.overflow {
+            OverflowPolicy::DropOldest => {
+                if self.events.force_push(event).is_some() {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            OverflowPolicy::DropNewest => {
+                if self.events.push(event).is_err() {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+            OverflowPolicy::Block => {
+                let mut event = event;
+                let mut waited = false;
+                while let Err(rejected) = self.events.push(event) {
+                    if !waited {
+                        self.blocked.fetch_add(1, Ordering::Relaxed);
+                        waited = true;
+                    }
+                    event = rejected;
+                    thread::sleep(BLOCK_RETRY);
+                }
+            }
+        }
+        self.arrived.notify_one();
+        if urgent {
+            self.urgent.notify_one();
+        }
+    }
+
+    /// Waits until a reader pushes an event; returns at once if one was pushed since the
+    /// last wait
+    pub async fn wait_for_events(&self) {
+        self.arrived.notified().await;
+    }
+
+    /// Waits until a reader pushes an event of an immediate kind; returns at once if one
+    /// was pushed since the last wait
+    pub async fn wait_for_urgent(&self) {
+        self.urgent.notified().await;
+    }
+
+    /// Pop the oldest event (destructive read)
+    pub fn pop_front(&self) -> Option<FactorioEvent> {
+        self.events.pop()
+    }
+
+    /// Drain all events (destructive read). Events pushed meanwhile are left for the
+    /// next drain, so a reader that keeps pushing can't hold the drain up.
+    pub fn drain_all(&self) -> Vec<FactorioEvent> {
+        let queued = self.events.len();
+        let mut events = Vec::with_capacity(queued);
+        while events.len() < queued {
+            match self.events.pop() {
+                Some(event) => events.push(event),
+                None => break,
+            }
+        }
+        events
+    }
+
+    /// Get the current number of cached events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Get the total number of events dropped due to cache overflow
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Get the total number of events a reader had to wait to cache (`block` policy)
+    pub fn blocked_count(&self) -> u64 {
+        self.blocked.load(Ordering::Relaxed)
+    }
+
+    /// Get the total number of lines discarded because they didn't parse
+    pub fn parse_error_count(&self) -> u64 {
+        self.parse_errors.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of UDP datagrams currently considered lost
+    pub fn udp_lost_count(&self) -> u64 {
+        self.udp_lost.load(Ordering::Relaxed)
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
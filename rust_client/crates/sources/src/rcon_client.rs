@@ -0,0 +1,292 @@
+use factorio_events_core::config::RconSettings;
+use factorio_events_core::upload_budget::UploadBudget;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Packet types of the Source RCON protocol Factorio speaks
+const TYPE_AUTH: i32 = 3;
+const TYPE_EXEC_COMMAND: i32 = 2;
+const TYPE_AUTH_RESPONSE: i32 = 2;
+const TYPE_RESPONSE_VALUE: i32 = 0;
+
+/// Largest packet accepted from the server; `size` counts id, type, body and padding
+const MAX_PACKET_BYTES: usize = 1024 * 1024;
+
+/// Connect, auth and command round trips give up after this long
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connection to a Factorio server's RCON port
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    /// Connects to `address` and authenticates with `password`
+    pub async fn connect(address: &str, password: &str) -> Result<Self, String> {
+        let stream = tokio::time::timeout(IO_TIMEOUT, TcpStream::connect(address))
+            .await
+            .map_err(|_| format!("Timed out connecting to RCON at {}", address))?
+            .map_err(|e| format!("Failed to connect to RCON at {}: {}", address, e))?;
+
+        let mut client = Self { stream, next_id: 1 };
+        let id = client.send(TYPE_AUTH, password).await?;
+
+        // Some servers send an empty response value before the auth response
+        loop {
+            let (response_id, kind, _) = client.receive().await?;
+            if kind != TYPE_AUTH_RESPONSE {
+                continue;
+            }
+            if response_id == -1 {
+                return Err("RCON authentication failed (wrong password?)".to_string());
+            }
+            if response_id == id {
+                return Ok(client);
+            }
+        }
+    }
+
+    /// Runs a console command and returns its output
+    pub async fn execute(&mut self, command: &str) -> Result<String, String> {
+        let id = self.send(TYPE_EXEC_COMMAND, command).await?;
+        loop {
+            let (response_id, kind, body) = self.receive().await?;
+            if response_id == id && kind == TYPE_RESPONSE_VALUE {
+                return Ok(body);
+            }
+        }
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32, String> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+
+        let size = (4 + 4 + body.len() + 2) as i32;
+        let mut packet = Vec::with_capacity(size as usize + 4);
+        packet.extend_from_slice(&size.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        tokio::time::timeout(IO_TIMEOUT, self.stream.write_all(&packet))
+            .await
+            .map_err(|_| "Timed out writing to RCON".to_string())?
+            .map_err(|e| format!("Failed to write to RCON: {}", e))?;
+        Ok(id)
+    }
+
+    /// Reads one packet as (id, type, body)
+    async fn receive(&mut self) -> Result<(i32, i32, String), String> {
+        tokio::time::timeout(IO_TIMEOUT, self.read_packet())
+            .await
+            .map_err(|_| "Timed out waiting for RCON response".to_string())?
+    }
+
+    async fn read_packet(&mut self) -> Result<(i32, i32, String), String> {
+        let size = self
+            .stream
+            .read_i32_le()
+            .await
+            .map_err(|e| format!("Failed to read from RCON: {}", e))?;
+        let size = usize::try_from(size).unwrap_or(0);
+        if !(10..=MAX_PACKET_BYTES).contains(&size) {
+            return Err(format!("Invalid RCON packet size {}", size));
+        }
+
+        let mut packet = vec![0; size];
+        self.stream
+            .read_exact(&mut packet)
+            .await
+            .map_err(|e| format!("Failed to read from RCON: {}", e))?;
+
+        let id = i32::from_le_bytes(packet[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(packet[4..8].try_into().unwrap());
+        let body = String::from_utf8_lossy(&packet[8..size - 2]).into_owned();
+        Ok((id, kind, body))
+    }
+}
+
+/// Game state returned by the poll query
+#[derive(Debug, Deserialize)]
+struct PollResult {
+    players: f64,
+    evolution: f64,
+    #[serde(default)]
+    production: HashMap<String, f64>,
+}
+
+/// Asks the mod to re-send the session, its recent stats and the current research,
+/// so a client started mid-session fills its gaps
+const CATCH_UP_COMMAND: &str =
+    "/silent-command rcon.print(remote.call(\"wandb_weave\", \"catch_up\"))";
+
+/// Typical size of a screenshot of the mod's, the upload budget kept free before one
+/// is requested
+const SCREENSHOT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Screenshot intervals the mod keeps to the client's schedule after a request; past
+/// them it goes back to one screenshot per stats cycle, in case the client stopped
+const SCREENSHOT_LEASE_INTERVALS: u64 = 3;
+
+/// Asks the mod to take a screenshot at its next stats cycle (`take`) or to skip them,
+/// renewing the client's hold on the schedule
+fn screenshot_command(lease_secs: u64, take: bool) -> String {
+    format!(
+        "/silent-command rcon.print(remote.call(\"wandb_weave\", \"schedule_screenshots\", \
+         {lease_secs}, {take}))"
+    )
+}
+
+/// Lua run by every poll; prints one JSON object with the polled values
+fn poll_command(surface: &str) -> String {
+    // A JSON string literal is also a valid Lua string literal
+    let surface = serde_json::to_string(surface).unwrap_or_else(|_| "\"nauvis\"".to_string());
+    format!(
+        "/silent-command rcon.print(helpers.table_to_json({{\
+         players = #game.connected_players, \
+         evolution = game.forces.enemy.get_evolution_factor({surface}), \
+         production = game.forces.player.get_item_production_statistics({surface}).input_counts\
+         }}))"
+    )
+}
+
+/// Runs the poll query and converts its output to `rcon/...` metrics
+async fn poll(client: &mut RconClient, surface: &str) -> Result<HashMap<String, f64>, String> {
+    let output = client.execute(&poll_command(surface)).await?;
+    let result: PollResult = serde_json::from_str(output.trim())
+        .map_err(|e| format!("Unexpected RCON poll output {:?}: {}", output.trim(), e))?;
+
+    let mut metrics = HashMap::new();
+    metrics.insert("rcon/players".to_string(), result.players);
+    metrics.insert("rcon/evolution".to_string(), result.evolution);
+    for (item, count) in result.production {
+        metrics.insert(format!("rcon/produced/{}", item), count);
+    }
+    Ok(metrics)
+}
+
+/// Spawns a task that polls the server over RCON every interval and hands the metrics
+/// to `on_metrics`, reconnecting after failures. With a screenshot interval it also
+/// schedules the mod's screenshots, skipping them while the upload budget couldn't
+/// carry one. Does nothing unless an address and FACTORIO_RCON_PASSWORD are set.
+pub fn start_polling<F>(
+    settings: RconSettings,
+    budget: Option<Arc<UploadBudget>>,
+    mut on_metrics: F,
+) where
+    F: FnMut(HashMap<String, f64>) + Send + 'static,
+{
+    let Some(address) = settings.address else {
+        return;
+    };
+    let Ok(password) = std::env::var("FACTORIO_RCON_PASSWORD") else {
+        eprintln!("⚠️  RCON address set but FACTORIO_RCON_PASSWORD is not, polling disabled");
+        return;
+    };
+
+    println!(
+        "🎮 Polling RCON at {} every {}s",
+        address, settings.poll_interval_secs
+    );
+    let interval = Duration::from_secs(settings.poll_interval_secs.max(1));
+    let screenshot_interval = Duration::from_secs(settings.screenshot_interval_secs.max(1));
+    let mut schedule_screenshots = settings.screenshot_interval_secs > 0;
+    if schedule_screenshots {
+        println!(
+            "🎮 Requesting screenshots every {}s over RCON",
+            settings.screenshot_interval_secs
+        );
+    }
+
+    tokio::spawn(async move {
+        let mut client: Option<RconClient> = None;
+        let mut ticker = tokio::time::interval(interval);
+        let mut screenshot_ticker = tokio::time::interval(screenshot_interval);
+        let mut skipping = false;
+
+        loop {
+            let screenshot_due = tokio::select! {
+                _ = ticker.tick() => false,
+                _ = screenshot_ticker.tick(), if schedule_screenshots => true,
+            };
+
+            if client.is_none() {
+                match RconClient::connect(&address, &password).await {
+                    Ok(mut connected) => {
+                        println!("✅ Connected to RCON at {}", address);
+                        if settings.catch_up {
+                            // The mod prints the number of re-sent cycles, or an error
+                            // when it predates the catch-up interface
+                            match connected.execute(CATCH_UP_COMMAND).await {
+                                Ok(output) => match output.trim().parse::<u64>() {
+                                    Ok(cycles) => println!(
+                                        "🔁 Requested a catch-up, the mod re-sent {} stats cycles",
+                                        cycles
+                                    ),
+                                    Err(_) => eprintln!(
+                                        "⚠️  Catch-up not supported by the mod: {}",
+                                        output.trim()
+                                    ),
+                                },
+                                Err(e) => eprintln!("⚠️  Catch-up request failed: {}", e),
+                            }
+                        }
+                        client = Some(connected);
+                    }
+                    Err(e) => {
+                        eprintln!("⚠️  {}", e);
+                        continue;
+                    }
+                }
+            }
+
+            if screenshot_due {
+                let take = match budget {
+                    Some(ref budget) => budget.can_afford(SCREENSHOT_BYTES),
+                    None => true,
+                };
+                if take == skipping {
+                    skipping = !take;
+                    if skipping {
+                        println!("📶 Upload budget low, skipping screenshots");
+                    } else {
+                        println!("📶 Upload budget recovered, requesting screenshots again");
+                    }
+                }
+
+                let lease_secs = settings.screenshot_interval_secs * SCREENSHOT_LEASE_INTERVALS;
+                let command = screenshot_command(lease_secs, take);
+                match client.as_mut().unwrap().execute(&command).await {
+                    Ok(output) if output.trim() == "ok" => {}
+                    Ok(output) => {
+                        // Mods predating the interface keep taking one every cycle
+                        eprintln!(
+                            "⚠️  Screenshot scheduling not supported by the mod: {}",
+                            output.trim()
+                        );
+                        schedule_screenshots = false;
+                    }
+                    Err(e) => {
+                        eprintln!("⚠️  RCON screenshot request failed, reconnecting: {}", e);
+                        client = None;
+                    }
+                }
+                continue;
+            }
+
+            match poll(client.as_mut().unwrap(), &settings.surface).await {
+                Ok(metrics) => on_metrics(metrics),
+                Err(e) => {
+                    eprintln!("⚠️  RCON poll failed, reconnecting: {}", e);
+                    client = None;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,140 @@
+use crate::chunking::{Chunk, Fragment};
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+/// A sequence number this far below the highest seen means the mod restarted
+/// numbering (a new game or an older save), not a late datagram
+const SEQUENCE_RESET_SLACK: u64 = 10_000;
+
+/// Datagram envelope sent by the mod: `{"seq": N, "event": {...}}`. Other senders name
+/// themselves in `source`, so their numbering is tracked apart from the mod's. An event
+/// too large for one datagram comes as fragments sharing its number:
+/// `{"seq": N, "chunk": {...}, "data": "..."}`.
+#[derive(Deserialize)]
+struct Envelope {
+    seq: u64,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    event: Option<serde_json::Value>,
+    #[serde(default)]
+    chunk: Option<Chunk>,
+    #[serde(default)]
+    data: Option<String>,
+}
+
+/// A decoded datagram carrying one event
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Datagram {
+    pub tick: u64,
+    pub seq: u64,
+    /// The event as a JSONL line
+    pub line: String,
+    /// Sender of the datagram, empty for the mod
+    pub source: String,
+}
+
+impl Datagram {
+    /// The datagram of an event line reassembled from fragments, or None if the line
+    /// isn't JSON
+    pub fn from_line(source: String, seq: u64, line: String) -> Option<Self> {
+        let event: serde_json::Value = serde_json::from_str(&line).ok()?;
+        Some(Self {
+            tick: event["tick"].as_u64().unwrap_or(0),
+            seq,
+            line,
+            source,
+        })
+    }
+}
+
+/// What a datagram carries: a whole event, or a fragment of one
+pub enum Decoded {
+    Event(Datagram),
+    Fragment {
+        source: String,
+        seq: u64,
+        fragment: Fragment,
+    },
+}
+
+/// Decodes a datagram envelope, or None if it is malformed
+pub fn decode_datagram(bytes: &[u8]) -> Option<Decoded> {
+    let envelope: Envelope = serde_json::from_slice(bytes).ok()?;
+    match (envelope.event, envelope.chunk, envelope.data) {
+        (Some(event), _, _) => Some(Decoded::Event(Datagram {
+            tick: event["tick"].as_u64().unwrap_or(0),
+            seq: envelope.seq,
+            line: event.to_string(),
+            source: envelope.source,
+        })),
+        (None, Some(chunk), Some(data)) => Some(Decoded::Fragment {
+            source: envelope.source,
+            seq: envelope.seq,
+            fragment: Fragment { chunk, data },
+        }),
+        _ => None,
+    }
+}
+
+/// Holds datagrams for a short window and releases them in tick order, so events
+/// that arrive slightly out of order reach the mediator in game order
+pub struct ReorderBuffer {
+    window: Duration,
+    /// Ordered by (tick, seq); each entry remembers when it arrived
+    pending: BinaryHeap<Reverse<(Datagram, Instant)>>,
+}
+
+impl ReorderBuffer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: BinaryHeap::new(),
+        }
+    }
+
+    pub fn push(&mut self, datagram: Datagram) {
+        self.pending.push(Reverse((datagram, Instant::now())));
+    }
+
+    /// Releases, in tick order, the datagrams that have waited out the window
+    pub fn release_due(&mut self) -> Vec<String> {
+        let mut released = Vec::new();
+        while let Some(Reverse((_, arrived))) = self.pending.peek() {
+            if arrived.elapsed() < self.window {
+                break;
+            }
+            let Reverse((datagram, _)) = self.pending.pop().unwrap();
+            released.push(datagram.line);
+        }
+        released
+    }
+}
+
+/// Counts datagrams missing from the sequence. A datagram that shows up after a later
+/// one was seen fills its gap again, so `lost` only counts datagrams still missing.
+#[derive(Default)]
+pub struct SequenceTracker {
+    highest: Option<u64>,
+    lost: u64,
+}
+
+impl SequenceTracker {
+    /// Records a received sequence number, returning the number of datagrams lost so far
+    pub fn observe(&mut self, seq: u64) -> u64 {
+        match self.highest {
+            None => self.highest = Some(seq),
+            Some(highest) if seq > highest => {
+                self.lost += seq - highest - 1;
+                self.highest = Some(seq);
+            }
+            Some(highest) if seq + SEQUENCE_RESET_SLACK < highest => {
+                self.highest = Some(seq);
+            }
+            Some(_) => self.lost = self.lost.saturating_sub(1),
+        }
+        self.lost
+    }
+}
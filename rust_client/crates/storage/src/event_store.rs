@@ -0,0 +1,100 @@
+use factorio_events_core::config::{self, AppConfig};
+use factorio_events_core::event_sink::{EventRecorder, RecordedEvent};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Local SQLite record of every parsed event, queued before the event is forwarded, so it
+/// survives WandB/Weave outages and can be backfilled from later
+pub struct EventStore {
+    conn: Mutex<Connection>,
+    /// Game instance of the events, when the client serves several
+    instance: Option<String>,
+}
+
+impl EventStore {
+    /// Store location: `event_store_path` from the config, else the state directory
+    pub fn path(config: &AppConfig) -> PathBuf {
+        config
+            .event_store_path
+            .clone()
+            .unwrap_or_else(|| config::state_dir().join("events.sqlite"))
+    }
+
+    /// Opens (or creates) the store
+    pub fn open(path: &Path, instance: Option<String>) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open event store {:?}: {}", path, e))?;
+        // WAL keeps the per-event inserts cheap and lets instances share the file
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                tick INTEGER NOT NULL,
+                type TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                instance TEXT,
+                received_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS events_by_session ON events (session_id, tick);",
+        )
+        .map_err(|e| format!("Failed to initialize event store: {}", e))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            instance,
+        })
+    }
+
+    /// Records a parsed event with its JSON line as the payload
+    pub fn insert(&self, event: &RecordedEvent) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare_cached(
+                "INSERT INTO events (session_id, tick, type, payload, instance, received_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| format!("Failed to prepare event insert: {}", e))?;
+        statement
+            .execute(params![
+                event.session_id,
+                event.tick as i64,
+                event.kind,
+                event.payload,
+                self.instance,
+                chrono::Utc::now().to_rfc3339(),
+            ])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store event: {}", e))
+    }
+
+    /// Returns the original JSON lines of a session's events, in the order received
+    pub fn session_payloads(&self, session_id: &str) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare("SELECT payload FROM events WHERE session_id = ?1 ORDER BY id")
+            .map_err(|e| format!("Failed to query event store: {}", e))?;
+        let rows = statement
+            .query_map(params![session_id], |row| row.get(0))
+            .map_err(|e| format!("Failed to query event store: {}", e))?;
+
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read event store: {}", e))
+    }
+}
+
+impl EventRecorder for EventStore {
+    fn name(&self) -> &'static str {
+        "event_store"
+    }
+
+    fn record(&mut self, event: &RecordedEvent) -> Result<(), String> {
+        self.insert(event)
+    }
+}
@@ -0,0 +1,5 @@
+//! Local SQLite state: the event store every parsed event is recorded to, and the run
+//! index of the runs published for each session.
+
+pub mod event_store;
+pub mod run_index;
@@ -0,0 +1,201 @@
+use factorio_events_core::config::{self, AppConfig};
+use factorio_events_core::run_index::{RunIndex, RunRecord};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Local SQLite index of session_id -> published run/trace identifiers, so scripts can
+/// find every artifact of a game session long after the fact
+pub struct SqliteRunIndex {
+    conn: Mutex<Connection>,
+    /// Capture log of this process, recorded as the archive of every run
+    archive_path: Option<String>,
+}
+
+impl SqliteRunIndex {
+    /// Index location: `run_index_path` from the config, else the state directory
+    pub fn path(config: &AppConfig) -> PathBuf {
+        config
+            .run_index_path
+            .clone()
+            .unwrap_or_else(|| config::state_dir().join("run_index.sqlite"))
+    }
+
+    /// Opens (or creates) the index
+    pub fn open(path: &Path, archive_path: Option<String>) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open run index {:?}: {}", path, e))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                run_name TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                wandb_run_id TEXT,
+                wandb_url TEXT,
+                weave_trace_id TEXT,
+                archive_path TEXT,
+                created_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS runs_by_session ON runs (session_id);",
+        )
+        .map_err(|e| format!("Failed to initialize run index: {}", e))?;
+        migrate(&conn).map_err(|e| format!("Failed to migrate run index: {}", e))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            archive_path,
+        })
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<RunRecord>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed to query run index: {}", e))?;
+        let rows = statement
+            .query_map(params, |row| {
+                Ok(RunRecord {
+                    session_id: row.get(0)?,
+                    run_name: row.get(1)?,
+                    wandb_run_id: row.get(2)?,
+                    wandb_url: row.get(3)?,
+                    weave_trace_id: row.get(4)?,
+                    archive_path: row.get(5)?,
+                    created_at: row.get(6)?,
+                    save_id: row.get(7)?,
+                    start_tick: row.get::<_, Option<i64>>(8)?.map(|tick| tick as u64),
+                })
+            })
+            .map_err(|e| format!("Failed to query run index: {}", e))?;
+
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read run index: {}", e))
+    }
+}
+
+impl RunIndex for SqliteRunIndex {
+    fn record(
+        &self,
+        session_id: &str,
+        run_name: &str,
+        wandb_run: Option<(String, String)>,
+        weave_trace_id: Option<String>,
+        save_id: Option<&str>,
+        start_tick: u64,
+    ) -> Result<(), String> {
+        let (wandb_run_id, wandb_url) = wandb_run.unzip();
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR REPLACE INTO runs
+                 (run_name, session_id, wandb_run_id, wandb_url, weave_trace_id, archive_path, created_at,
+                  save_id, start_tick)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    run_name,
+                    session_id,
+                    wandb_run_id,
+                    wandb_url,
+                    weave_trace_id,
+                    self.archive_path,
+                    chrono::Utc::now().to_rfc3339(),
+                    save_id,
+                    start_tick as i64,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record run {}: {}", run_name, e))
+    }
+
+    fn mark_finished(&self, run_name: &str) -> Result<(), String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE runs SET finished_at = ?2 WHERE run_name = ?1 AND finished_at IS NULL",
+                params![run_name, chrono::Utc::now().to_rfc3339()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to mark run {} finished: {}", run_name, e))
+    }
+
+    fn latest_unfinished(&self) -> Result<Option<RunRecord>, String> {
+        let runs = self.query(
+            &format!(
+                "SELECT {} FROM runs WHERE finished_at IS NULL AND archive_path IS ?1
+                 ORDER BY created_at DESC LIMIT 1",
+                COLUMNS
+            ),
+            params![self.archive_path],
+        )?;
+        Ok(runs.into_iter().next())
+    }
+
+    fn find(&self, session_id: &str) -> Result<Vec<RunRecord>, String> {
+        self.query(
+            &format!("SELECT {} FROM runs WHERE session_id = ?1 ORDER BY created_at", COLUMNS),
+            params![session_id],
+        )
+    }
+
+    fn find_parent(
+        &self,
+        save_id: &str,
+        session_id: &str,
+        tick: u64,
+    ) -> Result<Option<RunRecord>, String> {
+        let runs = self.query(
+            &format!(
+                "SELECT {} FROM runs
+                 WHERE save_id = ?1 AND session_id != ?2 AND start_tick <= ?3
+                 ORDER BY start_tick DESC, created_at DESC LIMIT 1",
+                COLUMNS
+            ),
+            params![save_id, session_id, tick as i64],
+        )?;
+        Ok(runs.into_iter().next())
+    }
+}
+
+/// Columns of a RunRecord, in field order
+const COLUMNS: &str = "session_id, run_name, wandb_run_id, wandb_url, weave_trace_id, archive_path, \
+                       created_at, save_id, start_tick";
+
+/// Adds the columns of newer versions to an index created by an older one
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let mut statement = conn.prepare("SELECT name FROM pragma_table_info('runs')")?;
+    let columns: Vec<String> = statement
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    for (column, column_type) in [
+        ("save_id", "TEXT"),
+        ("start_tick", "INTEGER"),
+        ("finished_at", "TEXT"),
+    ] {
+        if !columns.iter().any(|c| c == column) {
+            conn.execute_batch(&format!(
+                "ALTER TABLE runs ADD COLUMN {} {};",
+                column, column_type
+            ))?;
+        }
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS runs_by_save ON runs (save_id);")
+}
+
+/// Runs `find-session <session_id>`, printing one JSON line per run
+pub fn find_session(session_id: &str, config: &AppConfig) -> Result<(), String> {
+    let index = SqliteRunIndex::open(&SqliteRunIndex::path(config), None)?;
+    let records = index.find(session_id)?;
+    if records.is_empty() {
+        return Err(format!("No runs recorded for session {}", session_id));
+    }
+    for record in records {
+        println!("{}", serde_json::to_string(&record).unwrap());
+    }
+    Ok(())
+}
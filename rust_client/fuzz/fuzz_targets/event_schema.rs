@@ -0,0 +1,128 @@
+#![no_main]
+
+//! Feeds adversarial JSON close to the FactorioEvent schema through the mediator's
+//! parse/route decision and checks that it never panics and that every input is
+//! accounted for: either routed or dropped with a reason.
+
+use arbitrary::Unstructured;
+use factorio_events_core::event_mediator::{classify_event, EventOutcome};
+use libfuzzer_sys::fuzz_target;
+use serde_json::{json, Map, Value};
+
+const TYPES: &[&str] = &["session_init", "stats", "event", "pollution", "probe_registry", "Stats", ""];
+
+const FIELDS: &[&str] = &[
+    "session_id",
+    "tick",
+    "level_name",
+    "save_id",
+    "cycle",
+    "player",
+    "screenshot_path",
+    "participant",
+    "delta",
+    "labs",
+    "working",
+    "assemblers",
+    "recipe",
+    "category",
+    "utilization",
+    "probes",
+    "id",
+    "name",
+    "kind",
+    "products_production",
+    "materials_consumption",
+    "event_name",
+    "player_index",
+    "entity",
+    "position",
+    "surface",
+    "tech_name",
+    "tech_level",
+    "item",
+    "count",
+    "chunks",
+    "pollution",
+    "x",
+    "y",
+];
+
+const EVENT_NAMES: &[&str] = &[
+    "on_research_started",
+    "on_research_finished",
+    "on_built_entity",
+    "on_player_mined_entity",
+    "on_player_crafted_item",
+    "on_chunk_generated",
+    "on_chunk_charted",
+    "on_sector_scanned",
+    "on_unknown_event",
+];
+
+/// Generates a JSON value of a random kind, biased towards values that appear in events
+fn value(u: &mut Unstructured, depth: u32) -> arbitrary::Result<Value> {
+    Ok(match u.int_in_range(0..=9)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => json!(u.arbitrary::<u64>()?),
+        3 => json!(u.arbitrary::<i64>()?),
+        4 => json!(u.arbitrary::<f64>()?),
+        5 => Value::String(u.arbitrary()?),
+        6 => Value::String(u.choose(EVENT_NAMES)?.to_string()),
+        7 if depth < 3 => {
+            let mut map = Map::new();
+            for _ in 0..u.int_in_range(0..=4)? {
+                let key = if u.arbitrary()? {
+                    u.choose(FIELDS)?.to_string()
+                } else {
+                    u.arbitrary()?
+                };
+                map.insert(key, value(u, depth + 1)?);
+            }
+            Value::Object(map)
+        }
+        8 if depth < 3 => {
+            let mut items = Vec::new();
+            for _ in 0..u.int_in_range(0..=4)? {
+                items.push(value(u, depth + 1)?);
+            }
+            Value::Array(items)
+        }
+        _ => json!({ "x": u.arbitrary::<f64>()?, "y": u.arbitrary::<f64>()? }),
+    })
+}
+
+/// Generates an event object with a plausible type and a random subset of known fields
+fn near_schema_event(u: &mut Unstructured) -> arbitrary::Result<Value> {
+    let mut map = Map::new();
+    if u.ratio(9, 10)? {
+        map.insert("type".to_string(), json!(u.choose(TYPES)?));
+    }
+    for field in FIELDS {
+        if u.ratio(2, 3)? {
+            map.insert(field.to_string(), value(u, 0)?);
+        }
+    }
+    if u.ratio(1, 2)? {
+        map.insert("event_name".to_string(), json!(u.choose(EVENT_NAMES)?));
+    }
+    Ok(Value::Object(map))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let line = match u.arbitrary::<bool>() {
+        Ok(true) => near_schema_event(&mut u)
+            .map(|event| event.to_string())
+            .unwrap_or_default(),
+        _ => String::from_utf8_lossy(data).into_owned(),
+    };
+
+    match classify_event(&line) {
+        EventOutcome::Routed => {}
+        EventOutcome::Dropped(reason) => {
+            assert!(!reason.is_empty(), "input dropped without a reason: {}", line)
+        }
+    }
+});